@@ -0,0 +1,301 @@
+//! Offsite backups of ZFS-backed jails
+//!
+//! Streams `zfs send` over SSH into `zfs receive` on another host:
+//! - Each backup snapshots the jail dataset as `blackship-backup-<unix time>`
+//! - When the target already holds one of those snapshots, only the changes
+//!   since the newest common one are sent
+//! - The receive is verified by looking the snapshot up on the target
+//! - Older local backup snapshots are dropped; the newest is kept as the base
+//!   for the next incremental send
+
+use crate::error::{Error, Result};
+use std::process::{Command, Stdio};
+use std::time::{SystemTime, UNIX_EPOCH};
+
+/// Prefix of backup snapshot names
+pub const BACKUP_SNAPSHOT_PREFIX: &str = "blackship-backup-";
+
+/// Exit status ssh(1) uses for its own errors (connection, authentication)
+const SSH_ERROR_STATUS: i32 = 255;
+
+/// Where a backup is received: `[user@]host:pool/dataset`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BackupTarget {
+    /// SSH destination (`user@host` or `host`)
+    pub destination: String,
+    /// Dataset to receive into on the remote host
+    pub dataset: String,
+    /// SSH port (default: ssh's own default)
+    pub port: Option<u16>,
+}
+
+impl BackupTarget {
+    /// Parse `[user@]host:pool/dataset`
+    pub fn parse(target: &str) -> Result<Self> {
+        let invalid = || {
+            Error::ConfigValidation(format!(
+                "Invalid backup target '{}' (expected [user@]host:pool/dataset)",
+                target
+            ))
+        };
+        let (destination, dataset) = target.split_once(':').ok_or_else(invalid)?;
+        let host = destination.rsplit('@').next().unwrap_or_default();
+        if host.is_empty() || dataset.is_empty() || dataset.starts_with('/') {
+            return Err(invalid());
+        }
+        Ok(Self {
+            destination: destination.to_string(),
+            dataset: dataset.to_string(),
+            port: None,
+        })
+    }
+
+    /// Use a non-default SSH port
+    pub fn with_port(mut self, port: Option<u16>) -> Self {
+        self.port = port;
+        self
+    }
+
+    /// ssh(1) invocation running `remote` on the target
+    fn ssh(&self, remote: &[&str]) -> Command {
+        let mut cmd = Command::new("ssh");
+        // Never prompt: a backup must fail instead of hanging on a password
+        cmd.args(["-o", "BatchMode=yes"]);
+        if let Some(port) = self.port {
+            cmd.args(["-p", &port.to_string()]);
+        }
+        cmd.arg(&self.destination).args(remote);
+        cmd
+    }
+
+    /// Backup snapshots of the target dataset, oldest first (empty if the
+    /// dataset does not exist yet)
+    fn snapshots(&self) -> Result<Vec<String>> {
+        let output = self
+            .ssh(&["zfs", "list", "-H", "-o", "name", "-t", "snapshot", "-d", "1", &self.dataset])
+            .output()
+            .map_err(|e| ssh_failed(format!("Failed to run ssh: {}", e)))?;
+        let stderr = String::from_utf8_lossy(&output.stderr);
+        if output.status.code() == Some(SSH_ERROR_STATUS) {
+            return Err(ssh_error(&self.destination, &stderr));
+        }
+        if !output.status.success() {
+            if stderr.contains("does not exist") {
+                return Ok(Vec::new());
+            }
+            return Err(Error::Zfs(format!(
+                "Failed to list snapshots of {} on {}: {}",
+                self.dataset,
+                self.destination,
+                stderr.trim()
+            )));
+        }
+        Ok(backup_snapshot_names(&String::from_utf8_lossy(&output.stdout)))
+    }
+}
+
+/// Back up a jail dataset to a remote host
+///
+/// Returns the name of the snapshot that was sent.
+pub fn backup_jail_zfs(dataset: &str, target: &BackupTarget) -> Result<String> {
+    // Fails early (and clearly) if SSH does not work
+    let remote = target.snapshots()?;
+    let local = local_snapshots(dataset)?;
+    let base = latest_common_snapshot(&local, &remote);
+
+    let secs = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .unwrap_or_default()
+        .as_secs();
+    let snapshot = format!("{}{}", BACKUP_SNAPSHOT_PREFIX, secs);
+    let full = format!("{}@{}", dataset, snapshot);
+    let status = Command::new("zfs")
+        .args(["snapshot", &full])
+        .status()
+        .map_err(|e| Error::Zfs(format!("Failed to run zfs snapshot: {}", e)))?;
+    if !status.success() {
+        return Err(Error::Zfs(format!("Failed to create snapshot '{}'", full)));
+    }
+
+    match &base {
+        Some(base) => println!(
+            "Sending {} to {} (incremental from @{})",
+            full, target.destination, base
+        ),
+        None => println!("Sending {} to {} (full)", full, target.destination),
+    }
+
+    let result = send(dataset, &snapshot, base.as_deref(), target)
+        .and_then(|_| verify(&snapshot, target));
+    if let Err(e) = result {
+        let _ = Command::new("zfs").args(["destroy", &full]).status();
+        return Err(e);
+    }
+
+    // Keep only the newest backup snapshot as the next incremental base
+    for old in local.iter().filter(|s| **s != snapshot) {
+        let _ = Command::new("zfs")
+            .args(["destroy", &format!("{}@{}", dataset, old)])
+            .status();
+    }
+
+    println!("Backup complete: {}:{}@{}", target.destination, target.dataset, snapshot);
+    Ok(snapshot)
+}
+
+/// Pipe `zfs send` into `zfs receive` on the target
+fn send(dataset: &str, snapshot: &str, base: Option<&str>, target: &BackupTarget) -> Result<()> {
+    let mut send = Command::new("zfs");
+    send.arg("send");
+    if let Some(base) = base {
+        send.args(["-i", &format!("@{}", base)]);
+    }
+    let mut sender = send
+        .arg(format!("{}@{}", dataset, snapshot))
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| Error::Zfs(format!("Failed to run zfs send: {}", e)))?;
+    let stream = sender.stdout.take().expect("zfs send stdout is piped");
+
+    // -F rolls the target back to the base; -u leaves it unmounted
+    let receiver = target
+        .ssh(&["zfs", "receive", "-F", "-u", &target.dataset])
+        .stdin(stream)
+        .stderr(Stdio::piped())
+        .output();
+    let send_status = sender
+        .wait()
+        .map_err(|e| Error::Zfs(format!("Failed to wait for zfs send: {}", e)))?;
+    let receiver = receiver.map_err(|e| ssh_failed(format!("Failed to run ssh: {}", e)))?;
+
+    let stderr = String::from_utf8_lossy(&receiver.stderr);
+    if receiver.status.code() == Some(SSH_ERROR_STATUS) {
+        return Err(ssh_error(&target.destination, &stderr));
+    }
+    if !receiver.status.success() {
+        return Err(Error::Zfs(format!(
+            "zfs receive on {} failed: {}",
+            target.destination,
+            stderr.trim()
+        )));
+    }
+    if !send_status.success() {
+        return Err(Error::Zfs(format!("zfs send of {}@{} failed", dataset, snapshot)));
+    }
+    Ok(())
+}
+
+/// Check that the snapshot arrived on the target
+fn verify(snapshot: &str, target: &BackupTarget) -> Result<()> {
+    if target.snapshots()?.iter().any(|s| s == snapshot) {
+        Ok(())
+    } else {
+        Err(Error::Zfs(format!(
+            "Snapshot @{} not found on {}:{} after receive",
+            snapshot, target.destination, target.dataset
+        )))
+    }
+}
+
+/// Backup snapshots of a local dataset, oldest first
+fn local_snapshots(dataset: &str) -> Result<Vec<String>> {
+    let output = Command::new("zfs")
+        .args(["list", "-H", "-o", "name", "-t", "snapshot", "-d", "1", dataset])
+        .output()
+        .map_err(|e| Error::Zfs(format!("Failed to run zfs list: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::Zfs(format!(
+            "Failed to list snapshots of '{}': {}",
+            dataset,
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+    Ok(backup_snapshot_names(&String::from_utf8_lossy(&output.stdout)))
+}
+
+/// Backup snapshot names (after the `@`) from `zfs list -o name` output,
+/// oldest first
+fn backup_snapshot_names(list: &str) -> Vec<String> {
+    let mut names: Vec<(u64, String)> = list
+        .lines()
+        .filter_map(|line| line.trim().split_once('@'))
+        .filter_map(|(_, name)| {
+            let secs = name.strip_prefix(BACKUP_SNAPSHOT_PREFIX)?.parse().ok()?;
+            Some((secs, name.to_string()))
+        })
+        .collect();
+    names.sort();
+    names.into_iter().map(|(_, name)| name).collect()
+}
+
+/// Newest backup snapshot present on both sides
+fn latest_common_snapshot(local: &[String], remote: &[String]) -> Option<String> {
+    local.iter().rev().find(|s| remote.contains(s)).cloned()
+}
+
+fn ssh_failed(message: String) -> Error {
+    Error::CommandFailed {
+        command: "ssh".to_string(),
+        message,
+    }
+}
+
+/// Explain an ssh(1) failure, calling out authentication problems
+fn ssh_error(destination: &str, stderr: &str) -> Error {
+    let stderr = stderr.trim();
+    if stderr.contains("Permission denied") || stderr.contains("Host key verification failed") {
+        ssh_failed(format!(
+            "SSH authentication to {} failed; backups need key-based access \
+             without prompts (ssh-copy-id, known_hosts): {}",
+            destination, stderr
+        ))
+    } else {
+        ssh_failed(format!("Cannot reach {} over SSH: {}", destination, stderr))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_target() {
+        let target = BackupTarget::parse("backup@vault:tank/jails/web").unwrap();
+        assert_eq!(target.destination, "backup@vault");
+        assert_eq!(target.dataset, "tank/jails/web");
+
+        let target = BackupTarget::parse("vault:tank/web").unwrap().with_port(Some(2222));
+        assert_eq!(target.destination, "vault");
+        assert_eq!(target.port, Some(2222));
+
+        assert!(BackupTarget::parse("vault").is_err());
+        assert!(BackupTarget::parse("user@:tank/web").is_err());
+        assert!(BackupTarget::parse("vault:/tank/web").is_err());
+    }
+
+    #[test]
+    fn test_incremental_base() {
+        let list = "\
+tank/web@blackship-backup-1700000100
+tank/web@manual
+tank/web@blackship-backup-1700000000
+";
+        let local = backup_snapshot_names(list);
+        assert_eq!(local, vec!["blackship-backup-1700000000", "blackship-backup-1700000100"]);
+
+        let remote = vec!["blackship-backup-1700000000".to_string()];
+        assert_eq!(
+            latest_common_snapshot(&local, &remote).as_deref(),
+            Some("blackship-backup-1700000000")
+        );
+        assert_eq!(latest_common_snapshot(&local, &[]), None);
+    }
+
+    #[test]
+    fn test_ssh_auth_error() {
+        let err = ssh_error("vault", "backup@vault: Permission denied (publickey).\n");
+        assert!(err.to_string().contains("authentication"), "{}", err);
+        let err = ssh_error("vault", "ssh: connect to host vault port 22: Connection refused");
+        assert!(err.to_string().contains("Cannot reach vault"), "{}", err);
+    }
+}
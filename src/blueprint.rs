@@ -4,11 +4,17 @@
 //! - Jailfile parsing (TOML and line-based formats)
 //! - Build instructions (FROM, RUN, COPY, EXPOSE, CMD, etc.)
 //! - Build execution with context
+//! - `.jailignore` exclusions for COPY
+//! - ZFS snapshots of build layers for resuming failed builds
 //! - Template management
+//! - Jailfile generation from an existing jail root (heuristic)
 
 pub mod context;
 pub mod executor;
+pub mod generate;
+pub mod ignore;
 pub mod instructions;
+pub mod layers;
 pub mod parser;
 
 pub use context::BuildContext;
@@ -3,8 +3,9 @@
 //! Manages the build environment including:
 //! - Build arguments
 //! - Working directory
-//! - File copying context
+//! - File copying context (including `.jailignore` exclusions)
 
+use crate::blueprint::ignore::IgnoreRules;
 use std::collections::HashMap;
 use std::path::{Path, PathBuf};
 
@@ -13,6 +14,8 @@ use std::path::{Path, PathBuf};
 pub struct BuildContext {
     /// Context directory (where Jailfile and files are located)
     context_dir: PathBuf,
+    /// Exclusions from the context directory's `.jailignore`
+    ignore: IgnoreRules,
     /// Build arguments (ARG name=value)
     args: HashMap<String, String>,
     /// Environment variables
@@ -23,6 +26,12 @@ pub struct BuildContext {
     workdir: PathBuf,
     /// Jail name being built
     jail_name: String,
+    /// Default gateway of the jail's network (if known)
+    gateway: Option<String>,
+    /// Name of the jail's primary network (if attached)
+    network: Option<String>,
+    /// Subnet of the jail's primary network (if attached)
+    subnet: Option<String>,
     /// Verbose output
     verbose: bool,
 }
@@ -32,11 +41,15 @@ impl BuildContext {
     pub fn new(context_dir: &Path, target_path: &Path, jail_name: &str) -> Self {
         Self {
             context_dir: context_dir.to_path_buf(),
+            ignore: IgnoreRules::load(context_dir),
             args: HashMap::new(),
             env: HashMap::new(),
             target_path: target_path.to_path_buf(),
             workdir: PathBuf::from("/"),
             jail_name: jail_name.to_string(),
+            gateway: None,
+            network: None,
+            subnet: None,
             verbose: false,
         }
     }
@@ -47,6 +60,39 @@ impl BuildContext {
         self
     }
 
+    /// Set the jail's default gateway
+    pub fn with_gateway(mut self, gateway: &str) -> Self {
+        self.gateway = Some(gateway.to_string());
+        self
+    }
+
+    /// Set the jail's primary network name and subnet
+    pub fn with_network(mut self, network: &str, subnet: &str) -> Self {
+        self.network = Some(network.to_string());
+        self.subnet = Some(subnet.to_string());
+        self
+    }
+
+    /// Context for another build stage
+    ///
+    /// Keeps the build context directory, build arguments and network settings,
+    /// but builds into `target_path` with a fresh environment and workdir.
+    pub fn for_stage(&self, target_path: &Path) -> Self {
+        Self {
+            context_dir: self.context_dir.clone(),
+            ignore: self.ignore.clone(),
+            args: self.args.clone(),
+            env: HashMap::new(),
+            target_path: target_path.to_path_buf(),
+            workdir: PathBuf::from("/"),
+            jail_name: self.jail_name.clone(),
+            gateway: self.gateway.clone(),
+            network: self.network.clone(),
+            subnet: self.subnet.clone(),
+            verbose: self.verbose,
+        }
+    }
+
     /// Set a build argument
     pub fn set_arg(&mut self, name: &str, value: &str) {
         self.args.insert(name.to_string(), value.to_string());
@@ -57,6 +103,11 @@ impl BuildContext {
         self.args.get(name).map(|s| s.as_str())
     }
 
+    /// Get all build arguments
+    pub fn args(&self) -> &HashMap<String, String> {
+        &self.args
+    }
+
     /// Set an environment variable
     pub fn set_env(&mut self, name: &str, value: &str) {
         self.env.insert(name.to_string(), value.to_string());
@@ -108,6 +159,22 @@ impl BuildContext {
         }
     }
 
+    /// Whether a path inside the context directory is excluded by `.jailignore`
+    ///
+    /// Paths outside the context directory are never excluded.
+    pub fn is_ignored(&self, path: &Path, is_dir: bool) -> bool {
+        match path.strip_prefix(&self.context_dir) {
+            Ok(relative) => self.ignore.is_ignored(relative, is_dir),
+            Err(_) => false,
+        }
+    }
+
+    /// Whether `.jailignore` re-includes paths, so excluded directories must
+    /// still be walked
+    pub fn ignore_has_negations(&self) -> bool {
+        self.ignore.has_negations()
+    }
+
     /// Resolve a destination path relative to target jail
     pub fn resolve_dest(&self, dest: &str) -> PathBuf {
         let path = if Path::new(dest).is_absolute() {
@@ -128,6 +195,9 @@ impl BuildContext {
     /// - $ARG_NAME - Build arguments (simple form)
     /// - ${JAIL_NAME} - Current jail name
     /// - ${WORKDIR} - Current working directory
+    /// - ${JAIL_GATEWAY} - Default gateway
+    /// - ${JAIL_NETWORK} - Primary network name
+    /// - ${JAIL_SUBNET} - Primary network subnet (CIDR)
     pub fn substitute(&self, input: &str) -> String {
         let mut result = input.to_string();
 
@@ -149,6 +219,17 @@ impl BuildContext {
         result = result.replace("${WORKDIR}", self.workdir.to_str().unwrap_or("/"));
         result = result.replace("$WORKDIR", self.workdir.to_str().unwrap_or("/"));
 
+        let network_vars = [
+            ("JAIL_GATEWAY", &self.gateway),
+            ("JAIL_NETWORK", &self.network),
+            ("JAIL_SUBNET", &self.subnet),
+        ];
+        for (name, value) in network_vars {
+            let value = value.as_deref().unwrap_or("");
+            result = result.replace(&format!("${{{}}}", name), value);
+            result = result.replace(&format!("${}", name), value);
+        }
+
         result
     }
 
@@ -158,6 +239,11 @@ impl BuildContext {
             println!("[build] {}", message);
         }
     }
+
+    /// Print build progress (always shown)
+    pub fn progress(&self, message: &str) {
+        println!("{}", message);
+    }
 }
 
 #[cfg(test)]
@@ -188,6 +274,17 @@ mod tests {
         assert_eq!(ctx.substitute("jail=${JAIL_NAME}"), "jail=myapp");
     }
 
+    #[test]
+    fn test_network_substitution() {
+        let ctx = BuildContext::new(Path::new("/build"), Path::new("/jails/myapp"), "myapp")
+            .with_gateway("10.0.1.1")
+            .with_network("backend", "10.0.1.0/24");
+
+        assert_eq!(ctx.substitute("gw=${JAIL_GATEWAY}"), "gw=10.0.1.1");
+        assert_eq!(ctx.substitute("net=$JAIL_NETWORK"), "net=backend");
+        assert_eq!(ctx.substitute("subnet=${JAIL_SUBNET}"), "subnet=10.0.1.0/24");
+    }
+
     #[test]
     fn test_path_resolution() {
         let ctx = BuildContext::new(
@@ -3,14 +3,101 @@
 //! Executes Jailfile instructions to build a jail.
 
 use crate::blueprint::context::BuildContext;
-use crate::blueprint::instructions::{CopySpec, Instruction, Jailfile};
+use crate::blueprint::instructions::{CopySpec, Instruction, Jailfile, Stage};
+use crate::blueprint::layers::LayerSnapshots;
 use crate::error::{Error, Result};
-use crate::jail::jexec::chroot_exec;
+use crate::jail::jail_getid;
+use crate::jail::jexec::chroot_exec_with_timeout;
+use crate::jail::mount::unmount;
+use crate::sys::mounts_under;
+use crate::zfs::ZfsManager;
 use nix::unistd::{Group, User};
 use std::ffi::CString;
 use std::fs;
+use std::net::IpAddr;
 use std::os::unix::fs::PermissionsExt;
-use std::path::Path;
+use std::os::unix::io::AsRawFd;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+
+/// devfs ruleset applied to the build devfs by default (`devfsrules_jail`)
+pub const DEFAULT_DEVFS_RULESET: u16 = 4;
+
+/// Longest instruction text shown in progress lines
+const PROGRESS_WIDTH: usize = 60;
+
+/// DEVFSIO_SUSE: _IOW('D', 10, devfs_rsnum) - set and apply a mount's ruleset
+const DEVFSIO_SUSE: libc::c_ulong = 0x8002440a;
+
+/// Filesystems mounted under the jail root for the duration of a build
+///
+/// Unmounted in reverse order when the build finishes or the guard is
+/// dropped, so a failed instruction never leaks a mount.
+#[derive(Default)]
+struct BuildMounts(Vec<PathBuf>);
+
+impl BuildMounts {
+    /// Record a mount made by the build
+    fn push(&mut self, mount_point: PathBuf) {
+        self.0.push(mount_point);
+    }
+
+    /// Whether `mount_point` was mounted by this build
+    fn contains(&self, mount_point: &Path) -> bool {
+        self.0.iter().any(|m| m == mount_point)
+    }
+
+    /// Unmount everything, most recent first
+    fn release(&mut self) {
+        while let Some(mount_point) = self.0.pop() {
+            unmount(&mount_point);
+        }
+    }
+}
+
+impl Drop for BuildMounts {
+    fn drop(&mut self) {
+        self.release();
+    }
+}
+
+/// Unmount devfs/nullfs mounts left under `root` by an interrupted build,
+/// unless the jail is running and the mounts are its own
+fn unmount_stale(root: &Path, jail_name: &str) {
+    if jail_getid(jail_name).is_ok() {
+        return;
+    }
+    let Ok(stale) = mounts_under(root) else {
+        return;
+    };
+    for mount in stale.iter().filter(|m| matches!(m.fstype.as_str(), "devfs" | "nullfs")) {
+        eprintln!(
+            "Warning: Removing stale {} mount at {} from a previous build",
+            mount.fstype,
+            mount.mount_point.display()
+        );
+        unmount(&mount.mount_point);
+    }
+}
+
+/// Remove a build root, clearing the schg flags base system files carry
+fn remove_tree(root: &Path) {
+    let _ = std::process::Command::new("chflags")
+        .args(["-R", "noschg"])
+        .arg(root)
+        .status();
+    if let Err(e) = fs::remove_dir_all(root) {
+        eprintln!("Warning: Failed to remove {}: {}", root.display(), e);
+    }
+}
+
+/// resolv.conf(5) content for a list of nameservers
+fn resolv_conf(nameservers: &[IpAddr]) -> String {
+    nameservers
+        .iter()
+        .map(|ns| format!("nameserver {}\n", ns))
+        .collect()
+}
 
 /// Template executor for building jails
 pub struct TemplateExecutor {
@@ -18,6 +105,27 @@ pub struct TemplateExecutor {
     context: BuildContext,
     /// Dry run mode (don't execute, just print)
     dry_run: bool,
+    /// devfs ruleset for the build chroot (0 exposes every device)
+    devfs_ruleset: u16,
+    /// Timeout for RUN instructions without their own `--timeout` (0 = none)
+    run_timeout: u64,
+    /// Nameservers written to the build's resolv.conf (empty = copy the host's)
+    nameservers: Vec<IpAddr>,
+    /// Hostname RUN commands see (the Jailfile name, else the jail name)
+    hostname: Option<String>,
+    /// ZFS snapshots of the final stage's layers (`--snapshot-layers`)
+    layers: Option<LayerSnapshots>,
+    /// Mounts made by the current build
+    mounts: BuildMounts,
+    /// Directory holding bootstrapped releases (for intermediate stage roots)
+    releases_dir: Option<PathBuf>,
+    /// Roots of completed intermediate stages (name, root), in stage order
+    stage_roots: Vec<(Option<String>, PathBuf)>,
+    /// Number of the step being executed and the total for this build
+    step: usize,
+    total_steps: usize,
+    /// Time taken by each executed step
+    timings: Vec<(String, Duration)>,
 }
 
 impl TemplateExecutor {
@@ -26,6 +134,17 @@ impl TemplateExecutor {
         Self {
             context,
             dry_run: false,
+            devfs_ruleset: DEFAULT_DEVFS_RULESET,
+            run_timeout: 0,
+            nameservers: Vec::new(),
+            hostname: None,
+            layers: None,
+            mounts: BuildMounts::default(),
+            releases_dir: None,
+            stage_roots: Vec::new(),
+            step: 0,
+            total_steps: 0,
+            timings: Vec::new(),
         }
     }
 
@@ -35,8 +154,77 @@ impl TemplateExecutor {
         self
     }
 
+    /// Set the devfs ruleset applied to `/dev` while RUN commands execute
+    pub fn devfs_ruleset(mut self, ruleset: u16) -> Self {
+        self.devfs_ruleset = ruleset;
+        self
+    }
+
+    /// Set the default timeout in seconds for RUN instructions (0 = none)
+    pub fn run_timeout(mut self, secs: u64) -> Self {
+        self.run_timeout = secs;
+        self
+    }
+
+    /// Set the nameservers for the build's resolv.conf instead of the host's
+    pub fn nameservers(mut self, nameservers: &[IpAddr]) -> Self {
+        self.nameservers = nameservers.to_vec();
+        self
+    }
+
+    /// Snapshot the target's ZFS dataset after each step of the final stage,
+    /// resuming a failed build from its last good layer
+    pub fn snapshot_layers(mut self, zfs: ZfsManager) -> Self {
+        self.layers = Some(LayerSnapshots::new(zfs, self.context.jail_name()));
+        self
+    }
+
+    /// Set the releases directory used to populate intermediate stage roots
+    pub fn releases_dir(mut self, dir: &Path) -> Self {
+        self.releases_dir = Some(dir.to_path_buf());
+        self
+    }
+
     /// Execute a Jailfile to build a jail
+    ///
+    /// In a multi-stage Jailfile every stage but the last is built in a
+    /// scratch root next to the target; the last stage builds the target
+    /// itself. Any filesystem mounted for RUN commands is unmounted and the
+    /// scratch roots are removed afterwards, whether the build succeeded or not.
     pub fn execute(&mut self, jailfile: &Jailfile) -> Result<()> {
+        if !self.dry_run {
+            unmount_stale(self.context.target_path(), self.context.jail_name());
+        }
+        self.hostname = Some(
+            jailfile
+                .metadata
+                .name
+                .clone()
+                .unwrap_or_else(|| self.context.jail_name().to_string()),
+        );
+        let started = Instant::now();
+        let result = self.execute_instructions(jailfile);
+        self.mounts.release();
+        self.remove_stage_roots();
+        if let Some(layers) = &self.layers
+            && !self.dry_run
+        {
+            if result.is_ok() {
+                if let Err(e) = layers.prune() {
+                    eprintln!("Warning: Failed to prune layer snapshots: {}", e);
+                }
+            } else {
+                println!(
+                    "Layer snapshots kept; rerun the build to resume after the last good step"
+                );
+            }
+        }
+        self.print_summary(started.elapsed(), result.is_ok());
+        result
+    }
+
+    /// Run the Jailfile instructions
+    fn execute_instructions(&mut self, jailfile: &Jailfile) -> Result<()> {
         self.context.log(&format!(
             "Building jail '{}' from {:?}",
             self.context.jail_name(),
@@ -51,9 +239,55 @@ impl TemplateExecutor {
                 }
         }
 
-        // Execute each instruction
-        for instruction in &jailfile.instructions {
-            self.execute_instruction(instruction)?;
+        self.step = 0;
+        self.total_steps = jailfile
+            .instructions
+            .iter()
+            .filter(|i| !matches!(i, Instruction::Comment(_)))
+            .count();
+        self.timings.clear();
+
+        let stages = jailfile.stages();
+        let (last, intermediate) = stages.split_last().expect("a Jailfile has at least one stage");
+        for (index, stage) in intermediate.iter().enumerate() {
+            self.execute_stage(index, stage)?;
+        }
+
+        // Resume from the last good layer when snapshotting layers
+        let resume_from = match &mut self.layers {
+            Some(layers) if !self.dry_run => {
+                let prelude: Vec<&Instruction> =
+                    intermediate.iter().flat_map(|s| s.instructions).collect();
+                let steps: Vec<&Instruction> = last
+                    .instructions
+                    .iter()
+                    .filter(|i| !matches!(i, Instruction::Comment(_)))
+                    .collect();
+                layers.plan(&self.context, &prelude, &steps);
+                layers.resume()?
+            }
+            _ => 0,
+        };
+
+        // Execute each instruction of the final stage
+        let mut layer = 0;
+        for instruction in last.instructions {
+            if matches!(instruction, Instruction::Comment(_)) {
+                self.execute_step(instruction)?;
+                continue;
+            }
+            layer += 1;
+            if layer <= resume_from {
+                self.replay_step(instruction)?;
+                continue;
+            }
+            self.execute_step(instruction)?;
+            if let Some(layers) = &self.layers
+                && !self.dry_run
+                && let Err(e) = layers.snapshot(layer)
+            {
+                eprintln!("Warning: Failed to snapshot layer {}: {}", layer, e);
+            }
         }
 
         self.context.log(&format!(
@@ -64,11 +298,178 @@ impl TemplateExecutor {
         Ok(())
     }
 
+    /// Execute one instruction as a numbered, timed build step
+    ///
+    /// Prints `[n/total] INSTRUCTION (1.2s)`. In verbose mode the header is
+    /// printed on its own line so RUN output can stream below it.
+    fn execute_step(&mut self, instruction: &Instruction) -> Result<()> {
+        if matches!(instruction, Instruction::Comment(_)) {
+            return self.execute_instruction(instruction);
+        }
+
+        self.step += 1;
+        let label = truncate(&instruction.to_string(), PROGRESS_WIDTH);
+        let header = format!("[{}/{}] {}", self.step, self.total_steps, label);
+        if self.context.is_verbose() {
+            self.context.progress(&header);
+        } else {
+            print!("{}", header);
+            let _ = std::io::Write::flush(&mut std::io::stdout());
+        }
+
+        let started = Instant::now();
+        let result = self.execute_instruction(instruction);
+        let elapsed = started.elapsed();
+        self.timings.push((label, elapsed));
+
+        let status = if result.is_ok() { "" } else { " FAILED" };
+        if self.context.is_verbose() {
+            self.context.progress(&format!(
+                "[{}/{}] done{} ({:.1}s)",
+                self.step,
+                self.total_steps,
+                status,
+                elapsed.as_secs_f64()
+            ));
+        } else {
+            println!("{} ({:.1}s)", status, elapsed.as_secs_f64());
+        }
+        result
+    }
+
+    /// Account for a step whose layer was restored from a snapshot
+    ///
+    /// Nothing is executed, but the instruction's effect on the build context
+    /// (ENV, WORKDIR, USER, ...) is reapplied for the steps that follow.
+    fn replay_step(&mut self, instruction: &Instruction) -> Result<()> {
+        self.step += 1;
+        let label = truncate(&instruction.to_string(), PROGRESS_WIDTH);
+        println!("[{}/{}] {} (cached)", self.step, self.total_steps, label);
+        self.timings.push((label, Duration::ZERO));
+
+        let dry_run = std::mem::replace(&mut self.dry_run, true);
+        let result = self.execute_instruction(instruction);
+        self.dry_run = dry_run;
+        result
+    }
+
+    /// Print per-step timings and the total build time
+    fn print_summary(&self, total: Duration, succeeded: bool) {
+        if self.timings.is_empty() {
+            return;
+        }
+        println!();
+        println!("{:<8} {:>8}  INSTRUCTION", "STEP", "TIME");
+        for (index, (label, elapsed)) in self.timings.iter().enumerate() {
+            let step = format!("{}/{}", index + 1, self.total_steps);
+            println!("{:<8} {:>7.1}s  {}", step, elapsed.as_secs_f64(), label);
+        }
+        let outcome = if succeeded { "Total" } else { "Failed" };
+        println!("{:<8} {:>7.1}s", outcome, total.as_secs_f64());
+    }
+
+    /// Build an intermediate stage into its own scratch root
+    fn execute_stage(&mut self, index: usize, stage: &Stage<'_>) -> Result<()> {
+        let label = stage.name.map(String::from).unwrap_or_else(|| index.to_string());
+        let target = self.context.target_path();
+        let root = target.with_file_name(format!(
+            ".{}.stage-{}",
+            target.file_name().and_then(|n| n.to_str()).unwrap_or("build"),
+            label
+        ));
+        self.context
+            .log(&format!("Building stage '{}' in {}", label, root.display()));
+
+        // Record the root first so it is removed even if provisioning fails
+        self.stage_roots.push((stage.name.map(String::from), root.clone()));
+        if !self.dry_run {
+            self.provision_stage_root(stage.release, &root)?;
+        }
+
+        let stage_context = self.context.for_stage(&root);
+        let final_context = std::mem::replace(&mut self.context, stage_context);
+        let result = stage
+            .instructions
+            .iter()
+            .try_for_each(|instruction| self.execute_step(instruction));
+        // The stage's devfs must not outlive it
+        self.mounts.release();
+        self.context = final_context;
+        result
+    }
+
+    /// Populate a scratch root from a bootstrapped release
+    fn provision_stage_root(&self, release: Option<&str>, root: &Path) -> Result<()> {
+        let stage_error = |message: String| Error::BuildFailed {
+            step: "FROM".to_string(),
+            message,
+        };
+        let release =
+            release.ok_or_else(|| stage_error("Build stage has no FROM release".to_string()))?;
+        let releases_dir = self
+            .releases_dir
+            .as_ref()
+            .ok_or_else(|| stage_error("No releases directory for multi-stage build".to_string()))?;
+        let release_path = releases_dir.join(release);
+        if !release_path.exists() {
+            return Err(stage_error(format!(
+                "Release '{}' is not bootstrapped (run `blackship bootstrap {}`)",
+                release, release
+            )));
+        }
+
+        if root.exists() {
+            unmount_stale(root, self.context.jail_name());
+            remove_tree(root);
+        }
+        fs::create_dir_all(root).map_err(|e| {
+            stage_error(format!("Failed to create {}: {}", root.display(), e))
+        })?;
+        let status = std::process::Command::new("cp")
+            .arg("-a")
+            .arg(format!("{}/.", release_path.display()))
+            .arg(root)
+            .status()
+            .map_err(|e| stage_error(format!("Failed to copy base release: {}", e)))?;
+        if !status.success() {
+            return Err(stage_error("cp command failed".to_string()));
+        }
+        Ok(())
+    }
+
+    /// Remove the scratch roots of intermediate stages
+    fn remove_stage_roots(&mut self) {
+        for (_, root) in self.stage_roots.drain(..) {
+            if root.exists() {
+                remove_tree(&root);
+            }
+        }
+    }
+
+    /// Root of a completed stage, by name or index
+    fn stage_root(&self, stage: &str) -> Result<&Path> {
+        self.stage_roots
+            .iter()
+            .enumerate()
+            .find(|(index, (name, _))| name.as_deref() == Some(stage) || index.to_string() == stage)
+            .map(|(_, (_, root))| root.as_path())
+            .ok_or_else(|| Error::BuildFailed {
+                step: "COPY".to_string(),
+                message: format!(
+                    "Unknown build stage '{}' (stages must be built before they are copied from)",
+                    stage
+                ),
+            })
+    }
+
     /// Execute a single instruction
     fn execute_instruction(&mut self, instruction: &Instruction) -> Result<()> {
         match instruction {
-            Instruction::From(release) => {
-                self.context.log(&format!("FROM {}", release));
+            Instruction::From(release, stage) => {
+                match stage {
+                    Some(stage) => self.context.log(&format!("FROM {} AS {}", release, stage)),
+                    None => self.context.log(&format!("FROM {}", release)),
+                }
                 // FROM is handled at a higher level (bootstrap)
                 // The jail root should already be populated from the base release
             }
@@ -91,17 +492,22 @@ impl TemplateExecutor {
                 self.context.set_env(name, &value);
             }
 
-            Instruction::Run(command) => {
+            Instruction::Run(command, timeout) => {
                 let command = self.context.substitute(command);
                 self.context.log(&format!("RUN {}", command));
                 if !self.dry_run {
-                    self.execute_run(&command)?;
+                    self.execute_run(&command, timeout.unwrap_or(self.run_timeout))?;
                 }
             }
 
             Instruction::Copy(spec) => {
-                self.context
-                    .log(&format!("COPY {} -> {}", spec.src, spec.dest));
+                let from = spec.from.as_ref().map(|s| format!("--from={} ", s));
+                self.context.log(&format!(
+                    "COPY {}{} -> {}",
+                    from.unwrap_or_default(),
+                    spec.src,
+                    spec.dest
+                ));
                 if !self.dry_run {
                     self.execute_copy(spec)?;
                 }
@@ -178,41 +584,67 @@ impl TemplateExecutor {
         Ok(())
     }
 
-    /// Execute a RUN command inside the jail
-    fn execute_run(&self, command: &str) -> Result<()> {
-        let target_path = self.context.target_path();
-        let dev_path = target_path.join("dev");
-        let resolv_path = target_path.join("etc/resolv.conf");
-
-        // Copy host resolv.conf if jail doesn't have one
-        if !resolv_path.exists()
-            && let Ok(content) = fs::read_to_string("/etc/resolv.conf") {
-                let _ = fs::write(&resolv_path, content);
+    /// Mount devfs on the build root's `/dev` (once per build) and restrict
+    /// it with the configured ruleset
+    fn ensure_devfs(&mut self) -> Result<()> {
+        let dev_path = self.context.target_path().join("dev");
+        if self.mounts.contains(&dev_path) || dev_path.join("null").exists() {
+            return Ok(());
+        }
+        std::fs::create_dir_all(&dev_path).ok();
+
+        // Use native mount(2) syscall instead of spawning process
+        let fstype = CString::new("devfs").unwrap();
+        let from = CString::new("devfs").unwrap();
+        let to = CString::new(dev_path.to_str().unwrap()).unwrap();
+
+        let result = unsafe {
+            libc::mount(
+                from.as_ptr(),
+                to.as_ptr(),
+                0, // flags
+                fstype.as_ptr() as *mut libc::c_void,
+            )
+        };
+
+        if result != 0 {
+            eprintln!("Warning: Failed to mount devfs: {}", std::io::Error::last_os_error());
+            return Ok(());
+        }
+        self.mounts.push(dev_path.clone());
+
+        if self.devfs_ruleset != 0 {
+            // Equivalent of `devfs -m <dev> ruleset N`
+            let applied = fs::File::open(&dev_path).and_then(|dir| {
+                let mut ruleset = self.devfs_ruleset;
+                // SAFETY: DEVFSIO_SUSE reads a devfs_rsnum (u16) from a valid pointer
+                let rc = unsafe { libc::ioctl(dir.as_raw_fd(), DEVFSIO_SUSE, &mut ruleset) };
+                if rc == 0 { Ok(()) } else { Err(std::io::Error::last_os_error()) }
+            });
+            if let Err(e) = applied {
+                // Never hand RUN commands an unrestricted /dev
+                self.mounts.release();
+                return Err(Error::BuildFailed {
+                    step: "RUN".to_string(),
+                    message: format!(
+                        "Failed to apply devfs ruleset {} (is it loaded? see devfs.rules(5)): {}",
+                        self.devfs_ruleset, e
+                    ),
+                });
             }
+        }
 
-        // Mount devfs for the chroot environment
-        let need_devfs = !dev_path.join("null").exists();
-        if need_devfs {
-            std::fs::create_dir_all(&dev_path).ok();
-
-            // Use native mount(2) syscall instead of spawning process
-            let fstype = CString::new("devfs").unwrap();
-            let from = CString::new("devfs").unwrap();
-            let to = CString::new(dev_path.to_str().unwrap()).unwrap();
-
-            let result = unsafe {
-                libc::mount(
-                    from.as_ptr(),
-                    to.as_ptr(),
-                    0, // flags
-                    fstype.as_ptr() as *mut libc::c_void,
-                )
-            };
+        Ok(())
+    }
 
-            if result != 0 {
-                eprintln!("Warning: Failed to mount devfs: {}", std::io::Error::last_os_error());
-            }
-        }
+    /// Execute a RUN command inside the jail, killing it after `timeout_secs`
+    /// (0 = no timeout)
+    fn execute_run(&mut self, command: &str, timeout_secs: u64) -> Result<()> {
+        let target_path = self.context.target_path().to_path_buf();
+        self.refresh_resolv_conf(&target_path);
+
+        // Mount devfs for the chroot environment
+        self.ensure_devfs()?;
 
         // Use native chroot(2) syscall to run command in jail environment
         let env_vars: Vec<(String, String)> = self
@@ -222,41 +654,64 @@ impl TemplateExecutor {
             .map(|(k, v)| (k.clone(), v.clone()))
             .collect();
 
-        let result = chroot_exec(target_path.to_str().unwrap(), command, &env_vars);
-
-        // Unmount devfs if we mounted it
-        if need_devfs {
-            // Use native unmount(2) syscall instead of spawning process
-            let path = CString::new(dev_path.to_str().unwrap()).unwrap();
-            unsafe {
-                libc::unmount(path.as_ptr(), 0);
-            }
-        }
+        // Verbose builds stream output live instead of echoing it afterwards
+        let stream = self.context.is_verbose();
+        let result = chroot_exec_with_timeout(
+            target_path.to_str().unwrap(),
+            command,
+            &env_vars,
+            stream,
+            timeout_secs,
+            self.hostname.as_deref(),
+        );
 
         let (exit_code, stdout, stderr) = result.map_err(|e| Error::BuildFailed {
             step: "RUN".to_string(),
-            message: format!("Failed to execute chroot: {}", e),
+            message: match e {
+                Error::JailTimeout(secs) => {
+                    format!("Command timed out after {}s: {}", secs, command)
+                }
+                e => format!("Failed to execute chroot: {}", e),
+            },
         })?;
 
         if exit_code != 0 {
             let stderr_str = String::from_utf8_lossy(&stderr);
+            if !stream {
+                // Show what the command printed before it failed
+                for line in String::from_utf8_lossy(&stdout).lines() {
+                    println!("  {}", line);
+                }
+            }
             return Err(Error::BuildFailed {
                 step: "RUN".to_string(),
                 message: format!("Command failed with exit code {}: {}", exit_code, stderr_str),
             });
         }
 
-        // Print stdout if verbose
-        if self.context.is_verbose() {
-            let stdout_str = String::from_utf8_lossy(&stdout);
-            if !stdout_str.is_empty() {
-                for line in stdout_str.lines() {
-                    println!("  {}", line);
-                }
+        Ok(())
+    }
+
+    /// Write the build root's resolv.conf from the configured nameservers or
+    /// the host's, so RUN steps resolve names like the host does
+    fn refresh_resolv_conf(&self, root: &Path) {
+        let content = if self.nameservers.is_empty() {
+            match fs::read_to_string("/etc/resolv.conf") {
+                Ok(content) => content,
+                Err(_) => return,
             }
-        }
+        } else {
+            resolv_conf(&self.nameservers)
+        };
 
-        Ok(())
+        let resolv_path = root.join("etc/resolv.conf");
+        // Never write through a symlink that could point outside the root
+        if resolv_path.is_symlink() {
+            let _ = fs::remove_file(&resolv_path);
+        }
+        if let Err(e) = fs::write(&resolv_path, content) {
+            eprintln!("Warning: Failed to write {}: {}", resolv_path.display(), e);
+        }
     }
 
     /// Execute a COPY instruction
@@ -264,7 +719,10 @@ impl TemplateExecutor {
         let src = self.context.substitute(&spec.src);
         let dest = self.context.substitute(&spec.dest);
 
-        let src_path = self.context.resolve_source(&src);
+        let src_path = match &spec.from {
+            Some(stage) => self.stage_root(stage)?.join(src.trim_start_matches('/')),
+            None => self.context.resolve_source(&src),
+        };
         let dest_path = self.context.resolve_dest(&dest);
 
         // Ensure source exists
@@ -275,6 +733,19 @@ impl TemplateExecutor {
             });
         }
 
+        // .jailignore applies to the build context, not to other stages
+        let exclusions = spec.from.is_none().then_some(&self.context);
+        let is_dir = src_path.is_dir();
+        if exclusions.is_some()
+            && self.context.is_ignored(&src_path, is_dir)
+            && !(is_dir && self.context.ignore_has_negations())
+        {
+            return Err(Error::BuildFailed {
+                step: "COPY".to_string(),
+                message: format!("Source {} is excluded by .jailignore", src_path.display()),
+            });
+        }
+
         // Create destination parent directory if needed
         if let Some(parent) = dest_path.parent()
             && !parent.exists() {
@@ -285,8 +756,8 @@ impl TemplateExecutor {
             }
 
         // Copy file or directory
-        if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dest_path)?;
+        if is_dir {
+            copy_dir_recursive(&src_path, &dest_path, exclusions)?;
         } else {
             // If dest ends with /, treat as directory
             let final_dest = if dest.ends_with('/') {
@@ -340,9 +811,28 @@ impl TemplateExecutor {
     }
 }
 
+/// Shorten text to at most `max` characters, marking the cut with "..."
+fn truncate(text: &str, max: usize) -> String {
+    if text.chars().count() <= max {
+        text.to_string()
+    } else {
+        let kept: String = text.chars().take(max.saturating_sub(3)).collect();
+        format!("{}...", kept)
+    }
+}
+
 /// Recursively copy a directory
-fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
-    if !dest.exists() {
+///
+/// With a build context, paths excluded by its `.jailignore` are skipped.
+/// Excluded directories are still walked when the rules re-include paths,
+/// but only the re-included files (and their parents) are created.
+fn copy_dir_recursive(src: &Path, dest: &Path, exclusions: Option<&BuildContext>) -> Result<()> {
+    let ignored = |path: &Path, is_dir: bool| {
+        exclusions.is_some_and(|ctx| ctx.is_ignored(path, is_dir))
+    };
+    let walk_ignored = exclusions.is_some_and(|ctx| ctx.ignore_has_negations());
+
+    if !ignored(src, true) && !dest.exists() {
         fs::create_dir_all(dest).map_err(|e| Error::BuildFailed {
             step: "COPY".to_string(),
             message: format!("Failed to create directory {}: {}", dest.display(), e),
@@ -362,8 +852,20 @@ fn copy_dir_recursive(src: &Path, dest: &Path) -> Result<()> {
         let dest_path = dest.join(entry.file_name());
 
         if src_path.is_dir() {
-            copy_dir_recursive(&src_path, &dest_path)?;
+            if ignored(&src_path, true) && !walk_ignored {
+                continue;
+            }
+            copy_dir_recursive(&src_path, &dest_path, exclusions)?;
         } else {
+            if ignored(&src_path, false) {
+                continue;
+            }
+            if !dest.exists() {
+                fs::create_dir_all(dest).map_err(|e| Error::BuildFailed {
+                    step: "COPY".to_string(),
+                    message: format!("Failed to create directory {}: {}", dest.display(), e),
+                })?;
+            }
             fs::copy(&src_path, &dest_path).map_err(|e| Error::BuildFailed {
                 step: "COPY".to_string(),
                 message: format!(
@@ -454,6 +956,23 @@ mod tests {
         assert_eq!(executor.context().jail_name(), "test");
     }
 
+    #[test]
+    fn test_step_numbering() {
+        let ctx = BuildContext::new(Path::new("/build/context"), Path::new("/jails/test"), "test");
+        let mut executor = TemplateExecutor::new(ctx).dry_run(true);
+        let mut jailfile = Jailfile::from_release("14.2-RELEASE").run("echo one");
+        jailfile.instructions.push(Instruction::Comment("not a step".to_string()));
+        let jailfile = jailfile.run("echo two");
+
+        executor.execute(&jailfile).unwrap();
+        assert_eq!(executor.total_steps, 3);
+        assert_eq!(executor.timings.len(), 3);
+        assert_eq!(executor.timings[2].0, "RUN echo two");
+
+        assert_eq!(truncate("RUN short", 20), "RUN short");
+        assert_eq!(truncate("RUN a very long command", 12), "RUN a ver...");
+    }
+
     #[test]
     fn test_dry_run_mode() {
         let ctx = BuildContext::new(
@@ -471,6 +990,75 @@ mod tests {
         assert!(result.is_ok());
     }
 
+    #[test]
+    #[ignore] // Requires root (mounts devfs)
+    fn test_failed_run_leaves_no_mounts() {
+        let root = std::env::temp_dir().join(format!("blackship-build-{}", std::process::id()));
+        fs::create_dir_all(root.join("etc")).unwrap();
+
+        // No /bin/sh in the root, so the RUN step fails after devfs is mounted
+        let ctx = BuildContext::new(Path::new("/build/context"), &root, "test");
+        let mut executor = TemplateExecutor::new(ctx).devfs_ruleset(0);
+        let jailfile = Jailfile::from_release("14.2-RELEASE").run("exit 1");
+        assert!(executor.execute(&jailfile).is_err());
+
+        assert!(mounts_under(&root).unwrap().is_empty());
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_refresh_resolv_conf() {
+        let root = std::env::temp_dir().join(format!("blackship-resolv-{}", std::process::id()));
+        fs::create_dir_all(root.join("etc")).unwrap();
+        fs::write(root.join("etc/resolv.conf"), "nameserver 192.0.2.1\n").unwrap();
+
+        let ctx = BuildContext::new(Path::new("/build/context"), &root, "test");
+        let nameservers: Vec<IpAddr> = vec!["1.1.1.1".parse().unwrap(), "::1".parse().unwrap()];
+        let executor = TemplateExecutor::new(ctx).nameservers(&nameservers);
+        executor.refresh_resolv_conf(&root);
+
+        // An existing resolv.conf is replaced, not kept
+        let content = fs::read_to_string(root.join("etc/resolv.conf")).unwrap();
+        assert_eq!(content, "nameserver 1.1.1.1\nnameserver ::1\n");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_copy_respects_jailignore() {
+        let root = std::env::temp_dir().join(format!("blackship-ignore-{}", std::process::id()));
+        let context = root.join("context");
+        let target = root.join("target");
+        fs::create_dir_all(context.join("app/.git")).unwrap();
+        fs::create_dir_all(context.join("app/build")).unwrap();
+        fs::create_dir_all(&target).unwrap();
+        fs::write(context.join(".jailignore"), ".git\nbuild/\n!build/app.bin\n*.log\n").unwrap();
+        fs::write(context.join("app/main.c"), "int main;").unwrap();
+        fs::write(context.join("app/debug.log"), "noise").unwrap();
+        fs::write(context.join("app/.git/HEAD"), "ref").unwrap();
+        fs::write(context.join("app/build/main.o"), "obj").unwrap();
+        fs::write(context.join("app/build/app.bin"), "bin").unwrap();
+        fs::write(context.join("notes.log"), "noise").unwrap();
+
+        let ctx = BuildContext::new(&context, &target, "test");
+        let mut executor = TemplateExecutor::new(ctx);
+        let jailfile = Jailfile::new().copy("app", "/src");
+        executor.execute(&jailfile).unwrap();
+
+        assert!(target.join("src/main.c").exists());
+        assert!(!target.join("src/debug.log").exists());
+        assert!(!target.join("src/.git").exists());
+        assert!(!target.join("src/build/main.o").exists());
+        // `!build/app.bin` re-includes the file below the nested app/build/
+        assert!(target.join("src/build/app.bin").exists());
+
+        // Copying an excluded file directly is an error
+        let jailfile = Jailfile::new().copy("notes.log", "/notes.log");
+        assert!(executor.execute(&jailfile).is_err());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
     #[test]
     fn test_variable_substitution_in_instructions() {
         let ctx = BuildContext::new(
@@ -0,0 +1,242 @@
+//! Jailfile generation from an existing jail root
+//!
+//! The reverse of a build, and a heuristic one: the result is a starting
+//! point for codifying a hand-built jail and needs review before use.
+//! - `FROM` is the jail's release
+//! - Explicitly installed packages (`pkg query`) become one `RUN pkg install`
+//! - Configuration files that differ from the release (under `etc` and
+//!   `usr/local/etc`) are copied into the build context and `COPY`ed back
+//! - Credentials, host keys and generated databases are never copied
+
+use crate::blueprint::instructions::{CopySpec, Instruction};
+use crate::error::{Error, Result};
+use crate::provision::Drift;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+/// Directory in the build context holding captured configuration files
+pub const FILES_DIR: &str = "files";
+
+/// Configuration trees whose changes are captured
+const CONFIG_DIRS: &[&str] = &["etc", "usr/local/etc"];
+
+/// Files that are secrets or generated, and must not end up in a template
+const NEVER_COPIED: &[&str] = &[
+    "etc/master.passwd",
+    "etc/passwd",
+    "etc/group",
+    "etc/pwd.db",
+    "etc/spwd.db",
+    "etc/login.conf.db",
+    "etc/resolv.conf",
+    "etc/hosts",
+    "etc/localtime",
+];
+
+/// What was found in a jail root
+#[derive(Debug, Clone, Default)]
+pub struct GeneratedJailfile {
+    /// Release for `FROM`
+    pub release: String,
+    /// Explicitly installed packages
+    pub packages: Vec<String>,
+    /// Changed configuration files, relative to the jail root
+    pub config_files: Vec<PathBuf>,
+    /// Things the generator could not capture, emitted as comments
+    pub notes: Vec<String>,
+}
+
+impl GeneratedJailfile {
+    /// Start a Jailfile for `release`
+    pub fn new(release: &str) -> Self {
+        Self {
+            release: release.to_string(),
+            ..Default::default()
+        }
+    }
+
+    /// Add the configuration files from a comparison against the release
+    pub fn with_drift(mut self, drift: &Drift) -> Self {
+        self.config_files = config_files(drift);
+        if drift.added.iter().chain(&drift.modified).any(|p| is_never_copied(p)) {
+            self.notes.push(
+                "users, groups and other credentials were not captured; \
+                 recreate them with RUN pw useradd/groupadd"
+                    .to_string(),
+            );
+        }
+        self
+    }
+
+    /// Jailfile text
+    pub fn render(&self) -> String {
+        let mut out = String::from(
+            "# Generated by `blackship template generate` from an existing jail.\n\
+             # This is a best-effort reconstruction: review it before building.\n",
+        );
+        for note in &self.notes {
+            out.push_str(&format!("# NOTE: {}\n", note));
+        }
+        out.push('\n');
+
+        let mut instructions = vec![Instruction::From(self.release.clone(), None)];
+        if !self.packages.is_empty() {
+            instructions.push(Instruction::Run(
+                format!("pkg install -y {}", self.packages.join(" ")),
+                None,
+            ));
+        }
+        for file in &self.config_files {
+            let src = Path::new(FILES_DIR).join(file);
+            let dest = Path::new("/").join(file);
+            instructions.push(Instruction::Copy(CopySpec::new(
+                &src.to_string_lossy(),
+                &dest.to_string_lossy(),
+            )));
+        }
+        for instruction in instructions {
+            out.push_str(&format!("{}\n", instruction));
+        }
+        out
+    }
+
+    /// Write `Jailfile` and the captured files into `out_dir`
+    pub fn write(&self, jail_root: &Path, out_dir: &Path) -> Result<PathBuf> {
+        for file in &self.config_files {
+            let dest = out_dir.join(FILES_DIR).join(file);
+            if let Some(parent) = dest.parent() {
+                fs::create_dir_all(parent)?;
+            }
+            fs::copy(jail_root.join(file), &dest)?;
+        }
+        let jailfile = out_dir.join("Jailfile");
+        fs::write(&jailfile, self.render())?;
+        Ok(jailfile)
+    }
+}
+
+/// Packages installed explicitly (not as dependencies) in a jail root
+pub fn installed_packages(jail_root: &Path) -> Result<Vec<String>> {
+    if !jail_root.join("var/db/pkg/local.sqlite").exists() {
+        return Ok(Vec::new());
+    }
+    let output = Command::new("pkg")
+        .arg("-r")
+        .arg(jail_root)
+        .args(["query", "-e", "%a = 0", "%n"])
+        .output()
+        .map_err(|e| Error::CommandFailed {
+            command: "pkg query".to_string(),
+            message: e.to_string(),
+        })?;
+    if !output.status.success() {
+        return Err(Error::CommandFailed {
+            command: "pkg query".to_string(),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        });
+    }
+    let mut packages: Vec<String> = String::from_utf8_lossy(&output.stdout)
+        .lines()
+        .map(str::trim)
+        .filter(|name| !name.is_empty() && *name != "pkg")
+        .map(str::to_string)
+        .collect();
+    packages.sort();
+    Ok(packages)
+}
+
+/// Release a jail root was installed from, read from `/bin/freebsd-version`
+///
+/// Patch levels are dropped (`15.0-RELEASE-p2` gives `15.0-RELEASE`).
+pub fn userland_release(jail_root: &Path) -> Option<String> {
+    let script = fs::read_to_string(jail_root.join("bin/freebsd-version")).ok()?;
+    let version = script
+        .lines()
+        .find_map(|line| line.trim().strip_prefix("USERLAND_VERSION="))?
+        .trim_matches('"');
+    let release = match version.rsplit_once("-p") {
+        Some((release, patch)) if patch.chars().all(|c| c.is_ascii_digit()) => release,
+        _ => version,
+    };
+    Some(release.to_string())
+}
+
+/// Changed or added configuration files worth carrying into a template
+fn config_files(drift: &Drift) -> Vec<PathBuf> {
+    let mut files: Vec<PathBuf> = drift
+        .modified
+        .iter()
+        .chain(&drift.added)
+        .filter(|path| CONFIG_DIRS.iter().any(|dir| path.starts_with(dir)))
+        .filter(|path| !is_never_copied(path))
+        .filter(|path| path.extension().is_none_or(|ext| ext != "sample"))
+        .cloned()
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether a path holds secrets or generated data
+fn is_never_copied(path: &Path) -> bool {
+    NEVER_COPIED.iter().any(|p| path == Path::new(p))
+        || path.starts_with("etc/ssh")
+            && path
+                .file_name()
+                .is_some_and(|name| name.to_string_lossy().starts_with("ssh_host_"))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::blueprint::parse_jailfile;
+
+    #[test]
+    fn test_config_files() {
+        let drift = Drift {
+            modified: vec![
+                PathBuf::from("etc/rc.conf"),
+                PathBuf::from("etc/master.passwd"),
+                PathBuf::from("bin/sh"),
+            ],
+            added: vec![
+                PathBuf::from("usr/local/etc/nginx/nginx.conf"),
+                PathBuf::from("usr/local/etc/nginx/nginx.conf.sample"),
+                PathBuf::from("etc/ssh/ssh_host_ed25519_key"),
+            ],
+            removed: Vec::new(),
+        };
+        let generated = GeneratedJailfile::new("15.0-RELEASE").with_drift(&drift);
+        assert_eq!(
+            generated.config_files,
+            vec![PathBuf::from("etc/rc.conf"), PathBuf::from("usr/local/etc/nginx/nginx.conf")]
+        );
+        assert_eq!(generated.notes.len(), 1);
+    }
+
+    #[test]
+    fn test_render_parses() {
+        let mut generated = GeneratedJailfile::new("15.0-RELEASE");
+        generated.packages = vec!["nginx".to_string(), "curl".to_string()];
+        generated.config_files = vec![PathBuf::from("etc/rc.conf")];
+
+        let text = generated.render();
+        assert!(text.contains("RUN pkg install -y nginx curl"), "{}", text);
+        assert!(text.contains("COPY files/etc/rc.conf /etc/rc.conf"), "{}", text);
+        let jailfile = parse_jailfile(&text).unwrap();
+        assert_eq!(jailfile.from.as_deref(), Some("15.0-RELEASE"));
+    }
+
+    #[test]
+    fn test_userland_release() {
+        let root = std::env::temp_dir().join(format!("blackship-generate-{}", std::process::id()));
+        fs::create_dir_all(root.join("bin")).unwrap();
+        fs::write(
+            root.join("bin/freebsd-version"),
+            "#!/bin/sh\nUSERLAND_VERSION=\"15.0-RELEASE-p2\"\n",
+        )
+        .unwrap();
+        assert_eq!(userland_release(&root).as_deref(), Some("15.0-RELEASE"));
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -0,0 +1,205 @@
+//! Build context exclusions (`.jailignore`)
+//!
+//! One glob pattern per line, relative to the build context directory:
+//! - `*` and `?` match within a path segment, `**` matches any number of segments
+//! - A pattern without a `/` (other than a trailing one) matches at any depth;
+//!   a pattern with a leading or inner `/` is anchored to the context root
+//! - A trailing `/` only matches directories
+//! - `!pattern` re-includes paths excluded by an earlier pattern
+//! - Blank lines and lines starting with `#` are ignored
+//!
+//! The last matching pattern wins, and excluding a directory excludes
+//! everything below it.
+
+use crate::util::glob_match;
+use std::path::{Component, Path};
+
+/// Name of the exclusions file in the build context
+pub const IGNORE_FILE: &str = ".jailignore";
+
+/// A single `.jailignore` pattern
+#[derive(Debug, Clone)]
+struct Rule {
+    /// Pattern segments (split on `/`)
+    segments: Vec<String>,
+    /// Re-include instead of exclude
+    negate: bool,
+    /// Only matches directories
+    dir_only: bool,
+}
+
+impl Rule {
+    /// Parse one line, returning None for blanks and comments
+    fn parse(line: &str) -> Option<Self> {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') {
+            return None;
+        }
+        let (negate, pattern) = match line.strip_prefix('!') {
+            Some(rest) => (true, rest.trim_start()),
+            None => (false, line),
+        };
+        let dir_only = pattern.ends_with('/');
+        let anchored = pattern.trim_end_matches('/').contains('/');
+        let pattern = pattern.trim_matches('/');
+        if pattern.is_empty() {
+            return None;
+        }
+
+        let mut segments: Vec<String> = pattern
+            .split('/')
+            .filter(|s| !s.is_empty() && *s != ".")
+            .map(String::from)
+            .collect();
+        if segments.is_empty() {
+            return None;
+        }
+        // Single-segment patterns match at any depth
+        if !anchored && segments[0] != "**" {
+            segments.insert(0, "**".to_string());
+        }
+
+        Some(Self { segments, negate, dir_only })
+    }
+
+    /// Whether the rule matches a path given as its segments
+    fn matches(&self, path: &[&str], is_dir: bool) -> bool {
+        (is_dir || !self.dir_only) && match_segments(&self.segments, path)
+    }
+}
+
+/// Match pattern segments against path segments, expanding `**`
+fn match_segments(pattern: &[String], path: &[&str]) -> bool {
+    match pattern.split_first() {
+        None => path.is_empty(),
+        Some((first, rest)) if first == "**" => {
+            (0..=path.len()).any(|skip| match_segments(rest, &path[skip..]))
+        }
+        Some((first, rest)) => match path.split_first() {
+            Some((segment, path_rest)) => {
+                glob_match(first, segment) && match_segments(rest, path_rest)
+            }
+            None => false,
+        },
+    }
+}
+
+/// Parsed `.jailignore` rules
+#[derive(Debug, Clone, Default)]
+pub struct IgnoreRules {
+    rules: Vec<Rule>,
+}
+
+impl IgnoreRules {
+    /// Parse `.jailignore` content
+    pub fn parse(content: &str) -> Self {
+        Self {
+            rules: content.lines().filter_map(Rule::parse).collect(),
+        }
+    }
+
+    /// Load `.jailignore` from a context directory (empty if there is none)
+    pub fn load(context_dir: &Path) -> Self {
+        std::fs::read_to_string(context_dir.join(IGNORE_FILE))
+            .map(|content| Self::parse(&content))
+            .unwrap_or_default()
+    }
+
+    /// Whether there are no rules
+    pub fn is_empty(&self) -> bool {
+        self.rules.is_empty()
+    }
+
+    /// Whether any rule re-includes paths (so excluded directories must
+    /// still be walked)
+    pub fn has_negations(&self) -> bool {
+        self.rules.iter().any(|r| r.negate)
+    }
+
+    /// Whether a path relative to the context directory is excluded
+    pub fn is_ignored(&self, relative: &Path, is_dir: bool) -> bool {
+        let segments: Vec<&str> = relative
+            .components()
+            .filter_map(|c| match c {
+                Component::Normal(s) => s.to_str(),
+                _ => None,
+            })
+            .collect();
+        if segments.is_empty() {
+            return false;
+        }
+
+        let mut ignored = false;
+        for rule in &self.rules {
+            // A rule matching the path itself or any of its parent directories
+            let matched = (1..=segments.len()).any(|len| {
+                let is_parent = len < segments.len();
+                rule.matches(&segments[..len], is_parent || is_dir)
+            });
+            if matched {
+                ignored = !rule.negate;
+            }
+        }
+        ignored
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_nested_patterns() {
+        let rules = IgnoreRules::parse(
+            "# VCS and build output\n.git\ntarget/\n**/*.o\ndocs/*.md\n",
+        );
+
+        assert!(rules.is_ignored(Path::new(".git"), true));
+        assert!(rules.is_ignored(Path::new(".git/config"), false));
+        assert!(rules.is_ignored(Path::new("vendor/lib/.git/HEAD"), false));
+        assert!(rules.is_ignored(Path::new("target/release/app"), false));
+        // Directory-only pattern does not match a file with that name
+        assert!(!rules.is_ignored(Path::new("src/target"), false));
+        assert!(rules.is_ignored(Path::new("src/net/socket.o"), false));
+        assert!(rules.is_ignored(Path::new("docs/README.md"), false));
+        // `*` does not cross directories
+        assert!(!rules.is_ignored(Path::new("docs/api/index.md"), false));
+        // A pattern with an inner `/` is anchored to the context root
+        assert!(!rules.is_ignored(Path::new("site/docs/index.md"), false));
+        assert!(!rules.is_ignored(Path::new("src/main.c"), false));
+    }
+
+    #[test]
+    fn test_negation() {
+        let rules = IgnoreRules::parse("logs/\n!logs/keep.log\n*.tmp\n!important.tmp\n");
+
+        assert!(rules.is_ignored(Path::new("logs/debug.log"), false));
+        assert!(!rules.is_ignored(Path::new("logs/keep.log"), false));
+        assert!(rules.is_ignored(Path::new("scratch.tmp"), false));
+        assert!(!rules.is_ignored(Path::new("important.tmp"), false));
+        assert!(rules.has_negations());
+
+        // Negations with an inner slash are anchored like any other pattern
+        let rules = IgnoreRules::parse("build/\n!build/app.bin\n");
+        assert!(rules.is_ignored(Path::new("app/build/main.o"), false));
+        assert!(!rules.is_ignored(Path::new("build/app.bin"), false));
+        assert!(rules.is_ignored(Path::new("app/build/app.bin"), false));
+
+        // A leading slash anchors the pattern to the context root
+        let rules = IgnoreRules::parse("/target/\n");
+        assert!(rules.is_ignored(Path::new("target/app"), false));
+        assert!(!rules.is_ignored(Path::new("crates/cli/target/app"), false));
+
+        // Last match wins
+        let rules = IgnoreRules::parse("!config.yml\n*.yml\n");
+        assert!(rules.is_ignored(Path::new("config.yml"), false));
+        assert!(rules.is_ignored(Path::new("a.yml"), false));
+    }
+
+    #[test]
+    fn test_empty_rules() {
+        let rules = IgnoreRules::parse("\n# only a comment\n");
+        assert!(rules.is_empty());
+        assert!(!rules.is_ignored(Path::new("anything"), false));
+    }
+}
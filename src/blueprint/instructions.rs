@@ -2,8 +2,10 @@
 //!
 //! Defines the instructions that can be used in a Jailfile.
 
+use crate::error::{Error, Result};
 use serde::Deserialize;
 use std::collections::HashMap;
+use std::fmt;
 
 /// Build argument definition
 #[derive(Debug, Clone, Deserialize)]
@@ -87,6 +89,9 @@ pub struct CopySpec {
     pub mode: Option<u32>,
     /// Owner (optional)
     pub owner: Option<String>,
+    /// Build stage to copy from instead of the build context (`COPY --from=<stage>`)
+    #[serde(default)]
+    pub from: Option<String>,
 }
 
 impl CopySpec {
@@ -97,9 +102,16 @@ impl CopySpec {
             dest: dest.to_string(),
             mode: None,
             owner: None,
+            from: None,
         }
     }
 
+    /// Copy from another build stage's root instead of the build context
+    pub fn from_stage(mut self, stage: &str) -> Self {
+        self.from = Some(stage.to_string());
+        self
+    }
+
     /// Set file mode (_unused: future feature)
     #[allow(dead_code)]
     pub fn with_mode(mut self, mode: u32) -> Self {
@@ -118,14 +130,16 @@ impl CopySpec {
 /// A single build instruction
 #[derive(Debug, Clone)]
 pub enum Instruction {
-    /// FROM <release> - Base release to build from
-    From(String),
+    /// FROM <release> [AS <stage>] - Base release to build from, starting a
+    /// (optionally named) build stage
+    From(String, Option<String>),
     /// ARG <name>[=<default>] - Build argument
     Arg(BuildArg),
     /// ENV <name>=<value> - Environment variable
     Env(String, String),
-    /// RUN <command> - Execute a command
-    Run(String),
+    /// RUN [--timeout=<secs>] <command> - Execute a command, killing it if it
+    /// runs longer than the timeout
+    Run(String, Option<u64>),
     /// COPY <src> <dest> - Copy files into jail
     Copy(CopySpec),
     /// WORKDIR <path> - Set working directory
@@ -151,10 +165,10 @@ impl Instruction {
     /// Get instruction name
     pub fn name(&self) -> &'static str {
         match self {
-            Instruction::From(_) => "FROM",
+            Instruction::From(_, _) => "FROM",
             Instruction::Arg(_) => "ARG",
             Instruction::Env(_, _) => "ENV",
-            Instruction::Run(_) => "RUN",
+            Instruction::Run(_, _) => "RUN",
             Instruction::Copy(_) => "COPY",
             Instruction::Workdir(_) => "WORKDIR",
             Instruction::Expose(_) => "EXPOSE",
@@ -168,6 +182,42 @@ impl Instruction {
     }
 }
 
+impl fmt::Display for Instruction {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.name())?;
+        match self {
+            Instruction::From(release, Some(stage)) => write!(f, " {} AS {}", release, stage),
+            Instruction::From(release, None) => write!(f, " {}", release),
+            Instruction::Arg(arg) => match &arg.default {
+                Some(default) => write!(f, " {}={}", arg.name, default),
+                None => write!(f, " {}", arg.name),
+            },
+            Instruction::Env(key, value) | Instruction::Label(key, value) => {
+                write!(f, " {}={}", key, value)
+            }
+            Instruction::Copy(spec) => {
+                if let Some(stage) = &spec.from {
+                    write!(f, " --from={}", stage)?;
+                }
+                write!(f, " {} {}", spec.src, spec.dest)
+            }
+            Instruction::Expose(port) => write!(f, " {}/{}", port.port, port.protocol),
+            Instruction::Run(command, timeout) => {
+                if let Some(secs) = timeout {
+                    write!(f, " --timeout={}", secs)?;
+                }
+                write!(f, " {}", command)
+            }
+            Instruction::Workdir(text)
+            | Instruction::Cmd(text)
+            | Instruction::Entrypoint(text)
+            | Instruction::User(text)
+            | Instruction::Volume(text)
+            | Instruction::Comment(text) => write!(f, " {}", text),
+        }
+    }
+}
+
 /// Jailfile metadata
 #[derive(Debug, Clone, Default, Deserialize)]
 pub struct JailfileMetadata {
@@ -186,6 +236,24 @@ pub struct JailfileMetadata {
     pub labels: HashMap<String, String>,
 }
 
+/// One build stage of a multi-stage Jailfile
+#[derive(Debug, Clone, Copy)]
+pub struct Stage<'a> {
+    /// Stage name from `FROM <release> AS <name>`
+    pub name: Option<&'a str>,
+    /// Base release of the stage
+    pub release: Option<&'a str>,
+    /// The stage's instructions, starting with its FROM
+    pub instructions: &'a [Instruction],
+}
+
+impl Stage<'_> {
+    /// Whether this stage is referred to by `name` (its name or its index)
+    pub fn matches(&self, name: &str, index: usize) -> bool {
+        self.name == Some(name) || index.to_string() == name
+    }
+}
+
 /// A parsed Jailfile
 #[derive(Debug, Clone)]
 pub struct Jailfile {
@@ -242,10 +310,19 @@ impl Jailfile {
     pub fn from_release(release: &str) -> Self {
         let mut jf = Self::new();
         jf.from = Some(release.to_string());
-        jf.instructions.push(Instruction::From(release.to_string()));
+        jf.instructions.push(Instruction::From(release.to_string(), None));
         jf
     }
 
+    /// Start a new named build stage
+    #[allow(dead_code)] // Public API for programmatic Jailfile creation
+    pub fn stage(mut self, release: &str, name: &str) -> Self {
+        self.from = Some(release.to_string());
+        self.instructions
+            .push(Instruction::From(release.to_string(), Some(name.to_string())));
+        self
+    }
+
     /// Add a build argument
     #[allow(dead_code)] // Public API for programmatic Jailfile creation
     pub fn arg(mut self, name: &str, default: Option<&str>) -> Self {
@@ -271,7 +348,7 @@ impl Jailfile {
     #[allow(dead_code)] // Public API for programmatic Jailfile creation
     pub fn run(mut self, command: &str) -> Self {
         self.instructions
-            .push(Instruction::Run(command.to_string()));
+            .push(Instruction::Run(command.to_string(), None));
         self
     }
 
@@ -330,6 +407,61 @@ impl Jailfile {
         self
     }
 
+    /// Split the instructions into build stages at each FROM
+    ///
+    /// Instructions before the first FROM (e.g. global ARGs) belong to the
+    /// first stage. A Jailfile with at most one FROM has a single stage.
+    pub fn stages(&self) -> Vec<Stage<'_>> {
+        let mut starts: Vec<usize> = self
+            .instructions
+            .iter()
+            .enumerate()
+            .filter(|(_, i)| matches!(i, Instruction::From(_, _)))
+            .map(|(idx, _)| idx)
+            .collect();
+        if starts.is_empty() {
+            starts.push(0);
+        }
+        starts[0] = 0;
+
+        starts
+            .iter()
+            .enumerate()
+            .map(|(n, &start)| {
+                let end = starts.get(n + 1).copied().unwrap_or(self.instructions.len());
+                let instructions = &self.instructions[start..end];
+                let (release, name) = instructions
+                    .iter()
+                    .find_map(|i| match i {
+                        Instruction::From(release, name) => {
+                            Some((Some(release.as_str()), name.as_deref()))
+                        }
+                        _ => None,
+                    })
+                    .unwrap_or((None, None));
+                Stage { name, release, instructions }
+            })
+            .collect()
+    }
+
+    /// Drop every stage after `target`, making it the final stage
+    pub fn truncate_to_stage(&mut self, target: &str) -> Result<()> {
+        let stages = self.stages();
+        let index = stages
+            .iter()
+            .enumerate()
+            .position(|(idx, stage)| stage.matches(target, idx))
+            .ok_or_else(|| {
+                Error::TemplateParseFailed(format!("No build stage named '{}'", target))
+            })?;
+        let end: usize = stages[..=index].iter().map(|s| s.instructions.len()).sum();
+        let release = stages[index].release.map(String::from);
+
+        self.instructions.truncate(end);
+        self.from = release;
+        Ok(())
+    }
+
     /// Get the base release
     #[allow(dead_code)] // Public API for Jailfile inspection
     pub fn base_release(&self) -> Option<&str> {
@@ -342,7 +474,7 @@ impl Jailfile {
         self.instructions
             .iter()
             .filter_map(|i| match i {
-                Instruction::Run(cmd) => Some(cmd.as_str()),
+                Instruction::Run(cmd, _) => Some(cmd.as_str()),
                 _ => None,
             })
             .collect()
@@ -405,10 +537,54 @@ mod tests {
         assert_eq!(jf.expose.len(), 1);
     }
 
+    #[test]
+    fn test_stages() {
+        let mut jf = Jailfile::new()
+            .arg("VERSION", Some("1.0"))
+            .stage("14.2-RELEASE", "builder")
+            .run("make")
+            .stage("14.2-RELEASE", "test")
+            .run("make test")
+            .stage("15.0-RELEASE", "final");
+        let spec = CopySpec::new("/src/app", "/usr/local/bin/").from_stage("builder");
+        jf.instructions.push(Instruction::Copy(spec));
+
+        let stages = jf.stages();
+        assert_eq!(stages.len(), 3);
+        assert_eq!(stages[0].name, Some("builder"));
+        // Global ARG belongs to the first stage
+        assert_eq!(stages[0].instructions.len(), 3);
+        assert!(stages[1].matches("test", 1));
+        assert!(stages[2].matches("2", 2));
+        assert_eq!(stages[2].release, Some("15.0-RELEASE"));
+
+        jf.truncate_to_stage("test").unwrap();
+        assert_eq!(jf.stages().len(), 2);
+        assert_eq!(jf.instructions.len(), 5);
+        assert!(jf.truncate_to_stage("missing").is_err());
+
+        // A single-stage Jailfile has one stage covering everything
+        let jf = Jailfile::from_release("14.2-RELEASE").run("true");
+        assert_eq!(jf.stages().len(), 1);
+        assert_eq!(jf.stages()[0].instructions.len(), 2);
+    }
+
     #[test]
     fn test_instruction_names() {
-        assert_eq!(Instruction::From("test".to_string()).name(), "FROM");
-        assert_eq!(Instruction::Run("test".to_string()).name(), "RUN");
+        assert_eq!(Instruction::From("test".to_string(), None).name(), "FROM");
+        assert_eq!(Instruction::Run("test".to_string(), None).name(), "RUN");
         assert_eq!(Instruction::Copy(CopySpec::new("a", "b")).name(), "COPY");
     }
+
+    #[test]
+    fn test_instruction_display() {
+        let from = Instruction::From("14.2-RELEASE".to_string(), Some("builder".to_string()));
+        assert_eq!(from.to_string(), "FROM 14.2-RELEASE AS builder");
+        assert_eq!(Instruction::Run("make".to_string(), None).to_string(), "RUN make");
+        let run = Instruction::Run("make check".to_string(), Some(600));
+        assert_eq!(run.to_string(), "RUN --timeout=600 make check");
+        let spec = CopySpec::new("/app", "/usr/local/bin/").from_stage("builder");
+        let copy = Instruction::Copy(spec);
+        assert_eq!(copy.to_string(), "COPY --from=builder /app /usr/local/bin/");
+    }
 }
@@ -0,0 +1,209 @@
+//! ZFS snapshots of build layers
+//!
+//! With `blackship build --snapshot-layers` on a ZFS-backed jail root, the
+//! root is snapshotted before the first step of the final stage and after
+//! every step that succeeds. Each snapshot name carries a key chained over
+//! the build arguments, the instructions and the COPY sources up to that
+//! step, so rebuilding after a failure rolls back to the last layer that
+//! still matches and resumes from there instead of starting over.
+//!
+//! Layer snapshots are pruned once a build succeeds.
+
+use crate::blueprint::context::BuildContext;
+use crate::blueprint::instructions::Instruction;
+use crate::error::Result;
+use crate::zfs::ZfsManager;
+use sha2::{Digest, Sha256};
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use std::time::UNIX_EPOCH;
+
+/// Prefix of layer snapshot names (`layer-<step>-<key>`)
+pub const LAYER_PREFIX: &str = "layer-";
+
+/// Layer snapshots of one jail's build root
+pub struct LayerSnapshots {
+    zfs: ZfsManager,
+    /// Jail whose dataset is the build root
+    jail: String,
+    /// Key of the base layer followed by the key after each step
+    keys: Vec<String>,
+}
+
+impl LayerSnapshots {
+    /// Layer snapshots for a jail's dataset
+    pub fn new(zfs: ZfsManager, jail: &str) -> Self {
+        Self {
+            zfs,
+            jail: jail.to_string(),
+            keys: Vec::new(),
+        }
+    }
+
+    /// Compute the layer keys for a build
+    ///
+    /// `prelude` holds instructions that are not layered themselves (earlier
+    /// stages) but whose changes invalidate every layer.
+    pub fn plan(
+        &mut self,
+        context: &BuildContext,
+        prelude: &[&Instruction],
+        steps: &[&Instruction],
+    ) {
+        self.keys = layer_keys(context, prelude, steps);
+    }
+
+    /// Snapshot name of a layer (0 = before the first step)
+    fn name(&self, layer: usize) -> String {
+        format!("{}{:03}-{}", LAYER_PREFIX, layer, &self.keys[layer][..12])
+    }
+
+    /// Names of the jail's existing layer snapshots
+    fn existing(&self) -> Result<HashSet<String>> {
+        Ok(self
+            .zfs
+            .list_snapshots(&self.jail)?
+            .into_iter()
+            .map(|s| s.name)
+            .filter(|name| name.starts_with(LAYER_PREFIX))
+            .collect())
+    }
+
+    /// Prepare the build root, returning the number of steps already done
+    ///
+    /// Rolls back to the last matching layer (destroying newer snapshots).
+    /// When no layer matches, the root is rolled back to the base layer of
+    /// the previous build (if any), stale layers are dropped and the root is
+    /// snapshotted as the new base layer.
+    pub fn resume(&self) -> Result<usize> {
+        let existing = self.existing()?;
+        let matching = (0..self.keys.len()).rev().find(|&l| existing.contains(&self.name(l)));
+        if let Some(layer) = matching {
+            self.zfs.rollback_snapshot(&self.jail, &self.name(layer), true)?;
+            return Ok(layer);
+        }
+
+        let base = format!("{}000-", LAYER_PREFIX);
+        if let Some(stale_base) = existing.iter().find(|name| name.starts_with(&base)) {
+            self.zfs.rollback_snapshot(&self.jail, stale_base, true)?;
+        }
+        self.prune()?;
+        self.snapshot(0)?;
+        Ok(0)
+    }
+
+    /// Snapshot the build root after a successful step
+    pub fn snapshot(&self, layer: usize) -> Result<()> {
+        self.zfs.create_snapshot(&self.jail, Some(&self.name(layer)))?;
+        Ok(())
+    }
+
+    /// Remove all layer snapshots of the jail
+    pub fn prune(&self) -> Result<()> {
+        for name in self.existing()? {
+            self.zfs.delete_snapshot(&self.jail, &name)?;
+        }
+        Ok(())
+    }
+}
+
+/// Chained keys: one for the base layer, then one after each step
+fn layer_keys(
+    context: &BuildContext,
+    prelude: &[&Instruction],
+    steps: &[&Instruction],
+) -> Vec<String> {
+    let mut hasher = Sha256::new();
+    let mut args: Vec<_> = context.args().iter().collect();
+    args.sort();
+    for (name, value) in args {
+        hasher.update(format!("ARG {}={}\n", name, value));
+    }
+    for instruction in prelude {
+        hasher.update(fingerprint(context, instruction));
+    }
+
+    let mut keys = vec![format!("{:x}", hasher.clone().finalize())];
+    for instruction in steps {
+        hasher.update(fingerprint(context, instruction));
+        keys.push(format!("{:x}", hasher.clone().finalize()));
+    }
+    keys
+}
+
+/// What an instruction depends on: its text, plus the size and mtime of
+/// every file it copies from the build context
+fn fingerprint(context: &BuildContext, instruction: &Instruction) -> String {
+    let mut text = format!("{}\n", instruction);
+    if let Instruction::Copy(spec) = instruction
+        && spec.from.is_none()
+    {
+        let src = context.resolve_source(&context.substitute(&spec.src));
+        fingerprint_tree(context, &src, &mut text);
+    }
+    text
+}
+
+/// Append `path size mtime` lines for a file or directory tree
+fn fingerprint_tree(context: &BuildContext, path: &Path, text: &mut String) {
+    let Ok(meta) = fs::symlink_metadata(path) else {
+        return;
+    };
+    if context.is_ignored(path, meta.is_dir()) {
+        return;
+    }
+    let mtime = meta
+        .modified()
+        .ok()
+        .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+        .unwrap_or_default();
+    text.push_str(&format!("{} {} {}\n", path.display(), meta.len(), mtime.as_nanos()));
+
+    if meta.is_dir()
+        && let Ok(entries) = fs::read_dir(path)
+    {
+        let mut paths: Vec<_> = entries.flatten().map(|e| e.path()).collect();
+        paths.sort();
+        for path in paths {
+            fingerprint_tree(context, &path, text);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_layer_keys_chain() {
+        let root = std::env::temp_dir().join(format!("blackship-layers-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        fs::write(root.join("app.conf"), "a").unwrap();
+
+        let ctx = BuildContext::new(&root, Path::new("/jails/test"), "test");
+        let run = Instruction::Run("make".to_string(), None);
+        let copy = Instruction::Copy(crate::blueprint::instructions::CopySpec::new(
+            "app.conf",
+            "/etc/app.conf",
+        ));
+        let keys = layer_keys(&ctx, &[], &[&run, &copy]);
+        assert_eq!(keys.len(), 3);
+        assert_eq!(keys, layer_keys(&ctx, &[], &[&run, &copy]));
+
+        // Changing a step changes its key and every later key, not earlier ones
+        let other = Instruction::Run("make install".to_string(), None);
+        let changed = layer_keys(&ctx, &[], &[&other, &copy]);
+        assert_eq!(changed[0], keys[0]);
+        assert_ne!(changed[1], keys[1]);
+        assert_ne!(changed[2], keys[2]);
+
+        // So does changing a copied file
+        fs::write(root.join("app.conf"), "changed").unwrap();
+        let changed = layer_keys(&ctx, &[], &[&run, &copy]);
+        assert_eq!(changed[1], keys[1]);
+        assert_ne!(changed[2], keys[2]);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -11,7 +11,7 @@ use crate::blueprint::instructions::{
 use nom::{
     branch::alt,
     bytes::complete::{tag, tag_no_case, take_till, take_while1},
-    character::complete::{char, space0, space1},
+    character::complete::{char, space0, space1, u64 as parse_u64},
     combinator::{map, opt, rest},
     sequence::{delimited, pair, preceded},
     Parser,
@@ -63,7 +63,7 @@ pub fn parse_line_format(content: &str) -> Result<Jailfile> {
         if let Some(instruction) = parse_line(line)? {
             // Update Jailfile state based on instruction
             match &instruction {
-                Instruction::From(release) => {
+                Instruction::From(release, _) => {
                     jailfile.from = Some(release.clone());
                 }
                 Instruction::Arg(arg) => {
@@ -108,12 +108,14 @@ fn parse_line(line: &str) -> Result<Option<Instruction>> {
     }
 
     let result = alt((
-        map(parse_from, |r| Some(Instruction::From(r.to_string()))),
+        map(parse_from, |(r, stage)| {
+            Some(Instruction::From(r.to_string(), stage.map(String::from)))
+        }),
         map(parse_arg, Some),
         map(parse_env, |(k, v)| {
             Some(Instruction::Env(k.to_string(), v.to_string()))
         }),
-        map(parse_run, |c| Some(Instruction::Run(c.to_string()))),
+        map(parse_run, |(c, timeout)| Some(Instruction::Run(c.to_string(), timeout))),
         map(parse_copy, Some),
         map(parse_workdir, |p| Some(Instruction::Workdir(p.to_string()))),
         map(parse_expose, Some),
@@ -140,12 +142,19 @@ fn parse_line(line: &str) -> Result<Option<Instruction>> {
 
 // Nom parsers for each instruction type
 
-fn parse_from(input: &str) -> nom::IResult<&str, &str> {
-    preceded(
+fn parse_from(input: &str) -> nom::IResult<&str, (&str, Option<&str>)> {
+    let (input, release) = preceded(
         pair(tag_no_case("FROM"), space1),
         take_while1(|c: char| !c.is_whitespace()),
     )
-    .parse(input)
+    .parse(input)?;
+    let (input, stage) = opt(preceded(
+        (space1, tag_no_case("AS"), space1),
+        take_while1(|c: char| c.is_alphanumeric() || c == '_' || c == '-'),
+    ))
+    .parse(input)?;
+
+    Ok((input, (release, stage)))
 }
 
 fn parse_arg(input: &str) -> nom::IResult<&str, Instruction> {
@@ -173,20 +182,37 @@ fn parse_env(input: &str) -> nom::IResult<&str, (&str, &str)> {
     Ok((input, (name, value.trim())))
 }
 
-fn parse_run(input: &str) -> nom::IResult<&str, &str> {
-    preceded(pair(tag_no_case("RUN"), space1), rest).parse(input)
+fn parse_run(input: &str) -> nom::IResult<&str, (&str, Option<u64>)> {
+    let (input, _) = pair(tag_no_case("RUN"), space1).parse(input)?;
+
+    // Optional --timeout=<secs>
+    let (input, timeout) =
+        opt(delimited(tag("--timeout="), parse_u64, space1)).parse(input)?;
+    let (input, command) = rest.parse(input)?;
+
+    Ok((input, (command, timeout)))
 }
 
 fn parse_copy(input: &str) -> nom::IResult<&str, Instruction> {
     let (input, _) = tag_no_case("COPY").parse(input)?;
     let (input, _) = space1.parse(input)?;
 
+    // Optional --from=<stage>
+    let (input, stage) = opt(delimited(
+        tag("--from="),
+        take_while1(|c: char| !c.is_whitespace()),
+        space1,
+    ))
+    .parse(input)?;
+
     // Parse source and destination
     let (input, src) = take_while1(|c: char| !c.is_whitespace()).parse(input)?;
     let (input, _) = space1.parse(input)?;
     let (input, dest) = rest.parse(input)?;
 
-    Ok((input, Instruction::Copy(CopySpec::new(src, dest.trim()))))
+    let mut spec = CopySpec::new(src, dest.trim());
+    spec.from = stage.map(String::from);
+    Ok((input, Instruction::Copy(spec)))
 }
 
 fn parse_workdir(input: &str) -> nom::IResult<&str, &str> {
@@ -278,6 +304,8 @@ fn parse_toml_format(content: &str) -> Result<Jailfile> {
     #[derive(Debug, Deserialize)]
     struct TomlRun {
         command: String,
+        #[serde(default)]
+        timeout: Option<u64>,
     }
 
     #[derive(Debug, Deserialize)]
@@ -328,7 +356,7 @@ fn parse_toml_format(content: &str) -> Result<Jailfile> {
         // FROM
         if let Some(from) = build.from {
             jailfile.from = Some(from.clone());
-            jailfile.instructions.push(Instruction::From(from));
+            jailfile.instructions.push(Instruction::From(from, None));
         }
 
         // WORKDIR
@@ -355,7 +383,9 @@ fn parse_toml_format(content: &str) -> Result<Jailfile> {
 
         // RUN commands
         for run in build.run {
-            jailfile.instructions.push(Instruction::Run(run.command));
+            jailfile
+                .instructions
+                .push(Instruction::Run(run.command, run.timeout));
         }
 
         // COPY
@@ -365,6 +395,7 @@ fn parse_toml_format(content: &str) -> Result<Jailfile> {
                 dest: copy.dest,
                 mode: copy.mode,
                 owner: copy.owner,
+                from: None,
             };
             jailfile.instructions.push(Instruction::Copy(spec));
         }
@@ -416,13 +447,39 @@ mod tests {
     #[test]
     fn test_parse_line_from() {
         let result = parse_line("FROM 14.2-RELEASE").unwrap();
-        assert!(matches!(result, Some(Instruction::From(r)) if r == "14.2-RELEASE"));
+        assert!(matches!(result, Some(Instruction::From(r, None)) if r == "14.2-RELEASE"));
+    }
+
+    #[test]
+    fn test_parse_multi_stage() {
+        let content = r#"
+FROM 14.2-RELEASE AS builder
+RUN make -C /src
+FROM 14.2-RELEASE
+COPY --from=builder /src/app /usr/local/bin/app
+"#;
+
+        let jf = parse_line_format(content).unwrap();
+        let stages = jf.stages();
+        assert_eq!(stages.len(), 2);
+        assert_eq!(stages[0].name, Some("builder"));
+        assert_eq!(stages[1].name, None);
+
+        let spec = jf.copy_specs()[0];
+        assert_eq!(spec.from.as_deref(), Some("builder"));
+        assert_eq!(spec.src, "/src/app");
+        assert_eq!(spec.dest, "/usr/local/bin/app");
     }
 
     #[test]
     fn test_parse_line_run() {
         let result = parse_line("RUN pkg install -y nginx").unwrap();
-        assert!(matches!(result, Some(Instruction::Run(c)) if c == "pkg install -y nginx"));
+        assert!(
+            matches!(result, Some(Instruction::Run(c, None)) if c == "pkg install -y nginx")
+        );
+
+        let result = parse_line("RUN --timeout=300 make -j4").unwrap();
+        assert!(matches!(result, Some(Instruction::Run(c, Some(300))) if c == "make -j4"));
     }
 
     #[test]
@@ -7,27 +7,470 @@
 //! - Managing ZFS datasets if enabled
 
 use crate::bulkhead::{BulkheadManager, PortForward};
+use crate::console;
 use crate::error::{Error, Result};
-use crate::hooks::{HookContext, HookPhase, HookRunner};
+use crate::hooks::{
+    exec_start_command, native_exec_hooks, Hook, HookContext, HookPhase, HookRunner,
+    NATIVE_EXEC_PARAMS,
+};
+use crate::logging;
 use crate::jail::{
-    jail_create, jail_getid, jail_remove, JailConfig, JailInstance, ParamValue,
+    is_transient_error, jail_create, jail_getid, jail_is_paused, jail_remove, jail_signal,
+    jail_uptime, jexec_with_timeout, JailConfig, JailInstance, JailRecord, ParamValue,
 };
+use crate::jail::mount::{mount_all, unmount_all};
 use crate::jail::state::State as JailState;
-use crate::manifest::{BlackshipConfig, DnsConfig};
+use crate::manifest::{
+    instance_name, BlackshipConfig, DependencyCondition, DnsConfig, JailDef, JailNetworkConfig,
+    NetworkConfig, RestartPolicy, RetryConfig,
+};
+use crate::network::epair::orphaned_epairs;
+use crate::network::ioctl::{
+    add_ipv4_alias, delete_ipv4_alias, destroy_interface, host_networks, interface_exists,
+};
 use crate::network::{Bridge as NetworkBridge, IpAllocator, IpPool, VnetConfig, VnetSetup};
+use crate::sickbay::{HealthChecker, HealthStatus};
 use crate::sys::OsVersion;
+use crate::util::glob_match;
 use crate::warden::WardenHandle;
-use crate::zfs::ZfsManager;
+use crate::zfs::{DatasetUsage, ZfsManager};
 use ipnet::IpNet;
-use std::net::IpAddr;
+use nix::sys::signal::Signal;
+use std::net::{IpAddr, Ipv4Addr};
+use std::os::unix::process::ExitStatusExt;
 
+use chrono_machines::{BackoffStrategy, ExponentialBackoff};
 use petgraph::algo::toposort;
+use rand::rng;
+use state_machines::DynamicError;
 use petgraph::graph::DiGraph;
-use std::collections::HashMap;
-use std::path::Path;
-use std::sync::Mutex;
-use std::time::Instant;
+use std::collections::{HashMap, HashSet};
+use std::path::{Path, PathBuf};
+use std::sync::{Arc, Mutex};
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 use throttle_machines::token_bucket;
+use tokio::sync::Mutex as AsyncMutex;
+
+/// Jails added, removed and changed by a configuration reload (full names)
+#[derive(Debug, Default)]
+pub struct ReloadDiff {
+    /// Jails defined only in the new configuration
+    pub added: Vec<String>,
+    /// Jails no longer defined in the configuration
+    pub removed: Vec<String>,
+    /// Jails defined in both whose health checks changed
+    pub changed: Vec<String>,
+}
+
+/// A jail root or dataset that no configured jail refers to
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Orphan {
+    /// Directory under `data_dir/jails`
+    Root(PathBuf),
+    /// Dataset under `<pool>/<dataset>/jails` (jail name)
+    Dataset(String),
+}
+
+impl std::fmt::Display for Orphan {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Orphan::Root(path) => write!(f, "root     {}", path.display()),
+            Orphan::Dataset(name) => write!(f, "dataset  {}", name),
+        }
+    }
+}
+
+/// Status of a single jail as shown by `ps`
+struct PsRow {
+    /// Full jail name (project-prefixed)
+    name: String,
+    /// Name as defined in the configuration
+    service_name: String,
+    state: String,
+    jid: Option<i32>,
+    ip: Option<IpAddr>,
+    uptime: Option<Duration>,
+    path: PathBuf,
+}
+
+/// A `ps --filter` condition (`state=<state>` or `name=<glob>`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum PsFilter {
+    /// Match the jail state (case-insensitive)
+    State(String),
+    /// Match the jail name against a glob (`*` and `?`)
+    Name(String),
+}
+
+impl PsFilter {
+    /// Check whether a row passes this filter
+    fn matches(&self, row: &PsRow) -> bool {
+        match self {
+            PsFilter::State(state) => row
+                .state
+                .split(' ')
+                .next()
+                .is_some_and(|s| s.eq_ignore_ascii_case(state)),
+            PsFilter::Name(pattern) => {
+                glob_match(pattern, &row.name) || glob_match(pattern, &row.service_name)
+            }
+        }
+    }
+}
+
+impl std::str::FromStr for PsFilter {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s.split_once('=') {
+            Some(("state", value)) => Ok(PsFilter::State(value.to_string())),
+            Some(("name", value)) => Ok(PsFilter::Name(value.to_string())),
+            _ => Err(format!("invalid filter `{s}`: expected state=<state> or name=<glob>")),
+        }
+    }
+}
+
+/// A `ps --columns` column
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsColumn {
+    Name,
+    State,
+    Jid,
+    Ip,
+    Uptime,
+    Path,
+}
+
+impl PsColumn {
+    /// Columns accepted by `--columns`
+    const ALL: [PsColumn; 6] = [
+        PsColumn::Name,
+        PsColumn::State,
+        PsColumn::Jid,
+        PsColumn::Ip,
+        PsColumn::Uptime,
+        PsColumn::Path,
+    ];
+
+    /// Columns shown when `--columns` is not given
+    const DEFAULT: [PsColumn; 4] =
+        [PsColumn::Name, PsColumn::State, PsColumn::Jid, PsColumn::Uptime];
+
+    fn name(self) -> &'static str {
+        match self {
+            PsColumn::Name => "name",
+            PsColumn::State => "state",
+            PsColumn::Jid => "jid",
+            PsColumn::Ip => "ip",
+            PsColumn::Uptime => "uptime",
+            PsColumn::Path => "path",
+        }
+    }
+
+    fn width(self) -> usize {
+        match self {
+            PsColumn::Name => 20,
+            PsColumn::State | PsColumn::Jid | PsColumn::Uptime => 10,
+            PsColumn::Ip => 16,
+            PsColumn::Path => 30,
+        }
+    }
+
+    /// Render this column's value for a row
+    fn value(self, row: &PsRow) -> String {
+        match self {
+            PsColumn::Name => row.name.clone(),
+            PsColumn::State => row.state.clone(),
+            PsColumn::Jid => row.jid.map(|j| j.to_string()).unwrap_or_default(),
+            PsColumn::Ip => row.ip.map(|ip| ip.to_string()).unwrap_or_else(|| "-".to_string()),
+            PsColumn::Uptime => row.uptime.map(format_uptime).unwrap_or_else(|| "-".to_string()),
+            PsColumn::Path => row.path.display().to_string(),
+        }
+    }
+}
+
+impl std::str::FromStr for PsColumn {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        PsColumn::ALL
+            .into_iter()
+            .find(|c| c.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| {
+                let valid: Vec<&str> = PsColumn::ALL.iter().map(|c| c.name()).collect();
+                format!("unknown column `{s}` (valid columns: {})", valid.join(", "))
+            })
+    }
+}
+
+/// A `ps --sort` key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PsSort {
+    /// Alphabetical by full name
+    Name,
+    /// Alphabetical by state
+    State,
+    /// Longest-running first (stopped jails last)
+    Uptime,
+}
+
+impl PsSort {
+    fn sort(self, rows: &mut [PsRow]) {
+        match self {
+            PsSort::Name => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+            PsSort::State => rows.sort_by(|a, b| a.state.cmp(&b.state).then(a.name.cmp(&b.name))),
+            PsSort::Uptime => rows.sort_by_key(|row| std::cmp::Reverse(row.uptime)),
+        }
+    }
+}
+
+impl std::str::FromStr for PsSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(PsSort::Name),
+            "state" => Ok(PsSort::State),
+            "uptime" => Ok(PsSort::Uptime),
+            _ => Err(format!("invalid sort key `{s}`: expected name, state or uptime")),
+        }
+    }
+}
+
+/// Maximum number of entries walked when measuring a plain-directory jail
+const DU_MAX_ENTRIES: usize = 2_000_000;
+
+/// Disk usage of a single jail as shown by `du`
+struct DuRow {
+    /// Full jail name (project-prefixed)
+    name: String,
+    path: PathBuf,
+    /// Dataset accounting for ZFS jails
+    dataset: Option<DatasetUsage>,
+    /// Bytes allocated (dataset `used`, or the walked total for directories)
+    used: u64,
+    /// The directory walk hit its limit, so `used` is a lower bound
+    truncated: bool,
+}
+
+/// A `du --sort` key
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DuSort {
+    /// Alphabetical by full name
+    Name,
+    /// Largest first
+    Size,
+}
+
+impl std::str::FromStr for DuSort {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "name" => Ok(DuSort::Name),
+            "size" => Ok(DuSort::Size),
+            _ => Err(format!("invalid sort key `{s}`: expected name or size")),
+        }
+    }
+}
+
+/// Output format of `graph`
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    /// Graphviz DOT
+    Dot,
+    /// JSON adjacency list
+    Json,
+}
+
+impl std::str::FromStr for GraphFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "dot" => Ok(GraphFormat::Dot),
+            "json" => Ok(GraphFormat::Json),
+            _ => Err(format!("invalid graph format `{s}`: expected dot or json")),
+        }
+    }
+}
+
+/// Condition `wait` blocks on
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WaitCondition {
+    /// The jail exists
+    Running,
+    /// The jail is running and all its health checks pass
+    Healthy,
+    /// The jail does not exist
+    Stopped,
+}
+
+impl WaitCondition {
+    /// Whether the condition holds for a jail's current state
+    ///
+    /// `health` is only consulted for [`WaitCondition::Healthy`].
+    pub fn is_met(self, running: bool, health: Option<HealthStatus>) -> bool {
+        match self {
+            WaitCondition::Running => running,
+            WaitCondition::Healthy => running && health == Some(HealthStatus::Healthy),
+            WaitCondition::Stopped => !running,
+        }
+    }
+}
+
+impl std::fmt::Display for WaitCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            WaitCondition::Running => write!(f, "running"),
+            WaitCondition::Healthy => write!(f, "healthy"),
+            WaitCondition::Stopped => write!(f, "stopped"),
+        }
+    }
+}
+
+impl std::str::FromStr for WaitCondition {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "running" => Ok(WaitCondition::Running),
+            "healthy" => Ok(WaitCondition::Healthy),
+            "stopped" => Ok(WaitCondition::Stopped),
+            _ => Err(format!(
+                "invalid wait condition `{s}`: expected running, healthy or stopped"
+            )),
+        }
+    }
+}
+
+/// Block until a jail meets `condition`, polling every `interval` seconds
+///
+/// Fails with [`Error::WaitTimeout`] once `timeout` seconds have passed.
+pub fn wait_for(
+    config: &BlackshipConfig,
+    jail: &str,
+    condition: WaitCondition,
+    timeout: Option<u64>,
+    interval: u64,
+) -> Result<()> {
+    let (service_name, full_name) = config
+        .resolve_jail_names(jail)
+        .ok_or_else(|| Error::JailNotFound(jail.to_string()))?;
+
+    let mut checker = None;
+    if condition == WaitCondition::Healthy {
+        let jail_def = config
+            .get_jail(&service_name)
+            .ok_or_else(|| Error::JailNotFound(jail.to_string()))?;
+        if !jail_def.healthcheck.enabled || jail_def.healthcheck.checks.is_empty() {
+            return Err(Error::JailOperation(format!(
+                "Jail '{}' has no health checks to wait for",
+                jail
+            )));
+        }
+        let rate_limit = &config.config.rate_limit;
+        checker = Some(HealthChecker::with_rate_limit(
+            &full_name,
+            jail_def.healthcheck.clone(),
+            rate_limit.health_capacity,
+            rate_limit.health_refill_rate,
+        ));
+    }
+
+    let started = Instant::now();
+    loop {
+        let running = jail_getid(&full_name).is_ok();
+        let health = match &mut checker {
+            Some(checker) if running => Some(checker.run_all_checks()?),
+            _ => None,
+        };
+        if condition.is_met(running, health) {
+            println!("Jail '{}' is {}", jail, condition);
+            return Ok(());
+        }
+
+        let mut delay = Duration::from_secs(interval);
+        if let Some(secs) = timeout {
+            let remaining = Duration::from_secs(secs).saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(Error::WaitTimeout {
+                    jail: jail.to_string(),
+                    condition: condition.to_string(),
+                    secs,
+                });
+            }
+            delay = delay.min(remaining);
+        }
+        std::thread::sleep(delay);
+    }
+}
+
+/// Jail state copied out of the bridge for the supervisor status file
+///
+/// Taken under the bridge lock; [`StatusSnapshot::report`] probes the jails
+/// afterwards, so the lock is not held while they are inspected.
+pub struct StatusSnapshot {
+    data_dir: PathBuf,
+    jails: Vec<StatusEntry>,
+}
+
+/// A configured jail in a [`StatusSnapshot`]
+struct StatusEntry {
+    /// Full jail name (project-prefixed)
+    name: String,
+    /// State, JID and uptime of a jail tracked in this session
+    tracked: Option<(String, Option<i32>, Option<Duration>)>,
+    oneshot: bool,
+    /// The jail has enabled health checks
+    checked: bool,
+    ip: Option<IpAddr>,
+}
+
+impl StatusSnapshot {
+    /// Per-jail state, health, IP and uptime for the supervisor status file
+    ///
+    /// `health` holds the last reported status of jails with health checks;
+    /// jails without checks report a null health.
+    pub fn report(&self, health: &HashMap<String, HealthStatus>) -> serde_json::Value {
+        let jails: Vec<serde_json::Value> = self
+            .jails
+            .iter()
+            .map(|jail| {
+                let (mut state, jid, uptime) =
+                    jail.tracked.clone().unwrap_or_else(|| probe_status(&jail.name));
+                if jail.oneshot
+                    && jid.is_none()
+                    && let Some(code) = JailRecord::load(&self.data_dir, &jail.name).exit_code
+                {
+                    state = format!("Exited ({})", code);
+                }
+                let status = match health.get(&jail.name) {
+                    Some(status) => Some(status.to_string()),
+                    None if jail.checked => Some(HealthStatus::Unknown.to_string()),
+                    None => None,
+                };
+                serde_json::json!({
+                    "name": jail.name,
+                    "state": state,
+                    "health": status,
+                    "jid": jid,
+                    "ip": jail.ip.map(|ip| ip.to_string()),
+                    "uptime": uptime.map(|u| u.as_secs()),
+                })
+            })
+            .collect();
+
+        let updated_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+        serde_json::json!({
+            "updated_at": updated_at,
+            "jails": jails,
+        })
+    }
+}
 
 /// Bridge for managing jails
 pub struct Bridge {
@@ -46,8 +489,8 @@ pub struct Bridge {
     /// IP allocator for automatic IP assignment from network pools
     ip_allocator: IpAllocator,
 
-    /// Map of jail name to allocated IP (for cleanup on stop)
-    allocated_ips: HashMap<String, (String, IpAddr)>,
+    /// Map of jail name to allocated IPs with their networks (for cleanup on stop)
+    allocated_ips: HashMap<String, Vec<(String, IpAddr)>>,
 
     /// Running jail instances
     instances: HashMap<String, JailInstance>,
@@ -67,7 +510,7 @@ pub struct Bridge {
     /// Optional handle to notify the Warden of jail events
     warden_handle: Option<WardenHandle>,
 
-    /// VNET setups for VNET jails (jail name -> VnetSetup)
+    /// VNET setups for VNET jails (jail name -> VnetSetup), persisted in `data_dir/vnet`
     vnet_setups: HashMap<String, VnetSetup>,
 }
 
@@ -107,25 +550,7 @@ impl Bridge {
             }
         }
 
-        let mut graph = DiGraph::new();
-        let mut node_map = HashMap::new();
-
-        // Add nodes for each jail
-        for jail in &config.jails {
-            let idx = graph.add_node(jail.name.clone());
-            node_map.insert(jail.name.clone(), idx);
-        }
-
-        // Add edges for dependencies (dep -> jail)
-        for jail in &config.jails {
-            let to = node_map[&jail.name];
-            for dep in &jail.depends_on {
-                let from = node_map
-                    .get(dep)
-                    .ok_or_else(|| Error::UnknownDependency(dep.clone()))?;
-                graph.add_edge(*from, to, ());
-            }
-        }
+        let graph = build_graph(&config)?;
 
         // Initialize ZFS manager if enabled
         let zfs = if config.config.zfs_enabled {
@@ -143,22 +568,12 @@ impl Bridge {
         // Initialize IP allocator from network configurations
         let mut ip_allocator = IpAllocator::new();
         for network in &config.networks {
-            let subnet: IpNet = network.subnet.parse().map_err(|e| {
-                Error::Network(format!(
-                    "Invalid subnet '{}' for network '{}': {}",
-                    network.subnet, network.name, e
-                ))
-            })?;
-
-            let pool = if let Some(gateway) = network.gateway {
-                IpPool::with_gateway(subnet, gateway)?
-            } else {
-                IpPool::new(subnet)?
-            };
-
-            ip_allocator.add_pool(network.name.clone(), pool);
+            ip_allocator.add_pool(network.name.clone(), network_pool(network)?);
         }
 
+        // VNET setups of jails started by earlier invocations
+        let vnet_setups = VnetSetup::load_all(&config.config.data_dir);
+
         let jail_start_capacity = config.config.rate_limit.jail_start_capacity;
         let now = Instant::now();
         Ok(Self {
@@ -174,7 +589,7 @@ impl Bridge {
             rate_limiter_epoch: now,
             jail_start_capacity,
             warden_handle: None,
-            vnet_setups: HashMap::new(),
+            vnet_setups,
         })
     }
 
@@ -210,6 +625,20 @@ impl Bridge {
             })
     }
 
+    /// Render the dependency graph, with each jail numbered by start order
+    ///
+    /// Edges point from a dependency to the jail that depends on it.
+    pub fn graph(&self, format: GraphFormat) -> Result<String> {
+        let order = self.start_order()?;
+        let edges: Vec<(&str, &str)> = self
+            .graph
+            .raw_edges()
+            .iter()
+            .map(|edge| (self.graph[edge.source()].as_str(), self.graph[edge.target()].as_str()))
+            .collect();
+        Ok(render_graph(&order, &edges, format))
+    }
+
     /// Get the stop order (reverse of start order)
     pub fn stop_order(&self) -> Result<Vec<&str>> {
         let mut order = self.start_order()?;
@@ -217,126 +646,696 @@ impl Bridge {
         Ok(order)
     }
 
-    /// Start all jails (or a specific one with its dependencies)
-    pub fn up(&mut self, jail: Option<&str>) -> Result<()> {
-        // Collect to owned strings to avoid borrow conflict
-        let jails_to_start: Vec<String> = if let Some(name) = jail {
-            self.get_dependencies(name)?
-                .into_iter()
-                .map(String::from)
-                .collect()
-        } else {
-            self.start_order()?.into_iter().map(String::from).collect()
-        };
+    /// Get the start order of jails started by `up` without a name
+    ///
+    /// Includes every jail with autostart enabled, plus any jail they depend on.
+    pub fn autostart_order(&self) -> Result<Vec<&str>> {
+        let mut needed: HashSet<&str> = HashSet::new();
+        let mut pending: Vec<&str> = self
+            .config
+            .jails
+            .iter()
+            .filter(|j| j.autostarts())
+            .map(|j| j.name.as_str())
+            .collect();
+
+        while let Some(name) = pending.pop() {
+            if needed.insert(name)
+                && let Some(jail) = self.config.get_jail(name)
+            {
+                pending.extend(jail.depends_on.iter().map(String::as_str));
+            }
+        }
 
-        for name in &jails_to_start {
-            self.start_jail(name)?;
+        Ok(self
+            .start_order()?
+            .into_iter()
+            .filter(|name| needed.contains(name))
+            .collect())
+    }
+
+    /// Adopt jails that are already running into the tracked instances
+    ///
+    /// Lets the Warden supervise jails started by an earlier process without
+    /// restarting them. Returns the full names of adopted jails.
+    pub fn adopt_running(&mut self) -> Vec<String> {
+        let mut adopted = Vec::new();
+
+        for jail_def in &self.config.jails {
+            let full_name = self.config.jail_name(&jail_def.name);
+            if self.instances.contains_key(&full_name) {
+                continue;
+            }
+            let Ok(jid) = jail_getid(&full_name) else {
+                continue;
+            };
+
+            let path = jail_def.effective_path(&self.config.config, &full_name);
+            let mut jail_config = JailConfig::new(&full_name, &path);
+            if let Some(hostname) = &jail_def.hostname {
+                jail_config = jail_config.hostname(hostname);
+            }
+            if let Some(ip) = jail_def.network.as_ref().and_then(|n| n.ip) {
+                jail_config = jail_config.ip(ip);
+            }
+
+            let mut instance = JailInstance::new(jail_config);
+            instance.jid = Some(jid);
+            transition(self.verbose, &full_name, &mut instance, JailInstance::start);
+            transition(self.verbose, &full_name, &mut instance, JailInstance::started);
+            // Keep the real start time rather than the adoption time
+            if let Some(uptime) = jail_uptime(jid) {
+                instance.started_at = SystemTime::now().checked_sub(uptime);
+            }
+            self.instances.insert(full_name.clone(), instance);
+
+            println!("Adopted running jail '{}' (JID {})", full_name, jid);
+            adopted.push(full_name);
         }
 
-        Ok(())
+        adopted
     }
 
-    /// Stop all jails (or a specific one with its dependents)
-    pub fn down(&mut self, jail: Option<&str>) -> Result<()> {
-        // Collect to owned strings to avoid borrow conflict
-        let jails_to_stop: Vec<String> = if let Some(name) = jail {
-            self.get_dependents(name)?
-                .into_iter()
-                .map(String::from)
-                .collect()
-        } else {
-            self.stop_order()?.into_iter().map(String::from).collect()
-        };
+    /// Swap in a reloaded configuration, stopping removed jails and starting added ones
+    ///
+    /// Jails whose definition changed keep running with their current settings.
+    /// Failures to stop or start individual jails are reported as warnings.
+    pub fn reload(&mut self, config: BlackshipConfig) -> Result<ReloadDiff> {
+        let graph = build_graph(&config)?;
+        let mut diff = ReloadDiff::default();
+
+        // Stop removed jails (in reverse dependency order) while the old config is active
+        let removed: Vec<String> = self
+            .stop_order()?
+            .into_iter()
+            .filter(|name| config.get_jail(name).is_none())
+            .map(String::from)
+            .collect();
+        for name in &removed {
+            let full_name = self.config.jail_name(name);
+            if jail_getid(&full_name).is_ok()
+                && let Err(e) = self.stop_jail(name)
+            {
+                eprintln!("Warning: Failed to stop removed jail '{}': {}", full_name, e);
+            }
+            self.instances.remove(&full_name);
+            diff.removed.push(full_name);
+        }
 
-        for name in &jails_to_stop {
-            self.stop_jail(name)?;
+        // Register pools for newly defined networks
+        for network in &config.networks {
+            if self.ip_allocator.get_pool_mut(&network.name).is_none() {
+                self.ip_allocator.add_pool(network.name.clone(), network_pool(network)?);
+            }
         }
 
-        Ok(())
+        let added: HashSet<String> = config
+            .jails
+            .iter()
+            .filter(|j| self.config.get_jail(&j.name).is_none())
+            .map(|j| j.name.clone())
+            .collect();
+        diff.changed = config
+            .jails
+            .iter()
+            .filter(|j| {
+                self.config
+                    .get_jail(&j.name)
+                    .is_some_and(|old| old.healthcheck != j.healthcheck)
+            })
+            .map(|j| config.jail_name(&j.name))
+            .collect();
+
+        self.config = config;
+        self.graph = graph;
+
+        // Start added jails (in dependency order)
+        let to_start: Vec<String> = self
+            .autostart_order()?
+            .into_iter()
+            .filter(|name| added.contains(*name))
+            .map(String::from)
+            .collect();
+        for name in &to_start {
+            let full_name = self.config.jail_name(name);
+            if jail_getid(&full_name).is_err()
+                && let Err(e) = self.start_jail(name)
+            {
+                eprintln!("Warning: Failed to start added jail '{}': {}", full_name, e);
+            }
+        }
+        diff.added = added.iter().map(|name| self.config.jail_name(name)).collect();
+        diff.added.sort();
+
+        Ok(diff)
     }
 
-    /// Restart jails
-    pub fn restart(&mut self, jail: Option<&str>) -> Result<()> {
-        self.down(jail)?;
-        self.up(jail)?;
+    /// Start autostart jails that are not already running
+    pub fn up_missing(&mut self) -> Result<()> {
+        let jails_to_start: Vec<String> =
+            self.autostart_order()?.into_iter().map(String::from).collect();
+
+        for name in &jails_to_start {
+            if jail_getid(&self.config.jail_name(name)).is_ok() || self.stays_down(name) {
+                continue;
+            }
+            self.wait_for_dependencies(name)?;
+            self.start_jail(name)?;
+        }
+
         Ok(())
     }
 
-    /// Dry run: show what 'up' would do without making changes
-    pub fn up_dry_run(&self, jail: Option<&str>) -> Result<()> {
-        println!("=== DRY RUN - No changes will be made ===\n");
+    /// Start all autostart jails (or a specific one with its dependencies)
+    ///
+    /// Each jail starts once its dependencies meet their `conditions`.
+    pub fn up(&mut self, jail: Option<&str>) -> Result<()> {
+        for name in &self.up_order(jail)? {
+            self.wait_for_dependencies(name)?;
+            self.start_jail(name)?;
+        }
 
-        let jails_to_start: Vec<String> = if let Some(name) = jail {
+        Ok(())
+    }
+
+    /// Jails `up` would start, in dependency order
+    fn up_order(&self, jail: Option<&str>) -> Result<Vec<String>> {
+        // Collect to owned strings to avoid borrow conflict
+        let names = if let Some(name) = jail {
             self.get_dependencies(name)?
-                .into_iter()
-                .map(String::from)
-                .collect()
         } else {
-            self.start_order()?.into_iter().map(String::from).collect()
+            self.autostart_order()?
         };
+        Ok(names.into_iter().map(String::from).collect())
+    }
 
-        println!("Would start {} jail(s):\n", jails_to_start.len());
-
+    /// Async variant of [`Bridge::up`] for callers already inside a tokio runtime
+    ///
+    /// The bridge lock is only held while computing the start order and while
+    /// each individual jail is being started.
+    pub async fn up_async(bridge: &Arc<AsyncMutex<Self>>, jail: Option<&str>) -> Result<()> {
+        let jails_to_start = bridge.lock().await.up_order(jail)?;
         for name in &jails_to_start {
-            let (service_name, full_name) = self.resolve_jail_names(name)?;
-            let jail_def = self.config.get_jail(&service_name);
-            if let Some(jail_def) = jail_def {
-                let path = jail_def.effective_path(&self.config.config, &full_name);
-                let ip = jail_def
-                    .network
-                    .as_ref()
-                    .and_then(|n| n.ip)
-                    .map(|ip| ip.to_string())
-                    .unwrap_or_else(|| "none".to_string());
+            Self::wait_for_dependencies_async(bridge, name).await?;
+            Self::start_jail_async(bridge, name).await?;
+        }
+        Ok(())
+    }
 
-                println!("  [START] {}", full_name);
-                println!("          Path: {}", path.display());
-                println!("          IP: {}", ip);
+    /// Async variant of [`Bridge::up_missing`]
+    pub async fn up_missing_async(bridge: &Arc<AsyncMutex<Self>>) -> Result<()> {
+        let jails_to_start = bridge.lock().await.up_order(None)?;
+        for name in &jails_to_start {
+            let skip = {
+                let br = bridge.lock().await;
+                jail_getid(&br.config.jail_name(name)).is_ok() || br.stays_down(name)
+            };
+            if !skip {
+                Self::wait_for_dependencies_async(bridge, name).await?;
+                Self::start_jail_async(bridge, name).await?;
+            }
+        }
+        Ok(())
+    }
 
-                // Show if ZFS dataset would be created
-                if self.zfs.is_some() && jail_def.path.is_none() {
-                    println!("          ZFS: would create dataset");
+    /// Wait until a jail's dependencies meet their `depends_on` conditions
+    fn wait_for_dependencies(&self, name: &str) -> Result<()> {
+        for mut wait in self.dependency_waits(name)? {
+            loop {
+                match self.poll_dependency(&mut wait) {
+                    ConditionState::Met => break,
+                    ConditionState::Failed(reason) => return Err(wait.failed(&reason)),
+                    ConditionState::Pending(reason) => {
+                        if Instant::now() >= wait.deadline {
+                            return Err(wait.timed_out(&reason));
+                        }
+                        wait.announce(self.verbose, &reason);
+                        std::thread::sleep(DEPENDENCY_POLL_INTERVAL);
+                    }
                 }
+            }
+        }
+        Ok(())
+    }
 
-                // Show hooks that would run
-                if !jail_def.hooks.is_empty() {
-                    let pre_start: Vec<_> = jail_def
-                        .hooks
-                        .iter()
-                        .filter(|h| h.phase == crate::hooks::HookPhase::PreStart)
-                        .collect();
-                    let post_start: Vec<_> = jail_def
-                        .hooks
-                        .iter()
-                        .filter(|h| h.phase == crate::hooks::HookPhase::PostStart)
-                        .collect();
-
-                    if !pre_start.is_empty() {
-                        println!("          Hooks: {} pre_start", pre_start.len());
-                    }
-                    if !post_start.is_empty() {
-                        println!("          Hooks: {} post_start", post_start.len());
+    /// Async variant of [`Bridge::wait_for_dependencies`]
+    ///
+    /// The bridge lock is only held while a condition is being checked.
+    async fn wait_for_dependencies_async(bridge: &Arc<AsyncMutex<Self>>, name: &str) -> Result<()> {
+        let waits = bridge.lock().await.dependency_waits(name)?;
+        for mut wait in waits {
+            loop {
+                let (state, verbose) = {
+                    let br = bridge.lock().await;
+                    let state = tokio::task::block_in_place(|| br.poll_dependency(&mut wait));
+                    (state, br.verbose)
+                };
+                match state {
+                    ConditionState::Met => break,
+                    ConditionState::Failed(reason) => return Err(wait.failed(&reason)),
+                    ConditionState::Pending(reason) => {
+                        if Instant::now() >= wait.deadline {
+                            return Err(wait.timed_out(&reason));
+                        }
+                        wait.announce(verbose, &reason);
+                        tokio::time::sleep(DEPENDENCY_POLL_INTERVAL).await;
                     }
                 }
-                println!();
             }
         }
-
         Ok(())
     }
 
-    /// Dry run: show what 'down' would do without making changes
-    pub fn down_dry_run(&self, jail: Option<&str>) -> Result<()> {
-        println!("=== DRY RUN - No changes will be made ===\n");
+    /// The conditions a jail waits for, one per dependency
+    fn dependency_waits(&self, name: &str) -> Result<Vec<DependencyWait>> {
+        let (service_name, full_name) = self.resolve_jail_names(name)?;
+        let jail_def = self
+            .config
+            .get_jail(&service_name)
+            .ok_or_else(|| Error::JailNotFound(name.to_string()))?;
+        Ok(jail_def
+            .depends_on
+            .iter()
+            .map(|dep| {
+                let (condition, timeout) = jail_def.dependency_condition(dep);
+                DependencyWait {
+                    jail: full_name.clone(),
+                    dep: dep.clone(),
+                    dep_full_name: self.config.jail_name(dep),
+                    condition,
+                    timeout,
+                    deadline: Instant::now() + timeout,
+                    checker: None,
+                    announced: false,
+                }
+            })
+            .collect())
+    }
 
-        let jails_to_stop: Vec<String> = if let Some(name) = jail {
+    /// Last `exec.start` exit code of a jail, from its persisted record
+    fn exit_code(&self, full_name: &str) -> Option<i32> {
+        JailRecord::load(&self.config.config.data_dir, full_name).exit_code
+    }
+
+    /// Persist the exit code of a jail's `exec.start`
+    fn record_exit(&self, full_name: &str, code: i32) {
+        let data_dir = &self.config.config.data_dir;
+        let record = JailRecord::load(data_dir, full_name).exited(code);
+        if let Err(e) = record.save(data_dir, full_name) {
+            eprintln!("Warning: Failed to save exit code of jail '{}': {}", full_name, e);
+        }
+    }
+
+    /// Check a dependency's condition once
+    fn poll_dependency(&self, wait: &mut DependencyWait) -> ConditionState {
+        let running = jail_getid(&wait.dep_full_name).ok();
+        let exit_code = self.exit_code(&wait.dep_full_name);
+        match wait.condition {
+            DependencyCondition::ServiceStarted => {
+                // A one-shot jail is gone once its command succeeded
+                let oneshot =
+                    self.config.get_jail(&wait.dep).is_some_and(JailDef::oneshot_enabled);
+                if running.is_some() || (oneshot && exit_code == Some(0)) {
+                    ConditionState::Met
+                } else {
+                    ConditionState::Pending("not running".to_string())
+                }
+            }
+            DependencyCondition::ServiceHealthy => {
+                let Some(jid) = running else {
+                    return ConditionState::Pending("not running".to_string());
+                };
+                let checker = wait.checker.get_or_insert_with(|| {
+                    let healthcheck = self
+                        .config
+                        .get_jail(&wait.dep)
+                        .map(|dep| dep.healthcheck.clone())
+                        .unwrap_or_default();
+                    let rate_limit = &self.config.config.rate_limit;
+                    HealthChecker::with_rate_limit(
+                        &wait.dep_full_name,
+                        healthcheck,
+                        rate_limit.health_capacity,
+                        rate_limit.health_refill_rate,
+                    )
+                    .with_jid(jid)
+                });
+                match checker.run_all_checks() {
+                    Ok(HealthStatus::Healthy) => ConditionState::Met,
+                    Ok(status) => ConditionState::Pending(format!("health is {}", status)),
+                    Err(e) => ConditionState::Pending(e.to_string()),
+                }
+            }
+            DependencyCondition::ServiceCompletedSuccessfully => match exit_code {
+                Some(0) => ConditionState::Met,
+                Some(code) => {
+                    ConditionState::Failed(format!("exec.start exited with status {}", code))
+                }
+                None if running.is_some() => ConditionState::Pending("still running".to_string()),
+                None => ConditionState::Failed("exec.start has not run".to_string()),
+            },
+        }
+    }
+
+    /// Start a single jail from inside a tokio runtime
+    ///
+    /// Rate-limit waits use `tokio::time::sleep` instead of blocking a worker
+    /// thread, and the jail creation itself (FFI, ZFS, hooks) runs on the
+    /// blocking pool.
+    pub async fn start_jail_async(bridge: &Arc<AsyncMutex<Self>>, name: &str) -> Result<()> {
+        loop {
+            let wait = bridge.lock().await.try_start_token();
+            match wait {
+                Some(retry_after) => tokio::time::sleep(retry_after).await,
+                None => break,
+            }
+        }
+
+        let bridge = Arc::clone(bridge);
+        let name = name.to_string();
+        tokio::task::spawn_blocking(move || bridge.blocking_lock().start_jail_unthrottled(&name))
+            .await
+            .map_err(|e| Error::JailOperation(format!("Start task failed: {}", e)))?
+    }
+
+    /// Scale a jail defined with `count` to `replicas` running instances
+    ///
+    /// Starts missing instances `<name>-1` .. `<name>-<replicas>` and stops running
+    /// instances numbered above that. Returns the resulting instance names.
+    pub fn scale(&mut self, name: &str, replicas: u32) -> Result<Vec<String>> {
+        let template = self.config.templates.get(name).cloned().ok_or_else(|| {
+            Error::ConfigValidation(format!(
+                "Jail '{}' has no count; only jails defined with count can be scaled",
+                name
+            ))
+        })?;
+
+        let plan = scale_plan(
+            name,
+            replicas,
+            |instance| self.config.get_jail(instance).is_some(),
+            |instance| jail_getid(&self.config.jail_name(instance)).is_ok(),
+        );
+        for (instance, index) in &plan.define {
+            self.config.jails.push(template.instance(instance, *index));
+        }
+        for instance in &plan.stop {
+            self.stop_jail(instance)?;
+        }
+        for instance in &plan.start {
+            self.start_jail(instance)?;
+        }
+
+        Ok((1..=replicas)
+            .map(|i| self.config.jail_name(&instance_name(name, i)))
+            .collect())
+    }
+
+    /// Get the jails stopped by `down` (a specific one with its dependents, or all)
+    fn stop_list(&self, jail: Option<&str>) -> Result<Vec<String>> {
+        // Collect to owned strings to avoid borrow conflict
+        Ok(if let Some(name) = jail {
             self.get_dependents(name)?
                 .into_iter()
                 .map(String::from)
                 .collect()
         } else {
             self.stop_order()?.into_iter().map(String::from).collect()
+        })
+    }
+
+    /// Stop all jails (or a specific one with its dependents)
+    pub fn down(&mut self, jail: Option<&str>) -> Result<()> {
+        let jails_to_stop = self.stop_list(jail)?;
+
+        for name in &jails_to_stop {
+            self.stop_jail(name)?;
+            self.update_record(name, |record| record.stopped_by_user = true);
+        }
+
+        Ok(())
+    }
+
+    /// Restart policy of a jail, if it is configured
+    pub fn restart_policy(&self, name: &str) -> Option<RestartPolicy> {
+        let (service_name, _full_name) = self.resolve_jail_names(name).ok()?;
+        self.config.get_jail(&service_name).map(JailDef::restart_policy)
+    }
+
+    /// Whether the jail was last stopped with `down`
+    pub fn stopped_by_user(&self, name: &str) -> bool {
+        let full_name = self.config.jail_name(name);
+        JailRecord::load(&self.config.config.data_dir, &full_name).stopped_by_user
+    }
+
+    /// Whether an `unless-stopped` jail was stopped by the user and is left down
+    fn stays_down(&self, name: &str) -> bool {
+        self.restart_policy(name) == Some(RestartPolicy::UnlessStopped)
+            && self.stopped_by_user(name)
+    }
+
+    /// Change a jail's persisted record, writing it only when something changed
+    fn update_record(&self, name: &str, update: impl FnOnce(&mut JailRecord)) {
+        let data_dir = &self.config.config.data_dir;
+        let full_name = self.config.jail_name(name);
+        let mut record = JailRecord::load(data_dir, &full_name);
+        let before = record.clone();
+        update(&mut record);
+        if record == before {
+            return;
+        }
+        if let Err(e) = record.save(data_dir, &full_name) {
+            eprintln!("Warning: Failed to save state of jail '{}': {}", full_name, e);
+        }
+    }
+
+    /// Stop jails, then tear down their blackship-managed resources
+    ///
+    /// Only datasets of jails without a custom `path` are destroyed.
+    pub fn down_and_remove(&mut self, jail: Option<&str>) -> Result<()> {
+        let jails_to_remove = self.stop_list(jail)?;
+
+        self.down(jail)?;
+
+        for name in &jails_to_remove {
+            if !self.get_jail_port_forwards(name).is_empty() {
+                self.remove_port_forwards(name)?;
+            }
+            self.cleanup(name, false)?;
+        }
+
+        Ok(())
+    }
+
+    /// Pause a running jail by stopping all of its processes (SIGSTOP)
+    pub fn pause(&mut self, name: &str) -> Result<()> {
+        let (_service_name, full_name) = self.resolve_jail_names(name)?;
+        let jid = jail_getid(&full_name).map_err(|_| Error::JailNotRunning(full_name.clone()))?;
+
+        if jail_is_paused(jid) {
+            println!("Jail '{}' is already paused", full_name);
+            return Ok(());
+        }
+
+        let count = jail_signal(jid, Signal::SIGSTOP)?;
+
+        if let Some(instance) = self.instances.get_mut(&full_name) {
+            transition(self.verbose, &full_name, instance, JailInstance::pause);
+        }
+
+        println!("Jail '{}' paused ({} process(es) stopped)", full_name, count);
+        Ok(())
+    }
+
+    /// Resume a paused jail by continuing all of its processes (SIGCONT)
+    pub fn resume(&mut self, name: &str) -> Result<()> {
+        let (_service_name, full_name) = self.resolve_jail_names(name)?;
+        let jid = jail_getid(&full_name).map_err(|_| Error::JailNotRunning(full_name.clone()))?;
+
+        let count = jail_signal(jid, Signal::SIGCONT)?;
+
+        if let Some(instance) = self.instances.get_mut(&full_name) {
+            transition(self.verbose, &full_name, instance, JailInstance::resume);
+        }
+
+        println!("Jail '{}' resumed ({} process(es) continued)", full_name, count);
+        Ok(())
+    }
+
+    /// Restart jails
+    pub fn restart(&mut self, jail: Option<&str>) -> Result<()> {
+        self.down(jail)?;
+        self.up(jail)?;
+        Ok(())
+    }
+
+    /// Jails covered by a rolling restart, in restart order
+    ///
+    /// A jail defined with `count` expands to its configured replicas; no jail
+    /// means every jail in start order. One-shot jails are left out, since
+    /// they never come back running.
+    fn rolling_targets(&self, jail: Option<&str>) -> Result<Vec<String>> {
+        let targets: Vec<String> = match jail {
+            None => self.start_order()?.into_iter().map(String::from).collect(),
+            Some(name) if !self.config.templates.contains_key(name) => {
+                vec![self.resolve_jail_names(name)?.0]
+            }
+            Some(name) => (1..)
+                .map(|i| instance_name(name, i))
+                .take_while(|instance| self.config.get_jail(instance).is_some())
+                .collect(),
         };
+        Ok(targets
+            .into_iter()
+            .filter(|name| !self.config.get_jail(name).is_some_and(JailDef::oneshot_enabled))
+            .collect())
+    }
+
+    /// Restart jails a batch at a time, waiting for each batch to come back
+    ///
+    /// Only jails that are running are restarted. A jail with health checks
+    /// must become healthy, any other jail running, and the whole batch has
+    /// `timeout` seconds to get there before the next batch is restarted. The
+    /// rollout stops at the first jail that fails to restart or come back.
+    pub fn rolling_restart(
+        &mut self,
+        jail: Option<&str>,
+        batch_size: usize,
+        timeout: u64,
+    ) -> Result<()> {
+        let mut targets = self.rolling_targets(jail)?;
+        targets.retain(|name| {
+            let full_name = self.config.jail_name(name);
+            let running = jail_getid(&full_name).is_ok();
+            if !running && self.verbose {
+                println!("  Skipping '{}' (not running)", full_name);
+            }
+            running
+        });
+        if targets.is_empty() {
+            return Err(Error::JailOperation("No running jails to restart".into()));
+        }
+        let batches: Vec<&[String]> = targets.chunks(batch_size.max(1)).collect();
+
+        for (i, batch) in batches.iter().enumerate() {
+            println!(
+                "Rolling restart: batch {}/{} ({})",
+                i + 1,
+                batches.len(),
+                batch.join(", ")
+            );
+            let result = batch.iter().try_for_each(|name| self.restart_jail(name)).and_then(|()| {
+                let deadline = Instant::now() + Duration::from_secs(timeout);
+                batch.iter().try_for_each(|name| {
+                    let checked = self
+                        .config
+                        .get_jail(name)
+                        .is_some_and(|j| j.healthcheck.enabled && !j.healthcheck.checks.is_empty());
+                    let condition =
+                        if checked { WaitCondition::Healthy } else { WaitCondition::Running };
+                    let remaining = deadline.saturating_duration_since(Instant::now());
+                    wait_for(&self.config, name, condition, Some(remaining.as_secs()), 1)
+                })
+            });
+            if let Err(e) = result {
+                let done = batches[..i].iter().map(|b| b.len()).sum::<usize>();
+                eprintln!(
+                    "Rolling restart aborted in batch {}/{} ({} of {} jail(s) restarted)",
+                    i + 1,
+                    batches.len(),
+                    done,
+                    targets.len()
+                );
+                return Err(e);
+            }
+        }
+
+        println!("Rolling restart complete: {} jail(s) restarted", targets.len());
+        Ok(())
+    }
+
+    /// Dry run: show what 'up' would do without making changes
+    pub fn up_dry_run(&self, jail: Option<&str>) -> Result<()> {
+        println!("=== DRY RUN - No changes will be made ===\n");
+
+        let jails_to_start: Vec<String> = if let Some(name) = jail {
+            self.get_dependencies(name)?
+                .into_iter()
+                .map(String::from)
+                .collect()
+        } else {
+            self.autostart_order()?.into_iter().map(String::from).collect()
+        };
+
+        println!("Would start {} jail(s):\n", jails_to_start.len());
+
+        if jail.is_none() {
+            let skipped: Vec<String> = self
+                .start_order()?
+                .into_iter()
+                .filter(|name| !jails_to_start.iter().any(|j| j == name))
+                .map(|name| self.config.jail_name(name))
+                .collect();
+            for full_name in &skipped {
+                println!("  [SKIP]  {} (autostart = false)", full_name);
+            }
+            if !skipped.is_empty() {
+                println!();
+            }
+        }
+
+        for name in &jails_to_start {
+            let (service_name, full_name) = self.resolve_jail_names(name)?;
+            let jail_def = self.config.get_jail(&service_name);
+            if let Some(jail_def) = jail_def {
+                let path = jail_def.effective_path(&self.config.config, &full_name);
+                let ip = jail_def
+                    .network
+                    .as_ref()
+                    .and_then(|n| n.ip)
+                    .map(|ip| ip.to_string())
+                    .unwrap_or_else(|| "none".to_string());
+
+                println!("  [START] {}", full_name);
+                println!("          Path: {}", path.display());
+                println!("          IP: {}", ip);
+
+                // Show if ZFS dataset would be created
+                if self.zfs.is_some() && jail_def.path.is_none() {
+                    println!("          ZFS: would create dataset");
+                }
+
+                // Show hooks that would run, with variables resolved
+                let hooks = jail_def.effective_hooks(&self.config.config);
+                let mut hook_context = self.hook_context(jail_def, &full_name, &path);
+                if let Some(ip) = jail_def.network.as_ref().and_then(|n| n.ip) {
+                    hook_context = hook_context.with_ip(ip.to_string());
+                }
+                let creating_root =
+                    (self.zfs.is_some() && jail_def.path.is_none()) || !path.exists();
+                let phases: &[HookPhase] = if creating_root {
+                    &[
+                        HookPhase::PreCreate,
+                        HookPhase::PostCreate,
+                        HookPhase::PreStart,
+                        HookPhase::PostStart,
+                    ]
+                } else {
+                    &[HookPhase::PreStart, HookPhase::PostStart]
+                };
+                print_hook_preview(&hooks, phases, &hook_context, "          ");
+                println!();
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Dry run: show what 'down' would do without making changes
+    pub fn down_dry_run(&self, jail: Option<&str>, remove: bool) -> Result<()> {
+        println!("=== DRY RUN - No changes will be made ===\n");
+
+        let jails_to_stop = self.stop_list(jail)?;
 
         println!("Would stop {} jail(s):\n", jails_to_stop.len());
 
@@ -347,87 +1346,268 @@ impl Bridge {
 
             println!("  [STOP] {} (currently {})", full_name, status);
 
-            // Show hooks that would run
-            if let Some(jail_def) = self.config.get_jail(&service_name)
-                && !jail_def.hooks.is_empty() {
-                    let pre_stop: Vec<_> = jail_def
-                        .hooks
-                        .iter()
-                        .filter(|h| h.phase == crate::hooks::HookPhase::PreStop)
-                        .collect();
-                    let post_stop: Vec<_> = jail_def
-                        .hooks
-                        .iter()
-                        .filter(|h| h.phase == crate::hooks::HookPhase::PostStop)
-                        .collect();
+            // Show hooks that would run, with variables resolved
+            if let Some(jail_def) = self.config.get_jail(&service_name) {
+                let hooks = jail_def.effective_hooks(&self.config.config);
+                let path = jail_def.effective_path(&self.config.config, &full_name);
+                let mut hook_context = self.hook_context(jail_def, &full_name, &path);
+                if let Some(ip) = jail_def.network.as_ref().and_then(|n| n.ip) {
+                    hook_context = hook_context.with_ip(ip.to_string());
+                }
+                print_hook_preview(
+                    &hooks,
+                    &[HookPhase::PreStop, HookPhase::PostStop],
+                    &hook_context,
+                    "         ",
+                );
 
-                    if !pre_stop.is_empty() {
-                        println!("         Hooks: {} pre_stop", pre_stop.len());
+                if remove {
+                    if jail_def.path.is_some() {
+                        println!("         Keep custom path {}", path.display());
+                    } else if self.zfs.is_some() {
+                        println!("         Destroy ZFS dataset for {}", full_name);
+                    }
+                    if self.allocated_ips.contains_key(&full_name) {
+                        println!("         Release allocated IP");
                     }
-                    if !post_stop.is_empty() {
-                        println!("         Hooks: {} post_stop", post_stop.len());
+                    if !self.bulkhead.get_jail_forwards(&full_name).is_empty() {
+                        println!("         Remove port forwards");
                     }
                 }
+            }
         }
 
         println!();
         Ok(())
     }
 
-    /// Print jail status
-    pub fn ps(&self, json: bool) -> Result<()> {
-        if json {
-            let mut jails_data: Vec<serde_json::Value> = Vec::new();
+    /// Get the state, JID and uptime of a jail
+    ///
+    /// Jails not tracked in this session are inspected directly; their uptime is
+    /// derived from the oldest process in the jail.
+    fn runtime_status(&self, full_name: &str) -> (String, Option<i32>, Option<Duration>) {
+        self.tracked_status(full_name).unwrap_or_else(|| probe_status(full_name))
+    }
+
+    /// State, JID and uptime of a jail tracked in this session
+    fn tracked_status(&self, full_name: &str) -> Option<(String, Option<i32>, Option<Duration>)> {
+        let instance = self.instances.get(full_name)?;
+        Some((format!("{:?}", instance.state()), instance.jid, instance.uptime()))
+    }
 
-            for jail_def in &self.config.jails {
+    /// Collect the status of every configured jail
+    fn ps_rows(&self) -> Vec<PsRow> {
+        self.config
+            .jails
+            .iter()
+            .map(|jail_def| {
                 let full_name = self.config.jail_name(&jail_def.name);
-                let (state, jid) = if let Some(instance) = self.instances.get(&full_name) {
-                    let state = format!("{:?}", instance.state());
-                    let jid = instance.jid;
-                    (state, jid)
-                } else {
-                    match jail_getid(&full_name) {
-                        Ok(jid) => ("Running".to_string(), Some(jid)),
-                        Err(_) => ("Stopped".to_string(), None),
-                    }
-                };
+                let (mut state, jid, uptime) = self.runtime_status(&full_name);
+                if jail_def.oneshot_enabled()
+                    && jid.is_none()
+                    && let Some(code) = self.exit_code(&full_name)
+                {
+                    state = format!("Exited ({})", code);
+                }
+                PsRow {
+                    service_name: jail_def.name.clone(),
+                    ip: jail_def.network.as_ref().and_then(|n| n.ip),
+                    path: jail_def.effective_path(&self.config.config, &full_name),
+                    name: full_name,
+                    state,
+                    jid,
+                    uptime,
+                }
+            })
+            .collect()
+    }
 
-                let ip = jail_def
-                    .network
-                    .as_ref()
-                    .and_then(|n| n.ip)
-                    .map(|ip| ip.to_string());
+    /// Allocated and usable address counts of each network's IP pool
+    pub fn ip_pool_usage(&self) -> Vec<(String, usize, u128)> {
+        let mut usage: Vec<(String, usize, u128)> = self
+            .ip_allocator
+            .pools()
+            .map(|(name, pool)| (name.to_string(), pool.allocated_count(), pool.capacity()))
+            .collect();
+        usage.sort();
+        usage
+    }
+
+    /// Copy what the supervisor status file needs, without probing the jails
+    pub fn status_snapshot(&self) -> StatusSnapshot {
+        let jails = self
+            .config
+            .jails
+            .iter()
+            .map(|jail_def| {
+                let name = self.config.jail_name(&jail_def.name);
+                let health = &jail_def.healthcheck;
+                StatusEntry {
+                    tracked: self.tracked_status(&name),
+                    oneshot: jail_def.oneshot_enabled(),
+                    checked: health.enabled && !health.checks.is_empty(),
+                    ip: jail_def.network.as_ref().and_then(|n| n.ip),
+                    name,
+                }
+            })
+            .collect();
+        StatusSnapshot {
+            data_dir: self.config.config.data_dir.clone(),
+            jails,
+        }
+    }
+
+    /// Print jail status, keeping only jails matching every filter
+    ///
+    /// `columns` selects and orders the table columns (empty for the default layout).
+    pub fn ps(
+        &self,
+        json: bool,
+        filters: &[PsFilter],
+        columns: &[PsColumn],
+        sort: Option<PsSort>,
+    ) -> Result<()> {
+        let mut rows: Vec<PsRow> = self
+            .ps_rows()
+            .into_iter()
+            .filter(|row| filters.iter().all(|f| f.matches(row)))
+            .collect();
+        if let Some(sort) = sort {
+            sort.sort(&mut rows);
+        }
+
+        if json {
+            let mut jails_data: Vec<serde_json::Value> = Vec::new();
+
+            for row in &rows {
+                // Unix timestamp (seconds) of when the jail started
+                let started_at = row
+                    .uptime
+                    .and_then(|u| SystemTime::now().checked_sub(u))
+                    .and_then(|t| t.duration_since(UNIX_EPOCH).ok())
+                    .map(|d| d.as_secs());
 
                 jails_data.push(serde_json::json!({
-                    "name": full_name,
-                    "state": state,
-                    "jid": jid,
-                    "ip": ip,
-                    "path": jail_def.effective_path(&self.config.config, &full_name).to_string_lossy()
+                    "name": row.name,
+                    "state": row.state,
+                    "jid": row.jid,
+                    "ip": row.ip.map(|ip| ip.to_string()),
+                    "started_at": started_at,
+                    "uptime": row.uptime.map(|u| u.as_secs()),
+                    "path": row.path.to_string_lossy()
                 }));
             }
 
             println!("{}", serde_json::to_string_pretty(&jails_data).unwrap());
         } else {
-            println!("{:<20} {:<10} {:<10}", "NAME", "STATE", "JID");
-            println!("{}", "-".repeat(42));
+            let columns = if columns.is_empty() { &PsColumn::DEFAULT[..] } else { columns };
+            let render = |values: Vec<String>| {
+                values
+                    .iter()
+                    .zip(columns)
+                    .map(|(value, column)| format!("{:<width$}", value, width = column.width()))
+                    .collect::<Vec<_>>()
+                    .join(" ")
+            };
+
+            println!("{}", render(columns.iter().map(|c| c.name().to_uppercase()).collect()));
+            let total: usize = columns.iter().map(|c| c.width()).sum::<usize>() + columns.len() - 1;
+            println!("{}", "-".repeat(total));
+
+            for row in &rows {
+                println!("{}", render(columns.iter().map(|c| c.value(row)).collect()));
+            }
+        }
 
-            for jail_def in &self.config.jails {
+        Ok(())
+    }
+
+    /// Measure each configured jail's disk usage
+    ///
+    /// Jails on a blackship-managed ZFS dataset report the dataset's `used`
+    /// and `referenced`; other jails are measured by walking their root.
+    fn du_rows(&self) -> Vec<DuRow> {
+        self.config
+            .jails
+            .iter()
+            .map(|jail_def| {
                 let full_name = self.config.jail_name(&jail_def.name);
-                let (state, jid) = if let Some(instance) = self.instances.get(&full_name) {
-                    let state = format!("{:?}", instance.state());
-                    let jid = instance.jid.map(|j| j.to_string()).unwrap_or_default();
-                    (state, jid)
-                } else {
-                    match jail_getid(&full_name) {
-                        Ok(jid) => ("Running".to_string(), jid.to_string()),
-                        Err(_) => ("Stopped".to_string(), String::new()),
-                    }
+                let path = jail_def.effective_path(&self.config.config, &full_name);
+                let dataset = self
+                    .zfs
+                    .as_ref()
+                    .filter(|_| jail_def.path.is_none())
+                    .and_then(|zfs| zfs.jail_usage(&full_name).ok());
+                let (used, truncated) = match dataset {
+                    Some(usage) => (usage.used, false),
+                    None => crate::sys::disk_usage(&path, DU_MAX_ENTRIES),
                 };
+                DuRow { name: full_name, path, dataset, used, truncated }
+            })
+            .collect()
+    }
 
-                println!("{:<20} {:<10} {:<10}", full_name, state, jid);
-            }
+    /// Print disk usage per jail
+    pub fn du(&self, json: bool, sort: Option<DuSort>) -> Result<()> {
+        let mut rows = self.du_rows();
+        match sort {
+            Some(DuSort::Name) => rows.sort_by(|a, b| a.name.cmp(&b.name)),
+            Some(DuSort::Size) => rows.sort_by_key(|row| std::cmp::Reverse(row.used)),
+            None => {}
+        }
+
+        if json {
+            let data: Vec<serde_json::Value> = rows
+                .iter()
+                .map(|row| {
+                    serde_json::json!({
+                        "name": row.name,
+                        "path": row.path.to_string_lossy(),
+                        "zfs": row.dataset.is_some(),
+                        "used": row.used,
+                        "referenced": row.dataset.map(|d| d.referenced),
+                        "quota": row.dataset.and_then(|d| d.quota),
+                        "truncated": row.truncated,
+                    })
+                })
+                .collect();
+            println!("{}", serde_json::to_string_pretty(&data).unwrap());
+            return Ok(());
+        }
+
+        println!(
+            "{:<30} {:>10} {:>10} {:>10} {:<5} PATH",
+            "NAME", "USED", "REFER", "QUOTA", "TYPE"
+        );
+        println!("{}", "-".repeat(91));
+        for row in &rows {
+            let used = if row.truncated {
+                format!(">{}", format_size(row.used))
+            } else {
+                format_size(row.used)
+            };
+            let refer = row.dataset.map(|d| format_size(d.referenced)).unwrap_or("-".into());
+            let quota = match row.dataset.and_then(|d| d.quota) {
+                Some(quota) => {
+                    let percent = row.used as f64 * 100.0 / quota as f64;
+                    format!("{} {:.0}%", format_size(quota), percent)
+                }
+                None => "-".to_string(),
+            };
+            let kind = if row.dataset.is_some() { "zfs" } else { "dir" };
+            println!(
+                "{:<30} {:>10} {:>10} {:>10} {:<5} {}",
+                row.name,
+                used,
+                refer,
+                quota,
+                kind,
+                row.path.display()
+            );
         }
+        let total: u64 = rows.iter().map(|row| row.used).sum();
+        println!("{}", "-".repeat(91));
+        println!("{:<30} {:>10}", "TOTAL", format_size(total));
 
         Ok(())
     }
@@ -462,36 +1642,103 @@ impl Bridge {
             println!("  {}: {} ({})", full_name, path.display(), status);
         }
 
+        self.check_shared_ip_routes();
+
+        if let Ok(orphans) = orphaned_epairs(&self.configured_jail_names())
+            && !orphans.is_empty()
+        {
+            eprintln!(
+                "Warning: {} orphaned epair interface(s) ({}); \
+                 remove them with `blackship network gc`",
+                orphans.len(),
+                orphans.join(", ")
+            );
+        }
+
         Ok(())
     }
 
-    /// Start a single jail with cleanup on failure
-    fn start_jail(&mut self, name: &str) -> Result<()> {
-        // Rate limiting to prevent thundering herd on `up --all`
+    /// Warn about shared-IP (non-VNET) jails whose address the host can't route
+    fn check_shared_ip_routes(&self) {
+        let host_nets = match host_networks() {
+            Ok(nets) => nets,
+            Err(e) => {
+                eprintln!("Warning: Could not inspect host interfaces: {}", e);
+                return;
+            }
+        };
+        let routed = |ip: IpAddr| host_nets.iter().any(|(_, net)| net.contains(&ip));
+
+        for jail in &self.config.jails {
+            let Some(network) = jail.network.as_ref().filter(|n| !n.vnet) else {
+                continue;
+            };
+            let full_name = self.config.jail_name(&jail.name);
+
+            if let Some(interface) = &network.interface {
+                if !interface_exists(interface).unwrap_or(false) {
+                    eprintln!(
+                        "Warning: Jail '{}' uses host interface '{}', which does not exist",
+                        full_name, interface
+                    );
+                }
+                continue;
+            }
+
+            if let Some(ip) = network.address()
+                && !routed(ip)
+            {
+                eprintln!(
+                    "Warning: Jail '{}' has IP {} but no host interface is in that subnet; \
+                     set network.interface (e.g. \"lo1\") so blackship adds it as an alias",
+                    full_name, ip
+                );
+            }
+            if let Some(gateway) = network.gateway
+                && !routed(gateway)
+            {
+                eprintln!(
+                    "Warning: Gateway {} for jail '{}' is not on any host interface subnet",
+                    gateway, full_name
+                );
+            }
+        }
+    }
+
+    /// Take a jail start token from the rate limiter
+    ///
+    /// Returns `None` when a token was taken, otherwise how long to wait before
+    /// trying again. Limits starts to prevent a thundering herd on `up --all`.
+    fn try_start_token(&self) -> Option<Duration> {
         let capacity = self.jail_start_capacity;
         const REFILL_RATE: f64 = 1.0; // 1 jail/sec
 
-        loop {
-            let mut state = self.rate_limiter.lock().unwrap();
-            let (tokens, last_refill) = *state;
-            let now = Instant::now();
-            let now_secs = now.duration_since(self.rate_limiter_epoch).as_secs_f64();
-            let last_refill_secs = last_refill.duration_since(self.rate_limiter_epoch).as_secs_f64();
-
-            let result = token_bucket::check(tokens, last_refill_secs, now_secs, capacity, REFILL_RATE);
-
-            if result.allowed {
-                // Update state and proceed
-                *state = (result.new_tokens, now);
-                break;
-            } else {
-                // Release lock before sleeping
-                let retry_after = result.retry_after;
-                drop(state);
-                std::thread::sleep(std::time::Duration::from_secs_f64(retry_after));
-            }
+        let mut state = self.rate_limiter.lock().unwrap();
+        let (tokens, last_refill) = *state;
+        let now = Instant::now();
+        let now_secs = now.duration_since(self.rate_limiter_epoch).as_secs_f64();
+        let last_refill_secs = last_refill.duration_since(self.rate_limiter_epoch).as_secs_f64();
+
+        let result = token_bucket::check(tokens, last_refill_secs, now_secs, capacity, REFILL_RATE);
+
+        if result.allowed {
+            *state = (result.new_tokens, now);
+            None
+        } else {
+            Some(Duration::from_secs_f64(result.retry_after))
         }
+    }
+
+    /// Start a single jail with cleanup on failure
+    fn start_jail(&mut self, name: &str) -> Result<()> {
+        while let Some(retry_after) = self.try_start_token() {
+            std::thread::sleep(retry_after);
+        }
+        self.start_jail_unthrottled(name)
+    }
 
+    /// Start a single jail without waiting on the rate limiter
+    fn start_jail_unthrottled(&mut self, name: &str) -> Result<()> {
         let (service_name, full_name) = self.resolve_jail_names(name)?;
         let jail_def = self
             .config
@@ -503,19 +1750,55 @@ impl Bridge {
             return Err(Error::JailAlreadyRunning(full_name));
         }
 
+        self.validate_vnet(jail_def, &full_name)?;
+        if let Some(ruleset) = jail_def.devfs_ruleset
+            && !crate::sys::devfs_ruleset_exists(ruleset)?
+        {
+            return Err(Error::ConfigValidation(format!(
+                "Jail '{}' uses devfs ruleset {}, which is not loaded on the host \
+                 (see devfs.rules(5))",
+                full_name, ruleset
+            )));
+        }
+
         // Track resources for cleanup on failure
         let mut created_zfs_dataset = false;
+        let mut provisioned_root = false;
+
+        // The jail root is created when a ZFS dataset will be made for it or its path is missing
+        let managed_dataset = self.zfs.as_ref().filter(|_| jail_def.path.is_none());
+        let dataset_exists = match managed_dataset {
+            Some(zfs) => zfs.dataset_exists(&zfs.get_jail_dataset(&full_name))?,
+            None => false,
+        };
+        let creates_dataset = managed_dataset.is_some() && !dataset_exists;
+        let planned_path = jail_def.effective_path(&self.config.config, &full_name);
+        let creating_root = creates_dataset || !planned_path.exists();
+
+        // Setup hook runner and context
+        let hook_runner =
+            HookRunner::new(jail_def.effective_hooks(&self.config.config)).verbose(self.verbose);
+        let mut hook_context = self.hook_context(jail_def, &full_name, &planned_path);
+
+        // Execute pre_create hooks before any filesystem resources exist
+        if creating_root {
+            hook_runner.execute_phase(HookPhase::PreCreate, &hook_context)?;
+        }
 
         // Create ZFS dataset if needed (use full_name for system resources)
-        let path = if let Some(zfs) = &self.zfs {
-            if jail_def.path.is_none() {
-                created_zfs_dataset = true;
-                zfs.create_jail_dataset(&full_name)?
-            } else {
-                jail_def.effective_path(&self.config.config, &full_name)
-            }
+        let path = if let Some(zfs) = &self.zfs
+            && creates_dataset
+        {
+            created_zfs_dataset = true;
+            zfs.create_jail_dataset(&full_name, jail_def.quota.as_deref())?
         } else {
-            jail_def.effective_path(&self.config.config, &full_name)
+            // Keep the quota of an existing dataset in line with the config
+            if let Some(zfs) = managed_dataset
+                && let Some(quota) = &jail_def.quota
+            {
+                zfs.set_quota(&full_name, quota)?;
+            }
+            planned_path
         };
 
         // Check path exists - auto-provision from release if available
@@ -530,6 +1813,7 @@ impl Bridge {
                     );
 
                     // Create the jail directory
+                    provisioned_root = true;
                     if let Err(e) = std::fs::create_dir_all(&path) {
                         // Cleanup ZFS dataset if we created it
                         if created_zfs_dataset
@@ -602,6 +1886,20 @@ impl Bridge {
             }
         }
 
+        // Execute post_create hooks once the jail root is populated
+        if creating_root
+            && let Err(e) = hook_runner.execute_phase(HookPhase::PostCreate, &hook_context) {
+                // Remove the root so create hooks run again on the next start
+                if provisioned_root {
+                    let _ = std::fs::remove_dir_all(&path);
+                }
+                if created_zfs_dataset
+                    && let Some(zfs) = &self.zfs {
+                        let _ = zfs.destroy_jail_dataset(&full_name);
+                    }
+                return Err(e);
+            }
+
         // Configure DNS before starting the jail
         if let Some(network) = &jail_def.network
             && let Err(e) = self.configure_dns(&path, &network.dns) {
@@ -613,10 +1911,21 @@ impl Bridge {
                 return Err(e);
             }
 
-        // Determine IP address for this jail
-        // Priority: static IP > auto-allocate from network pool > none
-        let mut allocated_ip: Option<(String, IpAddr)> = None;
-        let effective_ip: Option<IpAddr> = if let Some(network) = &jail_def.network {
+        // Write /etc/hosts entries (configured ones and, if enabled, sibling jails)
+        if let Err(e) = self.configure_hosts(&path, jail_def, &full_name) {
+            if created_zfs_dataset
+                && let Some(zfs) = &self.zfs {
+                    let _ = zfs.destroy_jail_dataset(&full_name);
+                }
+            return Err(e);
+        }
+
+        // Determine IP addresses for this jail: a static IP covers its family, and
+        // each attached network adds one address of a family not yet covered, so a
+        // jail on an IPv4 and an IPv6 network is dual-stack
+        let mut allocated_ip: Vec<(String, IpAddr)> = Vec::new();
+        let mut jail_ips: Vec<IpAddr> = Vec::new();
+        if let Some(network) = &jail_def.network {
             if let Some(static_ip) = network.ip {
                 // Static IP configured - reserve it in pools if attached to a network
                 for net_name in &network.networks {
@@ -625,19 +1934,29 @@ impl Bridge {
                         let _ = pool.allocate_specific(static_ip);
                     }
                 }
-                Some(static_ip)
-            } else if let Some(first_network) = network.networks.first() {
-                // No static IP but attached to a network - auto-allocate
-                match self.ip_allocator.allocate(first_network) {
+                jail_ips.push(static_ip);
+            }
+
+            for net_name in &network.networks {
+                let Some(pool) = self.ip_allocator.get_pool(net_name) else {
+                    continue;
+                };
+                let is_v6 = matches!(pool.subnet(), IpNet::V6(_));
+                if jail_ips.iter().any(|ip| ip.is_ipv6() == is_v6) {
+                    continue;
+                }
+
+                match self.ip_allocator.allocate(net_name) {
                     Ok(ip) => {
-                        allocated_ip = Some((first_network.clone(), ip));
+                        allocated_ip.push((net_name.clone(), ip));
+                        jail_ips.push(ip);
                         if self.verbose {
-                            println!("  Auto-allocated IP {} from network '{}'", ip, first_network);
+                            println!("  Auto-allocated IP {} from network '{}'", ip, net_name);
                         }
-                        Some(ip)
                     }
                     Err(e) => {
                         // Cleanup on allocation failure
+                        self.release_ips(&allocated_ip);
                         if created_zfs_dataset
                             && let Some(zfs) = &self.zfs {
                                 let _ = zfs.destroy_jail_dataset(&full_name);
@@ -645,16 +1964,10 @@ impl Bridge {
                         return Err(e);
                     }
                 }
-            } else {
-                None
             }
-        } else {
-            None
-        };
-
-        // Setup hook runner and context
-        let hook_runner = HookRunner::new(jail_def.hooks.clone()).verbose(self.verbose);
-        let mut hook_context = HookContext::new(&full_name, &path);
+        }
+        // The primary address is used for hooks and the VNET interface
+        let effective_ip = jail_ips.first().copied();
 
         // Add IP to context if available
         if let Some(ip) = effective_ip {
@@ -664,9 +1977,7 @@ impl Bridge {
         // Execute pre_start hooks
         if let Err(e) = hook_runner.execute_phase(HookPhase::PreStart, &hook_context) {
             // Cleanup on pre_start hook failure
-            if let Some((network_name, ip)) = &allocated_ip {
-                self.ip_allocator.release(network_name, ip);
-            }
+            self.release_ips(&allocated_ip);
             if created_zfs_dataset
                 && let Some(zfs) = &self.zfs {
                     let _ = zfs.destroy_jail_dataset(&full_name);
@@ -681,50 +1992,77 @@ impl Bridge {
         let mut vnet_setup: Option<VnetSetup> = None;
         if is_vnet
             && let Some(network) = &jail_def.network {
-                // Validate VNET configuration
-                let bridge_name = network.bridge.as_ref().ok_or_else(|| {
-                    Error::Network(format!(
-                        "VNET jail '{}' requires a bridge configuration",
-                        full_name
-                    ))
-                })?;
-
-                // Build IP configuration string for VnetConfig
+                // Checked by validate_vnet; a passed-through interface needs no bridge
+                let bridge_name = network.bridge.clone().unwrap_or_default();
+
+                // Build IP configuration string for VnetConfig, taking the prefix
+                // length from the attached network's pool when there is one
+                let prefix_for = |ip: IpAddr| {
+                    network
+                        .networks
+                        .iter()
+                        .filter_map(|net| self.ip_allocator.get_pool(net))
+                        .map(|pool| pool.subnet())
+                        .find(|subnet| subnet.contains(&ip))
+                        .map(|subnet| subnet.prefix_len())
+                        .unwrap_or(if ip.is_ipv6() { 64 } else { 24 })
+                };
                 let ip_config = network
                     .ip_cidr
                     .as_ref()
                     .cloned()
-                    .or_else(|| network.ip.map(|ip| format!("{}/24", ip)))
-                    .or_else(|| effective_ip.map(|ip| format!("{}/24", ip)))
+                    .or_else(|| network.ip.map(|ip| format!("{}/{}", ip, prefix_for(ip))))
+                    .or_else(|| effective_ip.map(|ip| format!("{}/{}", ip, prefix_for(ip))))
                     .unwrap_or_else(|| "0.0.0.0/0".to_string());
 
-                // Get gateway (required for VnetConfig)
-                let gateway = network.gateway.unwrap_or_else(|| {
-                    // Default gateway - first IP in subnet if not specified
-                    "10.0.0.1".parse().unwrap()
-                });
+                // Without an explicit gateway, use the attached network's
+                let primary_ip = ip_config.split('/').next().and_then(|addr| addr.parse().ok());
+                let gateway = vnet_gateway(network, primary_ip, &self.ip_allocator);
 
                 // Build VnetConfig
-                let mut vnet_config = VnetConfig::new(bridge_name.clone(), ip_config, gateway);
+                let vnet_config = gateway.map(|gateway| {
+                    let mut vnet_config = VnetConfig::new(bridge_name.clone(), ip_config, gateway)
+                        .with_epair_naming(self.config.config.epair_naming);
+                    if let Some(interface) = &network.vnet_interface {
+                        vnet_config = vnet_config.with_interface(interface.clone());
+                    }
 
-                // Set static MAC address if configured
-                if let Some(ref mac) = network.mac_address {
-                    vnet_config = vnet_config.with_mac_address(mac.clone());
-                }
+                    // Dual-stack: configure the remaining addresses as aliases
+                    for ip in jail_ips.iter().skip(1) {
+                        vnet_config =
+                            vnet_config.with_extra_address(format!("{}/{}", ip, prefix_for(*ip)));
+                    }
 
-                // Set VLAN ID if configured (FreeBSD 15.0+)
-                if let Some(vlan_id) = network.vlan_id {
-                    vnet_config = vnet_config.with_vlan_id(vlan_id);
-                }
+                    // Set static MAC address if configured
+                    if let Some(ref mac) = network.mac_address {
+                        vnet_config = vnet_config.with_mac_address(mac.clone());
+                    }
+
+                    // Set VLAN ID if configured (FreeBSD 15.0+)
+                    if let Some(vlan_id) = network.vlan_id {
+                        vnet_config = vnet_config.with_vlan_id(vlan_id);
+                    }
+                    vnet_config
+                });
 
                 // Create VnetSetup - this handles epair creation, MAC setting, and bridge addition
-                let setup = match VnetSetup::create(&full_name, vnet_config) {
+                let created = match (self.vnet_interface_owner(network, &full_name), vnet_config) {
+                    (Some(owner), _) => Err(Error::Network(format!(
+                        "Interface '{}' is already in jail '{}'",
+                        network.vnet_interface.as_deref().unwrap_or_default(),
+                        owner
+                    ))),
+                    (None, Some(vnet_config)) => VnetSetup::create(&full_name, vnet_config),
+                    (None, None) => Err(Error::Network(format!(
+                        "VNET jail '{}' has no gateway: set gateway, or attach it to a network",
+                        full_name
+                    ))),
+                };
+                let setup = match created {
                     Ok(s) => s,
                     Err(e) => {
                         // Cleanup on VnetSetup creation failure
-                        if let Some((network_name, ip)) = &allocated_ip {
-                            self.ip_allocator.release(network_name, ip);
-                        }
+                        self.release_ips(&allocated_ip);
                         if created_zfs_dataset
                             && let Some(zfs) = &self.zfs {
                                 let _ = zfs.destroy_jail_dataset(&full_name);
@@ -734,16 +2072,19 @@ impl Bridge {
                 };
 
                 if self.verbose {
-                    println!(
-                        "  Created epair {} <-> {} for VNET jail",
-                        setup.epair.host_side(),
-                        setup.epair.jail_side()
-                    );
-                    println!(
-                        "  Added {} to bridge {}",
-                        setup.epair.host_side(),
-                        bridge_name
-                    );
+                    match &setup.epair {
+                        Some(epair) => {
+                            println!(
+                                "  Created epair {} <-> {} for VNET jail",
+                                epair.host_side(),
+                                epair.jail_side()
+                            );
+                            println!("  Added {} to bridge {}", epair.host_side(), bridge_name);
+                        }
+                        None => {
+                            println!("  Passing {} through to VNET jail", setup.jail_interface())
+                        }
+                    }
                 }
 
                 vnet_setup = Some(setup);
@@ -764,40 +2105,110 @@ impl Bridge {
         // For non-VNET jails, use traditional IP assignment
         if is_vnet {
             // Enable VNET for this jail
-            params.insert("vnet".to_string(), ParamValue::String("new".to_string()));
-        } else if let Some(ip) = effective_ip {
-            // Traditional jail: assign IP directly
-            match ip {
-                IpAddr::V4(addr) => {
-                    params.insert("ip4.addr".to_string(), ParamValue::Ipv4(vec![addr]));
-                }
-                IpAddr::V6(addr) => {
-                    params.insert("ip6.addr".to_string(), ParamValue::Ipv6(vec![addr]));
-                }
+            params.insert("vnet".to_string(), ParamValue::String("new".to_string()));
+        } else {
+            // Traditional jail: assign IPs directly, one list per family
+            let v4: Vec<_> = jail_ips
+                .iter()
+                .filter_map(|ip| match ip {
+                    IpAddr::V4(addr) => Some(*addr),
+                    IpAddr::V6(_) => None,
+                })
+                .collect();
+            let v6: Vec<_> = jail_ips
+                .iter()
+                .filter_map(|ip| match ip {
+                    IpAddr::V6(addr) => Some(*addr),
+                    IpAddr::V4(_) => None,
+                })
+                .collect();
+            if !v4.is_empty() {
+                params.insert("ip4.addr".to_string(), ParamValue::Ipv4(v4));
+            }
+            if !v6.is_empty() {
+                params.insert("ip6.addr".to_string(), ParamValue::Ipv6(v6));
             }
         }
 
-        // Add custom parameters
+        // Add custom parameters; exec.start and the other jail(8) exec.* params
+        // are not kernel parameters and are run by blackship itself
         for (key, value) in &jail_def.params {
+            if key == EXEC_START_PARAM || NATIVE_EXEC_PARAMS.iter().any(|(p, _, _)| p == key) {
+                continue;
+            }
             let param_value = ParamValue::try_from(value)?;
             params.insert(key.clone(), param_value);
         }
+        if let Some(level) = jail_def.securelevel {
+            params.insert("securelevel".to_string(), ParamValue::Int(level));
+        }
+        if let Some(ruleset) = jail_def.devfs_ruleset {
+            params.insert("devfs_ruleset".to_string(), ParamValue::Int(i32::from(ruleset)));
+        }
+
+        // Shared-IP jails: add the address to the configured host interface so the
+        // host has a route to it
+        let alias_ip = jail_ips.iter().copied().find(IpAddr::is_ipv4);
+        let host_alias = if is_vnet { None } else { host_alias(jail_def, alias_ip) };
+        let mut added_alias = None;
+        if let Some((interface, ip, prefix)) = host_alias {
+            let present = host_networks()
+                .map(|nets| nets.iter().any(|(_, net)| net.addr() == IpAddr::V4(ip)))
+                .unwrap_or(false);
+            if present {
+                if self.verbose {
+                    println!("  {} is already configured on the host", ip);
+                }
+                self.update_record(&full_name, |record| record.added_alias = false);
+            } else {
+                match add_ipv4_alias(interface, ip, prefix) {
+                    Ok(()) => {
+                        if self.verbose {
+                            println!("  Added alias {}/{} to {}", ip, prefix, interface);
+                        }
+                        added_alias = Some((interface, ip));
+                        self.update_record(&full_name, |record| record.added_alias = true);
+                    }
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+            }
+        }
 
         // Create the jail
         println!("Starting jail '{}'...", full_name);
-        let jid = match jail_create(&path, params) {
+        let retry = &self.config.config.retry;
+        let created = self
+            .mount_filesystems(jail_def, &path)
+            .and_then(|()| self.run_native_exec(jail_def, "exec.prestart", &hook_context))
+            .and_then(|()| {
+                create_with_retry(&full_name, retry, || jail_create(&path, params.clone()))
+            })
+            .and_then(|jid| {
+                let context = hook_context.clone().with_jid(jid);
+                match self.run_native_exec(jail_def, "exec.created", &context) {
+                    Ok(()) => Ok(jid),
+                    Err(e) => {
+                        let _ = jail_remove(jid);
+                        Err(e)
+                    }
+                }
+            });
+        let jid = match created {
             Ok(jid) => jid,
             Err(e) => {
                 // Cleanup on jail creation failure
                 eprintln!("Failed to create jail '{}': {}", full_name, e);
+                self.unmount_filesystems(jail_def, &path);
+                if let Some((interface, ip)) = added_alias {
+                    let _ = delete_ipv4_alias(interface, ip);
+                    self.update_record(&full_name, |record| record.added_alias = false);
+                }
                 // Cleanup VnetSetup if created
                 if let Some(setup) = vnet_setup {
                     let _ = setup.cleanup();
                 }
-                // Release allocated IP
-                if let Some((network_name, ip)) = &allocated_ip {
-                    self.ip_allocator.release(network_name, ip);
-                }
+                // Release allocated IPs
+                self.release_ips(&allocated_ip);
                 if created_zfs_dataset {
                     eprintln!("Cleaning up ZFS dataset...");
                     if let Some(zfs) = &self.zfs {
@@ -807,9 +2218,10 @@ impl Bridge {
                 // Track the failed instance
                 let jail_config = JailConfig::new(&full_name, &path);
                 let mut instance = JailInstance::new(jail_config);
-                instance.start().ok(); // Transition to Starting
-                instance.fail().ok();  // Transition to Failed
+                transition(self.verbose, &full_name, &mut instance, JailInstance::start);
+                transition(self.verbose, &full_name, &mut instance, JailInstance::fail);
                 self.instances.insert(full_name.clone(), instance);
+                self.update_record(&full_name, |record| record.failed = true);
                 // Notify Warden of failure
                 if let Some(handle) = &self.warden_handle {
                     let _ = handle.notify_failure_blocking(&full_name);
@@ -844,47 +2256,146 @@ impl Bridge {
                 println!("  Set default gateway to {}", setup.config.gateway);
             }
 
-            // Store the VnetSetup for cleanup on stop
+            // Store the VnetSetup for cleanup on stop, also by later invocations
+            if let Err(e) = setup.save(&self.config.config.data_dir, &full_name) {
+                eprintln!("Warning: Failed to save VNET state for jail '{}': {}", full_name, e);
+            }
             self.vnet_setups.insert(full_name.clone(), setup);
         }
 
+        // Run exec.start with its output captured on the host
+        let mut exit_code = None;
+        // validate() rejects values that are not a command or a list of commands
+        let start_command = jail_def.params.get(EXEC_START_PARAM).and_then(|value| {
+            exec_start_command(value).inspect_err(|e| eprintln!("Warning: {}", e)).ok()
+        });
+        if let Some(command) = start_command {
+            exit_code = self.exec_start(jid, &full_name, &command, &hook_context);
+
+            // A failed one-shot run is retried in place when its restart policy allows
+            if jail_def.oneshot_enabled() && jail_def.restart_policy() != RestartPolicy::No {
+                for attempt in 1..=ONESHOT_RETRIES {
+                    if exit_code == Some(0) {
+                        break;
+                    }
+                    println!(
+                        "  Retrying exec.start for '{}' ({}/{})",
+                        full_name, attempt, ONESHOT_RETRIES
+                    );
+                    exit_code = self.exec_start(jid, &full_name, &command, &hook_context);
+                }
+            }
+            if let Some(code) = exit_code {
+                self.record_exit(&full_name, code);
+            }
+        }
+
         // Update context with JID for post_start hooks
         let hook_context = hook_context.with_jid(jid);
 
+        if let Err(e) = self.run_native_exec(jail_def, "exec.poststart", &hook_context) {
+            eprintln!("Warning: {}", e);
+            eprintln!("Jail is running but may not be fully configured.");
+        }
+
         // Execute post_start hooks (failure here doesn't cleanup the jail)
         if let Err(e) = hook_runner.execute_phase(HookPhase::PostStart, &hook_context) {
             eprintln!("Warning: post_start hook failed for jail '{}': {}", full_name, e);
             eprintln!("Jail is running but may not be fully configured.");
         }
 
+        // Only a jail whose service answers counts as started
+        if let Some(command) = &jail_def.readiness
+            && let Err(e) = self.wait_ready(jid, &full_name, command, jail_def.start_timeout())
+        {
+            eprintln!("Jail '{}' did not become ready: {}", full_name, e);
+            if !allocated_ip.is_empty() {
+                self.allocated_ips.insert(full_name.clone(), allocated_ip);
+            }
+            match self.stop_jail(&full_name) {
+                Ok(()) => {
+                    // Remove a root created by this start, as on creation failure
+                    if provisioned_root {
+                        let _ = std::fs::remove_dir_all(&path);
+                    }
+                    if created_zfs_dataset {
+                        eprintln!("Cleaning up ZFS dataset...");
+                        if let Some(zfs) = &self.zfs {
+                            let _ = zfs.destroy_jail_dataset(&full_name);
+                        }
+                    }
+                }
+                Err(stop_error) => {
+                    eprintln!("Warning: Failed to stop jail '{}': {}", full_name, stop_error);
+                }
+            }
+            self.mark_failed(&full_name);
+            if let Some(handle) = &self.warden_handle {
+                let _ = handle.notify_failure_blocking(&full_name);
+            }
+            return Err(e);
+        }
+
         // Track the instance with full configuration
         let mut jail_config = JailConfig::new(&full_name, &path);
         if let Some(hostname) = &jail_def.hostname {
             jail_config = jail_config.hostname(hostname);
         }
-        if let Some(ip) = effective_ip {
-            jail_config = jail_config.ip(ip);
+        for ip in &jail_ips {
+            jail_config = jail_config.ip(*ip);
         }
         let mut instance = JailInstance::new(jail_config);
         instance.jid = Some(jid);
-        instance.start().ok();
-        instance.started().ok();
+        transition(self.verbose, &full_name, &mut instance, JailInstance::start);
+        transition(self.verbose, &full_name, &mut instance, JailInstance::started);
         self.instances.insert(full_name.clone(), instance);
 
-        // Track allocated IP for cleanup on stop
-        if let Some(alloc) = allocated_ip {
-            self.allocated_ips.insert(full_name.clone(), alloc);
+        // Track allocated IPs for cleanup on stop
+        if !allocated_ip.is_empty() {
+            self.allocated_ips.insert(full_name.clone(), allocated_ip);
         }
 
+        // A started jail is no longer failed or held down by an earlier `down`
+        self.update_record(&full_name, |record| {
+            record.failed = false;
+            record.stopped_by_user = false;
+        });
+
         // Notify Warden that jail started successfully
         if let Some(handle) = &self.warden_handle
             && let Err(e) = handle.notify_started_blocking(&full_name) {
                 eprintln!("Warning: Failed to notify Warden of jail start: {}", e);
             }
 
+        if jail_def.oneshot_enabled() {
+            return self.finish_oneshot(&full_name, exit_code);
+        }
+
         Ok(())
     }
 
+    /// Record a one-shot jail's result and remove the jail
+    ///
+    /// A command that could not be run at all is recorded as 127, like sh(1)
+    /// does for a missing command.
+    fn finish_oneshot(&mut self, full_name: &str, exit_code: Option<i32>) -> Result<()> {
+        let code = exit_code.unwrap_or(127);
+        if exit_code.is_none() {
+            self.record_exit(full_name, code);
+        }
+
+        self.stop_jail(full_name)?;
+        if code == 0 {
+            println!("One-shot jail '{}' completed", full_name);
+            Ok(())
+        } else {
+            Err(Error::JailOperation(format!(
+                "One-shot jail '{}' exited with status {}",
+                full_name, code
+            )))
+        }
+    }
+
     /// Force cleanup of a failed jail
     ///
     /// Removes any leftover resources from a failed jail start:
@@ -900,6 +2411,7 @@ impl Bridge {
 
         // Try to remove jail if it exists (even partially)
         if let Ok(jid) = jail_getid(&full_name) {
+            self.reclaim_vnet_interface(&full_name, jid);
             println!("  Removing jail (JID {})...", jid);
             if let Err(e) = jail_remove(jid) {
                 if force {
@@ -910,6 +2422,12 @@ impl Bridge {
             }
         }
 
+        // Filesystems mounted into the root would keep its dataset busy
+        if let Some(jail_def) = jail_def {
+            let path = jail_def.effective_path(&self.config.config, &full_name);
+            self.unmount_filesystems(jail_def, &path);
+        }
+
         // Clean up ZFS dataset if we manage it
         if let Some(zfs) = &self.zfs
             && let Some(jail_def) = jail_def {
@@ -928,9 +2446,11 @@ impl Bridge {
 
         // Remove from instances
         self.instances.remove(&full_name);
+        self.update_record(&full_name, |record| record.failed = false);
 
         // Cleanup VNET epair interface if present
         if let Some(vnet_setup) = self.vnet_setups.remove(&full_name) {
+            VnetSetup::forget(&self.config.config.data_dir, &full_name);
             println!("  Cleaning up VNET setup...");
             if let Err(e) = vnet_setup.cleanup() {
                 if force {
@@ -941,8 +2461,8 @@ impl Bridge {
             }
         }
 
-        // Release allocated IP back to the pool
-        if let Some((network_name, ip)) = self.allocated_ips.remove(&full_name) {
+        // Release allocated IPs back to their pools
+        for (network_name, ip) in self.allocated_ips.remove(&full_name).unwrap_or_default() {
             self.ip_allocator.release(&network_name, &ip);
             println!("  Released IP {} back to network '{}'", ip, network_name);
         }
@@ -951,10 +2471,477 @@ impl Bridge {
         Ok(())
     }
 
+    /// Configured jails in the Failed state, in this session or as persisted
+    pub fn failed_jails(&self) -> Vec<String> {
+        let data_dir = &self.config.config.data_dir;
+        self.config
+            .jails
+            .iter()
+            .map(|jail_def| self.config.jail_name(&jail_def.name))
+            .filter(|full_name| {
+                self.instances
+                    .get(full_name)
+                    .is_some_and(|instance| instance.state() == JailState::Failed)
+                    || JailRecord::load(data_dir, full_name).failed
+            })
+            .collect()
+    }
+
+    /// Clean up every failed jail
+    ///
+    /// With `force`, a jail whose cleanup fails is reported and the sweep
+    /// continues; otherwise the first error stops it.
+    pub fn cleanup_failed(&mut self, force: bool) -> Result<()> {
+        let failed = self.failed_jails();
+        if failed.is_empty() {
+            println!("No failed jails to clean up");
+            return Ok(());
+        }
+
+        let mut cleaned = Vec::new();
+        let mut errors = Vec::new();
+        for full_name in failed {
+            match self.cleanup(&full_name, force) {
+                Ok(()) => cleaned.push(full_name),
+                Err(e) if force => {
+                    eprintln!("Warning: Failed to clean up jail '{}': {}", full_name, e);
+                    errors.push(full_name);
+                }
+                Err(e) => return Err(e),
+            }
+        }
+
+        println!("\nCleaned up {} failed jail(s): {}", cleaned.len(), cleaned.join(", "));
+        if !errors.is_empty() {
+            return Err(Error::JailOperation(format!(
+                "Failed to clean up {} jail(s): {}",
+                errors.len(),
+                errors.join(", ")
+            )));
+        }
+        Ok(())
+    }
+
+    /// Rename a jail's resources to a new name
+    ///
+    /// Stops the jail if it is running, renames its ZFS dataset (or moves its
+    /// directory under `data_dir`), moves its persisted state and VNET setup,
+    /// and re-points port forwards and allocated IPs. Refuses when the new
+    /// name is taken or other jails depend on the jail. The config file is not
+    /// edited; the change needed is printed.
+    pub fn rename(&mut self, name: &str, new_name: &str) -> Result<()> {
+        let (service_name, full_name) = self.resolve_jail_names(name)?;
+        let jail_def = self
+            .config
+            .get_jail(&service_name)
+            .ok_or_else(|| Error::JailNotFound(name.to_string()))?
+            .clone();
+
+        let is_instance = self.config.templates.keys().any(|template| {
+            service_name
+                .strip_prefix(template.as_str())
+                .and_then(|rest| rest.strip_prefix('-'))
+                .is_some_and(|index| index.parse::<u32>().is_ok())
+        });
+        if is_instance {
+            return Err(Error::JailOperation(format!(
+                "'{}' is an instance of a jail with count; rename its definition instead",
+                service_name
+            )));
+        }
+
+        if new_name.is_empty() || new_name.contains(['/', '.', ' ']) {
+            return Err(Error::ConfigValidation(format!(
+                "Invalid jail name '{}'",
+                new_name
+            )));
+        }
+        let new_full_name = self.config.jail_name(new_name);
+        if self.config.resolve_jail_names(new_name).is_some()
+            || self.is_configured_name(&new_full_name)
+            || jail_getid(&new_full_name).is_ok()
+        {
+            return Err(Error::JailOperation(format!(
+                "Jail '{}' already exists",
+                new_name
+            )));
+        }
+
+        let dependents: Vec<&str> = self
+            .config
+            .jails
+            .iter()
+            .filter(|j| j.depends_on.contains(&service_name))
+            .map(|j| j.name.as_str())
+            .collect();
+        if !dependents.is_empty() {
+            return Err(Error::JailOperation(format!(
+                "Cannot rename '{}': jails depend on it ({}); update their depends_on first",
+                service_name,
+                dependents.join(", ")
+            )));
+        }
+
+        let old_path = jail_def.effective_path(&self.config.config, &full_name);
+        let new_path = jail_def.effective_path(&self.config.config, &new_full_name);
+        if jail_def.path.is_none() && new_path.exists() {
+            return Err(Error::JailOperation(format!(
+                "Path {} already exists",
+                new_path.display()
+            )));
+        }
+
+        if jail_getid(&full_name).is_ok() {
+            println!("Stopping jail '{}'...", full_name);
+            self.stop_jail(&full_name)?;
+        }
+
+        // A custom path belongs to the user and stays where it is
+        if jail_def.path.is_none() {
+            let moved = match &self.zfs {
+                Some(zfs) => zfs.rename_jail_dataset(&full_name, &new_full_name)?.is_some(),
+                None if old_path.exists() => {
+                    std::fs::rename(&old_path, &new_path)?;
+                    true
+                }
+                None => false,
+            };
+            if moved {
+                println!("Moved {} -> {}", old_path.display(), new_path.display());
+            }
+        }
+
+        // Restart and exit history, and a VNET setup whose cleanup failed
+        let data_dir = self.config.config.data_dir.clone();
+        if JailRecord::rename(&data_dir, &full_name, &new_full_name)? && self.verbose {
+            println!("  Moved state of '{}' to '{}'", full_name, new_full_name);
+        }
+        if VnetSetup::rename(&data_dir, &full_name, &new_full_name)? {
+            if let Some(setup) = self.vnet_setups.remove(&full_name) {
+                self.vnet_setups.insert(new_full_name.clone(), setup);
+            }
+            if self.verbose {
+                println!("  Moved VNET state of '{}' to '{}'", full_name, new_full_name);
+            }
+        }
+
+        self.bulkhead.rename_jail(&full_name, &new_full_name)?;
+        if let Some(ips) = self.allocated_ips.remove(&full_name) {
+            self.allocated_ips.insert(new_full_name.clone(), ips);
+        }
+        self.instances.remove(&full_name);
+
+        println!("Renamed jail '{}' to '{}'", full_name, new_full_name);
+        println!("\nUpdate blackship.toml to match:");
+        println!("  [[jails]]");
+        println!("  name = \"{}\"    # was \"{}\"", new_name, service_name);
+        if jail_def.hostname.as_deref() == Some(service_name.as_str()) {
+            println!("  hostname = \"{}\"", new_name);
+        }
+        println!("\nThen start it with: blackship up {}", new_name);
+        Ok(())
+    }
+
+    /// Whether a full jail name belongs to a configured jail or to an
+    /// instance of a jail defined with `count`
+    fn is_configured_name(&self, full_name: &str) -> bool {
+        if self.config.jails.iter().any(|j| self.config.jail_name(&j.name) == full_name) {
+            return true;
+        }
+        self.config.templates.keys().any(|template| {
+            full_name
+                .strip_prefix(&self.config.jail_name(template))
+                .and_then(|rest| rest.strip_prefix('-'))
+                .is_some_and(|index| index.parse::<u32>().is_ok())
+        })
+    }
+
+    /// Find jail roots and datasets no configured jail refers to
+    ///
+    /// Conservative by design: only entries named after this project's jails
+    /// (`<project>-*`) are considered, and anything that is a configured
+    /// jail's path, is running, or still has filesystems mounted is skipped.
+    pub fn orphans(&self) -> Result<Vec<Orphan>> {
+        let prefix = format!("{}-", self.config.config.project_name());
+        let in_use: HashSet<PathBuf> = self
+            .config
+            .jails
+            .iter()
+            .map(|j| j.effective_path(&self.config.config, &self.config.jail_name(&j.name)))
+            .collect();
+        let is_orphan_name = |name: &str| {
+            name.starts_with(&prefix)
+                && !self.is_configured_name(name)
+                && jail_getid(name).is_err()
+        };
+
+        let mut orphans = Vec::new();
+        if let Some(zfs) = &self.zfs {
+            for name in zfs.list_jail_datasets()? {
+                if is_orphan_name(&name) && !in_use.contains(&zfs.jail_path(&name)) {
+                    orphans.push(Orphan::Dataset(name));
+                }
+            }
+        }
+
+        let roots_dir = self.config.config.data_dir.join("jails");
+        if let Ok(entries) = std::fs::read_dir(&roots_dir) {
+            let mut roots: Vec<PathBuf> = entries
+                .flatten()
+                .filter(|e| e.file_type().is_ok_and(|t| t.is_dir()))
+                .map(|e| e.path())
+                .collect();
+            roots.sort();
+            for root in roots {
+                let Some(name) = root.file_name().and_then(|n| n.to_str()) else {
+                    continue;
+                };
+                let dataset_root = self.zfs.as_ref().is_some_and(|zfs| zfs.jail_path(name) == root);
+                if is_orphan_name(name)
+                    && !dataset_root
+                    && !in_use.contains(&root)
+                    && crate::sys::mounts_under(&root).is_ok_and(|m| m.is_empty())
+                {
+                    orphans.push(Orphan::Root(root));
+                }
+            }
+        }
+
+        Ok(orphans)
+    }
+
+    /// Remove orphaned jail roots and datasets
+    ///
+    /// Lists what would be removed and, unless `force` is set, asks for
+    /// confirmation first. With `dry_run` nothing is removed.
+    pub fn prune(&self, dry_run: bool, force: bool) -> Result<()> {
+        let orphans = self.orphans()?;
+        if orphans.is_empty() {
+            println!("Nothing to prune.");
+            return Ok(());
+        }
+
+        println!("Orphaned jail roots and datasets:");
+        for orphan in &orphans {
+            println!("  {}", orphan);
+        }
+        if dry_run {
+            println!("\n{} item(s) would be removed (dry run).", orphans.len());
+            return Ok(());
+        }
+        if !force && !confirm(&format!("\nRemove {} item(s)?", orphans.len()))? {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for orphan in &orphans {
+            let result = match orphan {
+                Orphan::Dataset(name) => match &self.zfs {
+                    Some(zfs) => zfs.destroy_jail_dataset(name),
+                    None => Ok(()),
+                },
+                Orphan::Root(root) => {
+                    // Clear immutable flags left by the base system first
+                    let _ = std::process::Command::new("chflags")
+                        .args(["-R", "noschg"])
+                        .arg(root)
+                        .status();
+                    std::fs::remove_dir_all(root).map_err(Error::from)
+                }
+            };
+            match result {
+                Ok(()) => println!("  Removed {}", orphan),
+                Err(e) => {
+                    eprintln!("  Warning: Failed to remove {}: {}", orphan, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        println!("Pruned {} of {} item(s).", orphans.len() - failed, orphans.len());
+        Ok(())
+    }
+
+    /// Full names of all configured jails
+    pub fn configured_jail_names(&self) -> Vec<String> {
+        self.config.jails.iter().map(|jail| self.config.jail_name(&jail.name)).collect()
+    }
+
+    /// Destroy epair interfaces left behind by failed VNET starts
+    ///
+    /// Lists them and, unless `force` is set, asks for confirmation first.
+    /// With `dry_run` nothing is destroyed.
+    pub fn gc_epairs(&self, dry_run: bool, force: bool) -> Result<()> {
+        // Persisted setups of jails that are gone, plus unrecorded leftovers
+        let stale: Vec<(&String, &VnetSetup)> = self
+            .vnet_setups
+            .iter()
+            .filter(|(jail, _)| jail_getid(jail).is_err())
+            .collect();
+        let mut orphans = orphaned_epairs(&self.configured_jail_names())?;
+        for epair in stale.iter().filter_map(|(_, setup)| setup.epair.as_ref()) {
+            let host_side = epair.host_side().to_string();
+            if !orphans.contains(&host_side) {
+                orphans.push(host_side);
+            }
+        }
+        orphans.sort();
+        if orphans.is_empty() {
+            println!("No orphaned epair interfaces.");
+            return Ok(());
+        }
+
+        println!("Orphaned epair interfaces (not in any running jail):");
+        for name in &orphans {
+            println!("  {}", name);
+        }
+        if dry_run {
+            println!("\n{} epair(s) would be destroyed (dry run).", orphans.len());
+            return Ok(());
+        }
+        if !force && !confirm(&format!("\nDestroy {} epair(s)?", orphans.len()))? {
+            println!("Aborted.");
+            return Ok(());
+        }
+
+        let mut failed = 0;
+        for name in &orphans {
+            // Destroying one end destroys the pair (and drops its bridge membership)
+            match destroy_interface(name) {
+                Ok(()) => println!("  Destroyed {}", name),
+                Err(e) => {
+                    eprintln!("  Warning: Failed to destroy {}: {}", name, e);
+                    failed += 1;
+                }
+            }
+        }
+
+        for (jail, _) in stale {
+            VnetSetup::forget(&self.config.config.data_dir, jail);
+        }
+        println!("Destroyed {} of {} epair(s).", orphans.len() - failed, orphans.len());
+        Ok(())
+    }
+
+    /// Check a VNET jail's network before anything is created for it
+    ///
+    /// The bridge must exist, an address and subnet must be determinable and the
+    /// gateway must lie in that subnet.
+    fn validate_vnet(&self, jail_def: &JailDef, full_name: &str) -> Result<()> {
+        let Some(network) = jail_def.network.as_ref().filter(|n| n.vnet) else {
+            return Ok(());
+        };
+        let invalid =
+            |reason: String| Error::Network(format!("VNET jail '{}' {}", full_name, reason));
+
+        if network.vnet_interface.is_none() {
+            let bridge = network
+                .bridge
+                .as_ref()
+                .ok_or_else(|| invalid("requires a bridge configuration".to_string()))?;
+            if !interface_exists(bridge)? {
+                return Err(invalid(format!(
+                    "uses bridge '{}', which does not exist (create it with `blackship network \
+                     create`)",
+                    bridge
+                )));
+            }
+        }
+
+        // The subnet comes from ip_cidr, else from the attached network (as at start)
+        let ip = network.address();
+        let mut pools = network
+            .networks
+            .iter()
+            .filter_map(|net| self.ip_allocator.get_pool(net))
+            .map(|pool| pool.subnet());
+        let subnet = match (&network.ip_cidr, ip) {
+            (Some(cidr), _) => Some(
+                cidr.parse::<IpNet>()
+                    .map_err(|e| invalid(format!("has an invalid ip_cidr '{}': {}", cidr, e)))?
+                    .trunc(),
+            ),
+            (None, Some(ip)) => pools.find(|subnet| subnet.contains(&ip)).or_else(|| {
+                let prefix = if ip.is_ipv6() { 64 } else { 24 };
+                IpNet::new(ip, prefix).ok().map(|net| net.trunc())
+            }),
+            (None, None) => pools.next(),
+        };
+        let Some(subnet) = subnet else {
+            return Err(invalid(
+                "has no address: set ip or ip_cidr, or attach it to a network".to_string(),
+            ));
+        };
+
+        let gateway = vnet_gateway(network, ip, &self.ip_allocator).ok_or_else(|| {
+            invalid("has no gateway: set gateway, or attach it to a network".to_string())
+        })?;
+        if !subnet.contains(&gateway) {
+            return Err(invalid(format!(
+                "has gateway {} outside its subnet {}",
+                gateway, subnet
+            )));
+        }
+        Ok(())
+    }
+
+    /// Jail other than `full_name` holding the network's passed-through interface
+    fn vnet_interface_owner(&self, network: &JailNetworkConfig, full_name: &str) -> Option<String> {
+        let interface = network.vnet_interface.as_ref()?;
+        self.vnet_setups
+            .iter()
+            .find(|(jail, setup)| {
+                jail.as_str() != full_name && setup.config.interface.as_ref() == Some(interface)
+            })
+            .map(|(jail, _)| jail.clone())
+    }
+
+    /// Return a jail's passed-through physical interface to the host
+    ///
+    /// Done before the jail is removed so the interface doesn't linger in a dying jail.
+    fn reclaim_vnet_interface(&self, full_name: &str, jid: i32) {
+        let Some(setup) = self.vnet_setups.get(full_name) else {
+            return;
+        };
+        match setup.detach_from_jail(jid) {
+            Ok(()) if self.verbose && setup.epair.is_none() => {
+                println!("  Returned {} to the host", setup.jail_interface());
+            }
+            Ok(()) => {}
+            Err(e) => eprintln!(
+                "Warning: Failed to return {} to the host: {}",
+                setup.jail_interface(),
+                e
+            ),
+        }
+    }
+
+    /// Release addresses allocated for a jail back to their pools
+    fn release_ips(&mut self, allocations: &[(String, IpAddr)]) {
+        for (network_name, ip) in allocations {
+            self.ip_allocator.release(network_name, ip);
+        }
+    }
+
     /// Stop a single jail
     fn stop_jail(&mut self, name: &str) -> Result<()> {
         let (service_name, full_name) = self.resolve_jail_names(name)?;
 
+        // The kernel removes nested jails with their parent; stop them properly first
+        let children: Vec<String> = self
+            .config
+            .jails
+            .iter()
+            .filter(|j| j.parent.as_deref() == Some(service_name.as_str()))
+            .map(|j| j.name.clone())
+            .collect();
+        for child in &children {
+            if jail_getid(&self.config.jail_name(child)).is_ok() {
+                self.stop_jail(child)?;
+            }
+        }
+
         // Get JID
         let jid = match jail_getid(&full_name) {
             Ok(jid) => jid,
@@ -963,14 +2950,20 @@ impl Bridge {
             }
         };
 
+        // Continue paused processes so they can shut down cleanly
+        if jail_is_paused(jid) {
+            jail_signal(jid, Signal::SIGCONT)?;
+        }
+
         // Get jail definition for hooks
         let jail_def = self.config.get_jail(&service_name);
 
         // Setup hooks if jail has hook configuration
         if let Some(jail_def) = jail_def {
             let path = jail_def.effective_path(&self.config.config, &full_name);
-            let hook_runner = HookRunner::new(jail_def.hooks.clone()).verbose(self.verbose);
-            let mut hook_context = HookContext::new(&full_name, &path).with_jid(jid);
+            let hook_runner = HookRunner::new(jail_def.effective_hooks(&self.config.config))
+                .verbose(self.verbose);
+            let mut hook_context = self.hook_context(jail_def, &full_name, &path).with_jid(jid);
 
             // Add IP to context if available
             if let Some(network) = &jail_def.network
@@ -981,31 +2974,67 @@ impl Bridge {
             // Execute pre_stop hooks (inside jail, while still running)
             hook_runner.execute_phase(HookPhase::PreStop, &hook_context)?;
 
+            // Native exec.prestop and exec.stop fail like jail(8): with a warning
+            for param in ["exec.prestop", "exec.stop"] {
+                if let Err(e) = self.run_native_exec(jail_def, param, &hook_context) {
+                    eprintln!("Warning: {}", e);
+                }
+            }
+
             // Remove the jail
             println!("Stopping jail '{}'...", full_name);
+            self.reclaim_vnet_interface(&full_name, jid);
             jail_remove(jid)?;
             println!("Jail '{}' stopped", full_name);
+            self.unmount_filesystems(jail_def, &path);
 
             // Execute post_stop hooks (on host, after jail stopped)
             // Note: JID is no longer valid, but path and name are
-            let hook_context = HookContext::new(&full_name, &path);
+            let hook_context = self.hook_context(jail_def, &full_name, &path);
+            if let Err(e) = self.run_native_exec(jail_def, "exec.poststop", &hook_context) {
+                eprintln!("Warning: {}", e);
+            }
             hook_runner.execute_phase(HookPhase::PostStop, &hook_context)?;
         } else {
             // No jail definition found, just stop directly
             println!("Stopping jail '{}'...", full_name);
+            self.reclaim_vnet_interface(&full_name, jid);
             jail_remove(jid)?;
             println!("Jail '{}' stopped", full_name);
         }
 
         // Update instance state
         if let Some(instance) = self.instances.get_mut(&full_name) {
-            instance.stop().ok();
-            instance.stopped().ok();
+            transition(self.verbose, &full_name, instance, JailInstance::stop);
+            transition(self.verbose, &full_name, instance, JailInstance::stopped);
             instance.jid = None;
         }
 
+        // Remove the host alias of a shared-IP jail, unless it was already on
+        // the host before the jail started
+        let added_alias = JailRecord::load(&self.config.config.data_dir, &full_name).added_alias;
+        if added_alias && let Some(jail_def) = self.config.get_jail(&service_name) {
+            let ip = self
+                .allocated_ips
+                .get(&full_name)
+                .and_then(|allocs| allocs.iter().map(|(_, ip)| *ip).find(IpAddr::is_ipv4))
+                .or_else(|| jail_def.network.as_ref().and_then(|n| n.address()));
+            let is_vnet = jail_def.network.as_ref().is_some_and(|n| n.vnet);
+            if !is_vnet
+                && let Some((interface, ip, _)) = host_alias(jail_def, ip)
+            {
+                match delete_ipv4_alias(interface, ip) {
+                    Ok(()) if self.verbose => println!("  Removed alias {} from {}", ip, interface),
+                    Ok(()) => {}
+                    Err(e) => eprintln!("Warning: {}", e),
+                }
+            }
+            self.update_record(&full_name, |record| record.added_alias = false);
+        }
+
         // Cleanup VNET setup if present
         if let Some(vnet_setup) = self.vnet_setups.remove(&full_name) {
+            VnetSetup::forget(&self.config.config.data_dir, &full_name);
             if let Err(e) = vnet_setup.cleanup() {
                 eprintln!("Warning: Failed to cleanup VNET setup for jail '{}': {}", full_name, e);
             } else if self.verbose {
@@ -1013,8 +3042,8 @@ impl Bridge {
             }
         }
 
-        // Release allocated IP back to the pool
-        if let Some((network_name, ip)) = self.allocated_ips.remove(&full_name) {
+        // Release allocated IPs back to their pools
+        for (network_name, ip) in self.allocated_ips.remove(&full_name).unwrap_or_default() {
             self.ip_allocator.release(&network_name, &ip);
             if self.verbose {
                 println!("  Released IP {} back to network '{}'", ip, network_name);
@@ -1030,6 +3059,27 @@ impl Bridge {
         Ok(())
     }
 
+    /// Mark a jail as Failed (used by the Warden when a jail is crash-looping)
+    pub fn mark_failed(&mut self, name: &str) {
+        let Ok((service_name, full_name)) = self.resolve_jail_names(name) else {
+            return;
+        };
+        let Some(jail_def) = self.config.get_jail(&service_name) else {
+            return;
+        };
+        let path = jail_def.effective_path(&self.config.config, &full_name);
+
+        let instance = self
+            .instances
+            .entry(full_name.clone())
+            .or_insert_with(|| JailInstance::new(JailConfig::new(&full_name, path)));
+        if instance.state() == JailState::Stopped {
+            transition(self.verbose, &full_name, instance, JailInstance::start);
+        }
+        transition(self.verbose, &full_name, instance, JailInstance::fail);
+        self.update_record(&full_name, |record| record.failed = true);
+    }
+
     /// Restart a jail (stop then start)
     ///
     /// Used by the Warden for automatic restart on failure
@@ -1040,7 +3090,7 @@ impl Bridge {
         // If the jail is in Failed state, recover it first
         if let Some(instance) = self.instances.get_mut(&full_name)
             && instance.state() == JailState::Failed {
-                instance.recover().ok(); // Transition from Failed to Stopped
+                transition(self.verbose, &full_name, instance, JailInstance::recover);
             }
 
         // Stop if running
@@ -1064,21 +3114,180 @@ impl Bridge {
             .position(|n| *n == service_name)
             .ok_or_else(|| Error::JailNotFound(name.to_string()))?;
 
-        // Return all jails up to and including this one
-        Ok(order[..=idx].to_vec())
+        // Return all jails up to and including this one
+        Ok(order[..=idx].to_vec())
+    }
+
+    /// Get all dependents of a jail (including the jail itself)
+    fn get_dependents(&self, name: &str) -> Result<Vec<&str>> {
+        let (service_name, _full_name) = self.resolve_jail_names(name)?;
+        let order = self.stop_order()?;
+        let idx = order
+            .iter()
+            .position(|n| *n == service_name)
+            .ok_or_else(|| Error::JailNotFound(name.to_string()))?;
+
+        // Return all jails from the start up to and including this one
+        Ok(order[..=idx].to_vec())
+    }
+
+    /// Build a hook context for a jail, including its network variables
+    fn hook_context(&self, jail_def: &JailDef, full_name: &str, path: &Path) -> HookContext {
+        let mut context = HookContext::new(full_name, path);
+        if let Some(gateway) = self.config.jail_gateway(jail_def) {
+            context = context.with_gateway(gateway.to_string());
+        }
+        if let Some(network) = self.config.primary_network(jail_def) {
+            context = context.with_network(&network.name, &network.subnet);
+        }
+        for dep in &jail_def.depends_on {
+            let Some(ip) = self
+                .config
+                .get_jail(dep)
+                .and_then(|dep_def| self.known_ip(dep_def))
+            else {
+                continue;
+            };
+            let (var, env) = dependency_vars(dep);
+            context = context.with_var(&var, &ip.to_string()).with_env(&env, &ip.to_string());
+        }
+        context
+    }
+
+    /// Address of a jail: the one allocated by this process, else the static one
+    fn known_ip(&self, jail_def: &JailDef) -> Option<IpAddr> {
+        self.allocated_ips
+            .get(&self.config.jail_name(&jail_def.name))
+            .and_then(|allocs| allocs.first().map(|(_, ip)| *ip))
+            .or_else(|| jail_def.network.as_ref().and_then(|n| n.address()))
+    }
+
+    /// Mount a jail's `[jails.mount]` filesystems under its root
+    fn mount_filesystems(&self, jail_def: &JailDef, root: &Path) -> Result<()> {
+        let Some(mount) = &jail_def.mount else {
+            return Ok(());
+        };
+        let specs = mount.specs().map_err(Error::ConfigValidation)?;
+        mount_all(&specs, root)?;
+        if self.verbose {
+            for spec in &specs {
+                println!("  Mounted {} on {}", spec.fstype, spec.jail_path.display());
+            }
+        }
+        Ok(())
+    }
+
+    /// Unmount a jail's `[jails.mount]` filesystems, in reverse mount order
+    fn unmount_filesystems(&self, jail_def: &JailDef, root: &Path) {
+        if let Some(specs) = jail_def.mount.as_ref().and_then(|m| m.specs().ok()) {
+            unmount_all(&specs, root);
+        }
+    }
+
+    /// Run one of a jail's native `exec.*` params (see [`NATIVE_EXEC_PARAMS`]), if set
+    fn run_native_exec(
+        &self,
+        jail_def: &JailDef,
+        param: &str,
+        context: &HookContext,
+    ) -> Result<()> {
+        let Some(value) = jail_def.params.get(param) else {
+            return Ok(());
+        };
+        let hooks = native_exec_hooks(param, value).map_err(Error::ConfigValidation)?;
+        let phase = hooks[0].phase;
+        HookRunner::new(hooks)
+            .verbose(self.verbose)
+            .execute_phase(phase, context)
+            .map_err(|e| {
+                let message = format!("{} failed for jail '{}': {}", param, context.jail_name, e);
+                Error::JailOperation(message)
+            })
+    }
+
+    /// Run a jail's `exec.start` command, appending its output to the jail's
+    /// console log (`data_dir/logs/<jail>.log`)
+    ///
+    /// The hooks' environment (such as `<DEP>_HOST` of dependencies) is passed
+    /// to the command. A failing command is reported but leaves the jail
+    /// running. Returns the command's exit code, if it ran.
+    fn exec_start(
+        &self,
+        jid: i32,
+        full_name: &str,
+        command: &str,
+        context: &HookContext,
+    ) -> Option<i32> {
+        let log = console::console_log_path(&self.config.config.data_dir, full_name);
+        let logging = &self.config.logging;
+        if let Err(e) = logging::rotate(&log, logging.max_size_bytes(), logging.keep) {
+            eprintln!("Warning: Failed to rotate {}: {}", log.display(), e);
+        }
+        if self.verbose {
+            println!("  Running exec.start (output in {})", log.display());
+        }
+        let mut env: Vec<(String, String)> =
+            context.env.iter().map(|(k, v)| (k.clone(), v.clone())).collect();
+        env.sort();
+        match console::exec_logged(jid, command, &env, &log) {
+            Ok(status) => {
+                if !status.success() {
+                    eprintln!(
+                        "Warning: exec.start for jail '{}' failed ({}); see {}",
+                        full_name,
+                        status,
+                        log.display()
+                    );
+                }
+                // Killed by a signal: report it like sh(1) does
+                status.code().or_else(|| status.signal().map(|sig| 128 + sig))
+            }
+            Err(e) => {
+                eprintln!("Warning: exec.start for jail '{}' failed: {}", full_name, e);
+                None
+            }
+        }
     }
 
-    /// Get all dependents of a jail (including the jail itself)
-    fn get_dependents(&self, name: &str) -> Result<Vec<&str>> {
-        let (service_name, _full_name) = self.resolve_jail_names(name)?;
-        let order = self.stop_order()?;
-        let idx = order
-            .iter()
-            .position(|n| *n == service_name)
-            .ok_or_else(|| Error::JailNotFound(name.to_string()))?;
+    /// Run a jail's readiness command until it exits 0 or `timeout` passes
+    fn wait_ready(
+        &self,
+        jid: i32,
+        full_name: &str,
+        command: &str,
+        timeout: Duration,
+    ) -> Result<()> {
+        if self.verbose {
+            println!("  Waiting for readiness: {}", command);
+        }
+        let started = Instant::now();
+        loop {
+            let remaining = timeout.saturating_sub(started.elapsed());
+            let secs = remaining.as_secs().max(1);
+            match jexec_with_timeout(jid, &["sh", "-c", command], secs) {
+                Ok((0, _, _)) => {
+                    if self.verbose {
+                        println!("  Jail '{}' ready after {:?}", full_name, started.elapsed());
+                    }
+                    return Ok(());
+                }
+                Ok((code, stdout, stderr)) if self.verbose => {
+                    let output = format!("{}{}", stdout, stderr);
+                    println!("  Not ready (exit {}): {}", code, output.trim());
+                }
+                Ok(_) | Err(Error::JailTimeout(_)) => {}
+                Err(e) => return Err(e),
+            }
 
-        // Return all jails from the start up to and including this one
-        Ok(order[..=idx].to_vec())
+            let remaining = timeout.saturating_sub(started.elapsed());
+            if remaining.is_zero() {
+                return Err(Error::StartTimeout {
+                    jail: full_name.to_string(),
+                    secs: timeout.as_secs(),
+                });
+            }
+            std::thread::sleep(remaining.min(Duration::from_secs(1)));
+        }
     }
 
     /// Configure DNS in a jail
@@ -1098,6 +3307,55 @@ impl Bridge {
         Ok(())
     }
 
+    /// Write a jail's managed /etc/hosts entries
+    ///
+    /// Entries go in a marked block so the rest of the file is kept; the block
+    /// is rewritten on every start and dropped when there is nothing to add.
+    fn configure_hosts(&self, jail_path: &Path, jail_def: &JailDef, full_name: &str) -> Result<()> {
+        let hosts_path = jail_path.join("etc/hosts");
+        let mut entries: Vec<(IpAddr, Vec<String>)> = jail_def
+            .network
+            .iter()
+            .flat_map(|n| &n.hosts)
+            .map(|entry| (entry.ip, entry.names.clone()))
+            .collect();
+        if self.config.config.sibling_hosts_enabled() {
+            entries.extend(self.sibling_hosts(full_name));
+        }
+
+        let existing = std::fs::read_to_string(&hosts_path).unwrap_or_default();
+        if entries.is_empty() && !existing.contains(HOSTS_BLOCK_BEGIN) {
+            return Ok(());
+        }
+        if !hosts_path.parent().is_some_and(Path::exists) {
+            return Ok(());
+        }
+        std::fs::write(&hosts_path, managed_hosts(&existing, &entries))
+            .map_err(|e| Error::JailOperation(format!("Failed to write /etc/hosts: {}", e)))?;
+        Ok(())
+    }
+
+    /// Names and addresses of every other jail with a known IP
+    fn sibling_hosts(&self, full_name: &str) -> Vec<(IpAddr, Vec<String>)> {
+        let mut hosts = Vec::new();
+        for jail in &self.config.jails {
+            let sibling = self.config.jail_name(&jail.name);
+            if sibling == full_name {
+                continue;
+            }
+            let Some(ip) = self.known_ip(jail) else { continue };
+
+            let mut names = vec![jail.name.clone(), sibling];
+            if let Some(hostname) = &jail.hostname
+                && !names.contains(hostname)
+            {
+                names.push(hostname.clone());
+            }
+            hosts.push((ip, names));
+        }
+        hosts
+    }
+
     /// Initialize the PF firewall anchor for port forwarding
     ///
     /// This should be called once at startup to ensure PF is properly configured.
@@ -1186,6 +3444,415 @@ impl Bridge {
     }
 }
 
+/// Instances to define, stop and start when scaling a jail to `replicas`
+#[derive(Debug, Default, PartialEq)]
+struct ScalePlan {
+    /// Instances missing from the config, with their index
+    define: Vec<(String, u32)>,
+    /// Running surplus instances, highest first
+    stop: Vec<String>,
+    /// Wanted instances that are not running
+    start: Vec<String>,
+}
+
+/// Plan a scale of `name` to `replicas`, given which instances are configured and running
+fn scale_plan(
+    name: &str,
+    replicas: u32,
+    known: impl Fn(&str) -> bool,
+    running: impl Fn(&str) -> bool,
+) -> ScalePlan {
+    let mut plan = ScalePlan::default();
+
+    // Define every instance that is wanted, configured or still running
+    let mut index = 1;
+    loop {
+        let instance = instance_name(name, index);
+        let is_known = known(&instance);
+        let is_running = running(&instance);
+        if index > replicas && !is_known && !is_running {
+            break;
+        }
+        if !is_known {
+            plan.define.push((instance.clone(), index));
+        }
+        if index > replicas {
+            if is_running {
+                plan.stop.push(instance);
+            }
+        } else if !is_running {
+            plan.start.push(instance);
+        }
+        index += 1;
+    }
+    plan.stop.reverse();
+
+    plan
+}
+
+/// Build the dependency graph (dep -> jail) for a configuration
+fn build_graph(config: &BlackshipConfig) -> Result<DiGraph<String, ()>> {
+    let mut graph = DiGraph::new();
+    let mut node_map = HashMap::new();
+
+    // Add nodes for each jail
+    for jail in &config.jails {
+        let idx = graph.add_node(jail.name.clone());
+        node_map.insert(jail.name.clone(), idx);
+    }
+
+    // Add edges for dependencies (dep -> jail)
+    for jail in &config.jails {
+        let to = node_map[&jail.name];
+        for dep in &jail.depends_on {
+            let from = node_map
+                .get(dep)
+                .ok_or_else(|| Error::UnknownDependency(dep.clone()))?;
+            graph.add_edge(*from, to, ());
+        }
+    }
+
+    Ok(graph)
+}
+
+/// Render jails (in start order) and dependency edges (dep -> jail)
+fn render_graph(order: &[&str], edges: &[(&str, &str)], format: GraphFormat) -> String {
+    match format {
+        GraphFormat::Dot => {
+            let quote = |name: &str| format!("\"{}\"", name.replace('"', "\\\""));
+            let mut out = String::from("digraph blackship {\n    rankdir=LR;\n");
+            for (i, name) in order.iter().enumerate() {
+                out.push_str(&format!(
+                    "    {} [label={}];\n",
+                    quote(name),
+                    quote(&format!("{}. {}", i + 1, name))
+                ));
+            }
+            for (from, to) in edges {
+                out.push_str(&format!("    {} -> {};\n", quote(from), quote(to)));
+            }
+            out.push_str("}\n");
+            out
+        }
+        GraphFormat::Json => {
+            let jails: Vec<serde_json::Value> = order
+                .iter()
+                .enumerate()
+                .map(|(i, name)| {
+                    let depends_on: Vec<&str> =
+                        edges.iter().filter(|(_, to)| to == name).map(|(from, _)| *from).collect();
+                    let dependents: Vec<&str> =
+                        edges.iter().filter(|(from, _)| from == name).map(|(_, to)| *to).collect();
+                    serde_json::json!({
+                        "name": name,
+                        "order": i + 1,
+                        "depends_on": depends_on,
+                        "dependents": dependents,
+                    })
+                })
+                .collect();
+            let json = serde_json::json!({ "jails": jails });
+            format!("{}\n", serde_json::to_string_pretty(&json).unwrap_or_default())
+        }
+    }
+}
+
+/// State, JID and uptime of a jail as seen by the kernel
+///
+/// The uptime is derived from the oldest process in the jail.
+fn probe_status(full_name: &str) -> (String, Option<i32>, Option<Duration>) {
+    match jail_getid(full_name) {
+        Ok(jid) => {
+            let state = if jail_is_paused(jid) { "Paused" } else { "Running" };
+            (state.to_string(), Some(jid), jail_uptime(jid))
+        }
+        Err(_) => ("Stopped".to_string(), None, None),
+    }
+}
+
+/// Create the IP pool for a network definition
+fn network_pool(network: &NetworkConfig) -> Result<IpPool> {
+    let subnet: IpNet = network.subnet.parse().map_err(|e| {
+        Error::Network(format!(
+            "Invalid subnet '{}' for network '{}': {}",
+            network.subnet, network.name, e
+        ))
+    })?;
+
+    if let Some(gateway) = network.gateway {
+        IpPool::with_gateway(subnet, gateway)
+    } else {
+        IpPool::new(subnet)
+    }
+}
+
+/// Create a jail, retrying transient failures (EAGAIN, ENOMEM) with backoff
+///
+/// Any other error is returned at once.
+fn create_with_retry(
+    full_name: &str,
+    retry: &RetryConfig,
+    mut create: impl FnMut() -> Result<i32>,
+) -> Result<i32> {
+    let backoff = ExponentialBackoff::new()
+        .base_delay_ms(retry.base_delay_ms)
+        .max_delay_ms(retry.max_delay_ms)
+        .multiplier(retry.multiplier)
+        .max_attempts(retry.max_attempts)
+        .jitter_factor(retry.jitter_factor);
+    let mut rng = rng();
+    let mut attempt: u8 = 0;
+
+    loop {
+        attempt += 1;
+        match create() {
+            Err(e) if is_transient_error(&e) => {
+                let Some(delay_ms) = backoff.delay(attempt, &mut rng) else {
+                    return Err(e);
+                };
+                eprintln!(
+                    "Creating jail '{}' failed ({}), retrying in {}ms (attempt {})...",
+                    full_name, e, delay_ms, attempt
+                );
+                std::thread::sleep(Duration::from_millis(delay_ms));
+            }
+            result => return result,
+        }
+    }
+}
+
+/// Move a jail instance through its state machine
+///
+/// Each transition is printed under `--verbose`. An illegal transition leaves
+/// the state untouched; it is reported (again under `--verbose`) instead of
+/// being swallowed, so a jail stuck in e.g. `Starting` can be spotted.
+fn transition(
+    verbose: bool,
+    full_name: &str,
+    instance: &mut JailInstance,
+    event: fn(&mut JailInstance) -> std::result::Result<(), DynamicError>,
+) {
+    let from = instance.state();
+    match event(instance) {
+        Ok(()) if verbose => {
+            println!("  State of '{}': {} -> {}", full_name, from, instance.state())
+        }
+        Ok(()) => {}
+        Err(_) if !verbose => {}
+        Err(DynamicError::InvalidTransition { event, .. }) => {
+            let allowed: Vec<&str> = from.transitions().into_iter().map(|(e, _)| e).collect();
+            eprintln!(
+                "Warning: Jail '{}' cannot '{}' from {} (allowed: {})",
+                full_name,
+                event,
+                from,
+                if allowed.is_empty() { "none".to_string() } else { allowed.join(", ") }
+            );
+        }
+        Err(e) => eprintln!("Warning: Jail '{}' state transition failed: {:?}", full_name, e),
+    }
+}
+
+/// Default gateway of a VNET jail: its own `gateway`, else the gateway of the
+/// attached network holding `ip` (the first attached network without an address),
+/// else the first usable address of its `ip_cidr` subnet
+fn vnet_gateway(
+    network: &JailNetworkConfig,
+    ip: Option<IpAddr>,
+    allocator: &IpAllocator,
+) -> Option<IpAddr> {
+    if network.gateway.is_some() {
+        return network.gateway;
+    }
+
+    let mut pools = network.networks.iter().filter_map(|net| allocator.get_pool(net));
+    let pool = match ip {
+        Some(ip) => pools.find(|pool| pool.subnet().contains(&ip)),
+        None => pools.next(),
+    };
+    if let Some(pool) = pool {
+        return Some(pool.gateway());
+    }
+
+    let subnet: IpNet = network.ip_cidr.as_deref()?.parse().ok()?;
+    IpPool::new(subnet.trunc()).ok().map(|pool| pool.gateway())
+}
+
+/// Host interface alias for a shared-IP jail: (interface, address, prefix length)
+///
+/// Only IPv4 addresses are aliased; IPv6 addresses must be configured on the host.
+fn host_alias(jail_def: &JailDef, ip: Option<IpAddr>) -> Option<(&str, Ipv4Addr, u8)> {
+    let network = jail_def.network.as_ref()?;
+    let interface = network.interface.as_deref()?;
+    match ip? {
+        IpAddr::V4(ip) => Some((interface, ip, network.alias_prefix_len())),
+        IpAddr::V6(_) => None,
+    }
+}
+
+/// Print the resolved command of each hook in the given phases (dry-run preview)
+fn print_hook_preview(hooks: &[Hook], phases: &[HookPhase], context: &HookContext, indent: &str) {
+    for phase in phases {
+        for hook in hooks.iter().filter(|h| h.phase == *phase) {
+            println!("{}Hook {} ({}): {}", indent, phase, hook.target, hook.render(context));
+        }
+    }
+}
+
+/// First line of the /etc/hosts block blackship manages
+const HOSTS_BLOCK_BEGIN: &str = "# BEGIN blackship";
+/// How often a pending dependency condition is checked again
+const DEPENDENCY_POLL_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Progress of a dependency towards its `depends_on` condition
+enum ConditionState {
+    /// The condition holds
+    Met,
+    /// Not yet (with the reason, reported on timeout)
+    Pending(String),
+    /// The condition can no longer be met
+    Failed(String),
+}
+
+/// A jail waiting for one dependency to meet its condition
+struct DependencyWait {
+    /// Full name of the waiting jail
+    jail: String,
+    /// Dependency service name
+    dep: String,
+    /// Dependency full jail name
+    dep_full_name: String,
+    condition: DependencyCondition,
+    timeout: Duration,
+    deadline: Instant,
+    /// Health checker kept across polls for `service_healthy`
+    checker: Option<HealthChecker>,
+    /// Whether the wait has been reported to the user
+    announced: bool,
+}
+
+impl DependencyWait {
+    /// Tell the user once that the jail is waiting
+    fn announce(&mut self, verbose: bool, reason: &str) {
+        if !self.announced {
+            println!(
+                "Waiting for '{}' ({}) before starting '{}'...",
+                self.dep_full_name, self.condition, self.jail
+            );
+            self.announced = true;
+        } else if verbose {
+            println!("  '{}': {}", self.dep_full_name, reason);
+        }
+    }
+
+    fn timed_out(&self, reason: &str) -> Error {
+        Error::JailOperation(format!(
+            "Dependency '{}' of jail '{}' did not reach {} within {}s ({})",
+            self.dep_full_name,
+            self.jail,
+            self.condition,
+            self.timeout.as_secs(),
+            reason
+        ))
+    }
+
+    fn failed(&self, reason: &str) -> Error {
+        Error::JailOperation(format!(
+            "Dependency '{}' of jail '{}' cannot reach {}: {}",
+            self.dep_full_name, self.jail, self.condition, reason
+        ))
+    }
+}
+
+/// Jail parameter naming the command run inside the jail after it starts
+const EXEC_START_PARAM: &str = "exec.start";
+
+/// How many times a failed one-shot `exec.start` is run again
+const ONESHOT_RETRIES: u32 = 3;
+
+/// Last line of the /etc/hosts block blackship manages
+const HOSTS_BLOCK_END: &str = "# END blackship";
+
+/// Hook variable and environment variable names carrying a dependency's IP
+///
+/// `db` gives `dep_db_ip` (for `${dep_db_ip}`) and `DB_HOST`.
+fn dependency_vars(dep: &str) -> (String, String) {
+    let name: String = dep
+        .chars()
+        .map(|c| if c.is_ascii_alphanumeric() { c } else { '_' })
+        .collect();
+    (format!("dep_{}_ip", name.to_lowercase()), format!("{}_HOST", name.to_uppercase()))
+}
+
+/// /etc/hosts content with the managed block replaced by `entries`
+///
+/// Lines outside the block are kept; with no entries the block is removed.
+fn managed_hosts(existing: &str, entries: &[(IpAddr, Vec<String>)]) -> String {
+    let mut content = String::new();
+    let mut in_block = false;
+    for line in existing.lines() {
+        match line.trim() {
+            HOSTS_BLOCK_BEGIN => in_block = true,
+            HOSTS_BLOCK_END => in_block = false,
+            _ if !in_block => {
+                content.push_str(line);
+                content.push('\n');
+            }
+            _ => {}
+        }
+    }
+    if !entries.is_empty() {
+        content.push_str(HOSTS_BLOCK_BEGIN);
+        content.push('\n');
+        for (ip, names) in entries {
+            content.push_str(&format!("{}\t{}\n", ip, names.join(" ")));
+        }
+        content.push_str(HOSTS_BLOCK_END);
+        content.push('\n');
+    }
+    content
+}
+
+/// Ask a yes/no question on the terminal (default: no)
+fn confirm(prompt: &str) -> Result<bool> {
+    print!("{} [y/N] ", prompt);
+    let _ = std::io::Write::flush(&mut std::io::stdout());
+    let mut answer = String::new();
+    std::io::stdin().read_line(&mut answer)?;
+    Ok(matches!(answer.trim(), "y" | "Y" | "yes"))
+}
+
+/// Format a byte count with a binary unit, e.g. "512B", "1.5K", "2.3G"
+pub fn format_size(bytes: u64) -> String {
+    const UNITS: [&str; 5] = ["K", "M", "G", "T", "P"];
+    if bytes < 1024 {
+        return format!("{}B", bytes);
+    }
+    let mut size = bytes as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit < UNITS.len() - 1 {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1}{}", size, UNITS[unit])
+}
+
+/// Format an uptime compactly, e.g. "3d 4h", "2h 5m", "45s"
+pub fn format_uptime(uptime: Duration) -> String {
+    let secs = uptime.as_secs();
+    let (days, hours, mins) = (secs / 86_400, (secs % 86_400) / 3_600, (secs % 3_600) / 60);
+
+    if days > 0 {
+        format!("{}d {}h", days, hours)
+    } else if hours > 0 {
+        format!("{}h {}m", hours, mins)
+    } else if mins > 0 {
+        format!("{}m {}s", mins, secs % 60)
+    } else {
+        format!("{}s", secs)
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -1214,6 +3881,30 @@ depends_on = ["backend"]
         .unwrap()
     }
 
+    #[test]
+    fn test_scale_plan() {
+        // Scale up from one running instance to three
+        let plan = scale_plan("worker", 3, |i| i == "worker-1", |i| i == "worker-1");
+        assert_eq!(
+            plan.define,
+            vec![("worker-2".to_string(), 2), ("worker-3".to_string(), 3)]
+        );
+        assert!(plan.stop.is_empty());
+        assert_eq!(plan.start, vec!["worker-2", "worker-3"]);
+
+        // Scale down from three to one, stopping the highest first
+        let up = ["worker-1", "worker-2", "worker-3"];
+        let plan = scale_plan("worker", 1, |i| up.contains(&i), |i| up.contains(&i));
+        assert!(plan.define.is_empty());
+        assert_eq!(plan.stop, vec!["worker-3", "worker-2"]);
+        assert!(plan.start.is_empty());
+
+        // A surplus instance still running but no longer configured is defined and stopped
+        let plan = scale_plan("worker", 0, |_| false, |i| i == "worker-1");
+        assert_eq!(plan.define, vec![("worker-1".to_string(), 1)]);
+        assert_eq!(plan.stop, vec!["worker-1"]);
+    }
+
     #[test]
     fn test_start_order() {
         let config = test_config();
@@ -1222,6 +3913,98 @@ depends_on = ["backend"]
         assert_eq!(order, vec!["database", "backend", "frontend"]);
     }
 
+    #[test]
+    fn test_reload_reports_changed_healthchecks() {
+        let mut bridge = Bridge::new(test_config()).unwrap();
+
+        let mut config = test_config();
+        config.jails[1].healthcheck = toml::from_str(
+            "enabled = true\n\n[[checks]]\nname = \"http\"\ncommand = \"true\"\n",
+        )
+        .unwrap();
+        let backend = config.jail_name("backend");
+
+        let diff = bridge.reload(config).unwrap();
+        assert!(diff.added.is_empty());
+        assert!(diff.removed.is_empty());
+        assert_eq!(diff.changed, vec![backend]);
+    }
+
+    #[test]
+    fn test_nested_start_order() {
+        let mut config: BlackshipConfig = toml::from_str(
+            r#"
+[config]
+data_dir = "/var/blackship"
+project = "app"
+
+[[jails]]
+name = "web"
+parent = "host"
+
+[[jails]]
+name = "host"
+
+[jails.params]
+"children.max" = 1
+"#,
+        )
+        .unwrap();
+        config.link_parents();
+        let bridge = Bridge::new(config).unwrap();
+
+        // The parent starts before the jail nested in it and stops after it
+        assert_eq!(bridge.start_order().unwrap(), vec!["host", "web"]);
+        assert_eq!(bridge.stop_order().unwrap(), vec!["web", "host"]);
+        assert_eq!(bridge.get_dependents("host").unwrap(), vec!["web", "host"]);
+    }
+
+    #[test]
+    fn test_start_token_exhaustion() {
+        let mut bridge = Bridge::new(test_config()).unwrap();
+        bridge.jail_start_capacity = 2.0;
+        *bridge.rate_limiter.lock().unwrap() = (2.0, Instant::now());
+
+        assert!(bridge.try_start_token().is_none());
+        assert!(bridge.try_start_token().is_none());
+        let wait = bridge.try_start_token().expect("bucket should be empty");
+        assert!(wait <= Duration::from_secs(1));
+    }
+
+    #[test]
+    fn test_rolling_targets() {
+        let mut config: BlackshipConfig = toml::from_str(
+            r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+count = 3
+
+[[jails]]
+name = "db"
+
+[[jails]]
+name = "migrate"
+oneshot = true
+
+[jails.params]
+"exec.start" = "/usr/local/bin/migrate"
+"#,
+        )
+        .unwrap();
+        config.expand_instances().unwrap();
+        let bridge = Bridge::new(config).unwrap();
+
+        assert_eq!(bridge.rolling_targets(Some("web")).unwrap(), vec!["web-1", "web-2", "web-3"]);
+        assert_eq!(bridge.rolling_targets(Some("web-2")).unwrap(), vec!["web-2"]);
+        // One-shot jails never come back running
+        assert_eq!(bridge.rolling_targets(None).unwrap().len(), 4);
+        assert!(bridge.rolling_targets(Some("migrate")).unwrap().is_empty());
+        assert!(bridge.rolling_targets(Some("missing")).is_err());
+    }
+
     #[test]
     fn test_stop_order() {
         let config = test_config();
@@ -1229,4 +4012,266 @@ depends_on = ["backend"]
         let order = bridge.stop_order().unwrap();
         assert_eq!(order, vec!["frontend", "backend", "database"]);
     }
+
+    #[test]
+    fn test_configured_names_are_not_orphans() {
+        let bridge = Bridge::new(test_config()).unwrap();
+        assert!(bridge.is_configured_name("blackship-database"));
+        assert!(!bridge.is_configured_name("blackship-old-service"));
+        assert_eq!(
+            Orphan::Dataset("blackship-old".to_string()).to_string(),
+            "dataset  blackship-old"
+        );
+    }
+
+    #[test]
+    fn test_rename_refusals() {
+        let mut bridge = Bridge::new(test_config()).unwrap();
+        let err = bridge.rename("database", "db").unwrap_err();
+        assert!(err.to_string().contains("backend"), "{}", err);
+        assert!(bridge.rename("frontend", "backend").is_err());
+        assert!(bridge.rename("frontend", "web.1").is_err());
+    }
+
+    #[test]
+    fn test_managed_hosts() {
+        let base = "::1\tlocalhost\n127.0.0.1\tlocalhost\n";
+        let entries = vec![(
+            "10.0.1.11".parse().unwrap(),
+            vec!["db".to_string(), "blackship-db".to_string()],
+        )];
+        let written = managed_hosts(base, &entries);
+        assert_eq!(
+            written,
+            format!("{}# BEGIN blackship\n10.0.1.11\tdb blackship-db\n# END blackship\n", base)
+        );
+        // Rewriting replaces the block instead of appending another
+        assert_eq!(managed_hosts(&written, &entries), written);
+        assert_eq!(managed_hosts(&written, &[]), base);
+    }
+
+    #[test]
+    fn test_sibling_hosts() {
+        let mut config = test_config();
+        config.config.sibling_hosts = Some(true);
+        config.jails[0].network = Some(toml::from_str(r#"ip = "10.0.1.11""#).unwrap());
+        let bridge = Bridge::new(config).unwrap();
+        let hosts = bridge.sibling_hosts("blackship-backend");
+        assert_eq!(hosts.len(), 1);
+        assert_eq!(hosts[0].1, vec!["database", "blackship-database"]);
+        assert!(bridge.sibling_hosts("blackship-database").is_empty());
+    }
+
+    #[test]
+    fn test_dependency_vars() {
+        let mut config = test_config();
+        config.jails[0].network = Some(toml::from_str(r#"ip = "10.0.1.11""#).unwrap());
+        let bridge = Bridge::new(config).unwrap();
+        let backend = bridge.config.get_jail("backend").unwrap();
+        let context = bridge.hook_context(backend, "blackship-backend", Path::new("/jails/b"));
+        assert_eq!(context.substitute("${dep_database_ip}"), "10.0.1.11");
+        assert_eq!(context.env.get("DATABASE_HOST").map(String::as_str), Some("10.0.1.11"));
+
+        assert_eq!(
+            dependency_vars("my-db"),
+            ("dep_my_db_ip".to_string(), "MY_DB_HOST".to_string())
+        );
+    }
+
+    #[test]
+    fn test_vnet_gateway() {
+        let mut allocator = IpAllocator::new();
+        for network in [
+            "name = \"lan\"\nsubnet = \"10.0.5.0/24\"",
+            "name = \"dmz\"\nsubnet = \"10.0.6.0/24\"\ngateway = \"10.0.6.254\"",
+        ] {
+            let network: NetworkConfig = toml::from_str(network).unwrap();
+            allocator.add_pool(network.name.clone(), network_pool(&network).unwrap());
+        }
+        let jail = |toml: &str| toml::from_str::<JailNetworkConfig>(toml).unwrap();
+        let ip = |s: &str| Some(s.parse::<IpAddr>().unwrap());
+
+        // The attached network's pool supplies the gateway
+        let network = jail("vnet = true\nnetworks = [\"lan\", \"dmz\"]");
+        let lan_gateway = allocator.get_pool("lan").unwrap().gateway();
+        assert_eq!(vnet_gateway(&network, ip("10.0.5.7"), &allocator), Some(lan_gateway));
+        assert_eq!(vnet_gateway(&network, ip("10.0.6.7"), &allocator), ip("10.0.6.254"));
+        assert_eq!(vnet_gateway(&network, None, &allocator), Some(lan_gateway));
+
+        // An explicit gateway wins; without a network, ip_cidr's subnet is used
+        let network = jail("vnet = true\nnetworks = [\"lan\"]\ngateway = \"10.0.5.99\"");
+        assert_eq!(vnet_gateway(&network, ip("10.0.5.7"), &allocator), ip("10.0.5.99"));
+        let network = jail("vnet = true\nip_cidr = \"192.168.9.20/24\"");
+        assert_eq!(vnet_gateway(&network, ip("192.168.9.20"), &allocator), ip("192.168.9.1"));
+
+        assert_eq!(vnet_gateway(&jail("vnet = true"), ip("10.9.9.9"), &allocator), None);
+    }
+
+    #[test]
+    fn test_validate_vnet() {
+        let config: BlackshipConfig = toml::from_str(
+            r#"
+[config]
+data_dir = "/var/blackship"
+
+[[networks]]
+name = "lan"
+subnet = "10.0.5.0/24"
+
+[[jails]]
+name = "pooled"
+[jails.network]
+vnet = true
+vnet_interface = "ix8"
+networks = ["lan"]
+
+[[jails]]
+name = "stray"
+[jails.network]
+vnet = true
+vnet_interface = "ix9"
+networks = ["lan"]
+gateway = "10.0.9.1"
+
+[[jails]]
+name = "unaddressed"
+[jails.network]
+vnet = true
+vnet_interface = "ix7"
+
+[[jails]]
+name = "unbridged"
+[jails.network]
+vnet = true
+bridge = "bsmissing0"
+ip_cidr = "10.0.5.20/24"
+"#,
+        )
+        .unwrap();
+        let bridge = Bridge::new(config).unwrap();
+        let validate = |name: &str| {
+            let jail_def = bridge.config.get_jail(name).unwrap();
+            bridge.validate_vnet(jail_def, name).map_err(|e| e.to_string())
+        };
+
+        assert!(validate("pooled").is_ok());
+        let err = validate("stray").unwrap_err();
+        assert!(err.contains("gateway 10.0.9.1 outside its subnet 10.0.5.0/24"), "{}", err);
+        let err = validate("unaddressed").unwrap_err();
+        assert!(err.contains("has no address"), "{}", err);
+        let err = validate("unbridged").unwrap_err();
+        assert!(err.contains("bridge 'bsmissing0', which does not exist"), "{}", err);
+    }
+
+    #[test]
+    fn test_create_with_retry() {
+        let retry = RetryConfig {
+            base_delay_ms: 1,
+            max_delay_ms: 1,
+            max_attempts: 3,
+            ..RetryConfig::default()
+        };
+        let transient = || Error::Io(std::io::Error::from_raw_os_error(libc::EAGAIN));
+
+        // Transient failures are retried until the create succeeds
+        let mut calls = 0;
+        let jid = create_with_retry("web", &retry, || {
+            calls += 1;
+            if calls < 3 { Err(transient()) } else { Ok(7) }
+        });
+        assert_eq!(jid.unwrap(), 7);
+        assert_eq!(calls, 3);
+
+        // ...but only as often as the retry config allows
+        let mut calls = 0;
+        let result = create_with_retry("web", &retry, || {
+            calls += 1;
+            Err(transient())
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 3);
+
+        // Permanent failures are not retried
+        for errno in [libc::EINVAL, libc::EEXIST] {
+            let mut calls = 0;
+            let result = create_with_retry("web", &retry, || {
+                calls += 1;
+                Err(Error::Io(std::io::Error::from_raw_os_error(errno)))
+            });
+            assert!(result.is_err());
+            assert_eq!(calls, 1);
+        }
+        let mut calls = 0;
+        let result = create_with_retry("web", &retry, || {
+            calls += 1;
+            Err(Error::JailSet("path is not a directory".into()))
+        });
+        assert!(result.is_err());
+        assert_eq!(calls, 1);
+    }
+
+    #[test]
+    fn test_wait_condition() {
+        assert_eq!("healthy".parse::<WaitCondition>(), Ok(WaitCondition::Healthy));
+        assert!("up".parse::<WaitCondition>().is_err());
+
+        assert!(WaitCondition::Running.is_met(true, None));
+        assert!(!WaitCondition::Running.is_met(false, None));
+        assert!(WaitCondition::Stopped.is_met(false, None));
+        assert!(WaitCondition::Healthy.is_met(true, Some(HealthStatus::Healthy)));
+        assert!(!WaitCondition::Healthy.is_met(true, Some(HealthStatus::Starting)));
+        assert!(!WaitCondition::Healthy.is_met(false, Some(HealthStatus::Healthy)));
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(512), "512B");
+        assert_eq!(format_size(1536), "1.5K");
+        assert_eq!(format_size(3 * 1024 * 1024 * 1024), "3.0G");
+        assert_eq!("size".parse::<DuSort>(), Ok(DuSort::Size));
+        assert!("bytes".parse::<DuSort>().is_err());
+    }
+
+    #[test]
+    fn test_format_uptime() {
+        assert_eq!(format_uptime(Duration::from_secs(45)), "45s");
+        assert_eq!(format_uptime(Duration::from_secs(125)), "2m 5s");
+        assert_eq!(format_uptime(Duration::from_secs(7_500)), "2h 5m");
+        assert_eq!(format_uptime(Duration::from_secs(273_600)), "3d 4h");
+    }
+
+    #[test]
+    fn test_render_graph() {
+        let order = ["db", "cache", "web"];
+        let edges = [("db", "web"), ("cache", "web")];
+
+        let dot = render_graph(&order, &edges, GraphFormat::Dot);
+        assert!(dot.starts_with("digraph blackship {"), "{}", dot);
+        assert!(dot.contains("    \"web\" [label=\"3. web\"];"), "{}", dot);
+        assert!(dot.contains("    \"db\" -> \"web\";"), "{}", dot);
+
+        let json = render_graph(&order, &edges, GraphFormat::Json);
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let web = &value["jails"][2];
+        assert_eq!(web["order"], 3);
+        assert_eq!(web["depends_on"], serde_json::json!(["db", "cache"]));
+        assert_eq!(value["jails"][0]["dependents"], serde_json::json!(["web"]));
+    }
+
+    #[test]
+    fn test_ps_filter_parse() {
+        assert_eq!("state=running".parse(), Ok(PsFilter::State("running".to_string())));
+        assert_eq!("name=web*".parse(), Ok(PsFilter::Name("web*".to_string())));
+        assert!("jid=3".parse::<PsFilter>().is_err());
+        assert!("running".parse::<PsFilter>().is_err());
+    }
+
+    #[test]
+    fn test_ps_column_parse() {
+        assert_eq!("uptime".parse(), Ok(PsColumn::Uptime));
+        assert_eq!("IP".parse(), Ok(PsColumn::Ip));
+
+        let err = "size".parse::<PsColumn>().unwrap_err();
+        assert!(err.contains("name, state, jid, ip, uptime, path"), "{}", err);
+    }
 }
@@ -136,6 +136,21 @@ impl BulkheadManager {
         self.apply_rules()
     }
 
+    /// Re-point a jail's port forwards at its new name
+    ///
+    /// Rules are only reapplied when the jail had forwards.
+    pub fn rename_jail(&mut self, jail_name: &str, new_name: &str) -> Result<()> {
+        let mut renamed = false;
+        for forward in self.forwards.iter_mut().filter(|f| f.jail_name == jail_name) {
+            forward.jail_name = new_name.to_string();
+            renamed = true;
+        }
+        if renamed {
+            self.apply_rules()?;
+        }
+        Ok(())
+    }
+
     /// Apply all rules to the PF anchor
     fn apply_rules(&self) -> Result<()> {
         // Generate rules
@@ -2,9 +2,15 @@
 //!
 //! Uses clap with derive for type-safe CLI parsing
 
+use blackship::bridge::{DuSort, GraphFormat, PsColumn, PsFilter, PsSort, WaitCondition};
+use blackship::logging::LogFormat;
+use blackship::manifest;
+use blackship::provision::{self, Arch};
 use clap::{CommandFactory, Parser, Subcommand};
 use clap_complete::Shell;
+use std::net::IpAddr;
 use std::path::PathBuf;
+use std::time::Duration;
 
 /// Blackship - FreeBSD jail orchestrator
 #[derive(Parser)]
@@ -12,14 +18,26 @@ use std::path::PathBuf;
 #[command(author, version, about, long_about = None)]
 #[command(propagate_version = true)]
 pub struct Cli {
-    /// Configuration file path
-    #[arg(short, long, default_value = "blackship.toml")]
-    pub config: PathBuf,
+    /// Configuration file path (default: nearest blackship.toml in this or a parent directory)
+    #[arg(short, long)]
+    pub config: Option<PathBuf>,
 
     /// Enable verbose output
     #[arg(short, long)]
     pub verbose: bool,
 
+    /// Also send status and health events to syslog
+    #[arg(long)]
+    pub syslog: bool,
+
+    /// Never use the network: bootstrap releases only from the download cache
+    #[arg(long)]
+    pub offline: bool,
+
+    /// Error output format: text, or json with a stable error code
+    #[arg(long, default_value = "text")]
+    pub log_format: LogFormat,
+
     #[command(subcommand)]
     pub command: Commands,
 }
@@ -50,11 +68,22 @@ pub enum Commands {
         #[arg(long, conflicts_with = "jail")]
         all: bool,
 
+        /// Also destroy managed datasets, release IPs and remove port forwards
+        #[arg(long)]
+        remove: bool,
+
         /// Show what would be done without making changes
         #[arg(long)]
         dry_run: bool,
     },
 
+    /// Start or stop instances of jails defined with `count`
+    Scale {
+        /// Target instance counts (NAME=N)
+        #[arg(required = true, value_parser = parse_scale)]
+        targets: Vec<(String, u32)>,
+    },
+
     /// Restart jails
     Restart {
         /// Specific jail to restart
@@ -67,6 +96,31 @@ pub enum Commands {
         /// Show what would be done without making changes
         #[arg(long)]
         dry_run: bool,
+
+        /// Restart one batch at a time, waiting for each to come back healthy
+        /// (a jail defined with count restarts its replicas)
+        #[arg(long, conflicts_with = "dry_run")]
+        rolling: bool,
+
+        /// Jails restarted together in a rolling restart
+        #[arg(long, default_value = "1", requires = "rolling")]
+        batch_size: usize,
+
+        /// Seconds each batch of a rolling restart has to become healthy
+        #[arg(long, default_value = "120", requires = "rolling")]
+        timeout: u64,
+    },
+
+    /// Pause a running jail (suspend all of its processes)
+    Pause {
+        /// Jail to pause
+        jail: String,
+    },
+
+    /// Resume a paused jail
+    Resume {
+        /// Jail to resume
+        jail: String,
     },
 
     /// List jail status
@@ -74,6 +128,36 @@ pub enum Commands {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+
+        /// Only show matching jails (state=<state> or name=<glob>, repeatable)
+        #[arg(long = "filter", value_name = "KEY=VALUE")]
+        filters: Vec<PsFilter>,
+
+        /// Table columns to show, in order (name,state,jid,ip,uptime,path)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<PsColumn>,
+
+        /// Sort jails by name, state or uptime
+        #[arg(long)]
+        sort: Option<PsSort>,
+    },
+
+    /// Show disk usage per jail
+    Du {
+        /// Output in JSON format
+        #[arg(long)]
+        json: bool,
+
+        /// Sort jails by name or size (largest first)
+        #[arg(long)]
+        sort: Option<DuSort>,
+    },
+
+    /// Print the jail dependency graph, numbered by start order
+    Graph {
+        /// Output format: dot (Graphviz) or json (adjacency list)
+        #[arg(long, default_value = "dot")]
+        format: GraphFormat,
     },
 
     /// Validate configuration
@@ -88,7 +172,7 @@ pub enum Commands {
         #[arg(short, long, default_value = "Jailfile")]
         file: PathBuf,
 
-        /// Base FreeBSD release
+        /// Base FreeBSD release (default: latest)
         #[arg(short, long)]
         release: Option<String>,
 
@@ -113,8 +197,25 @@ pub enum Commands {
 
     /// Execute a command in a running jail
     Exec {
-        /// Jail name or ID
-        jail: String,
+        /// Jail name(s) or ID(s); more than one captures output per jail
+        #[arg(required_unless_present = "all")]
+        jails: Vec<String>,
+
+        /// Run in every running jail from the configuration
+        #[arg(long, conflicts_with = "jails")]
+        all: bool,
+
+        /// Run in all target jails concurrently (multi-jail mode)
+        #[arg(long)]
+        parallel: bool,
+
+        /// Start the command in the background and print its PID (not tracked by blackship)
+        #[arg(short, long, conflicts_with_all = ["all", "parallel"])]
+        detach: bool,
+
+        /// Kill the command after this many seconds (exit code 124; output is captured)
+        #[arg(long, value_name = "SECS", conflicts_with = "detach")]
+        timeout: Option<u64>,
 
         /// User to run as
         #[arg(short, long, default_value = "root")]
@@ -147,6 +248,19 @@ pub enum Commands {
         /// Archives to download (default: base)
         #[arg(short, long, value_delimiter = ',')]
         archives: Option<Vec<String>>,
+
+        /// Architecture to fetch (amd64, arm64/aarch64, i386; default: host)
+        #[arg(long)]
+        arch: Option<Arch>,
+
+        /// Archives downloaded and extracted at once
+        #[arg(short, long, default_value_t = provision::DEFAULT_BOOTSTRAP_JOBS)]
+        jobs: usize,
+
+        /// Bandwidth limit per download in bytes/s, K/M/G suffixes accepted
+        /// (default: download_rate_limit from config, else unlimited)
+        #[arg(long, value_parser = parse_rate)]
+        rate_limit: Option<u64>,
     },
 
     /// List or manage releases
@@ -159,6 +273,48 @@ pub enum Commands {
         json: bool,
     },
 
+    /// Append a jail to the configuration file, optionally starting it
+    AddJail {
+        /// Name of the new jail
+        name: String,
+
+        /// FreeBSD release to provision it from (e.g. 15.0-RELEASE)
+        #[arg(short, long)]
+        release: Option<String>,
+
+        /// Network to attach to; the next free address is used unless --ip is given
+        #[arg(short, long)]
+        network: Option<String>,
+
+        /// IP address
+        #[arg(long)]
+        ip: Option<IpAddr>,
+
+        /// Jails that must start first (comma-separated)
+        #[arg(long, value_delimiter = ',')]
+        depends_on: Vec<String>,
+
+        /// Start the jail once it is added
+        #[arg(long)]
+        start: bool,
+    },
+
+    /// Read or change a value in the configuration file (dotted paths, e.g. jails.web.network.ip)
+    Config {
+        #[command(subcommand)]
+        action: ConfigAction,
+    },
+
+    /// List, measure or clear the download cache
+    Cache {
+        #[command(subcommand)]
+        action: Option<CacheAction>,
+
+        /// Output in JSON format (for list action)
+        #[arg(long)]
+        json: bool,
+    },
+
     /// Network management
     Network {
         #[command(subcommand)]
@@ -166,7 +322,11 @@ pub enum Commands {
     },
 
     /// Health check status and monitoring
+    #[command(args_conflicts_with_subcommands = true)]
     Health {
+        #[command(subcommand)]
+        action: Option<HealthAction>,
+
         /// Specific jail to check (shows all if not specified)
         jail: Option<String>,
 
@@ -174,7 +334,15 @@ pub enum Commands {
         #[arg(short, long)]
         watch: bool,
 
-        /// Update interval in seconds (for watch mode)
+        /// Run one pass and exit non-zero unless every jail is healthy (for CI)
+        #[arg(long, conflicts_with = "watch")]
+        once: bool,
+
+        /// Poll until every jail is healthy or this many seconds pass (implies --once)
+        #[arg(long, value_name = "SECS", conflicts_with = "watch")]
+        wait: Option<u64>,
+
+        /// Update interval in seconds (for watch mode and --wait)
         #[arg(short, long, default_value = "5")]
         interval: u64,
 
@@ -183,6 +351,24 @@ pub enum Commands {
         json: bool,
     },
 
+    /// Block until a jail is running, healthy or stopped
+    Wait {
+        /// Jail name
+        jail: String,
+
+        /// Condition to wait for (running, healthy or stopped)
+        #[arg(long = "for", value_name = "CONDITION", default_value = "running")]
+        condition: WaitCondition,
+
+        /// Give up after this many seconds (default: wait forever)
+        #[arg(long, value_name = "SECS")]
+        timeout: Option<u64>,
+
+        /// Poll interval in seconds
+        #[arg(short, long, default_value = "1")]
+        interval: u64,
+    },
+
     /// Build a jail from a Jailfile
     Build {
         /// Path to Jailfile (default: ./Jailfile)
@@ -204,6 +390,15 @@ pub enum Commands {
         /// Don't execute, just show what would be done
         #[arg(long)]
         dry_run: bool,
+
+        /// Stop at this build stage (name from `FROM ... AS <name>`, or index)
+        #[arg(long)]
+        target: Option<String>,
+
+        /// Snapshot the jail's ZFS dataset after each step so a failed build
+        /// resumes from the last good step (requires zfs_enabled)
+        #[arg(long)]
+        snapshot_layers: bool,
     },
 
     /// Template management
@@ -249,9 +444,25 @@ pub enum Commands {
     /// Clean up a failed jail (remove leftover resources)
     Cleanup {
         /// Jail name to clean up
-        jail: String,
+        #[arg(required_unless_present = "all")]
+        jail: Option<String>,
+
+        /// Clean up every jail in the Failed state
+        #[arg(long, conflicts_with = "jail")]
+        all: bool,
 
-        /// Force cleanup even if errors occur
+        /// Force cleanup even if errors occur (with --all, continue with the next jail)
+        #[arg(short, long)]
+        force: bool,
+    },
+
+    /// Remove jail roots and ZFS datasets no configured jail refers to
+    Prune {
+        /// Only list what would be removed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Remove without asking for confirmation
         #[arg(short, long)]
         force: bool,
     },
@@ -268,6 +479,37 @@ pub enum Commands {
         /// Use ZFS send for faster export (requires ZFS)
         #[arg(long)]
         zfs_send: bool,
+
+        /// Byte-identical archives for identical roots (sorted, clamped mtimes; no ACLs/xattrs)
+        #[arg(long, conflicts_with = "zfs_send")]
+        reproducible: bool,
+
+        /// zstd compression level (1-22)
+        #[arg(
+            long,
+            default_value_t = blackship::export::DEFAULT_ZSTD_LEVEL,
+            value_parser = clap::value_parser!(i32).range(1..=22),
+            conflicts_with = "zfs_send"
+        )]
+        compress_level: i32,
+
+        /// zstd worker threads (0 = single-threaded)
+        #[arg(long, default_value_t = 0, conflicts_with = "zfs_send")]
+        threads: u32,
+    },
+
+    /// Back up a ZFS-backed jail to a remote host over SSH
+    Backup {
+        /// Jail name to back up
+        jail: String,
+
+        /// Target in the form [user@]host:pool/dataset
+        #[arg(long)]
+        to: String,
+
+        /// SSH port on the target host
+        #[arg(short, long)]
+        port: Option<u16>,
     },
 
     /// Import a jail from an archive
@@ -282,6 +524,10 @@ pub enum Commands {
         /// Overwrite existing jail
         #[arg(long)]
         force: bool,
+
+        /// Check the archive against its .sha256 sidecar before extracting
+        #[arg(long)]
+        verify: bool,
     },
 
     /// Manage jail snapshots
@@ -290,15 +536,24 @@ pub enum Commands {
         action: SnapshotAction,
     },
 
-    /// Clone a jail from a snapshot
+    /// Clone a jail from a snapshot (or copy it without ZFS)
     Clone {
-        /// Source jail and snapshot (format: jail@snapshot)
+        /// Source jail and snapshot (format: jail@snapshot; just jail without ZFS)
         source: String,
 
         /// Name for the new jail
         name: String,
     },
 
+    /// Rename a jail (stops it, moves its dataset or directory)
+    Rename {
+        /// Current jail name
+        jail: String,
+
+        /// New jail name
+        new_name: String,
+    },
+
     /// Generate shell completion scripts
     Completion {
         /// Shell to generate completion for
@@ -307,9 +562,17 @@ pub enum Commands {
     },
 
     /// Start the Warden supervisor to monitor and auto-restart jails
-    Supervise,
+    Supervise {
+        /// Serve data_dir/status.json over HTTP (a `/path` unix socket or `host:port`)
+        #[arg(long, value_name = "ADDR")]
+        status_addr: Option<String>,
 
-    /// Tail logs from a running jail
+        /// Serve Prometheus metrics on http://<ADDR>/metrics (e.g. 127.0.0.1:9100)
+        #[arg(long, value_name = "ADDR")]
+        metrics_addr: Option<String>,
+    },
+
+    /// Tail logs from a jail (read from the host when it is stopped)
     Logs {
         /// Jail name
         jail: String,
@@ -321,6 +584,10 @@ pub enum Commands {
         /// Number of lines to show
         #[arg(short = 'n', long, default_value = "100")]
         lines: usize,
+
+        /// Show the captured exec.start output (data_dir/logs/<jail>.log)
+        #[arg(long)]
+        console: bool,
     },
 }
 
@@ -332,6 +599,38 @@ fn parse_key_val(s: &str) -> Result<(String, String), String> {
     Ok((s[..pos].to_string(), s[pos + 1..].to_string()))
 }
 
+/// Parse a rate in bytes per second ("500K", "10M")
+fn parse_rate(s: &str) -> Result<u64, String> {
+    manifest::parse_size(s)
+        .filter(|&rate| rate > 0)
+        .ok_or_else(|| format!("invalid rate `{s}`: expected bytes per second such as 500K or 10M"))
+}
+
+/// Parse an age such as "30m", "12h" or "7d"
+fn parse_age(s: &str) -> Result<Duration, String> {
+    manifest::parse_schedule(s)
+        .ok_or_else(|| format!("invalid age `{s}`: expected e.g. 30m, 12h or 7d"))
+}
+
+/// Parse name=count pairs for scale
+fn parse_scale(s: &str) -> Result<(String, u32), String> {
+    let (name, count) = parse_key_val(s)?;
+    let count = count
+        .parse()
+        .map_err(|_| format!("invalid instance count `{count}` in `{s}`"))?;
+    Ok((name, count))
+}
+
+/// Actions for the health command
+#[derive(Subcommand)]
+pub enum HealthAction {
+    /// Force-close a supervised jail's circuit breakers so suspended checks resume
+    Reset {
+        /// Jail whose breakers to reset
+        jail: String,
+    },
+}
+
 /// Actions for the template command
 #[derive(Subcommand)]
 pub enum TemplateAction {
@@ -350,6 +649,20 @@ pub enum TemplateAction {
         #[arg(default_value = "Jailfile")]
         file: PathBuf,
     },
+
+    /// Generate a best-effort Jailfile from an existing jail (review the output)
+    Generate {
+        /// Jail name
+        jail: String,
+
+        /// Build context directory to write (default: ./<jail>)
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+
+        /// Overwrite an existing Jailfile
+        #[arg(short = 'y', long)]
+        force: bool,
+    },
 }
 
 /// Actions for the releases command
@@ -369,6 +682,59 @@ pub enum ReleasesAction {
         /// Release to verify
         release: String,
     },
+
+    /// Compare a jail root against its base release and report drift
+    VerifyJail {
+        /// Jail name
+        jail: String,
+    },
+
+    /// Bootstrap the newest RELEASE on the mirror and point `latest` at it
+    FetchLatest {
+        /// Architecture to fetch (default: host)
+        #[arg(long)]
+        arch: Option<Arch>,
+
+        /// Force re-download even if the release exists
+        #[arg(short, long)]
+        force: bool,
+    },
+}
+
+/// Actions for the config command
+#[derive(Subcommand)]
+pub enum ConfigAction {
+    /// Print the value at a dotted path
+    Get {
+        /// Dotted path; `jails.<name>` selects a [[jails]] entry
+        path: String,
+    },
+
+    /// Set the value at a dotted path, validating the result before writing
+    Set {
+        /// Dotted path; `jails.<name>` selects a [[jails]] entry
+        path: String,
+
+        /// TOML value (42, true, ["a"], "quoted"); anything else is a string
+        value: String,
+    },
+}
+
+/// Actions for the cache command
+#[derive(Subcommand)]
+pub enum CacheAction {
+    /// List cached downloads with sizes and ages (default)
+    List,
+
+    /// Show the total size of the cache
+    Size,
+
+    /// Delete cached downloads (partial downloads still being written are kept)
+    Clear {
+        /// Only delete files older than this (e.g. 30m, 12h, 7d)
+        #[arg(long, value_parser = parse_age)]
+        older_than: Option<Duration>,
+    },
 }
 
 /// Actions for the snapshot command
@@ -428,6 +794,10 @@ pub enum ArmadaAction {
         /// Overwrite existing file
         #[arg(short = 'y', long)]
         force: bool,
+
+        /// Generate the configuration from a docker-compose.yml (best effort)
+        #[arg(long, value_name = "COMPOSE_FILE")]
+        compose_import: Option<PathBuf>,
     },
 
     /// Start all jails (auto-builds if needed)
@@ -457,6 +827,10 @@ pub enum ArmadaAction {
         /// Only stop specific jails
         jails: Vec<String>,
 
+        /// Also destroy managed datasets, release IPs and remove port forwards
+        #[arg(long)]
+        remove: bool,
+
         /// Show what would be done without making changes
         #[arg(long)]
         dry_run: bool,
@@ -477,6 +851,18 @@ pub enum ArmadaAction {
         /// Output in JSON format
         #[arg(long)]
         json: bool,
+
+        /// Only show matching jails (state=<state> or name=<glob>, repeatable)
+        #[arg(long = "filter", value_name = "KEY=VALUE")]
+        filters: Vec<PsFilter>,
+
+        /// Table columns to show, in order (name,state,jid,ip,uptime,path)
+        #[arg(long, value_delimiter = ',')]
+        columns: Vec<PsColumn>,
+
+        /// Sort jails by name, state or uptime
+        #[arg(long)]
+        sort: Option<PsSort>,
     },
 
     /// Validate and show configuration
@@ -484,6 +870,18 @@ pub enum ArmadaAction {
         /// Show resolved (merged) configuration
         #[arg(long)]
         show: bool,
+
+        /// Only validate; exit non-zero with the validation error if invalid
+        #[arg(long, conflicts_with = "show")]
+        validate_only: bool,
+
+        /// Print nothing on success (for use with --validate-only)
+        #[arg(short, long)]
+        quiet: bool,
+
+        /// Annotate each value with the file it came from (requires --show)
+        #[arg(long, requires = "show")]
+        explain: bool,
     },
 }
 
@@ -506,6 +904,10 @@ pub enum NetworkAction {
         /// Bridge interface name (defaults to blackship0)
         #[arg(short, long, default_value = "blackship0")]
         bridge: String,
+
+        /// Physical interface to add to the bridge as an uplink (repeatable)
+        #[arg(short, long = "member")]
+        members: Vec<String>,
     },
 
     /// Destroy a network
@@ -542,6 +944,30 @@ pub enum NetworkAction {
         /// Network name
         network: String,
     },
+
+    /// Check a jail's connectivity (gateway/IP ping and DNS resolution)
+    Ping {
+        /// Jail name
+        jail: String,
+
+        /// Number of ping packets to send
+        #[arg(short, long, default_value = "3")]
+        count: u32,
+
+        /// Hostname to resolve inside the jail
+        #[arg(long, default_value = "freebsd.org")]
+        resolve: String,
+    },
+    /// Destroy epair interfaces left behind by failed VNET starts
+    Gc {
+        /// Only list what would be destroyed
+        #[arg(long)]
+        dry_run: bool,
+
+        /// Destroy without asking for confirmation
+        #[arg(short, long)]
+        force: bool,
+    },
 }
 
 impl Cli {
@@ -550,6 +976,21 @@ impl Cli {
         Self::parse()
     }
 
+    /// Resolve the configuration file path
+    ///
+    /// An explicit `--config` wins; otherwise the current directory and its
+    /// ancestors are searched for `blackship.toml`, falling back to
+    /// `./blackship.toml` when none is found.
+    pub fn config_path(&self) -> PathBuf {
+        if let Some(path) = &self.config {
+            return path.clone();
+        }
+        std::env::current_dir()
+            .ok()
+            .and_then(|cwd| manifest::discover(&cwd))
+            .unwrap_or_else(|| PathBuf::from(manifest::DEFAULT_CONFIG_FILE))
+    }
+
     /// Generate shell completion scripts
     pub fn generate_completion(shell: Shell) {
         let mut cmd = Self::command();
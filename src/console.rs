@@ -2,12 +2,19 @@
 //!
 //! Provides the ability to:
 //! - Execute commands inside a running jail
+//! - Execute the same command across several jails with captured output
 //! - Open an interactive console session
+//! - Read the tail of a stopped jail's log from the host
+//! - Run a jail's `exec.start` with its output captured in a host-side log
 
 use crate::error::{Error, Result};
-use crate::jail::{jail_attach, jail_getid};
+use crate::hooks::shell_quote;
+use crate::jail::jexec::jexec_with_timeout;
+use crate::jail::{jail_attach, jail_getid, jexec_with_output};
 use std::ffi::CString;
 use std::os::unix::process::ExitStatusExt;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
 use std::process::{Command, ExitStatus, Stdio};
 
 /// Options for executing commands in a jail
@@ -81,6 +88,125 @@ pub fn exec_in_jail(jail: &str, command: &[String], opts: &ExecOptions) -> Resul
     Ok(status)
 }
 
+/// Log inside the jail receiving the output of detached commands
+pub const DETACHED_LOG: &str = "/var/log/blackship-detached.log";
+
+/// Start a command in a jail in the background and return its PID
+///
+/// The command is backgrounded by a shell inside the jail, which prints its
+/// PID and exits, so jexec returns immediately and the command is re-parented
+/// to init. It ignores SIGHUP, reads from /dev/null and appends its output to
+/// [`DETACHED_LOG`]. Blackship does not track detached processes.
+pub fn exec_detached(jail: &str, command: &[String], user: &str) -> Result<u32> {
+    let jid = jail_getid(jail)?;
+    let script = format!("nohup \"$@\" >> {} 2>&1 < /dev/null & echo $!", DETACHED_LOG);
+
+    let output = Command::new("/usr/sbin/jexec")
+        .arg("-u")
+        .arg(user)
+        .arg(jid.to_string())
+        .args(["/bin/sh", "-c", &script, "sh"])
+        .args(command)
+        .stdin(Stdio::null())
+        .output()
+        .map_err(|e| Error::JailExecFailed(format!("Failed to execute jexec: {}", e)))?;
+    if !output.status.success() {
+        return Err(Error::JailExecFailed(format!(
+            "Failed to start detached command: {}",
+            String::from_utf8_lossy(&output.stderr).trim()
+        )));
+    }
+
+    String::from_utf8_lossy(&output.stdout)
+        .trim()
+        .parse()
+        .map_err(|_| Error::JailExecFailed("Detached command did not report a PID".to_string()))
+}
+
+/// Captured result of running a command in one of several jails
+#[derive(Debug)]
+pub struct JailExecResult {
+    /// Jail the command ran in
+    pub jail: String,
+    /// Exit code, stdout and stderr (or why the command could not run)
+    pub output: Result<(i32, Vec<u8>, Vec<u8>)>,
+}
+
+/// Exit code of `exec` when the command was killed by `--timeout` (as timeout(1))
+pub const EXEC_TIMEOUT_EXIT_CODE: i32 = 124;
+
+/// Execute a command in several jails with output capture
+///
+/// Uses the native jexec; commands for a non-root user are wrapped in `su`.
+/// Results are returned in the order of `jails`, even when run in parallel.
+/// A command still running after `timeout_secs` (0 = no timeout) is killed
+/// and its result is `Error::JailTimeout`.
+pub fn exec_in_jails(
+    jails: &[String],
+    command: &[String],
+    user: &str,
+    parallel: bool,
+    timeout_secs: u64,
+) -> Vec<JailExecResult> {
+    let command = user_command(command, user);
+    // jexec_with_timeout runs its command through /bin/sh
+    let shell_line: Vec<String> = command.iter().map(|arg| shell_quote(arg)).collect();
+    let shell_line = shell_line.join(" ");
+
+    let run = |jail: &String| {
+        let args: Vec<&str> = command.iter().map(String::as_str).collect();
+        let output = jail_getid(jail).and_then(|jid| {
+            if timeout_secs > 0 {
+                jexec_with_timeout(jid, &[&shell_line], timeout_secs)
+                    .map(|(code, stdout, stderr)| (code, stdout.into_bytes(), stderr.into_bytes()))
+            } else {
+                jexec_with_output(jid, &args)
+            }
+        });
+        JailExecResult {
+            jail: jail.clone(),
+            output,
+        }
+    };
+
+    if parallel {
+        std::thread::scope(|scope| {
+            let handles: Vec<_> = jails
+                .iter()
+                .map(|jail| scope.spawn(move || run(jail)))
+                .collect();
+            handles
+                .into_iter()
+                .zip(jails)
+                .map(|(handle, jail)| {
+                    handle.join().unwrap_or_else(|_| JailExecResult {
+                        jail: jail.clone(),
+                        output: Err(Error::JailExecFailed("exec thread panicked".to_string())),
+                    })
+                })
+                .collect()
+        })
+    } else {
+        jails.iter().map(run).collect()
+    }
+}
+
+/// Wrap a command so it runs as `user` (unchanged for root)
+fn user_command(command: &[String], user: &str) -> Vec<String> {
+    if user == "root" {
+        return command.to_vec();
+    }
+
+    let quoted: Vec<String> = command.iter().map(|arg| shell_quote(arg)).collect();
+    vec![
+        "/usr/bin/su".to_string(),
+        "-m".to_string(),
+        user.to_string(),
+        "-c".to_string(),
+        quoted.join(" "),
+    ]
+}
+
 /// Open an interactive console in a jail
 ///
 /// This opens a login shell inside the jail.
@@ -175,6 +301,79 @@ pub fn exec_in_jail_direct(
     }
 }
 
+/// Host-side file capturing a jail's console output: `data_dir/logs/<jail>.log`
+pub fn console_log_path(data_dir: &Path, jail: &str) -> PathBuf {
+    data_dir.join("logs").join(format!("{}.log", jail))
+}
+
+/// Run a command through /bin/sh inside a jail, appending its output to `log`
+///
+/// `env` is set for the command through env(1) inside the jail. Processes
+/// the command leaves behind (daemons started by /etc/rc) keep writing to the
+/// same file. Waits for the command itself to exit.
+pub fn exec_logged(
+    jid: i32,
+    command: &str,
+    env: &[(String, String)],
+    log: &Path,
+) -> Result<ExitStatus> {
+    if let Some(dir) = log.parent() {
+        std::fs::create_dir_all(dir)?;
+    }
+    let stdout = OpenOptions::new().create(true).append(true).open(log)?;
+    let stderr = stdout.try_clone()?;
+
+    Command::new("/usr/sbin/jexec")
+        .arg(jid.to_string())
+        .args(logged_command(command, env))
+        .stdin(Stdio::null())
+        .stdout(stdout)
+        .stderr(stderr)
+        .status()
+        .map_err(|e| Error::JailExecFailed(format!("Failed to execute jexec: {}", e)))
+}
+
+/// Arguments jexec(8) runs for [`exec_logged`]: `/bin/sh -c command`,
+/// behind `/usr/bin/env K=V ...` when there is an environment
+fn logged_command(command: &str, env: &[(String, String)]) -> Vec<String> {
+    let mut args = Vec::new();
+    if !env.is_empty() {
+        args.push("/usr/bin/env".to_string());
+        args.extend(env.iter().map(|(name, value)| format!("{}={}", name, value)));
+    }
+    args.extend(["/bin/sh", "-c", command].map(String::from));
+    args
+}
+
+/// Last `lines` lines of a file inside a stopped jail, read from the host
+///
+/// The jail's filesystem is untrusted: paths that resolve outside `root`
+/// (through a symlink) are refused rather than read with host privileges.
+pub fn tail_host_file(root: &Path, path: &Path, lines: usize) -> Result<String> {
+    let resolved = path.canonicalize()?;
+    if !resolved.starts_with(root.canonicalize()?) || !resolved.is_file() {
+        return Err(Error::JailOperation(format!(
+            "Refusing to read {}: not a regular file inside the jail",
+            path.display()
+        )));
+    }
+    let content = std::fs::read(&resolved)?;
+    Ok(last_lines(&String::from_utf8_lossy(&content), lines).to_string())
+}
+
+/// The last `n` lines of `content`
+fn last_lines(content: &str, n: usize) -> &str {
+    if n == 0 {
+        return "";
+    }
+    let trimmed = content.strip_suffix('\n').unwrap_or(content);
+    let start = trimmed
+        .rmatch_indices('\n')
+        .nth(n - 1)
+        .map_or(0, |(i, _)| i + 1);
+    &content[start..]
+}
+
 /// Set the current user (drop privileges)
 fn set_user(username: &str) -> Result<()> {
     let username_c = CString::new(username)
@@ -213,6 +412,17 @@ fn set_user(username: &str) -> Result<()> {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_logged_command() {
+        assert_eq!(logged_command("/bin/sh /etc/rc", &[]), ["/bin/sh", "-c", "/bin/sh /etc/rc"]);
+
+        let env = vec![("DB_HOST".to_string(), "10.0.1.10".to_string())];
+        assert_eq!(
+            logged_command("nginx", &env),
+            ["/usr/bin/env", "DB_HOST=10.0.1.10", "/bin/sh", "-c", "nginx"]
+        );
+    }
+
     #[test]
     fn test_exec_options_default() {
         let opts = ExecOptions::default();
@@ -220,4 +430,24 @@ mod tests {
         assert!(opts.workdir.is_none());
         assert!(opts.env.is_empty());
     }
+
+    #[test]
+    fn test_user_command() {
+        let command = vec!["echo".to_string(), "it's".to_string()];
+        assert_eq!(user_command(&command, "root"), command);
+
+        let wrapped = user_command(&command, "www");
+        assert_eq!(wrapped[..4], ["/usr/bin/su", "-m", "www", "-c"]);
+        assert_eq!(wrapped[4], "echo 'it'\\''s'");
+    }
+
+    #[test]
+    fn test_last_lines() {
+        let log = "one\ntwo\nthree\n";
+        assert_eq!(last_lines(log, 2), "two\nthree\n");
+        assert_eq!(last_lines(log, 3), log);
+        assert_eq!(last_lines(log, 10), log);
+        assert_eq!(last_lines(log, 0), "");
+        assert_eq!(last_lines("one\ntwo", 1), "two");
+    }
 }
@@ -5,6 +5,25 @@ use std::path::PathBuf;
 use thiserror::Error;
 
 /// Main error type for Blackship operations
+///
+/// Every variant has a stable identifier for scripts ([`Error::code`]) and
+/// maps onto a sysexits(3) exit status by category ([`Error::exit_code`]):
+/// - Configuration (exit 78): `E_CONFIG_READ`, `E_CONFIG_PARSE`,
+///   `E_CONFIG_PARSE_YAML`, `E_CONFIG_PARSE_JSON`, `E_CONFIG_INVALID`,
+///   `E_UNKNOWN_DEPENDENCY`, `E_TEMPLATE_PARSE`
+/// - Network (exit 69): `E_NETWORK`, `E_INTERFACE_NOT_FOUND`, `E_BRIDGE_EXISTS`,
+///   `E_DOWNLOAD`, `E_OFFLINE`
+/// - Timeout (exit 75): `E_HOOK_TIMEOUT`, `E_JAIL_TIMEOUT`, `E_WAIT_TIMEOUT`,
+///   `E_START_TIMEOUT`
+/// - Permission (exit 77): `E_IO` when permission was denied
+/// - Runtime (exit 1): `E_IO`, `E_JAIL_NOT_FOUND`, `E_JAIL_ALREADY_RUNNING`,
+///   `E_JAIL_NOT_RUNNING`, `E_JAIL_PATH_NOT_FOUND`, `E_JAIL_OPERATION`,
+///   `E_JAIL_GET`, `E_JAIL_SET`, `E_JAIL_REMOVE`, `E_JAIL_ATTACH`, `E_JAIL_EXEC`,
+///   `E_INVALID_STRING`, `E_ZFS`, `E_ZFS_NOT_ENABLED`, `E_CHECKSUM_MISMATCH`,
+///   `E_RELEASE_NOT_FOUND`, `E_RELEASE_EXISTS`, `E_EXTRACTION`,
+///   `E_UNSUPPORTED_ARCH`, `E_UNSUPPORTED_OS_VERSION`, `E_INVALID_VERSION`,
+///   `E_COMMAND_FAILED`, `E_HOOK_FAILED`, `E_HEALTH_CHECK_FAILED`,
+///   `E_BUILD_FAILED`
 #[derive(Error, Debug)]
 pub enum Error {
     // IO errors
@@ -18,6 +37,12 @@ pub enum Error {
     #[error("Failed to parse config: {0}")]
     ConfigParse(#[from] toml::de::Error),
 
+    #[error("Failed to parse YAML config: {0}")]
+    ConfigParseYaml(#[from] serde_yaml::Error),
+
+    #[error("Failed to parse JSON config: {0}")]
+    ConfigParseJson(#[from] serde_json::Error),
+
     #[error("Config validation failed: {0}")]
     ConfigValidation(String),
 
@@ -90,6 +115,9 @@ pub enum Error {
     #[error("Unsupported architecture: {0}")]
     UnsupportedArch(String),
 
+    #[error("Offline mode: {0}")]
+    Offline(String),
+
     // System errors
     #[error("Feature '{feature}' requires FreeBSD {minimum}+, but running {current}")]
     UnsupportedOsVersion {
@@ -129,6 +157,18 @@ pub enum Error {
     #[error("Jail command timeout after {0} seconds")]
     JailTimeout(u64),
 
+    // `wait` gave up
+    #[error("Timed out after {secs} seconds waiting for jail '{jail}' to be {condition}")]
+    WaitTimeout {
+        jail: String,
+        condition: String,
+        secs: u64,
+    },
+
+    // A jail's readiness command never succeeded
+    #[error("Jail '{jail}' was not ready within {secs} seconds")]
+    StartTimeout { jail: String, secs: u64 },
+
     // Health check errors
     #[error("Health check failed for jail '{jail}' ({check}): {message}")]
     HealthCheckFailed {
@@ -145,5 +185,191 @@ pub enum Error {
     BuildFailed { step: String, message: String },
 }
 
+/// sysexits(3) statuses used by [`Error::exit_code`]
+const EX_UNAVAILABLE: i32 = 69;
+const EX_TEMPFAIL: i32 = 75;
+const EX_NOPERM: i32 = 77;
+const EX_CONFIG: i32 = 78;
+
+impl Error {
+    /// Stable identifier of the error, for scripts
+    pub fn code(&self) -> &'static str {
+        match self {
+            Error::Io(_) => "E_IO",
+            Error::ConfigRead { .. } => "E_CONFIG_READ",
+            Error::ConfigParse(_) => "E_CONFIG_PARSE",
+            Error::ConfigParseYaml(_) => "E_CONFIG_PARSE_YAML",
+            Error::ConfigParseJson(_) => "E_CONFIG_PARSE_JSON",
+            Error::ConfigValidation(_) => "E_CONFIG_INVALID",
+            Error::UnknownDependency(_) => "E_UNKNOWN_DEPENDENCY",
+            Error::JailNotFound(_) => "E_JAIL_NOT_FOUND",
+            Error::JailAlreadyRunning(_) => "E_JAIL_ALREADY_RUNNING",
+            Error::JailNotRunning(_) => "E_JAIL_NOT_RUNNING",
+            Error::JailPathNotFound(_) => "E_JAIL_PATH_NOT_FOUND",
+            Error::JailOperation(_) => "E_JAIL_OPERATION",
+            Error::JailGet(_) => "E_JAIL_GET",
+            Error::JailSet(_) => "E_JAIL_SET",
+            Error::JailRemoveFailed => "E_JAIL_REMOVE",
+            Error::JailAttachFailed(_) => "E_JAIL_ATTACH",
+            Error::JailExecFailed(_) => "E_JAIL_EXEC",
+            Error::CString(_) => "E_INVALID_STRING",
+            Error::Zfs(_) => "E_ZFS",
+            Error::ZfsNotEnabled => "E_ZFS_NOT_ENABLED",
+            Error::DownloadFailed(_) => "E_DOWNLOAD",
+            Error::ChecksumMismatch { .. } => "E_CHECKSUM_MISMATCH",
+            Error::ReleaseNotFound(_) => "E_RELEASE_NOT_FOUND",
+            Error::ReleaseAlreadyExists(_) => "E_RELEASE_EXISTS",
+            Error::ExtractionFailed(_) => "E_EXTRACTION",
+            Error::UnsupportedArch(_) => "E_UNSUPPORTED_ARCH",
+            Error::Offline(_) => "E_OFFLINE",
+            Error::UnsupportedOsVersion { .. } => "E_UNSUPPORTED_OS_VERSION",
+            Error::InvalidVersion(_) => "E_INVALID_VERSION",
+            Error::CommandFailed { .. } => "E_COMMAND_FAILED",
+            Error::Network(_) => "E_NETWORK",
+            Error::InterfaceNotFound(_) => "E_INTERFACE_NOT_FOUND",
+            Error::BridgeAlreadyExists(_) => "E_BRIDGE_EXISTS",
+            Error::HookFailed { .. } => "E_HOOK_FAILED",
+            Error::HookTimeout(_) => "E_HOOK_TIMEOUT",
+            Error::JailTimeout(_) => "E_JAIL_TIMEOUT",
+            Error::WaitTimeout { .. } => "E_WAIT_TIMEOUT",
+            Error::StartTimeout { .. } => "E_START_TIMEOUT",
+            Error::HealthCheckFailed { .. } => "E_HEALTH_CHECK_FAILED",
+            Error::TemplateParseFailed(_) => "E_TEMPLATE_PARSE",
+            Error::BuildFailed { .. } => "E_BUILD_FAILED",
+        }
+    }
+
+    /// Process exit status for the error's category
+    pub fn exit_code(&self) -> i32 {
+        match self {
+            Error::ConfigRead { .. }
+            | Error::ConfigParse(_)
+            | Error::ConfigParseYaml(_)
+            | Error::ConfigParseJson(_)
+            | Error::ConfigValidation(_)
+            | Error::UnknownDependency(_)
+            | Error::TemplateParseFailed(_) => EX_CONFIG,
+            Error::Network(_)
+            | Error::InterfaceNotFound(_)
+            | Error::BridgeAlreadyExists(_)
+            | Error::DownloadFailed(_)
+            | Error::Offline(_) => EX_UNAVAILABLE,
+            Error::HookTimeout(_)
+            | Error::JailTimeout(_)
+            | Error::WaitTimeout { .. }
+            | Error::StartTimeout { .. } => EX_TEMPFAIL,
+            Error::Io(e) if e.kind() == io::ErrorKind::PermissionDenied => EX_NOPERM,
+            _ => 1,
+        }
+    }
+
+    /// How to fix the error, when there is an obvious remedy
+    pub fn hint(&self) -> Option<String> {
+        let hint = match self {
+            Error::Io(e) if e.kind() == io::ErrorKind::PermissionDenied => {
+                "most operations need root; run blackship as root (e.g. with doas)".to_string()
+            }
+            Error::ConfigRead { .. } => {
+                "pass the file with -c <path>, or create one with `blackship init`".to_string()
+            }
+            Error::ConfigParse(_)
+            | Error::ConfigParseYaml(_)
+            | Error::ConfigParseJson(_)
+            | Error::ConfigValidation(_) => {
+                "`blackship check` validates the configuration without touching jails".to_string()
+            }
+            Error::UnknownDependency(dep) => format!(
+                "add a [[jails]] entry named '{}' or remove it from depends_on",
+                dep
+            ),
+            Error::JailNotFound(_) => {
+                "`blackship ps` lists the jails defined in the configuration".to_string()
+            }
+            Error::JailAlreadyRunning(name) => format!(
+                "stop it with `blackship down {}`, or use `blackship restart {}`",
+                name, name
+            ),
+            Error::JailNotRunning(name) => format!("start it with `blackship up {}`", name),
+            Error::JailPathNotFound(_) => {
+                "bootstrap the jail's release with `blackship bootstrap <release>`, or point \
+                 `path` at an existing jail root"
+                    .to_string()
+            }
+            Error::ZfsNotEnabled => {
+                "set zfs_enabled = true and zpool = \"<pool>\" in [config]".to_string()
+            }
+            Error::DownloadFailed(_) => {
+                "check network access to the mirror, or list fallbacks in mirror_urls in [config]"
+                    .to_string()
+            }
+            Error::ChecksumMismatch { .. } => {
+                "the file is corrupt or incomplete; retry the download with `blackship bootstrap \
+                 --force`, or transfer the archive again"
+                    .to_string()
+            }
+            Error::Offline(_) => {
+                "bootstrap the release while online first (its archives stay in cache_dir), or \
+                 run without --offline"
+                    .to_string()
+            }
+            Error::ReleaseNotFound(_) => {
+                "download it with `blackship bootstrap <release>`; `blackship releases` lists \
+                 the installed ones"
+                    .to_string()
+            }
+            Error::ReleaseAlreadyExists(_) => {
+                "pass --force to `blackship bootstrap` to download it again".to_string()
+            }
+            Error::UnsupportedOsVersion { feature, .. } => {
+                format!("upgrade the host, or stop using {}", feature)
+            }
+            Error::Network(_) => {
+                "check the [[networks]] definitions and that `blackship setup` has been run"
+                    .to_string()
+            }
+            Error::InterfaceNotFound(_) => "`ifconfig -l` lists the host's interfaces".to_string(),
+            Error::HookTimeout(_) => {
+                "raise the hook's `timeout` (default: 30 seconds)".to_string()
+            }
+            _ => return None,
+        };
+        Some(hint)
+    }
+}
+
 /// Result type alias for Blackship operations
 pub type Result<T> = std::result::Result<T, Error>;
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_hint() {
+        let hint = Error::JailNotRunning("web".to_string()).hint().unwrap();
+        assert_eq!(hint, "start it with `blackship up web`");
+        assert!(Error::ZfsNotEnabled.hint().unwrap().contains("zfs_enabled = true"));
+
+        let denied = Error::Io(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert!(denied.hint().is_some());
+        assert!(Error::Io(io::Error::from(io::ErrorKind::NotFound)).hint().is_none());
+        assert!(Error::JailRemoveFailed.hint().is_none());
+    }
+
+    #[test]
+    fn test_code_and_exit_code() {
+        let not_found = Error::JailNotFound("web".to_string());
+        assert_eq!(not_found.code(), "E_JAIL_NOT_FOUND");
+        assert_eq!(not_found.exit_code(), 1);
+
+        let invalid = Error::ConfigValidation("bad".to_string());
+        assert_eq!(invalid.code(), "E_CONFIG_INVALID");
+        assert_eq!(invalid.exit_code(), EX_CONFIG);
+        assert_eq!(Error::Network("down".to_string()).exit_code(), EX_UNAVAILABLE);
+        assert_eq!(Error::JailTimeout(5).exit_code(), EX_TEMPFAIL);
+
+        let denied = Error::Io(io::Error::from(io::ErrorKind::PermissionDenied));
+        assert_eq!(denied.code(), "E_IO");
+        assert_eq!(denied.exit_code(), EX_NOPERM);
+    }
+}
@@ -3,15 +3,63 @@
 //! Provides:
 //! - Export jails to tar.zst archives
 //! - Import jails from archives
+//! - Preserve file flags, ACLs and extended attributes via bsdtar
+//! - Reproducible exports
+//! - `.sha256` sidecars verified on import
 //! - ZFS send/receive for efficient transfers
+//! - Copy a jail root when cloning without ZFS
+//!
+//! Archives are written and extracted with the base system's bsdtar in pax
+//! format, which carries file flags (the `schg` on base system binaries),
+//! ACLs and extended attributes; extraction restores them.
+//!
+//! Reproducible exports are written in-process instead: entries are sorted and
+//! timestamps clamped, so identical roots give byte-identical archives.
+//!
+//! Every export writes a `<archive>.sha256` sidecar that `import --verify`
+//! checks before extracting.
 
+use crate::bridge::format_size;
 use crate::error::{Error, Result};
 use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
 use std::fs::File;
 use std::io::{Read, Write};
-use std::path::Path;
-use std::process::Command;
-use tar::{Archive, Builder};
+use std::os::freebsd::fs::MetadataExt as _;
+use std::os::unix::fs::{FileTypeExt, MetadataExt};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
+use std::time::Instant;
+use tar::{Archive, Builder, EntryType, Header, HeaderMode};
+
+/// Archive entry holding the export metadata, ahead of `rootfs/`
+const METADATA_FILE: &str = ".blackship-metadata.json";
+
+/// bsdtar options archiving and restoring file flags, ACLs and extended attributes
+const TAR_METADATA_ARGS: &[&str] = &["--fflags", "--acls", "--xattrs"];
+
+/// Default zstd level of tar exports
+pub const DEFAULT_ZSTD_LEVEL: i32 = 3;
+
+/// File flags and their chflags(1) names, as bsdtar reads them from `SCHILY.fflags`
+const FILE_FLAG_NAMES: &[(libc::c_ulong, &str)] = &[
+    (libc::UF_NODUMP, "nodump"),
+    (libc::UF_IMMUTABLE, "uchg"),
+    (libc::UF_APPEND, "uappnd"),
+    (libc::UF_OPAQUE, "opaque"),
+    (libc::UF_NOUNLINK, "uunlnk"),
+    (libc::UF_SYSTEM, "system"),
+    (libc::UF_SPARSE, "sparse"),
+    (libc::UF_OFFLINE, "offline"),
+    (libc::UF_REPARSE, "reparse"),
+    (libc::UF_ARCHIVE, "uarch"),
+    (libc::UF_READONLY, "rdonly"),
+    (libc::UF_HIDDEN, "uhidden"),
+    (libc::SF_ARCHIVED, "arch"),
+    (libc::SF_IMMUTABLE, "schg"),
+    (libc::SF_APPEND, "sappnd"),
+    (libc::SF_NOUNLINK, "sunlnk"),
+];
 
 /// Metadata stored in the archive
 #[derive(Debug, Serialize, Deserialize)]
@@ -30,6 +78,94 @@ pub struct ExportMetadata {
     pub hostname: Option<String>,
 }
 
+/// How `export_jail` writes its archive
+#[derive(Debug, Clone)]
+pub struct ExportOptions {
+    /// Sorted entries and clamped timestamps (see [`export_jail`])
+    pub reproducible: bool,
+    /// zstd compression level
+    pub level: i32,
+    /// zstd worker threads (0 = compress on the calling thread)
+    pub threads: u32,
+}
+
+impl Default for ExportOptions {
+    fn default() -> Self {
+        Self {
+            reproducible: false,
+            level: DEFAULT_ZSTD_LEVEL,
+            threads: 0,
+        }
+    }
+}
+
+/// Counts the bytes passing through to the compressor
+struct CountingWriter<W> {
+    inner: W,
+    bytes: u64,
+}
+
+impl<W: Write> Write for CountingWriter<W> {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        let written = self.inner.write(buf)?;
+        self.bytes += written as u64;
+        Ok(written)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        self.inner.flush()
+    }
+}
+
+/// Path of the SHA256 sidecar written next to an archive
+pub fn checksum_path(archive_path: &Path) -> PathBuf {
+    let mut path = archive_path.as_os_str().to_owned();
+    path.push(".sha256");
+    PathBuf::from(path)
+}
+
+/// Write the `sha256sum`-style sidecar for a finished archive
+fn write_checksum(archive_path: &Path) -> Result<()> {
+    let digest = crate::supply::sha256_file(archive_path)?;
+    let file_name = archive_path
+        .file_name()
+        .map(|n| n.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    std::fs::write(
+        checksum_path(archive_path),
+        format!("{}  {}\n", digest, file_name),
+    )
+    .map_err(|e| Error::JailOperation(format!("Failed to write checksum: {}", e)))
+}
+
+/// Check an archive against its sidecar, failing on a missing sidecar or a mismatch
+pub fn verify_archive(archive_path: &Path) -> Result<()> {
+    let sidecar = checksum_path(archive_path);
+    let content = std::fs::read_to_string(&sidecar).map_err(|e| {
+        Error::JailOperation(format!(
+            "Cannot verify {}: failed to read {}: {}",
+            archive_path.display(),
+            sidecar.display(),
+            e
+        ))
+    })?;
+    let expected = content
+        .split_whitespace()
+        .next()
+        .ok_or_else(|| Error::JailOperation(format!("Empty checksum file {}", sidecar.display())))?
+        .to_lowercase();
+
+    let actual = crate::supply::sha256_file(archive_path)?;
+    if actual != expected {
+        return Err(Error::ChecksumMismatch {
+            file: archive_path.display().to_string(),
+            expected,
+            actual,
+        });
+    }
+    Ok(())
+}
+
 /// Read export metadata without importing the archive
 pub fn read_metadata(archive_path: &Path) -> Result<ExportMetadata> {
     // Open archive
@@ -77,7 +213,7 @@ pub fn read_metadata(archive_path: &Path) -> Result<ExportMetadata> {
             .map_err(|e| Error::JailOperation(format!("Failed to read entry path: {}", e)))?
             .to_path_buf();
 
-        if path.to_string_lossy() == ".blackship-metadata.json" {
+        if path.to_string_lossy() == METADATA_FILE {
             let mut content = String::new();
             entry
                 .read_to_string(&mut content)
@@ -91,13 +227,21 @@ pub fn read_metadata(archive_path: &Path) -> Result<ExportMetadata> {
 }
 
 /// Export a jail to a tar.zst archive
+///
+/// A `reproducible` export has sorted entries and timestamps clamped to
+/// `SOURCE_DATE_EPOCH` (default 0), so identical roots give identical bytes
+/// for the same level and thread setting. It keeps ownership, modes and file
+/// flags, but not ACLs or extended attributes.
 pub fn export_jail(
     name: &str,
     jail_path: &Path,
     output_path: &Path,
     hostname: Option<&str>,
     ip: Option<&str>,
+    opts: &ExportOptions,
 ) -> Result<()> {
+    let reproducible = opts.reproducible;
+    let started = Instant::now();
     println!("Exporting jail '{}' to {}", name, output_path.display());
 
     // Create output file
@@ -105,17 +249,28 @@ pub fn export_jail(
         .map_err(|e| Error::JailOperation(format!("Failed to create output file: {}", e)))?;
 
     // Wrap in zstd compressor
-    let encoder = zstd::stream::Encoder::new(file, 3)
+    let mut encoder = zstd::stream::Encoder::new(file, opts.level)
         .map_err(|e| Error::JailOperation(format!("Failed to create compressor: {}", e)))?;
-
-    // Create tar builder
-    let mut builder = Builder::new(encoder);
+    if opts.threads > 0 {
+        encoder.multithread(opts.threads).map_err(|e| {
+            Error::JailOperation(format!("Failed to enable {} zstd threads: {}", opts.threads, e))
+        })?;
+    }
+    let mut counter = CountingWriter {
+        inner: encoder,
+        bytes: 0,
+    };
+    let epoch = source_date_epoch();
 
     // Create and add metadata
     let metadata = ExportMetadata {
         name: name.to_string(),
         version: env!("CARGO_PKG_VERSION").to_string(),
-        timestamp: chrono_lite_timestamp(),
+        timestamp: if reproducible {
+            epoch.to_string()
+        } else {
+            chrono_lite_timestamp()
+        },
         original_path: jail_path.to_string_lossy().to_string(),
         ip: ip.map(String::from),
         hostname: hostname.map(String::from),
@@ -124,33 +279,58 @@ pub fn export_jail(
     let metadata_json = serde_json::to_string_pretty(&metadata)
         .map_err(|e| Error::JailOperation(format!("Failed to serialize metadata: {}", e)))?;
 
-    // Add metadata as first file
-    let metadata_bytes = metadata_json.as_bytes();
-    let mut header = tar::Header::new_gnu();
-    header.set_size(metadata_bytes.len() as u64);
-    header.set_mode(0o644);
-    header.set_cksum();
-
-    builder
-        .append_data(&mut header, ".blackship-metadata.json", metadata_bytes)
-        .map_err(|e| Error::JailOperation(format!("Failed to add metadata: {}", e)))?;
-
-    // Add jail root filesystem
-    println!("  Adding jail filesystem...");
-    builder
-        .append_dir_all("rootfs", jail_path)
-        .map_err(|e| Error::JailOperation(format!("Failed to add jail files: {}", e)))?;
+    if reproducible {
+        println!("  Adding jail filesystem (reproducible)...");
+        let mut builder = Builder::new(counter);
+        append_reproducible(&mut builder, metadata_json.as_bytes(), jail_path, epoch)
+            .map_err(|e| Error::JailOperation(format!("Failed to add jail files: {}", e)))?;
+        counter = builder
+            .into_inner()
+            .map_err(|e| Error::JailOperation(format!("Failed to finalize archive: {}", e)))?;
+        return finish_export(counter, output_path, started);
+    }
 
-    // Finish archive
-    let encoder = builder
-        .into_inner()
-        .map_err(|e| Error::JailOperation(format!("Failed to finalize archive: {}", e)))?;
+    // Stage the metadata next to a link to the jail root; `-H` follows the
+    // link, so the archive holds the metadata first and the root as `rootfs/`
+    let staging = std::env::temp_dir().join(format!("blackship-export-{}", std::process::id()));
+    std::fs::create_dir_all(&staging)
+        .map_err(|e| Error::JailOperation(format!("Failed to create staging dir: {}", e)))?;
+    let result = (|| {
+        std::fs::write(staging.join(METADATA_FILE), &metadata_json)?;
+        std::os::unix::fs::symlink(jail_path.canonicalize()?, staging.join("rootfs"))?;
+
+        println!("  Adding jail filesystem...");
+        archive_dir(&staging, &[METADATA_FILE, "rootfs"], &mut counter)
+    })();
+    let _ = std::fs::remove_dir_all(&staging);
+    result.map_err(|e| Error::JailOperation(format!("Failed to add jail files: {}", e)))?;
+
+    finish_export(counter, output_path, started)
+}
 
-    encoder
+/// Finish compression and report the size, compression ratio and throughput
+fn finish_export<W: Write>(
+    counter: CountingWriter<zstd::stream::Encoder<'static, W>>,
+    output_path: &Path,
+    started: Instant,
+) -> Result<()> {
+    counter
+        .inner
         .finish()
         .map_err(|e| Error::JailOperation(format!("Failed to finish compression: {}", e)))?;
 
+    let compressed = std::fs::metadata(output_path)?.len();
+    let secs = started.elapsed().as_secs_f64().max(0.001);
+    write_checksum(output_path)?;
     println!("Export complete: {}", output_path.display());
+    println!(
+        "  {} -> {} ({:.2}x) in {:.1}s, {}/s",
+        format_size(counter.bytes),
+        format_size(compressed),
+        counter.bytes as f64 / compressed.max(1) as f64,
+        secs,
+        format_size((counter.bytes as f64 / secs) as u64)
+    );
     Ok(())
 }
 
@@ -234,6 +414,7 @@ pub fn export_jail_zfs(
         return Err(Error::Zfs("ZFS send failed".into()));
     }
 
+    write_checksum(output_path)?;
     println!("Export complete: {}", output_path.display());
     Ok(())
 }
@@ -260,65 +441,34 @@ pub fn import_jail(
         }
     }
 
+    let metadata = read_metadata(archive_path)?;
+    let jail_name = new_name.unwrap_or(&metadata.name);
+
     // Reopen file for tar/zstd
     let file = File::open(archive_path)
         .map_err(|e| Error::JailOperation(format!("Failed to reopen archive: {}", e)))?;
 
     // Decompress
-    let decoder = zstd::stream::Decoder::new(file)
+    let mut decoder = zstd::stream::Decoder::new(file)
         .map_err(|e| Error::JailOperation(format!("Failed to decompress: {}", e)))?;
 
-    // Open tar archive
-    let mut archive = Archive::new(decoder);
-
-    // Extract metadata first
-    let mut metadata: Option<ExportMetadata> = None;
-
     // Create temp dir for extraction
     let temp_dir = target_path.parent().unwrap_or(Path::new("/tmp"));
     let temp_extract = temp_dir.join(format!(".import-{}", std::process::id()));
     std::fs::create_dir_all(&temp_extract)
         .map_err(|e| Error::JailOperation(format!("Failed to create temp dir: {}", e)))?;
 
-    // Extract archive
-    for entry in archive
-        .entries()
-        .map_err(|e| Error::JailOperation(format!("Failed to read archive entries: {}", e)))?
-    {
-        let mut entry = entry
-            .map_err(|e| Error::JailOperation(format!("Failed to read archive entry: {}", e)))?;
-
-        let path = entry
-            .path()
-            .map_err(|e| Error::JailOperation(format!("Failed to read entry path: {}", e)))?
-            .to_path_buf(); // Convert to owned PathBuf to avoid borrow conflict
-
-        if path.to_string_lossy() == ".blackship-metadata.json" {
-            let mut content = String::new();
-            entry
-                .read_to_string(&mut content)
-                .map_err(|e| Error::JailOperation(format!("Failed to read metadata: {}", e)))?;
-            metadata =
-                Some(serde_json::from_str(&content).map_err(|e| {
-                    Error::JailOperation(format!("Failed to parse metadata: {}", e))
-                })?);
-        } else {
-            entry.unpack_in(&temp_extract).map_err(|e| {
-                Error::JailOperation(format!("Failed to extract {}: {}", path.display(), e))
-            })?;
-        }
+    // Extract archive, restoring permissions, flags, ACLs and extended attributes
+    if let Err(e) = extract_archive(&mut decoder, &temp_extract) {
+        let _ = remove_jail_tree(&temp_extract);
+        return Err(Error::JailOperation(format!("Failed to extract archive: {}", e)));
     }
 
-    let metadata =
-        metadata.ok_or_else(|| Error::JailOperation("Archive missing metadata".into()))?;
-
-    let jail_name = new_name.unwrap_or(&metadata.name);
-
     // Move rootfs to target
     let rootfs_src = temp_extract.join("rootfs");
     if rootfs_src.exists() {
         if target_path.exists() {
-            std::fs::remove_dir_all(target_path)
+            remove_jail_tree(target_path)
                 .map_err(|e| Error::JailOperation(format!("Failed to remove existing: {}", e)))?;
         }
         std::fs::rename(&rootfs_src, target_path)
@@ -326,7 +476,7 @@ pub fn import_jail(
     }
 
     // Clean up temp
-    let _ = std::fs::remove_dir_all(&temp_extract);
+    let _ = remove_jail_tree(&temp_extract);
 
     println!("Imported jail '{}' to {}", jail_name, target_path.display());
     println!("  Original: {}", metadata.name);
@@ -337,6 +487,176 @@ pub fn import_jail(
     Ok(jail_name.to_string())
 }
 
+/// Write a pax archive of `entries` (relative to `dir`) to `out` with bsdtar
+///
+/// Symlinks named in `entries` are followed.
+fn archive_dir(dir: &Path, entries: &[&str], out: &mut impl Write) -> std::io::Result<()> {
+    let mut child = Command::new("tar")
+        .args(["-c", "-H", "--format", "pax"])
+        .args(TAR_METADATA_ARGS)
+        .arg("-f")
+        .arg("-")
+        .arg("-C")
+        .arg(dir)
+        .args(entries)
+        .stdout(Stdio::piped())
+        .spawn()?;
+    let copied = match child.stdout.take() {
+        Some(mut stdout) => std::io::copy(&mut stdout, out).map(|_| ()),
+        None => Err(std::io::Error::other("tar stdout not captured")),
+    };
+    let status = child.wait()?;
+    copied?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("tar exited with {}", status)));
+    }
+    Ok(())
+}
+
+/// `SOURCE_DATE_EPOCH` from the environment, or 0
+fn source_date_epoch() -> u64 {
+    std::env::var("SOURCE_DATE_EPOCH")
+        .ok()
+        .and_then(|epoch| epoch.trim().parse().ok())
+        .unwrap_or(0)
+}
+
+/// Write the metadata and a jail root as a deterministic archive
+fn append_reproducible<W: Write>(
+    builder: &mut Builder<W>,
+    metadata_json: &[u8],
+    root: &Path,
+    epoch: u64,
+) -> std::io::Result<()> {
+    let mut header = Header::new_gnu();
+    header.set_entry_type(EntryType::Regular);
+    header.set_size(metadata_json.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(epoch);
+    builder.append_data(&mut header, METADATA_FILE, metadata_json)?;
+
+    let mut hardlinks = HashMap::new();
+    append_tree(builder, root, Path::new("rootfs"), epoch, &mut hardlinks)
+}
+
+/// Append `path` as `name`, then its children in sorted order
+///
+/// Sockets and device nodes are skipped (daemons and devfs recreate them).
+/// Files with several links are stored once, later names as hardlinks.
+fn append_tree<W: Write>(
+    builder: &mut Builder<W>,
+    path: &Path,
+    name: &Path,
+    epoch: u64,
+    hardlinks: &mut HashMap<(u64, u64), PathBuf>,
+) -> std::io::Result<()> {
+    let meta = std::fs::symlink_metadata(path)?;
+    let file_type = meta.file_type();
+    if file_type.is_socket() || file_type.is_char_device() || file_type.is_block_device() {
+        return Ok(());
+    }
+
+    let mut header = Header::new_gnu();
+    header.set_metadata_in_mode(&meta, HeaderMode::Complete);
+    header.set_mtime((meta.mtime().max(0) as u64).min(epoch));
+    header.set_size(0);
+    if let Some(flags) = file_flag_names(meta.st_flags()) {
+        append_pax_record(builder, "SCHILY.fflags", &flags)?;
+    }
+
+    if file_type.is_dir() {
+        builder.append_data(&mut header, name, std::io::empty())?;
+        let mut children = std::fs::read_dir(path)?
+            .map(|entry| entry.map(|entry| entry.file_name()))
+            .collect::<std::io::Result<Vec<_>>>()?;
+        children.sort();
+        for child in children {
+            append_tree(builder, &path.join(&child), &name.join(&child), epoch, hardlinks)?;
+        }
+    } else if file_type.is_symlink() {
+        builder.append_link(&mut header, name, std::fs::read_link(path)?)?;
+    } else if file_type.is_file() {
+        let key = (meta.dev(), meta.ino());
+        if let Some(first) = hardlinks.get(&key) {
+            header.set_entry_type(EntryType::Link);
+            builder.append_link(&mut header, name, first)?;
+        } else {
+            if meta.nlink() > 1 {
+                hardlinks.insert(key, name.to_path_buf());
+            }
+            header.set_size(meta.len());
+            builder.append_data(&mut header, name, File::open(path)?)?;
+        }
+    } else {
+        builder.append_data(&mut header, name, std::io::empty())?;
+    }
+    Ok(())
+}
+
+/// chflags(1) names of a file's flags (None without flags)
+fn file_flag_names(flags: u32) -> Option<String> {
+    let names: Vec<&str> = FILE_FLAG_NAMES
+        .iter()
+        .filter(|(flag, _)| flags as libc::c_ulong & flag != 0)
+        .map(|(_, name)| *name)
+        .collect();
+    (!names.is_empty()).then(|| names.join(","))
+}
+
+/// Append a pax extended header applying `key=value` to the next entry
+fn append_pax_record<W: Write>(
+    builder: &mut Builder<W>,
+    key: &str,
+    value: &str,
+) -> std::io::Result<()> {
+    let record = pax_record(key, value);
+    let mut header = Header::new_ustar();
+    header.set_entry_type(EntryType::XHeader);
+    header.set_size(record.len() as u64);
+    header.set_mode(0o644);
+    header.set_mtime(0);
+    builder.append_data(&mut header, "PaxHeader", record.as_slice())
+}
+
+/// A pax record, `<length> <key>=<value>\n`, where the length counts itself
+fn pax_record(key: &str, value: &str) -> Vec<u8> {
+    let body = format!(" {}={}\n", key, value);
+    let mut len = body.len() + 1;
+    while len.to_string().len() + body.len() != len {
+        len = len.to_string().len() + body.len();
+    }
+    format!("{}{}", len, body).into_bytes()
+}
+
+/// Extract an archive read from `input` into `dest` with bsdtar
+fn extract_archive(input: &mut impl Read, dest: &Path) -> std::io::Result<()> {
+    let mut child = Command::new("tar")
+        .args(["-x", "-p"])
+        .args(TAR_METADATA_ARGS)
+        .arg("-f")
+        .arg("-")
+        .arg("-C")
+        .arg(dest)
+        .stdin(Stdio::piped())
+        .spawn()?;
+    let copied = match child.stdin.take() {
+        // Closing stdin at the end of the block lets tar finish
+        Some(mut stdin) => std::io::copy(input, &mut stdin).map(|_| ()),
+        None => Err(std::io::Error::other("tar stdin not captured")),
+    };
+    let status = child.wait()?;
+    if !status.success() {
+        return Err(std::io::Error::other(format!("tar exited with {}", status)));
+    }
+    copied
+}
+
+/// Remove a jail tree, clearing the file flags (e.g. `schg`) that block removal
+fn remove_jail_tree(path: &Path) -> std::io::Result<()> {
+    let _ = Command::new("chflags").args(["-R", "0"]).arg(path).status();
+    std::fs::remove_dir_all(path)
+}
+
 /// Import from ZFS stream
 fn import_jail_zfs(
     archive_path: &Path,
@@ -406,6 +726,69 @@ fn import_jail_zfs(
     Ok(jail_name.to_string())
 }
 
+/// Copy a jail root to a new path (clone without ZFS)
+///
+/// This is a full copy, not copy-on-write. A `hostname=` line in the copy's
+/// `/etc/rc.conf` is changed to `hostname`.
+pub fn copy_jail(source: &Path, dest: &Path, hostname: &str) -> Result<()> {
+    if !source.is_dir() {
+        return Err(Error::JailOperation(format!(
+            "Jail root {} does not exist",
+            source.display()
+        )));
+    }
+    if dest.exists() {
+        return Err(Error::JailOperation(format!(
+            "Path {} already exists",
+            dest.display()
+        )));
+    }
+    std::fs::create_dir_all(dest)?;
+
+    let status = Command::new("cp")
+        .arg("-a")
+        .arg(format!("{}/.", source.display()))
+        .arg(dest)
+        .status()
+        .map_err(|e| Error::CommandFailed {
+            command: "cp".to_string(),
+            message: e.to_string(),
+        })?;
+    if !status.success() {
+        // Clear the schg flags base system files carry before removing
+        let _ = remove_jail_tree(dest);
+        return Err(Error::CommandFailed {
+            command: "cp".to_string(),
+            message: format!("Failed to copy {} (exit status {})", source.display(), status),
+        });
+    }
+
+    let rc_conf = dest.join("etc/rc.conf");
+    if let Ok(contents) = std::fs::read_to_string(&rc_conf)
+        && let Some(updated) = set_rc_hostname(&contents, hostname)
+    {
+        std::fs::write(&rc_conf, updated)?;
+    }
+    Ok(())
+}
+
+/// Replace `hostname=` assignments in rc.conf content (None if there are none)
+fn set_rc_hostname(rc_conf: &str, hostname: &str) -> Option<String> {
+    let mut found = false;
+    let lines: Vec<String> = rc_conf
+        .lines()
+        .map(|line| {
+            if line.trim_start().starts_with("hostname=") {
+                found = true;
+                format!("hostname=\"{}\"", hostname)
+            } else {
+                line.to_string()
+            }
+        })
+        .collect();
+    found.then(|| lines.join("\n") + "\n")
+}
+
 /// Simple timestamp without external crate
 fn chrono_lite_timestamp() -> String {
     use std::time::{SystemTime, UNIX_EPOCH};
@@ -419,6 +802,85 @@ fn chrono_lite_timestamp() -> String {
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_set_rc_hostname() {
+        let rc_conf = "sshd_enable=\"YES\"\nhostname=\"web\"\n";
+        assert_eq!(
+            set_rc_hostname(rc_conf, "web-copy").unwrap(),
+            "sshd_enable=\"YES\"\nhostname=\"web-copy\"\n"
+        );
+        assert_eq!(set_rc_hostname("sshd_enable=\"YES\"\n", "web-copy"), None);
+    }
+
+    #[test]
+    fn test_file_flags_round_trip() {
+        let base = std::env::temp_dir().join(format!("blackship-flags-{}", std::process::id()));
+        let root = base.join("root");
+        std::fs::create_dir_all(root.join("bin")).unwrap();
+        std::fs::write(root.join("bin/tool"), "#!/bin/sh\n").unwrap();
+        // uchg (user immutable) can be set without root, unlike schg
+        let status = Command::new("chflags")
+            .arg("uchg")
+            .arg(root.join("bin/tool"))
+            .status()
+            .unwrap();
+        assert!(status.success());
+
+        let archive = base.join("export.tar.zst");
+        export_jail("flags", &root, &archive, None, None, &ExportOptions::default()).unwrap();
+        let imported = base.join("imported");
+        import_jail(&archive, &imported, None).unwrap();
+
+        let flags = std::fs::metadata(imported.join("bin/tool")).unwrap().st_flags();
+        let result = flags & libc::UF_IMMUTABLE as u32;
+        remove_jail_tree(&base).unwrap();
+        assert_ne!(result, 0, "uchg flag was lost (flags {:#x})", flags);
+    }
+
+    #[test]
+    fn test_pax_record() {
+        assert_eq!(pax_record("SCHILY.fflags", "schg"), b"22 SCHILY.fflags=schg\n");
+        // The length grows a digit when the record reaches 100 bytes
+        let record = pax_record("k", &"v".repeat(94));
+        assert_eq!(record.len(), 101);
+        assert!(record.starts_with(b"101 k="));
+    }
+
+    #[test]
+    fn test_file_flag_names() {
+        assert_eq!(file_flag_names(0), None);
+        let flags = (libc::SF_IMMUTABLE | libc::UF_NODUMP) as u32;
+        assert_eq!(file_flag_names(flags).as_deref(), Some("nodump,schg"));
+    }
+
+    #[test]
+    fn test_reproducible_export() {
+        let base = std::env::temp_dir().join(format!("blackship-repro-{}", std::process::id()));
+        let root = base.join("root");
+        std::fs::create_dir_all(root.join("etc")).unwrap();
+        std::fs::write(root.join("etc/rc.conf"), "sshd_enable=\"YES\"\n").unwrap();
+        std::fs::write(root.join("etc/motd"), "hello\n").unwrap();
+        std::fs::hard_link(root.join("etc/motd"), root.join("etc/motd.link")).unwrap();
+
+        let first = base.join("first.tar.zst");
+        let second = base.join("second.tar.zst");
+        let opts = ExportOptions {
+            reproducible: true,
+            ..Default::default()
+        };
+        export_jail("repro", &root, &first, None, None, &opts).unwrap();
+        // A later modification time alone must not change the archive
+        std::fs::write(root.join("etc/rc.conf"), "sshd_enable=\"YES\"\n").unwrap();
+        export_jail("repro", &root, &second, None, None, &opts).unwrap();
+        assert_eq!(std::fs::read(&first).unwrap(), std::fs::read(&second).unwrap());
+
+        let imported = base.join("imported");
+        import_jail(&first, &imported, None).unwrap();
+        assert_eq!(std::fs::read_to_string(imported.join("etc/motd.link")).unwrap(), "hello\n");
+
+        std::fs::remove_dir_all(&base).unwrap();
+    }
+
     #[test]
     fn test_metadata_serialization() {
         let metadata = ExportMetadata {
@@ -437,4 +899,29 @@ mod tests {
         let parsed: ExportMetadata = serde_json::from_str(&json).unwrap();
         assert_eq!(parsed.name, "test");
     }
+
+    #[test]
+    fn test_verify_archive() {
+        let dir = std::env::temp_dir().join(format!("blackship-verify-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let archive = dir.join("web.tar.zst");
+        std::fs::write(&archive, b"archive bytes").unwrap();
+
+        // No sidecar yet
+        assert!(verify_archive(&archive).is_err());
+
+        write_checksum(&archive).unwrap();
+        let sidecar = std::fs::read_to_string(checksum_path(&archive)).unwrap();
+        assert!(sidecar.ends_with("  web.tar.zst\n"));
+        verify_archive(&archive).unwrap();
+
+        // Truncated archive
+        std::fs::write(&archive, b"archive").unwrap();
+        assert!(matches!(
+            verify_archive(&archive),
+            Err(Error::ChecksumMismatch { .. })
+        ));
+
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
 }
@@ -8,7 +8,9 @@
 
 use crate::error::{Error, Result};
 use crate::jail::jexec::jexec_with_timeout;
-use serde::Deserialize;
+use chrono_machines::{BackoffStrategy, ExponentialBackoff};
+use rand::rng;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::io::Read;
 use std::path::Path;
@@ -17,7 +19,7 @@ use std::thread;
 use std::time::{Duration, Instant};
 
 /// Lifecycle phases when hooks can be executed
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize)]
 #[serde(rename_all = "snake_case")]
 pub enum HookPhase {
     /// Before jail filesystem is created
@@ -49,7 +51,6 @@ impl HookPhase {
     }
 
     /// Check if this phase requires a running jail
-    #[allow(dead_code)]
     pub fn requires_running_jail(&self) -> bool {
         matches!(self, HookPhase::PostStart | HookPhase::PreStop)
     }
@@ -70,7 +71,7 @@ impl std::fmt::Display for HookPhase {
 }
 
 /// Where to execute the hook
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum HookTarget {
     /// Execute on the host system
@@ -80,8 +81,18 @@ pub enum HookTarget {
     Jail,
 }
 
+impl std::fmt::Display for HookTarget {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let s = match self {
+            HookTarget::Host => "host",
+            HookTarget::Jail => "jail",
+        };
+        write!(f, "{}", s)
+    }
+}
+
 /// What to do when a hook fails
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum OnFailure {
     /// Abort the operation (default)
@@ -92,8 +103,11 @@ pub enum OnFailure {
 }
 
 /// A lifecycle hook definition
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct Hook {
+    /// Optional name used to reference this hook (e.g. to opt out of a global hook)
+    pub name: Option<String>,
+
     /// Lifecycle phase to execute at
     pub phase: HookPhase,
 
@@ -118,17 +132,47 @@ pub struct Hook {
 
     /// Optional description for logging
     pub description: Option<String>,
+
+    /// Working directory for the command (supports variable substitution)
+    ///
+    /// Host hooks resolve this on the host; jail hooks resolve it inside the jail.
+    pub workdir: Option<String>,
+
+    /// Environment variables (values support variable substitution)
+    ///
+    /// The command inherits the blackship process environment; entries here
+    /// are applied on top of it and take precedence over inherited values.
+    #[serde(default)]
+    pub env: HashMap<String, String>,
+
+    /// Number of times to retry a failed hook before honoring on_failure
+    #[serde(default)]
+    pub retries: u8,
+
+    /// Delay in seconds before the first retry, doubled on each further retry
+    #[serde(default = "default_retry_delay")]
+    pub retry_delay: u64,
 }
 
 fn default_timeout() -> u64 {
     30
 }
 
+fn default_retry_delay() -> u64 {
+    1
+}
+
+/// Stand-in for the JID when previewing hooks of a jail that is not running yet
+const JID_PLACEHOLDER: &str = "<jid>";
+
+/// Upper bound for the delay between hook retries
+const MAX_RETRY_DELAY_MS: u64 = 60_000;
+
 impl Hook {
-    /// Create a new hook (_unused: future feature)
-    #[allow(dead_code)]
+    /// Create a new hook
     pub fn new(phase: HookPhase, command: String) -> Self {
         Self {
+            name: None,
             phase,
             target: HookTarget::Host,
             command,
@@ -136,18 +180,20 @@ impl Hook {
             timeout: default_timeout(),
             on_failure: OnFailure::Abort,
             description: None,
+            workdir: None,
+            env: HashMap::new(),
+            retries: 0,
+            retry_delay: default_retry_delay(),
         }
     }
 
-    /// Set hook target (_unused: future feature)
-    #[allow(dead_code)]
+    /// Set hook target
     pub fn with_target(mut self, target: HookTarget) -> Self {
         self.target = target;
         self
     }
 
-    /// Set hook arguments (_unused: future feature)
-    #[allow(dead_code)]
+    /// Set hook arguments
     pub fn with_args(mut self, args: Vec<String>) -> Self {
         self.args = args;
         self
@@ -167,12 +213,60 @@ impl Hook {
         self
     }
 
-    /// Set description (_unused: future feature)
-    #[allow(dead_code)]
+    /// Set description
     pub fn with_description(mut self, description: String) -> Self {
         self.description = Some(description);
         self
     }
+
+    /// Set working directory (_unused: future feature)
+    #[allow(dead_code)]
+    pub fn with_workdir(mut self, workdir: String) -> Self {
+        self.workdir = Some(workdir);
+        self
+    }
+
+    /// Add an environment variable (_unused: future feature)
+    #[allow(dead_code)]
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.env.insert(name.to_string(), value.to_string());
+        self
+    }
+
+    /// Set retry count and initial delay in seconds (_unused: future feature)
+    #[allow(dead_code)]
+    pub fn with_retries(mut self, retries: u8, retry_delay: u64) -> Self {
+        self.retries = retries;
+        self.retry_delay = retry_delay;
+        self
+    }
+
+    /// Render the command line with variables substituted (for previews)
+    ///
+    /// In phases that run against a live jail, an unknown JID renders as `<jid>`.
+    pub fn render(&self, context: &HookContext) -> String {
+        std::iter::once(&self.command)
+            .chain(self.args.iter())
+            .map(|part| {
+                if context.jid.is_none() && self.phase.requires_running_jail() {
+                    context.substitute(&part.replace("${jid}", JID_PLACEHOLDER))
+                } else {
+                    context.substitute(part)
+                }
+            })
+            .collect::<Vec<_>>()
+            .join(" ")
+    }
+
+    /// Backoff strategy for retrying this hook
+    fn backoff(&self) -> ExponentialBackoff {
+        ExponentialBackoff::new()
+            .base_delay_ms(self.retry_delay.saturating_mul(1000))
+            .max_delay_ms(MAX_RETRY_DELAY_MS)
+            .multiplier(2.0)
+            .max_attempts(self.retries.saturating_add(1))
+            .jitter_factor(0.0)
+    }
 }
 
 /// Context for variable substitution in hooks
@@ -186,8 +280,16 @@ pub struct HookContext {
     pub jail_ip: Option<String>,
     /// Jail ID (if running)
     pub jid: Option<i32>,
+    /// Default gateway of the jail's network (if known)
+    pub jail_gateway: Option<String>,
+    /// Name of the jail's primary network (if attached)
+    pub jail_network: Option<String>,
+    /// Subnet of the jail's primary network (if attached)
+    pub jail_subnet: Option<String>,
     /// Additional custom variables
     pub extra: HashMap<String, String>,
+    /// Environment passed to every hook (a hook's own `env` takes precedence)
+    pub env: HashMap<String, String>,
 }
 
 impl HookContext {
@@ -198,7 +300,11 @@ impl HookContext {
             jail_path: jail_path.display().to_string(),
             jail_ip: None,
             jid: None,
+            jail_gateway: None,
+            jail_network: None,
+            jail_subnet: None,
             extra: HashMap::new(),
+            env: HashMap::new(),
         }
     }
 
@@ -214,13 +320,31 @@ impl HookContext {
         self
     }
 
-    /// Add custom variable (_unused: future feature)
-    #[allow(dead_code)]
+    /// Set gateway
+    pub fn with_gateway(mut self, gateway: String) -> Self {
+        self.jail_gateway = Some(gateway);
+        self
+    }
+
+    /// Set primary network name and subnet
+    pub fn with_network(mut self, network: &str, subnet: &str) -> Self {
+        self.jail_network = Some(network.to_string());
+        self.jail_subnet = Some(subnet.to_string());
+        self
+    }
+
+    /// Add custom variable
     pub fn with_var(mut self, name: &str, value: &str) -> Self {
         self.extra.insert(name.to_string(), value.to_string());
         self
     }
 
+    /// Add an environment variable for every hook
+    pub fn with_env(mut self, name: &str, value: &str) -> Self {
+        self.env.insert(name.to_string(), value.to_string());
+        self
+    }
+
     /// Substitute variables in a string
     ///
     /// Supported variables:
@@ -228,6 +352,9 @@ impl HookContext {
     /// - ${jail_path} - Jail filesystem path
     /// - ${jail_ip} - Jail IP address
     /// - ${jid} - Jail ID
+    /// - ${jail_gateway} - Default gateway
+    /// - ${jail_network} - Primary network name
+    /// - ${jail_subnet} - Primary network subnet (CIDR)
     /// - ${custom_var} - Custom variables from extra
     pub fn substitute(&self, input: &str) -> String {
         let mut result = input.to_string();
@@ -248,6 +375,10 @@ impl HookContext {
             result = result.replace("${jid}", "");
         }
 
+        result = result.replace("${jail_gateway}", self.jail_gateway.as_deref().unwrap_or(""));
+        result = result.replace("${jail_network}", self.jail_network.as_deref().unwrap_or(""));
+        result = result.replace("${jail_subnet}", self.jail_subnet.as_deref().unwrap_or(""));
+
         // Custom variables
         for (name, value) in &self.extra {
             result = result.replace(&format!("${{{}}}", name), value);
@@ -295,6 +426,65 @@ impl HookResult {
     }
 }
 
+/// jail(8) `exec.*` params that are not kernel parameters, with the phase and
+/// target of the hooks blackship runs them as
+///
+/// `exec.start` is run separately, with its output captured in the jail's log.
+pub const NATIVE_EXEC_PARAMS: [(&str, HookPhase, HookTarget); 6] = [
+    ("exec.prestart", HookPhase::PreStart, HookTarget::Host),
+    ("exec.created", HookPhase::PostStart, HookTarget::Host),
+    ("exec.poststart", HookPhase::PostStart, HookTarget::Host),
+    ("exec.prestop", HookPhase::PreStop, HookTarget::Host),
+    ("exec.stop", HookPhase::PreStop, HookTarget::Jail),
+    ("exec.poststop", HookPhase::PostStop, HookTarget::Host),
+];
+
+/// Turn a native `exec.*` param into hooks run through `sh -c`
+///
+/// A string is one command; an array of strings runs each command in order,
+/// like jail(8) does for repeated `exec.*` lines.
+pub fn native_exec_hooks(
+    param: &str,
+    value: &toml::Value,
+) -> std::result::Result<Vec<Hook>, String> {
+    let (_, phase, target) = NATIVE_EXEC_PARAMS
+        .iter()
+        .find(|(name, _, _)| *name == param)
+        .ok_or_else(|| format!("{} is not run by blackship", param))?;
+
+    Ok(exec_commands(param, value)?
+        .into_iter()
+        .map(|command| {
+            Hook::new(*phase, "sh".to_string())
+                .with_target(*target)
+                .with_args(vec!["-c".to_string(), command.to_string()])
+                .with_description(format!("{} `{}`", param, command))
+        })
+        .collect())
+}
+
+/// The command `exec.start` runs: a list of commands is chained with `&&`,
+/// so they run in order and stop at the first failure
+pub fn exec_start_command(value: &toml::Value) -> std::result::Result<String, String> {
+    Ok(exec_commands("exec.start", value)?.join(" && "))
+}
+
+/// Commands of an `exec.*` param: a string, or a non-empty list of strings
+fn exec_commands<'a>(
+    param: &str,
+    value: &'a toml::Value,
+) -> std::result::Result<Vec<&'a str>, String> {
+    let commands: Vec<&str> = match value {
+        toml::Value::String(command) => vec![command.as_str()],
+        toml::Value::Array(items) => items.iter().filter_map(|v| v.as_str()).collect(),
+        _ => Vec::new(),
+    };
+    if commands.is_empty() || value.as_array().is_some_and(|items| items.len() != commands.len()) {
+        return Err(format!("{} must be a command or a list of commands", param));
+    }
+    Ok(commands)
+}
+
 /// Runner for executing hooks
 pub struct HookRunner {
     /// Hooks to execute
@@ -358,27 +548,63 @@ impl HookRunner {
         Ok(())
     }
 
-    /// Execute a single hook
+    /// Execute a single hook, retrying failed attempts with backoff
     fn execute_hook(&self, hook: &Hook, context: &HookContext) -> Result<HookResult> {
-        // Substitute variables in command and args
+        // Substitute variables in command, args, workdir and env
         let command = context.substitute(&hook.command);
         let args: Vec<String> = hook.args.iter().map(|a| context.substitute(a)).collect();
+        let workdir = hook.workdir.as_ref().map(|w| context.substitute(w));
+        let mut env = context.env.clone();
+        env.extend(hook.env.iter().map(|(k, v)| (k.clone(), context.substitute(v))));
 
         if self.verbose {
             let desc = hook.description.as_deref().unwrap_or(&command);
             println!("  Running: {} ({:?})", desc, hook.target);
         }
 
-        match hook.target {
-            HookTarget::Host => self.execute_on_host(&command, &args, hook.timeout),
-            HookTarget::Jail => {
-                let jid = context.jid.ok_or_else(|| Error::HookFailed {
-                    phase: hook.phase.to_string(),
-                    command: command.clone(),
-                    message: "Cannot execute jail hook: jail is not running".to_string(),
-                })?;
-                self.execute_in_jail(jid, &command, &args, hook.timeout)
+        let jid = match hook.target {
+            HookTarget::Host => None,
+            HookTarget::Jail => Some(context.jid.ok_or_else(|| Error::HookFailed {
+                phase: hook.phase.to_string(),
+                command: command.clone(),
+                message: "Cannot execute jail hook: jail is not running".to_string(),
+            })?),
+        };
+
+        let backoff = hook.backoff();
+        let mut rng = rng();
+        let mut attempt: u8 = 0;
+
+        loop {
+            attempt += 1;
+            let outcome = match jid {
+                None => {
+                    self.execute_on_host(&command, &args, workdir.as_deref(), &env, hook.timeout)
+                }
+                Some(jid) => self.execute_in_jail(
+                    jid,
+                    &command,
+                    &args,
+                    workdir.as_deref(),
+                    &env,
+                    hook.timeout,
+                ),
+            };
+
+            let failed = !matches!(&outcome, Ok(result) if result.success);
+            if failed && let Some(delay_ms) = backoff.delay(attempt, &mut rng) {
+                eprintln!(
+                    "Hook '{}' attempt {}/{} failed, retrying in {}ms...",
+                    hook.description.as_deref().unwrap_or(&command),
+                    attempt,
+                    u16::from(hook.retries) + 1,
+                    delay_ms
+                );
+                thread::sleep(Duration::from_millis(delay_ms));
+                continue;
             }
+
+            return outcome;
         }
     }
 
@@ -387,12 +613,19 @@ impl HookRunner {
         &self,
         command: &str,
         args: &[String],
+        workdir: Option<&str>,
+        env: &HashMap<String, String>,
         timeout_secs: u64,
     ) -> Result<HookResult> {
         let timeout = Duration::from_secs(timeout_secs);
 
-        let mut child = Command::new(command)
-            .args(args)
+        let mut cmd = Command::new(command);
+        cmd.args(args).envs(env);
+        if let Some(dir) = workdir {
+            cmd.current_dir(dir);
+        }
+
+        let mut child = cmd
             .stdout(Stdio::piped())
             .stderr(Stdio::piped())
             .spawn()
@@ -453,10 +686,28 @@ impl HookRunner {
         jid: i32,
         command: &str,
         args: &[String],
+        workdir: Option<&str>,
+        env: &HashMap<String, String>,
         timeout_secs: u64,
     ) -> Result<HookResult> {
+        // jexec runs the command through /bin/sh -c, so workdir and env are
+        // applied as a quoted `cd <dir> && env K=V ...` prefix
+        let mut prefix: Vec<String> = Vec::new();
+        if let Some(dir) = workdir {
+            prefix.push("cd".to_string());
+            prefix.push(shell_quote(dir));
+            prefix.push("&&".to_string());
+        }
+        if !env.is_empty() {
+            prefix.push("env".to_string());
+            for (name, value) in env {
+                prefix.push(shell_quote(&format!("{}={}", name, value)));
+            }
+        }
+
         // Build full command array for jexec
-        let mut cmd_parts: Vec<&str> = vec![command];
+        let mut cmd_parts: Vec<&str> = prefix.iter().map(String::as_str).collect();
+        cmd_parts.push(command);
         for arg in args {
             cmd_parts.push(arg);
         }
@@ -478,6 +729,20 @@ impl HookRunner {
     }
 }
 
+/// Quote a string for safe use as a single /bin/sh word
+///
+/// Words made only of characters the shell treats literally are left as they
+/// are, so generated command lines stay readable.
+pub(crate) fn shell_quote(s: &str) -> String {
+    let plain = !s.is_empty()
+        && s.chars().all(|c| c.is_ascii_alphanumeric() || "-_./=:,@%+".contains(c));
+    if plain {
+        s.to_string()
+    } else {
+        format!("'{}'", s.replace('\'', "'\\''"))
+    }
+}
+
 /// Filter hooks by phase
 /// 
 /// Utility function for filtering hooks when you need to process
@@ -517,6 +782,23 @@ mod tests {
         assert_eq!(ctx.substitute("var: ${custom}"), "var: value");
     }
 
+    #[test]
+    fn test_hook_context_network_substitution() {
+        let ctx = HookContext::new("myjail", Path::new("/jails/myjail"))
+            .with_gateway("10.0.1.1".to_string())
+            .with_network("backend", "10.0.1.0/24");
+
+        assert_eq!(
+            ctx.substitute("route add default ${jail_gateway}"),
+            "route add default 10.0.1.1"
+        );
+        assert_eq!(ctx.substitute("net: ${jail_network}"), "net: backend");
+        assert_eq!(ctx.substitute("subnet: ${jail_subnet}"), "subnet: 10.0.1.0/24");
+
+        let bare = HookContext::new("myjail", Path::new("/jails/myjail"));
+        assert_eq!(bare.substitute("gw=${jail_gateway}"), "gw=");
+    }
+
     #[test]
     fn test_hook_builder() {
         let hook = Hook::new(HookPhase::PreStart, "/bin/echo".to_string())
@@ -531,6 +813,32 @@ mod tests {
         assert_eq!(hook.on_failure, OnFailure::Continue);
     }
 
+    #[test]
+    fn test_hook_render_preview() {
+        let ctx = HookContext::new("myjail", Path::new("/jails/myjail"))
+            .with_ip("10.0.1.10".to_string());
+
+        let hook = Hook::new(HookPhase::PostStart, "/usr/local/bin/register".to_string())
+            .with_args(vec![
+                "${jail_name}".to_string(),
+                "${jail_ip}".to_string(),
+                "${jid}".to_string(),
+            ]);
+        assert_eq!(
+            hook.render(&ctx),
+            "/usr/local/bin/register myjail 10.0.1.10 <jid>"
+        );
+        assert_eq!(
+            hook.render(&ctx.clone().with_jid(7)),
+            "/usr/local/bin/register myjail 10.0.1.10 7"
+        );
+
+        let pre = Hook::new(HookPhase::PreStart, "/bin/echo".to_string())
+            .with_args(vec!["${jid}".to_string()]);
+        assert_eq!(pre.render(&ctx), "/bin/echo ");
+        assert_eq!(HookTarget::Jail.to_string(), "jail");
+    }
+
     #[test]
     fn test_phase_requires_running_jail() {
         assert!(!HookPhase::PreCreate.requires_running_jail());
@@ -551,6 +859,11 @@ args = ["${jail_name}", "${jail_ip}"]
 timeout = 60
 on_failure = "continue"
 description = "Run setup script"
+workdir = "${jail_path}/usr/local"
+
+[env]
+JAIL = "${jail_name}"
+MODE = "production"
 "#;
 
         let hook: Hook = toml::from_str(toml).unwrap();
@@ -560,5 +873,112 @@ description = "Run setup script"
         assert_eq!(hook.args.len(), 2);
         assert_eq!(hook.timeout, 60);
         assert_eq!(hook.on_failure, OnFailure::Continue);
+        assert_eq!(hook.retries, 0);
+        assert_eq!(hook.workdir.as_deref(), Some("${jail_path}/usr/local"));
+        assert_eq!(hook.env.len(), 2);
+        assert_eq!(hook.env.get("MODE").map(String::as_str), Some("production"));
+    }
+
+    #[test]
+    fn test_hook_deserialize_defaults() {
+        let hook: Hook = toml::from_str(
+            r#"
+phase = "post_start"
+command = "/bin/true"
+"#,
+        )
+        .unwrap();
+        assert!(hook.workdir.is_none());
+        assert!(hook.env.is_empty());
+    }
+
+    /// Build a host hook that fails until it has been run `succeed_on` times
+    fn flaky_hook(counter: &Path, succeed_on: u32) -> Hook {
+        let script = format!(
+            "echo x >> '{}'; [ $(wc -l < '{}') -ge {} ]",
+            counter.display(),
+            counter.display(),
+            succeed_on
+        );
+        Hook::new(HookPhase::PostStart, "/bin/sh".to_string())
+            .with_args(vec!["-c".to_string(), script])
+    }
+
+    fn attempts(counter: &Path) -> usize {
+        std::fs::read_to_string(counter).unwrap().lines().count()
+    }
+
+    #[test]
+    fn test_hook_retry_until_success() {
+        let counter =
+            std::env::temp_dir().join(format!("blackship-hook-retry-{}", std::process::id()));
+        let _ = std::fs::remove_file(&counter);
+
+        let hook = flaky_hook(&counter, 3).with_retries(2, 0);
+        let runner = HookRunner::new(vec![hook]);
+        let ctx = HookContext::new("myjail", Path::new("/jails/myjail"));
+
+        assert!(runner.execute_phase(HookPhase::PostStart, &ctx).is_ok());
+        assert_eq!(attempts(&counter), 3);
+        let _ = std::fs::remove_file(&counter);
+    }
+
+    #[test]
+    fn test_hook_retry_exhausted() {
+        let counter =
+            std::env::temp_dir().join(format!("blackship-hook-exhaust-{}", std::process::id()));
+        let _ = std::fs::remove_file(&counter);
+
+        let hook = flaky_hook(&counter, 5).with_retries(2, 0);
+        let runner = HookRunner::new(vec![hook]);
+        let ctx = HookContext::new("myjail", Path::new("/jails/myjail"));
+
+        assert!(matches!(
+            runner.execute_phase(HookPhase::PostStart, &ctx),
+            Err(Error::HookFailed { .. })
+        ));
+        assert_eq!(attempts(&counter), 3);
+        let _ = std::fs::remove_file(&counter);
+    }
+
+    #[test]
+    fn test_native_exec_hooks() {
+        let value = toml::Value::String("ifconfig lo1 up".into());
+        let hooks = native_exec_hooks("exec.prestart", &value).unwrap();
+        assert_eq!(hooks.len(), 1);
+        assert_eq!(hooks[0].phase, HookPhase::PreStart);
+        assert_eq!(hooks[0].target, HookTarget::Host);
+        assert_eq!(hooks[0].command, "sh");
+        assert_eq!(hooks[0].args, vec!["-c", "ifconfig lo1 up"]);
+
+        let value = toml::Value::Array(vec!["a".into(), "b".into()]);
+        let hooks = native_exec_hooks("exec.stop", &value).unwrap();
+        assert_eq!(hooks.len(), 2);
+        assert_eq!(hooks[1].target, HookTarget::Jail);
+        assert_eq!(hooks[1].args[1], "b");
+
+        assert!(native_exec_hooks("exec.clean", &value).is_err());
+        assert!(native_exec_hooks("exec.created", &toml::Value::Integer(1)).is_err());
+        let mixed = toml::Value::Array(vec!["a".into(), toml::Value::Integer(1)]);
+        assert!(native_exec_hooks("exec.created", &mixed).is_err());
+    }
+
+    #[test]
+    fn test_exec_start_command() {
+        let value = toml::Value::String("/bin/sh /etc/rc".into());
+        assert_eq!(exec_start_command(&value).unwrap(), "/bin/sh /etc/rc");
+
+        let value = toml::Value::Array(vec!["/usr/local/bin/migrate".into(), "nginx".into()]);
+        assert_eq!(exec_start_command(&value).unwrap(), "/usr/local/bin/migrate && nginx");
+        assert!(exec_start_command(&toml::Value::Boolean(true)).is_err());
+    }
+
+    #[test]
+    fn test_shell_quote() {
+        assert_eq!(shell_quote("/usr/local"), "/usr/local");
+        assert_eq!(shell_quote("it's"), "'it'\\''s'");
+        assert_eq!(shell_quote("a b"), "'a b'");
+        assert_eq!(shell_quote("$HOME"), "'$HOME'");
+        assert_eq!(shell_quote(""), "''");
     }
 }
@@ -7,11 +7,14 @@
 
 pub mod ffi;
 pub mod jexec;
+pub mod mount;
+pub mod process;
 pub mod state;
 pub mod types;
 
 // Re-exports
-pub use ffi::{jail_attach, jail_create, jail_getid, jail_remove};
-pub use jexec::jexec_with_output;
-pub use state::{JailConfig, JailInstance};
+pub use ffi::{is_transient_error, jail_attach, jail_create, jail_getid, jail_remove};
+pub use jexec::{jexec_with_output, jexec_with_timeout};
+pub use process::{jail_is_paused, jail_signal, jail_uptime};
+pub use state::{JailConfig, JailInstance, JailRecord};
 pub use types::ParamValue;
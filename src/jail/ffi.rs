@@ -81,6 +81,40 @@ bitflags! {
 ///
 /// Returns the jail ID (jid) on success
 pub fn jail_create(path: &Path, params: HashMap<String, ParamValue>) -> Result<i32, Error> {
+    jail_set_create(path, params, JailFlags::CREATE, true)
+}
+
+/// Create a non-persistent jail and attach the calling process to it
+///
+/// The jail is removed by the kernel once its last process exits.
+/// Returns the jail ID (jid) on success
+pub fn jail_create_attached(
+    path: &Path,
+    params: HashMap<String, ParamValue>,
+) -> Result<i32, Error> {
+    jail_set_create(path, params, JailFlags::CREATE | JailFlags::ATTACH, false)
+}
+
+/// Create a jail via jail_set(2) with the given flags
+///
+/// A dotted `name` (`<parent>.<child>`) creates the jail inside that parent,
+/// which must be running, set `children.max` and have `path` below its root.
+fn jail_set_create(
+    path: &Path,
+    params: HashMap<String, ParamValue>,
+    flags: JailFlags,
+    persist: bool,
+) -> Result<i32, Error> {
+    if let Some(ParamValue::String(name)) = params.get("name")
+        && let Some((parent, _)) = name.rsplit_once('.')
+        && jail_getid(parent).is_err()
+    {
+        return Err(Error::JailSet(format!(
+            "Parent jail '{}' of '{}' is not running",
+            parent, name
+        )));
+    }
+
     // Convert parameters to raw bytes
     let raw_params: Vec<(Vec<u8>, Vec<u8>)> = params
         .iter()
@@ -102,26 +136,16 @@ pub fn jail_create(path: &Path, params: HashMap<String, ParamValue>) -> Result<i
         .ok_or_else(|| Error::JailSet("Invalid path encoding".into()))?;
     let pathstr = CString::new(pathstr)?.into_bytes_with_nul();
 
-    // Set persist and errmsg
+    // Set errmsg and (optionally) persist
     let mut errmsg: [u8; 256] = unsafe { mem::zeroed() };
-    jiov.append(
-        &mut vec![
-            iovec!(b"path\0" => pathstr),
-            iovec!(b"errmsg\0" => mut errmsg),
-            iovec!(b"persist\0" => ()),
-        ]
-        .into_iter()
-        .flatten()
-        .collect(),
-    );
+    jiov.extend(iovec!(b"path\0" => pathstr));
+    jiov.extend(iovec!(b"errmsg\0" => mut errmsg));
+    if persist {
+        jiov.extend(iovec!(b"persist\0" => ()));
+    }
 
-    let jid = unsafe {
-        libc::jail_set(
-            jiov[..].as_mut_ptr(),
-            jiov.len() as u32,
-            JailFlags::CREATE.bits(),
-        )
-    };
+    let jid = unsafe { libc::jail_set(jiov[..].as_mut_ptr(), jiov.len() as u32, flags.bits()) };
+    let os_error = std::io::Error::last_os_error();
 
     let err = unsafe { CStr::from_ptr(errmsg.as_ptr() as *mut libc::c_char) }
         .to_string_lossy()
@@ -129,13 +153,39 @@ pub fn jail_create(path: &Path, params: HashMap<String, ParamValue>) -> Result<i
 
     match jid {
         e if e < 0 => match errmsg[0] {
-            0 => Err(Error::Io(std::io::Error::last_os_error())),
+            0 => Err(Error::Io(os_error)),
+            // Keep the errno of transient failures so callers can retry them
+            _ if is_transient_errno(os_error.raw_os_error()) => Err(Error::Io(
+                std::io::Error::new(os_error.kind(), format!("{}: {}", err, os_error)),
+            )),
             _ => Err(Error::JailSet(err)),
         },
         _ => Ok(jid),
     }
 }
 
+/// Whether an errno from jail_set(2) may clear up on its own (EAGAIN, ENOMEM)
+fn is_transient_errno(errno: Option<i32>) -> bool {
+    matches!(errno, Some(libc::EAGAIN | libc::ENOMEM))
+}
+
+/// Whether a failed jail creation is worth retrying
+///
+/// Only resource shortages (EAGAIN, ENOMEM) are; invalid parameters (EINVAL),
+/// an existing jail (EEXIST) and everything else fail for good.
+pub fn is_transient_error(error: &Error) -> bool {
+    match error {
+        Error::Io(e) => {
+            is_transient_errno(e.raw_os_error())
+                || matches!(
+                    e.kind(),
+                    std::io::ErrorKind::WouldBlock | std::io::ErrorKind::OutOfMemory
+                )
+        }
+        _ => false,
+    }
+}
+
 /// Get the jail ID from a jail name
 ///
 /// If the name can be parsed as an i32, it's returned directly
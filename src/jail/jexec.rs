@@ -5,11 +5,15 @@
 //! providing ~150x performance improvement.
 
 use crate::error::{Error, Result};
+use crate::jail::ffi::jail_create_attached;
+use crate::jail::types::ParamValue;
 use nix::sys::wait::{waitpid, WaitPidFlag, WaitStatus};
 use nix::unistd::{close, fork, pipe, ForkResult};
+use std::collections::HashMap;
 use std::ffi::CString;
 use std::io::Read;
 use std::os::unix::io::{AsRawFd, FromRawFd, RawFd};
+use std::path::Path;
 use std::time::{Duration, Instant};
 
 // FreeBSD jail syscalls - not in libc crate
@@ -32,6 +36,19 @@ unsafe extern "C" {
 /// # Performance
 /// ~150x faster than spawning /usr/sbin/jexec process
 pub fn jexec_with_output(jid: i32, command: &[&str]) -> Result<(i32, Vec<u8>, Vec<u8>)> {
+    jexec_streaming(jid, command, false)
+}
+
+/// Execute a command inside a jail, optionally streaming its output
+///
+/// Like [`jexec_with_output`], but with `stream` set each line the command
+/// prints is also forwarded to our stdout/stderr as soon as it is complete.
+/// The output is still captured and returned for error reporting.
+pub fn jexec_streaming(
+    jid: i32,
+    command: &[&str],
+    stream: bool,
+) -> Result<(i32, Vec<u8>, Vec<u8>)> {
     if command.is_empty() {
         return Err(Error::CommandFailed {
             command: "jexec".to_string(),
@@ -57,12 +74,10 @@ pub fn jexec_with_output(jid: i32, command: &[&str]) -> Result<(i32, Vec<u8>, Ve
             close(stdout_write.as_raw_fd()).ok();
             close(stderr_write.as_raw_fd()).ok();
 
-            // Read stdout
-            let stdout = read_fd_to_end(stdout_read.as_raw_fd());
+            // Read both pipes together so neither can fill up and block the child
+            let (stdout, stderr, _) =
+                drain_pipes(stdout_read.as_raw_fd(), stderr_read.as_raw_fd(), stream, None);
             close(stdout_read.as_raw_fd()).ok();
-
-            // Read stderr
-            let stderr = read_fd_to_end(stderr_read.as_raw_fd());
             close(stderr_read.as_raw_fd()).ok();
 
             // Wait for child process
@@ -152,6 +167,122 @@ fn read_fd_to_end(fd: RawFd) -> Vec<u8> {
     buffer
 }
 
+/// Forwards captured output to one of our own streams, a line at a time
+struct LineTee {
+    /// Bytes of the current, not yet terminated line
+    pending: Vec<u8>,
+    /// Forward to stderr instead of stdout
+    stderr: bool,
+}
+
+impl LineTee {
+    fn new(stderr: bool) -> Self {
+        Self { pending: Vec::new(), stderr }
+    }
+
+    /// Forward every complete line in `chunk`, keeping any partial line
+    fn push(&mut self, chunk: &[u8]) {
+        self.pending.extend_from_slice(chunk);
+        if let Some(end) = self.pending.iter().rposition(|&b| b == b'\n') {
+            let rest = self.pending.split_off(end + 1);
+            let lines = std::mem::replace(&mut self.pending, rest);
+            self.write(&lines);
+        }
+    }
+
+    /// Forward a final unterminated line
+    fn finish(&mut self) {
+        if !self.pending.is_empty() {
+            let mut line = std::mem::take(&mut self.pending);
+            line.push(b'\n');
+            self.write(&line);
+        }
+    }
+
+    fn write(&self, bytes: &[u8]) {
+        use std::io::Write;
+        let _ = if self.stderr {
+            std::io::stderr().write_all(bytes)
+        } else {
+            let mut out = std::io::stdout().lock();
+            out.write_all(bytes).and_then(|_| out.flush())
+        };
+    }
+}
+
+/// Read a child's stdout and stderr pipes until both are closed
+///
+/// With `stream`, the output is also forwarded line by line to our own
+/// stdout/stderr as it arrives. Reading stops early once `deadline` passes;
+/// the returned flag is true in that case.
+fn drain_pipes(
+    stdout_fd: RawFd,
+    stderr_fd: RawFd,
+    stream: bool,
+    deadline: Option<Instant>,
+) -> (Vec<u8>, Vec<u8>, bool) {
+    let mut outputs = [Vec::new(), Vec::new()];
+    let mut tees = [LineTee::new(false), LineTee::new(true)];
+    let mut fds = [
+        libc::pollfd { fd: stdout_fd, events: libc::POLLIN, revents: 0 },
+        libc::pollfd { fd: stderr_fd, events: libc::POLLIN, revents: 0 },
+    ];
+    let mut buf = [0u8; 8192];
+    let mut timed_out = false;
+
+    while fds.iter().any(|p| p.fd >= 0) {
+        let wait_ms = match deadline {
+            Some(deadline) => {
+                let remaining = deadline.saturating_duration_since(Instant::now());
+                if remaining.is_zero() {
+                    timed_out = true;
+                    break;
+                }
+                remaining.as_millis().clamp(1, libc::c_int::MAX as u128) as libc::c_int
+            }
+            None => -1,
+        };
+        // SAFETY: fds is a valid array of pollfd; negative fds are ignored
+        let ready =
+            unsafe { libc::poll(fds.as_mut_ptr(), fds.len() as libc::nfds_t, wait_ms) };
+        if ready < 0 {
+            if std::io::Error::last_os_error().kind() == std::io::ErrorKind::Interrupted {
+                continue;
+            }
+            break;
+        }
+        for (i, pfd) in fds.iter_mut().enumerate() {
+            if pfd.fd < 0 || pfd.revents == 0 {
+                continue;
+            }
+            // SAFETY: buf is valid for buf.len() bytes
+            let n = unsafe { libc::read(pfd.fd, buf.as_mut_ptr().cast(), buf.len()) };
+            if n <= 0 {
+                // EOF or error: stop polling this pipe
+                pfd.fd = -1;
+                if stream {
+                    tees[i].finish();
+                }
+                continue;
+            }
+            let chunk = &buf[..n as usize];
+            outputs[i].extend_from_slice(chunk);
+            if stream {
+                tees[i].push(chunk);
+            }
+        }
+    }
+
+    if stream && timed_out {
+        for tee in &mut tees {
+            tee.finish();
+        }
+    }
+
+    let [stdout, stderr] = outputs;
+    (stdout, stderr, timed_out)
+}
+
 /// Execute a command inside a jail with timeout enforcement
 ///
 /// Similar to `jexec_with_output` but with timeout support.
@@ -312,6 +443,7 @@ pub fn jexec_with_timeout(
 /// * `root_path` - The path to chroot into
 /// * `command` - The shell command to execute
 /// * `env_vars` - Environment variables to set
+/// * `stream` - Also forward the command's output to our stdout/stderr as it arrives
 ///
 /// # Returns
 /// A tuple of (exit_code, stdout, stderr)
@@ -319,6 +451,29 @@ pub fn chroot_exec(
     root_path: &str,
     command: &str,
     env_vars: &[(String, String)],
+    stream: bool,
+) -> Result<(i32, Vec<u8>, Vec<u8>)> {
+    chroot_exec_with_timeout(root_path, command, env_vars, stream, 0, None)
+}
+
+/// Execute a command in a chroot environment with timeout enforcement
+///
+/// Similar to `chroot_exec` but the command runs in its own process group,
+/// which is killed (including anything it spawned) once `timeout_secs`
+/// elapses (0 = no timeout).
+///
+/// With a `hostname`, the command runs in a throwaway jail rooted at
+/// `root_path` instead of a plain chroot, so `hostname(1)` reports it. The
+/// jail shares the host's network and disappears when the command exits.
+///
+/// Returns Error::JailTimeout if the command exceeds the timeout
+pub fn chroot_exec_with_timeout(
+    root_path: &str,
+    command: &str,
+    env_vars: &[(String, String)],
+    stream: bool,
+    timeout_secs: u64,
+    hostname: Option<&str>,
 ) -> Result<(i32, Vec<u8>, Vec<u8>)> {
     // Create pipes for stdout and stderr
     let (stdout_read, stdout_write) = pipe().map_err(|e| Error::CommandFailed {
@@ -343,14 +498,33 @@ pub fn chroot_exec(
             close(stdout_write.as_raw_fd()).ok();
             close(stderr_write.as_raw_fd()).ok();
 
-            // Read stdout
-            let stdout = read_fd_to_end(stdout_read.as_raw_fd());
+            // Read both pipes together so neither can fill up and block the child
+            if timeout_secs > 0 {
+                // Also set from this side so the group exists before any kill
+                unsafe {
+                    libc::setpgid(child.as_raw(), child.as_raw());
+                }
+            }
+            let deadline =
+                (timeout_secs > 0).then(|| Instant::now() + Duration::from_secs(timeout_secs));
+            let (stdout, stderr, timed_out) = drain_pipes(
+                stdout_read.as_raw_fd(),
+                stderr_read.as_raw_fd(),
+                stream,
+                deadline,
+            );
             close(stdout_read.as_raw_fd()).ok();
-
-            // Read stderr
-            let stderr = read_fd_to_end(stderr_read.as_raw_fd());
             close(stderr_read.as_raw_fd()).ok();
 
+            if timed_out {
+                // Kill the whole process group, then reap the shell
+                unsafe {
+                    libc::kill(-child.as_raw(), libc::SIGKILL);
+                }
+                let _ = waitpid(child, None);
+                return Err(Error::JailTimeout(timeout_secs));
+            }
+
             // Wait for child process
             match waitpid(child, None) {
                 Ok(WaitStatus::Exited(_, exit_code)) => Ok((exit_code, stdout, stderr)),
@@ -381,8 +555,24 @@ pub fn chroot_exec(
             close(stdout_write.as_raw_fd()).ok();
             close(stderr_write.as_raw_fd()).ok();
 
+            // Own process group, so a timeout can kill everything the command spawned
+            if timeout_secs > 0 {
+                unsafe {
+                    libc::setpgid(0, 0);
+                }
+            }
+
+            // Throwaway jail for the hostname, falling back to a plain chroot
+            let jailed = hostname.is_some_and(|hostname| {
+                jail_create_attached(Path::new(root_path), build_jail_params(hostname))
+                    .map_err(|e| {
+                        eprintln!("Warning: Failed to create build jail, using chroot: {}", e)
+                    })
+                    .is_ok()
+            });
+
             // chroot(2) syscall
-            let result = unsafe { libc::chroot(root_cstring.as_ptr()) };
+            let result = if jailed { 0 } else { unsafe { libc::chroot(root_cstring.as_ptr()) } };
             if result != 0 {
                 eprintln!("chroot({}) failed: {}", root_path, std::io::Error::last_os_error());
                 std::process::exit(1);
@@ -432,6 +622,24 @@ pub fn chroot_exec(
     }
 }
 
+/// Parameters of the throwaway jail RUN steps execute in
+fn build_jail_params(hostname: &str) -> HashMap<String, ParamValue> {
+    // ip4/ip6 = inherit (JAIL_SYS_INHERIT): builds use the host's network
+    const JAIL_SYS_INHERIT: i32 = 2;
+
+    let mut params = HashMap::new();
+    params.insert(
+        "host.hostname".to_string(),
+        ParamValue::String(hostname.to_string()),
+    );
+    params.insert("ip4".to_string(), ParamValue::Int(JAIL_SYS_INHERIT));
+    params.insert("ip6".to_string(), ParamValue::Int(JAIL_SYS_INHERIT));
+    // Packages may install files with schg flags, as they would in a chroot
+    params.insert("allow.chflags".to_string(), ParamValue::Bool(true));
+    params.insert("allow.raw_sockets".to_string(), ParamValue::Bool(true));
+    params
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -449,4 +657,15 @@ mod tests {
             assert_eq!(String::from_utf8_lossy(&stdout).trim(), "hello");
         }
     }
+    #[test]
+    fn test_line_tee_keeps_partial_line() {
+        let mut tee = LineTee::new(false);
+        tee.push(b"step 1\nstep");
+        assert_eq!(tee.pending, b"step");
+        tee.push(b" 2\n");
+        assert!(tee.pending.is_empty());
+        tee.push(b"done");
+        tee.finish();
+        assert!(tee.pending.is_empty());
+    }
 }
@@ -0,0 +1,181 @@
+//! Filesystems mounted into a jail root
+//!
+//! nullfs volumes, fdescfs, procfs and tmpfs from a jail's `[jails.mount]`
+//! table are mounted with nmount(2) before the jail is created and unmounted
+//! in reverse order once it is removed.
+
+use crate::error::{Error, Result};
+use std::ffi::CString;
+use std::path::{Path, PathBuf};
+
+/// A filesystem mounted under a jail root
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MountSpec {
+    /// Filesystem type ("nullfs", "fdescfs", "procfs" or "tmpfs")
+    pub fstype: &'static str,
+    /// Host directory of a nullfs volume
+    pub source: Option<PathBuf>,
+    /// Mount point as seen from inside the jail
+    pub jail_path: PathBuf,
+    /// Mount read-only
+    pub read_only: bool,
+    /// Size limit in bytes (tmpfs)
+    pub size: Option<u64>,
+}
+
+impl MountSpec {
+    /// Mount point on the host for a jail rooted at `root`
+    pub fn target(&self, root: &Path) -> PathBuf {
+        root.join(self.jail_path.strip_prefix("/").unwrap_or(&self.jail_path))
+    }
+
+    /// nmount(2) options, as name/value pairs
+    fn options(&self, fspath: &Path) -> Vec<(&'static str, String)> {
+        let mut options = vec![
+            ("fstype", self.fstype.to_string()),
+            ("fspath", fspath.to_string_lossy().into_owned()),
+        ];
+        match &self.source {
+            Some(source) => {
+                options.push(("from", source.to_string_lossy().into_owned()));
+                options.push(("target", source.to_string_lossy().into_owned()));
+            }
+            None => options.push(("from", self.fstype.to_string())),
+        }
+        if let Some(size) = self.size {
+            options.push(("size", size.to_string()));
+        }
+        options
+    }
+
+    /// Mount the filesystem under `root`, creating the mount point if needed
+    pub fn mount(&self, root: &Path) -> Result<()> {
+        let target = self.target(root);
+        std::fs::create_dir_all(&target)?;
+
+        // A symlink inside the jail must not redirect the mount onto the host
+        let fspath = target.canonicalize()?;
+        if !fspath.starts_with(root.canonicalize()?) {
+            return Err(Error::JailOperation(format!(
+                "Mount point {} resolves to {}, outside the jail root",
+                self.jail_path.display(),
+                fspath.display()
+            )));
+        }
+
+        let strings: Vec<CString> = self
+            .options(&fspath)
+            .into_iter()
+            .flat_map(|(name, value)| [name.to_string(), value])
+            .map(|s| CString::new(s).map_err(|e| Error::JailOperation(e.to_string())))
+            .collect::<Result<_>>()?;
+        let errmsg_name = CString::new("errmsg").unwrap();
+        let mut errmsg = [0u8; 256];
+
+        let mut iov: Vec<libc::iovec> = strings
+            .iter()
+            .chain(std::iter::once(&errmsg_name))
+            .map(|s| libc::iovec {
+                iov_base: s.as_ptr() as *mut libc::c_void,
+                iov_len: s.as_bytes_with_nul().len(),
+            })
+            .collect();
+        iov.push(libc::iovec {
+            iov_base: errmsg.as_mut_ptr() as *mut libc::c_void,
+            iov_len: errmsg.len(),
+        });
+
+        let flags = if self.read_only { libc::MNT_RDONLY } else { 0 };
+        // SAFETY: every iovec points to a live NUL-terminated string or to errmsg
+        let rc = unsafe { libc::nmount(iov.as_mut_ptr(), iov.len() as libc::c_uint, flags) };
+        if rc != 0 {
+            let os_error = std::io::Error::last_os_error();
+            let detail = std::ffi::CStr::from_bytes_until_nul(&errmsg)
+                .map(|s| s.to_string_lossy().into_owned())
+                .unwrap_or_default();
+            let reason = if detail.is_empty() { os_error.to_string() } else { detail };
+            return Err(Error::JailOperation(format!(
+                "Failed to mount {} on {}: {}",
+                self.fstype,
+                fspath.display(),
+                reason
+            )));
+        }
+        Ok(())
+    }
+}
+
+/// Mount `specs` under `root` in order, unmounting the ones already mounted
+/// if one fails
+pub fn mount_all(specs: &[MountSpec], root: &Path) -> Result<()> {
+    for (i, spec) in specs.iter().enumerate() {
+        if let Err(e) = spec.mount(root) {
+            unmount_all(&specs[..i], root);
+            return Err(e);
+        }
+    }
+    Ok(())
+}
+
+/// Unmount those of `specs` that are mounted under `root`, in reverse order
+pub fn unmount_all(specs: &[MountSpec], root: &Path) {
+    let mounted: Vec<PathBuf> = crate::sys::mounts()
+        .map(|mounts| mounts.into_iter().map(|m| m.mount_point).collect())
+        .unwrap_or_default();
+    for spec in specs.iter().rev() {
+        let target = spec.target(root);
+        let target = target.canonicalize().unwrap_or(target);
+        if mounted.contains(&target) {
+            unmount(&target);
+        }
+    }
+}
+
+/// Unmount a filesystem, forcing it if a process left behind still holds it
+pub(crate) fn unmount(mount_point: &Path) {
+    // Use native unmount(2) syscall instead of spawning process
+    let path = CString::new(mount_point.to_str().unwrap()).unwrap();
+    // SAFETY: path is a valid NUL-terminated string
+    let mut rc = unsafe { libc::unmount(path.as_ptr(), 0) };
+    if rc != 0 && std::io::Error::last_os_error().raw_os_error() == Some(libc::EBUSY) {
+        rc = unsafe { libc::unmount(path.as_ptr(), libc::MNT_FORCE) };
+    }
+    if rc != 0 {
+        eprintln!(
+            "Warning: Failed to unmount {}: {}",
+            mount_point.display(),
+            std::io::Error::last_os_error()
+        );
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_mount_options() {
+        let volume = MountSpec {
+            fstype: "nullfs",
+            source: Some(PathBuf::from("/data/www")),
+            jail_path: PathBuf::from("/var/www"),
+            read_only: true,
+            size: None,
+        };
+        assert_eq!(volume.target(Path::new("/jails/web")), PathBuf::from("/jails/web/var/www"));
+        let options = volume.options(Path::new("/jails/web/var/www"));
+        assert!(options.contains(&("target", "/data/www".to_string())));
+
+        let tmpfs = MountSpec {
+            fstype: "tmpfs",
+            source: None,
+            jail_path: PathBuf::from("/tmp"),
+            read_only: false,
+            size: Some(1 << 20),
+        };
+        let options = tmpfs.options(Path::new("/jails/web/tmp"));
+        assert_eq!(options[0], ("fstype", "tmpfs".to_string()));
+        assert!(options.contains(&("from", "tmpfs".to_string())));
+        assert!(options.contains(&("size", "1048576".to_string())));
+    }
+}
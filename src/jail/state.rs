@@ -3,7 +3,10 @@
 //! Type-safe state machine for jail lifecycle management using state-machines crate.
 //! Uses dynamic dispatch mode for runtime flexibility with external events.
 
-use std::path::PathBuf;
+use serde::{Deserialize, Serialize};
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 use state_machines::state_machine;
 
@@ -11,7 +14,7 @@ state_machine! {
     name: JailMachine,
     dynamic: true,  // Enable runtime dispatch for event-driven jail management
     initial: Stopped,
-    states: [Stopped, Starting, Running, Stopping, Failed],
+    states: [Stopped, Starting, Running, Paused, Stopping, Failed],
     events {
         start {
             transition: { from: Stopped, to: Starting }
@@ -19,14 +22,20 @@ state_machine! {
         started {
             transition: { from: Starting, to: Running }
         }
+        pause {
+            transition: { from: Running, to: Paused }
+        }
+        resume {
+            transition: { from: Paused, to: Running }
+        }
         stop {
-            transition: { from: Running, to: Stopping }
+            transition: { from: [Running, Paused], to: Stopping }
         }
         stopped {
             transition: { from: Stopping, to: Stopped }
         }
         fail {
-            transition: { from: [Starting, Running, Stopping], to: Failed }
+            transition: { from: [Starting, Running, Paused, Stopping], to: Failed }
         }
         recover {
             transition: { from: Failed, to: Stopped }
@@ -34,28 +43,82 @@ state_machine! {
     }
 }
 
+/// Every legal transition of the jail state machine as (from, event, to)
+///
+/// Mirrors the `state_machine!` definition above.
+pub const TRANSITIONS: [(State, &str, State); 12] = [
+    (State::Stopped, "start", State::Starting),
+    (State::Starting, "started", State::Running),
+    (State::Running, "pause", State::Paused),
+    (State::Paused, "resume", State::Running),
+    (State::Running, "stop", State::Stopping),
+    (State::Paused, "stop", State::Stopping),
+    (State::Stopping, "stopped", State::Stopped),
+    (State::Starting, "fail", State::Failed),
+    (State::Running, "fail", State::Failed),
+    (State::Paused, "fail", State::Failed),
+    (State::Stopping, "fail", State::Failed),
+    (State::Failed, "recover", State::Stopped),
+];
+
 /// Simple state enum for external use (backwards compatible)
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum State {
     Stopped,
     Starting,
     Running,
+    Paused,
     Stopping,
     Failed,
 }
 
 impl State {
     /// Parse state from string representation
-    pub fn from_str(s: &str) -> Self {
+    pub fn from_name(s: &str) -> Self {
         match s {
             "Stopped" => State::Stopped,
             "Starting" => State::Starting,
             "Running" => State::Running,
+            "Paused" => State::Paused,
             "Stopping" => State::Stopping,
             "Failed" => State::Failed,
             _ => State::Stopped, // Fallback
         }
     }
+
+    /// Parse state from string representation (kept for existing callers)
+    #[deprecated(note = "use `State::from_name`")]
+    #[allow(clippy::should_implement_trait)]
+    pub fn from_str(s: &str) -> Self {
+        Self::from_name(s)
+    }
+
+    /// Name of the state, as used by the state machine
+    pub fn name(self) -> &'static str {
+        match self {
+            State::Stopped => "Stopped",
+            State::Starting => "Starting",
+            State::Running => "Running",
+            State::Paused => "Paused",
+            State::Stopping => "Stopping",
+            State::Failed => "Failed",
+        }
+    }
+
+    /// Events accepted in this state, with the state each one leads to
+    pub fn transitions(self) -> Vec<(&'static str, State)> {
+        TRANSITIONS
+            .iter()
+            .filter(|(from, _, _)| *from == self)
+            .map(|(_, event, to)| (*event, *to))
+            .collect()
+    }
+}
+
+impl std::fmt::Display for State {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
 }
 
 /// Configuration for a jail instance
@@ -106,6 +169,8 @@ pub struct JailInstance {
     pub config: JailConfig,
     /// Jail ID (when running)
     pub jid: Option<i32>,
+    /// When the jail last transitioned to Running
+    pub started_at: Option<SystemTime>,
 }
 
 impl JailInstance {
@@ -116,12 +181,18 @@ impl JailInstance {
             machine,
             config,
             jid: None,
+            started_at: None,
         }
     }
 
     /// Get current state as enum
     pub fn state(&self) -> State {
-        State::from_str(self.machine.current_state())
+        State::from_name(self.machine.current_state())
+    }
+
+    /// Events the jail can take from its current state
+    pub fn transitions(&self) -> Vec<(&'static str, State)> {
+        self.state().transitions()
     }
 
     /// Check if the jail is currently in Running state
@@ -137,7 +208,24 @@ impl JailInstance {
 
     /// Trigger started event (transition to Running)
     pub fn started(&mut self) -> Result<(), state_machines::DynamicError> {
-        self.machine.handle(JailMachineEvent::Started)
+        self.machine.handle(JailMachineEvent::Started)?;
+        self.started_at = Some(SystemTime::now());
+        Ok(())
+    }
+
+    /// Time since the jail started, if it is up
+    pub fn uptime(&self) -> Option<Duration> {
+        self.started_at.and_then(|t| t.elapsed().ok())
+    }
+
+    /// Trigger pause event (processes suspended)
+    pub fn pause(&mut self) -> Result<(), state_machines::DynamicError> {
+        self.machine.handle(JailMachineEvent::Pause)
+    }
+
+    /// Trigger resume event (transition back to Running)
+    pub fn resume(&mut self) -> Result<(), state_machines::DynamicError> {
+        self.machine.handle(JailMachineEvent::Resume)
     }
 
     /// Trigger stop event
@@ -147,12 +235,16 @@ impl JailInstance {
 
     /// Trigger stopped event (transition to Stopped)
     pub fn stopped(&mut self) -> Result<(), state_machines::DynamicError> {
-        self.machine.handle(JailMachineEvent::Stopped)
+        self.machine.handle(JailMachineEvent::Stopped)?;
+        self.started_at = None;
+        Ok(())
     }
 
     /// Trigger fail event
     pub fn fail(&mut self) -> Result<(), state_machines::DynamicError> {
-        self.machine.handle(JailMachineEvent::Fail)
+        self.machine.handle(JailMachineEvent::Fail)?;
+        self.started_at = None;
+        Ok(())
     }
 
     /// Trigger recover event
@@ -161,6 +253,70 @@ impl JailInstance {
     }
 }
 
+/// Facts about a jail that outlive a blackship invocation, persisted in
+/// `data_dir/state/<jail>.json`
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct JailRecord {
+    /// Exit code of the jail's last `exec.start` run
+    pub exit_code: Option<i32>,
+    /// When that run finished (seconds since the epoch)
+    pub finished_at: Option<u64>,
+    /// Stopped with `down`, so `unless-stopped` jails stay down
+    #[serde(default)]
+    pub stopped_by_user: bool,
+    /// Failed to start (or crash-looped) and was not cleaned up since
+    #[serde(default)]
+    pub failed: bool,
+    /// Start added the shared-IP alias to the host interface, so stop removes it
+    #[serde(default)]
+    pub added_alias: bool,
+}
+
+impl JailRecord {
+    /// File holding a jail's record
+    fn path(data_dir: &Path, jail_name: &str) -> PathBuf {
+        data_dir.join("state").join(format!("{}.json", jail_name))
+    }
+
+    /// Load a jail's record (empty if there is none)
+    pub fn load(data_dir: &Path, jail_name: &str) -> Self {
+        fs::read_to_string(Self::path(data_dir, jail_name))
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    /// Persist the record
+    pub fn save(&self, data_dir: &Path, jail_name: &str) -> crate::error::Result<()> {
+        let path = Self::path(data_dir, jail_name);
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)?;
+        Ok(())
+    }
+
+    /// Move a jail's record to a new jail name, returning whether there was one
+    pub fn rename(data_dir: &Path, jail_name: &str, new_name: &str) -> crate::error::Result<bool> {
+        let path = Self::path(data_dir, jail_name);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::rename(path, Self::path(data_dir, new_name))?;
+        Ok(true)
+    }
+
+    /// Record a finished run
+    pub fn exited(mut self, code: i32) -> Self {
+        self.exit_code = Some(code);
+        self.finished_at = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .ok()
+            .map(|d| d.as_secs());
+        self
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -211,6 +367,102 @@ mod tests {
         assert_eq!(machine.current_state(), "Stopped");
     }
 
+    #[test]
+    fn test_pause_and_resume() {
+        let mut machine = JailMachine::new(()).into_dynamic();
+
+        // Can't pause a jail that isn't running
+        assert!(machine.handle(JailMachineEvent::Pause).is_err());
+
+        machine.handle(JailMachineEvent::Start).unwrap();
+        machine.handle(JailMachineEvent::Started).unwrap();
+        machine.handle(JailMachineEvent::Pause).unwrap();
+        assert_eq!(machine.current_state(), "Paused");
+
+        machine.handle(JailMachineEvent::Resume).unwrap();
+        assert_eq!(machine.current_state(), "Running");
+
+        // A paused jail can be stopped directly
+        machine.handle(JailMachineEvent::Pause).unwrap();
+        machine.handle(JailMachineEvent::Stop).unwrap();
+        assert_eq!(machine.current_state(), "Stopping");
+    }
+
+    #[test]
+    fn test_jail_record_roundtrip() {
+        let dir = std::env::temp_dir().join(format!("blackship-record-{}", std::process::id()));
+        assert_eq!(JailRecord::load(&dir, "migrate"), JailRecord::default());
+
+        let record = JailRecord::default().exited(3);
+        record.save(&dir, "migrate").unwrap();
+        let loaded = JailRecord::load(&dir, "migrate");
+        assert_eq!(loaded.exit_code, Some(3));
+        assert!(loaded.finished_at.is_some());
+        assert!(!loaded.stopped_by_user);
+
+        // Renaming a jail carries its record over
+        assert!(JailRecord::rename(&dir, "migrate", "migrate2").unwrap());
+        assert_eq!(JailRecord::load(&dir, "migrate2").exit_code, Some(3));
+        assert_eq!(JailRecord::load(&dir, "migrate"), JailRecord::default());
+        assert!(!JailRecord::rename(&dir, "migrate", "migrate3").unwrap());
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_transitions_match_machine() {
+        fn event(name: &str) -> JailMachineEvent {
+            match name {
+                "start" => JailMachineEvent::Start,
+                "started" => JailMachineEvent::Started,
+                "pause" => JailMachineEvent::Pause,
+                "resume" => JailMachineEvent::Resume,
+                "stop" => JailMachineEvent::Stop,
+                "stopped" => JailMachineEvent::Stopped,
+                "fail" => JailMachineEvent::Fail,
+                "recover" => JailMachineEvent::Recover,
+                _ => unreachable!(),
+            }
+        }
+        let events = ["start", "started", "pause", "resume", "stop", "stopped", "fail", "recover"];
+        // Shortest path from Stopped to each state
+        let paths: [(State, &[&str]); 6] = [
+            (State::Stopped, &[]),
+            (State::Starting, &["start"]),
+            (State::Running, &["start", "started"]),
+            (State::Paused, &["start", "started", "pause"]),
+            (State::Stopping, &["start", "started", "stop"]),
+            (State::Failed, &["start", "fail"]),
+        ];
+
+        for (state, path) in paths {
+            for name in events {
+                let mut machine = JailMachine::new(()).into_dynamic();
+                for step in path {
+                    machine.handle(event(step)).unwrap();
+                }
+                assert_eq!(machine.current_state(), state.name());
+
+                let listed = state.transitions().into_iter().find(|(e, _)| *e == name);
+                match (machine.handle(event(name)), listed) {
+                    (Ok(()), Some((_, to))) => assert_eq!(machine.current_state(), to.name()),
+                    (Err(_), None) => {}
+                    (result, listed) => {
+                        panic!("{} from {}: machine {:?}, listed {:?}", name, state, result, listed)
+                    }
+                }
+            }
+        }
+
+        let mut instance = JailInstance::new(JailConfig::new("web", "/jails/web"));
+        assert_eq!(instance.transitions(), vec![("start", State::Starting)]);
+        instance.start().unwrap();
+        assert_eq!(
+            instance.transitions(),
+            vec![("started", State::Running), ("fail", State::Failed)]
+        );
+    }
+
     #[test]
     fn test_invalid_transition() {
         let mut machine = JailMachine::new(()).into_dynamic();
@@ -228,7 +480,13 @@ mod tests {
         instance.start().unwrap();
         assert_eq!(instance.state(), State::Starting);
 
+        assert!(instance.uptime().is_none());
         instance.started().unwrap();
         assert!(instance.is_running());
+        assert!(instance.uptime().is_some());
+
+        instance.stop().unwrap();
+        instance.stopped().unwrap();
+        assert!(instance.started_at.is_none());
     }
 }
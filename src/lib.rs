@@ -0,0 +1,49 @@
+//! Blackship - FreeBSD jail orchestrator
+//!
+//! A jail manager with TOML configuration, dependency management,
+//! and state machine-controlled lifecycle.
+//!
+//! The `blackship` binary is a thin CLI over this library. Entry points for
+//! embedding it in another program:
+//!
+//! - [`manifest::load`] / [`manifest::load_merged`] read a [`BlackshipConfig`]
+//! - [`Bridge`] starts, stops and inspects the configured jails
+//! - [`HealthChecker`] runs a jail's health checks
+//! - [`Warden`] supervises jails and restarts them on failure (async, tokio)
+//! - [`metrics`] exposes supervisor metrics to Prometheus
+//! - [`export`] exports and imports jails as archives
+
+#[cfg(not(target_os = "freebsd"))]
+compile_error!(
+    "blackship only supports FreeBSD. This crate requires jail(2) syscalls \
+     which are only available on FreeBSD."
+);
+
+pub mod backup;
+pub mod blueprint;
+pub mod bridge;
+pub mod console;
+pub mod error;
+pub mod export;
+pub mod hooks;
+pub mod jail;
+pub mod logging;
+pub mod manifest;
+pub mod metrics;
+pub mod network;
+pub mod provision;
+pub mod sickbay;
+pub mod warden;
+pub mod zfs;
+
+// Internal modules
+mod bulkhead;
+mod supply;
+mod sys;
+mod util;
+
+pub use bridge::Bridge;
+pub use error::{Error, Result};
+pub use manifest::BlackshipConfig;
+pub use sickbay::{HealthChecker, HealthStatus};
+pub use warden::{Warden, WardenHandle};
@@ -0,0 +1,174 @@
+//! Status and lifecycle event logging
+//!
+//! Events are always printed (stdout for informational levels, stderr for
+//! warnings and errors). When syslog is enabled via `--syslog` or
+//! `[logging] syslog = true`, they are also sent to the host's syslog
+//! under the `daemon` facility.
+//!
+//! Captured jail console logs (`data_dir/logs/<jail>.log`) are rotated by
+//! size, keeping a fixed number of older files.
+
+use crate::error::Result;
+use std::ffi::CString;
+use std::fs::OpenOptions;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// Whether events are also sent to syslog
+static SYSLOG_ENABLED: AtomicBool = AtomicBool::new(false);
+
+/// Event severity, mapped onto syslog priorities
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Level {
+    /// Routine status (jail started/stopped)
+    Info,
+    /// Significant but normal condition (recoveries)
+    Notice,
+    /// Failure transitions
+    Warning,
+    /// Errors requiring attention
+    Error,
+}
+
+impl Level {
+    /// Corresponding syslog(3) priority
+    fn priority(self) -> libc::c_int {
+        match self {
+            Level::Info => libc::LOG_INFO,
+            Level::Notice => libc::LOG_NOTICE,
+            Level::Warning => libc::LOG_WARNING,
+            Level::Error => libc::LOG_ERR,
+        }
+    }
+}
+
+/// Output format of `--log-format`
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum LogFormat {
+    /// Human-readable text
+    #[default]
+    Text,
+    /// One JSON object per message
+    Json,
+}
+
+impl std::str::FromStr for LogFormat {
+    type Err = String;
+
+    fn from_str(s: &str) -> std::result::Result<Self, Self::Err> {
+        match s {
+            "text" => Ok(LogFormat::Text),
+            "json" => Ok(LogFormat::Json),
+            _ => Err(format!("invalid log format `{s}`: expected text or json")),
+        }
+    }
+}
+
+/// Enable syslog output for all subsequent events
+pub fn enable_syslog() {
+    if SYSLOG_ENABLED.swap(true, Ordering::SeqCst) {
+        return;
+    }
+    // SAFETY: the ident is a static C string, valid for the life of the process
+    unsafe {
+        libc::openlog(c"blackship".as_ptr(), libc::LOG_PID, libc::LOG_DAEMON);
+    }
+}
+
+/// Log a status or health event
+pub fn event(level: Level, message: &str) {
+    match level {
+        Level::Info | Level::Notice => println!("{}", message),
+        Level::Warning | Level::Error => eprintln!("{}", message),
+    }
+
+    if !SYSLOG_ENABLED.load(Ordering::SeqCst) {
+        return;
+    }
+    // Interior NULs would truncate the message; replace them
+    let Ok(msg) = CString::new(message.replace('\0', " ")) else {
+        return;
+    };
+    // SAFETY: a constant "%s" format with a single valid C string argument
+    unsafe {
+        libc::syslog(level.priority(), c"%s".as_ptr(), msg.as_ptr());
+    }
+}
+
+/// Rotate a log once it grows past `max_size` bytes, keeping `keep` old files
+///
+/// Older files shift up (`<log>.1` becomes `<log>.2`, ...) and the current
+/// content is copied to `<log>.1`. The log itself is truncated in place rather
+/// than renamed: jail processes hold it open for appending and a `tail -F`
+/// follower picks up the truncation. Lines written between the copy and the
+/// truncation are lost. Returns whether the log was rotated.
+pub fn rotate(log: &Path, max_size: u64, keep: usize) -> Result<bool> {
+    let size = match std::fs::metadata(log) {
+        Ok(metadata) => metadata.len(),
+        Err(e) if e.kind() == std::io::ErrorKind::NotFound => return Ok(false),
+        Err(e) => return Err(e.into()),
+    };
+    if size <= max_size {
+        return Ok(false);
+    }
+
+    if keep > 0 {
+        let oldest = rotated_path(log, keep);
+        if oldest.exists() {
+            std::fs::remove_file(&oldest)?;
+        }
+        for n in (1..keep).rev() {
+            let from = rotated_path(log, n);
+            if from.exists() {
+                std::fs::rename(&from, rotated_path(log, n + 1))?;
+            }
+        }
+        std::fs::copy(log, rotated_path(log, 1))?;
+    }
+    OpenOptions::new().write(true).open(log)?.set_len(0)?;
+    Ok(true)
+}
+
+/// `<log>.<n>`
+fn rotated_path(log: &Path, n: usize) -> PathBuf {
+    let mut path = log.as_os_str().to_owned();
+    path.push(format!(".{}", n));
+    PathBuf::from(path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_level_priority() {
+        assert_eq!(Level::Warning.priority(), libc::LOG_WARNING);
+        assert_eq!(Level::Notice.priority(), libc::LOG_NOTICE);
+        assert!(Level::Error.priority() < Level::Info.priority());
+    }
+
+    #[test]
+    fn test_rotate() {
+        let dir = std::env::temp_dir().join(format!("blackship-rotate-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let log = dir.join("web.log");
+        let read = |n: usize| std::fs::read_to_string(rotated_path(&log, n)).ok();
+
+        std::fs::write(&log, "first\n").unwrap();
+        assert!(!rotate(&log, 100, 2).unwrap());
+        assert!(rotate(&log, 3, 2).unwrap());
+        assert_eq!(std::fs::read_to_string(&log).unwrap(), "");
+        assert_eq!(read(1).as_deref(), Some("first\n"));
+
+        for content in ["second\n", "third\n"] {
+            std::fs::write(&log, content).unwrap();
+            assert!(rotate(&log, 3, 2).unwrap());
+        }
+        assert_eq!(read(1).as_deref(), Some("third\n"));
+        assert_eq!(read(2).as_deref(), Some("second\n"));
+        assert_eq!(read(3), None);
+
+        assert!(!rotate(&dir.join("missing.log"), 3, 2).unwrap());
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+}
@@ -1,62 +1,388 @@
 //! Blackship - FreeBSD jail orchestrator
 //!
-//! A jail manager with TOML configuration, dependency management,
-//! and state machine-controlled lifecycle.
+//! Command-line interface over the `blackship` library.
 
-#[cfg(not(target_os = "freebsd"))]
-compile_error!(
-    "blackship only supports FreeBSD. This crate requires jail(2) syscalls \
-     which are only available on FreeBSD."
-);
-
-mod provision;
 mod cli;
-mod manifest;
-mod console;
-mod error;
-mod export;
-mod supply;
-mod bulkhead;
-mod sickbay;
-mod hooks;
-mod sys;
-mod jail;
-mod network;
-mod bridge;
-mod blueprint;
-mod warden;
-mod zfs;
-
-use cli::{ArmadaAction, Cli, Commands, NetworkAction, ReleasesAction, SnapshotAction, TemplateAction};
+
+use blackship::{
+    backup, blueprint, bridge, console, error, export, jail, logging, manifest, metrics, network,
+    provision, sickbay, warden, zfs,
+};
+use cli::{
+    ArmadaAction, CacheAction, Cli, Commands, ConfigAction, HealthAction, NetworkAction,
+    ReleasesAction, SnapshotAction, TemplateAction,
+};
 use error::Result;
 
+use std::collections::HashMap;
+use std::io::IsTerminal;
+use std::path::Path;
+use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
+use tokio::signal::unix::{signal, SignalKind};
 use tokio::sync::Mutex;
 
 fn main() {
-    if let Err(e) = run() {
-        eprintln!("Error: {}", e);
-        std::process::exit(1);
+    let cli = Cli::parse_args();
+    let log_format = cli.log_format;
+    if let Err(e) = run(cli) {
+        report_error(&e, log_format);
+        std::process::exit(e.exit_code());
     }
 }
 
-fn run() -> Result<()> {
-    let cli = Cli::parse_args();
+/// Print an error and its remediation hint, colored when stderr is a terminal
+///
+/// Colors are left out when `NO_COLOR` is set. With `--log-format json` the
+/// error is one JSON object carrying its stable code and exit status.
+fn report_error(e: &error::Error, format: logging::LogFormat) {
+    if format == logging::LogFormat::Json {
+        let error = serde_json::json!({
+            "error": {
+                "code": e.code(),
+                "message": e.to_string(),
+                "hint": e.hint(),
+                "exit_code": e.exit_code(),
+            }
+        });
+        eprintln!("{}", error);
+        return;
+    }
+
+    let color = std::io::stderr().is_terminal() && std::env::var_os("NO_COLOR").is_none();
+    let (red, yellow, reset) = if color {
+        ("\x1b[1;31m", "\x1b[33m", "\x1b[0m")
+    } else {
+        ("", "", "")
+    };
+    eprintln!("{}Error:{} {}", red, reset, e);
+    if let Some(hint) = e.hint() {
+        eprintln!("{}hint:{} {}", yellow, reset, hint);
+    }
+}
+
+/// Load the configuration file, reporting the resolved path in verbose mode
+fn load_config(path: &Path, verbose: bool) -> Result<manifest::BlackshipConfig> {
+    if verbose {
+        println!("Using configuration: {}", path.display());
+    }
+    let config = manifest::load(path)?;
+    if config.logging.syslog_enabled() {
+        logging::enable_syslog();
+    }
+    Ok(config)
+}
+
+/// Spawn a health monitor task for a jail, returning its stop signal
+///
+/// Returns None when the jail has no enabled health checks.
+fn spawn_health_monitor(
+    jail_def: &manifest::JailDef,
+    full_name: &str,
+    data_dir: &Path,
+    rate_limit: &manifest::RateLimitConfig,
+    handle: warden::WardenHandle,
+    metrics: Option<Arc<metrics::Metrics>>,
+) -> Option<Arc<AtomicBool>> {
+    if !jail_def.healthcheck.enabled || jail_def.healthcheck.checks.is_empty() {
+        return None;
+    }
+
+    // Create health checker with warden handle
+    let mut checker = sickbay::HealthChecker::with_rate_limit(
+        full_name,
+        jail_def.healthcheck.clone(),
+        rate_limit.health_capacity,
+        rate_limit.health_refill_rate,
+    )
+    .with_warden_handle(handle.clone());
+
+    // Try to get JID for the jail
+    if let Ok(jid) = jail::jail_getid(full_name) {
+        checker = checker.with_jid(jid);
+    }
+
+    // Get stop signal before moving checker into spawned task
+    let stop_signal = checker.stop_signal();
+    let reset_marker = sickbay::breaker_reset_marker(data_dir, full_name);
+
+    tokio::spawn(async move {
+        let mut last_status = checker.status();
+        handle.report_health(checker.jail_name(), last_status);
+
+        // Run health checks in a loop until stopped
+        while !checker.is_stopped() {
+            // Honour `blackship health reset <jail>`
+            if reset_marker.exists() {
+                checker.reset_breakers();
+                let _ = std::fs::remove_file(&reset_marker);
+                println!("Circuit breakers reset for jail '{}'", checker.jail_name());
+            }
+
+            if let Err(e) = checker.run_checks() {
+                eprintln!("Health check error for {}: {}", checker.jail_name(), e);
+            }
+            if let Some(metrics) = &metrics {
+                for (check, result, _, _) in checker.get_check_results() {
+                    if let Some(result) = result {
+                        metrics.record_check(checker.jail_name(), &check.name, result.duration);
+                    }
+                }
+            }
+
+            // Check status and log transitions
+            let status = checker.status();
+            if status != last_status {
+                handle.report_health(checker.jail_name(), status);
+                if status == sickbay::HealthStatus::Failing {
+                    logging::event(
+                        logging::Level::Warning,
+                        &format!("Health check failing for jail '{}'", checker.jail_name()),
+                    );
+                } else if last_status == sickbay::HealthStatus::Failing
+                    && status == sickbay::HealthStatus::Healthy
+                {
+                    logging::event(
+                        logging::Level::Notice,
+                        &format!("Health checks recovered for jail '{}'", checker.jail_name()),
+                    );
+                }
+                last_status = status;
+            }
+
+            // Wake when the next check is due
+            tokio::time::sleep(checker.next_due()).await;
+        }
+        println!("Health monitor stopped for jail '{}'", checker.jail_name());
+    });
+
+    println!("Spawned health monitor for jail '{}'", full_name);
+    Some(stop_signal)
+}
+
+/// Spawn the snapshot scheduler for ZFS-backed jails (`[snapshots] schedule`)
+///
+/// Returns a stop signal, or None when no schedule is configured.
+fn spawn_snapshot_scheduler(
+    snapshots: &manifest::SnapshotsConfig,
+    zfs: Option<zfs::ZfsManager>,
+    jails: &[(String, manifest::JailDef)],
+) -> Option<Arc<AtomicBool>> {
+    let interval = snapshots.interval()?;
+    let zfs = zfs?;
+    let keep = snapshots.keep;
+    // Jails with a custom path have no dataset to snapshot
+    let jails: Vec<String> = jails
+        .iter()
+        .filter(|(_, jail_def)| jail_def.path.is_none())
+        .map(|(full_name, _)| full_name.clone())
+        .collect();
+
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop_signal);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(interval);
+        // The first tick completes immediately; snapshot after a full interval
+        ticker.tick().await;
+        loop {
+            ticker.tick().await;
+            if stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            for jail in &jails {
+                let result = tokio::task::block_in_place(|| {
+                    // Jails that were never started have no dataset yet
+                    if !zfs.dataset_exists(&zfs.get_jail_dataset(jail))? {
+                        return Ok(None);
+                    }
+                    zfs.auto_snapshot(jail, keep).map(Some)
+                });
+                match result {
+                    Ok(Some(name)) => logging::event(
+                        logging::Level::Info,
+                        &format!("Snapshot {}@{} taken", jail, name),
+                    ),
+                    Ok(None) => {}
+                    Err(e) => logging::event(
+                        logging::Level::Warning,
+                        &format!("Scheduled snapshot of jail '{}' failed: {}", jail, e),
+                    ),
+                }
+            }
+        }
+    });
+
+    println!("Spawned snapshot scheduler (every {}s)", interval.as_secs());
+    Some(stop_signal)
+}
+
+/// Spawn the rotator for captured console logs (`data_dir/logs/*.log`)
+fn spawn_log_rotator(data_dir: &std::path::Path, max_size: u64, keep: usize) -> Arc<AtomicBool> {
+    let logs_dir = data_dir.join("logs");
+    let stop_signal = Arc::new(AtomicBool::new(false));
+    let stopped = Arc::clone(&stop_signal);
+
+    tokio::spawn(async move {
+        let mut ticker = tokio::time::interval(std::time::Duration::from_secs(60));
+        loop {
+            ticker.tick().await;
+            if stopped.load(Ordering::SeqCst) {
+                break;
+            }
+            let Ok(entries) = std::fs::read_dir(&logs_dir) else { continue };
+            for path in entries.flatten().map(|entry| entry.path()) {
+                if path.extension().is_none_or(|ext| ext != "log") {
+                    continue;
+                }
+                match logging::rotate(&path, max_size, keep) {
+                    Ok(true) => logging::event(
+                        logging::Level::Info,
+                        &format!("Rotated {}", path.display()),
+                    ),
+                    Ok(false) => {}
+                    Err(e) => logging::event(
+                        logging::Level::Warning,
+                        &format!("Failed to rotate {}: {}", path.display(), e),
+                    ),
+                }
+            }
+        }
+    });
+
+    stop_signal
+}
+
+/// ZFS manager for the configured pool, if ZFS is enabled
+fn zfs_manager(config: &manifest::GlobalConfig) -> Option<zfs::ZfsManager> {
+    let pool = config.zpool.as_ref().filter(|_| config.zfs_enabled)?;
+    Some(zfs::ZfsManager::new(pool, &config.dataset))
+}
+
+fn run(cli: Cli) -> Result<()> {
+    let config_path = cli.config_path();
+    let offline = cli.offline;
+    if cli.syslog {
+        logging::enable_syslog();
+    }
 
     // Execute command
     match cli.command {
         // Commands that don't require config
         Commands::Exec {
-            jail,
+            jails,
+            all,
+            parallel,
+            detach,
+            timeout,
             user,
             command,
         } => {
-            let opts = console::ExecOptions {
-                user,
-                ..Default::default()
+            // Detached: start in the background and report the PIDs
+            if detach {
+                for jail in &jails {
+                    let pid = console::exec_detached(jail, &command, &user)?;
+                    println!(
+                        "Started PID {} in jail '{}' (output in {})",
+                        pid,
+                        jail,
+                        console::DETACHED_LOG
+                    );
+                }
+                return Ok(());
+            }
+
+            // Single jail with a timeout: captured output, killed when it runs too long
+            if let Some(secs) = timeout
+                && !all
+                && jails.len() == 1
+            {
+                let result = console::exec_in_jails(&jails, &command, &user, false, secs)
+                    .remove(0)
+                    .output;
+                match result {
+                    Ok((code, stdout, stderr)) => {
+                        print!("{}", String::from_utf8_lossy(&stdout));
+                        eprint!("{}", String::from_utf8_lossy(&stderr));
+                        std::process::exit(code);
+                    }
+                    Err(e @ error::Error::JailTimeout(_)) => {
+                        report_error(&e, cli.log_format);
+                        std::process::exit(console::EXEC_TIMEOUT_EXIT_CODE);
+                    }
+                    Err(e) => return Err(e),
+                }
+            }
+
+            // Single jail: interactive exec with inherited stdio
+            if !all && jails.len() == 1 {
+                let opts = console::ExecOptions {
+                    user,
+                    ..Default::default()
+                };
+                let status = console::exec_in_jail(&jails[0], &command, &opts)?;
+                std::process::exit(status.code().unwrap_or(1));
+            }
+
+            // Multiple jails: resolve names via the configuration
+            let targets: Vec<String> = if all {
+                let config = load_config(&config_path, cli.verbose)?;
+                let (running, stopped): (Vec<String>, Vec<String>) = config
+                    .jails
+                    .iter()
+                    .map(|j| config.jail_name(&j.name))
+                    .partition(|name| jail::jail_getid(name).is_ok());
+                if !stopped.is_empty() {
+                    println!("Skipping {} jail(s) that are not running", stopped.len());
+                }
+                running
+            } else {
+                match manifest::load(&config_path) {
+                    Ok(config) => jails
+                        .iter()
+                        .map(|name| {
+                            config
+                                .resolve_jail_names(name)
+                                .map(|(_, full_name)| full_name)
+                                .unwrap_or_else(|| name.clone())
+                        })
+                        .collect(),
+                    Err(_) => jails,
+                }
             };
-            let status = console::exec_in_jail(&jail, &command, &opts)?;
-            std::process::exit(status.code().unwrap_or(1));
+
+            let results =
+                console::exec_in_jails(&targets, &command, &user, parallel, timeout.unwrap_or(0));
+
+            for result in &results {
+                println!("=== {} ===", result.jail);
+                match &result.output {
+                    Ok((_, stdout, stderr)) => {
+                        print!("{}", String::from_utf8_lossy(stdout));
+                        eprint!("{}", String::from_utf8_lossy(stderr));
+                    }
+                    Err(e) => eprintln!("Error: {}", e),
+                }
+            }
+
+            println!("\nSummary:");
+            let mut failed = 0;
+            for result in &results {
+                match &result.output {
+                    Ok((0, _, _)) => println!("  {:<20} exit 0", result.jail),
+                    Ok((code, _, _)) => {
+                        failed += 1;
+                        println!("  {:<20} exit {}", result.jail, code);
+                    }
+                    Err(e) => {
+                        failed += 1;
+                        println!("  {:<20} error: {}", result.jail, e);
+                    }
+                }
+            }
+            println!("{} of {} jail(s) succeeded", results.len() - failed, results.len());
+
+            std::process::exit(if failed == 0 { 0 } else { 1 });
         }
         Commands::Console { jail, user } => {
             let status = console::console(&jail, &user)?;
@@ -78,7 +404,8 @@ fn run() -> Result<()> {
             }
 
             // Determine release to use
-            let base_release = release.unwrap_or_else(|| "15.0-RELEASE".to_string());
+            let base_release =
+                release.unwrap_or_else(|| provision::LATEST_ALIAS.to_string());
 
             let content = if toml {
                 format!(r#"[metadata]
@@ -142,7 +469,7 @@ FROM {}
         // Armada (docker-compose style orchestration)
         Commands::Armada { files, action } => {
             match action {
-                ArmadaAction::Init { file, force } => {
+                ArmadaAction::Init { file, force, compose_import } => {
                     use std::fs;
 
                     if file.exists() && !force {
@@ -150,6 +477,16 @@ FROM {}
                         std::process::exit(1);
                     }
 
+                    if let Some(compose) = compose_import {
+                        let content = manifest::compose::import(&fs::read_to_string(&compose)?)?;
+                        fs::write(&file, content)?;
+                        println!("Imported {} into {}", compose.display(), file.display());
+                        println!("\nNext steps:");
+                        println!("  1. Review the commented lines in {}", file.display());
+                        println!("  2. Run 'blackship armada up' to start all jails");
+                        return Ok(());
+                    }
+
                     let content = r#"# Blackship Armada Configuration
 # https://github.com/seuros/blackship
 
@@ -217,23 +554,26 @@ data_dir = "/var/blackship"
                     }
                 }
 
-                ArmadaAction::Down { jails, dry_run } => {
+                ArmadaAction::Down {
+                    jails,
+                    remove,
+                    dry_run,
+                } => {
                     let config = manifest::load_merged(&files)?;
                     let mut bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
 
-                    if jails.is_empty() {
+                    let targets: Vec<Option<&str>> = if jails.is_empty() {
+                        vec![None]
+                    } else {
+                        jails.iter().map(|j| Some(j.as_str())).collect()
+                    };
+                    for jail in targets {
                         if dry_run {
-                            bridge.down_dry_run(None)?;
+                            bridge.down_dry_run(jail, remove)?;
+                        } else if remove {
+                            bridge.down_and_remove(jail)?;
                         } else {
-                            bridge.down(None)?;
-                        }
-                    } else {
-                        for jail in &jails {
-                            if dry_run {
-                                bridge.down_dry_run(Some(jail))?;
-                            } else {
-                                bridge.down(Some(jail))?;
-                            }
+                            bridge.down(jail)?;
                         }
                     }
                 }
@@ -291,7 +631,8 @@ data_dir = "/var/blackship"
 
                                 // Copy base release if needed
                                 if let Some(release) = &jailfile.from {
-                                    let bs = provision::Provisioner::from_config(&config.config)?;
+                                    let bs = provision::Provisioner::from_config(&config.config)?
+                                        .with_offline(offline);
                                     let release_path = config.config.releases_dir.join(release);
 
                                     if !release_path.exists() {
@@ -324,9 +665,20 @@ data_dir = "/var/blackship"
                                 }
 
                                 // Create build context and execute
-                                let ctx = BuildContext::new(build_path, &target_path, &full_name)
+                                let mut ctx = BuildContext::new(build_path, &target_path, &full_name)
                                     .verbose(cli.verbose);
-                                let mut executor = TemplateExecutor::new(ctx).dry_run(dry_run);
+                                if let Some(gateway) = config.jail_gateway(jail_def) {
+                                    ctx = ctx.with_gateway(&gateway.to_string());
+                                }
+                                if let Some(network) = config.primary_network(jail_def) {
+                                    ctx = ctx.with_network(&network.name, &network.subnet);
+                                }
+                                let mut executor = TemplateExecutor::new(ctx)
+                                    .dry_run(dry_run)
+                                    .devfs_ruleset(config.config.build_devfs_ruleset)
+                                    .run_timeout(config.config.build_run_timeout)
+                                    .nameservers(&config.config.build_nameservers)
+                                    .releases_dir(&config.config.releases_dir);
                                 executor.execute(&jailfile)?;
 
                                 if !dry_run {
@@ -357,7 +709,8 @@ data_dir = "/var/blackship"
                                     let context_dir = jailfile_explicit.parent().unwrap_or(std::path::Path::new("."));
 
                                     if let Some(release) = &jailfile.from {
-                                        let bs = provision::Provisioner::from_config(&config.config)?;
+                                        let bs = provision::Provisioner::from_config(&config.config)?
+                                            .with_offline(offline);
                                         let release_path = config.config.releases_dir.join(release);
 
                                         if !release_path.exists() && !dry_run {
@@ -386,9 +739,20 @@ data_dir = "/var/blackship"
                                         }
                                     }
 
-                                    let ctx = BuildContext::new(context_dir, &target_path, &full_name)
+                                    let mut ctx = BuildContext::new(context_dir, &target_path, &full_name)
                                         .verbose(cli.verbose);
-                                    let mut executor = TemplateExecutor::new(ctx).dry_run(dry_run);
+                                    if let Some(gateway) = config.jail_gateway(jail_def) {
+                                        ctx = ctx.with_gateway(&gateway.to_string());
+                                    }
+                                    if let Some(network) = config.primary_network(jail_def) {
+                                        ctx = ctx.with_network(&network.name, &network.subnet);
+                                    }
+                                    let mut executor = TemplateExecutor::new(ctx)
+                                        .dry_run(dry_run)
+                                        .devfs_ruleset(config.config.build_devfs_ruleset)
+                                        .run_timeout(config.config.build_run_timeout)
+                                        .nameservers(&config.config.build_nameservers)
+                                        .releases_dir(&config.config.releases_dir);
                                     executor.execute(&jailfile)?;
 
                                     if !dry_run {
@@ -404,45 +768,87 @@ data_dir = "/var/blackship"
                     }
                 }
 
-                ArmadaAction::Ps { json } => {
+                ArmadaAction::Ps {
+                    json,
+                    filters,
+                    columns,
+                    sort,
+                } => {
                     let config = manifest::load_merged(&files)?;
                     let bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
-                    bridge.ps(json)?;
+                    bridge.ps(json, &filters, &columns, sort)?;
                 }
 
-                ArmadaAction::Config { show } => {
-                    let config = manifest::load_merged(&files)?;
+                ArmadaAction::Config { show, validate_only, quiet, explain } => {
+                    if validate_only {
+                        // An invalid configuration goes through the error printer and exit codes
+                        let config = manifest::load_merged(&files)?;
+                        if !quiet {
+                            println!(
+                                "Configuration valid ({} file(s), {} jail(s)).",
+                                files.len(),
+                                config.jails.len()
+                            );
+                        }
+                        return Ok(());
+                    }
+
+                    let (config, provenance) = manifest::load_merged_explained(&files)?;
 
                     if show {
+                        // Annotation naming the file a field came from (--explain only)
+                        let from = |field: &str| -> String {
+                            match provenance.source(field) {
+                                Some(path) if explain => format!("  # from {}", path.display()),
+                                _ => String::new(),
+                            }
+                        };
+
                         // Print merged config as TOML
                         println!("# Merged configuration from: {:?}\n", files);
                         println!("[config]");
-                        println!("data_dir = \"{}\"", config.config.data_dir.display());
+                        println!(
+                            "data_dir = \"{}\"{}",
+                            config.config.data_dir.display(),
+                            from("config.data_dir")
+                        );
                         if config.config.zfs_enabled {
-                            println!("zfs_enabled = true");
+                            println!("zfs_enabled = true{}", from("config.zfs_enabled"));
                             if let Some(pool) = &config.config.zpool {
-                                println!("zpool = \"{}\"", pool);
+                                println!("zpool = \"{}\"{}", pool, from("config.zpool"));
                             }
                         }
                         println!();
                         for jail in &config.jails {
+                            let field = |name: &str| from(&format!("jails.{}.{}", jail.name, name));
                             println!("[[jails]]");
-                            println!("name = \"{}\"", jail.name);
+                            println!("name = \"{}\"{}", jail.name, field("name"));
                             if let Some(path) = &jail.path {
-                                println!("path = \"{}\"", path.display());
+                                println!("path = \"{}\"{}", path.display(), field("path"));
                             }
                             if let Some(release) = &jail.release {
-                                println!("release = \"{}\"", release);
+                                println!("release = \"{}\"{}", release, field("release"));
                             }
                             if let Some(build) = &jail.build {
-                                println!("build = \"{}\"", build.display());
+                                println!("build = \"{}\"{}", build.display(), field("build"));
                             }
                             if !jail.depends_on.is_empty() {
-                                println!("depends_on = {:?}", jail.depends_on);
+                                println!(
+                                    "depends_on = {:?}{}",
+                                    jail.depends_on,
+                                    field("depends_on")
+                                );
                             }
                             println!();
                         }
-                    } else {
+
+                        if explain {
+                            println!("# Field sources:");
+                            for (field, path) in provenance.iter() {
+                                println!("#   {} <- {}", field, path.display());
+                            }
+                        }
+                    } else if !quiet {
                         // Just validate
                         println!("Configuration valid.");
                         println!("  Files: {:?}", files);
@@ -457,8 +863,9 @@ data_dir = "/var/blackship"
             jail,
             follow,
             lines,
+            console,
         } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
 
             // Find jail config to get its path
             let (service_name, full_name) = config
@@ -468,6 +875,24 @@ data_dir = "/var/blackship"
                 .get_jail(&service_name)
                 .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
 
+            // The console log lives on the host: tail it there (-F survives rotation)
+            if console {
+                let log = console::console_log_path(&config.config.data_dir, &full_name);
+                if !log.exists() {
+                    return Err(error::Error::JailOperation(format!(
+                        "No console log for jail '{}' at {}",
+                        full_name,
+                        log.display()
+                    )));
+                }
+                let mut tail = std::process::Command::new("tail");
+                if follow {
+                    tail.arg("-F");
+                }
+                let status = tail.arg("-n").arg(lines.to_string()).arg(&log).status()?;
+                std::process::exit(status.code().unwrap_or(1));
+            }
+
             let jail_path = jail_def.effective_path(&config.config, &full_name);
             let log_dir = jail_path.join("var/log");
 
@@ -488,6 +913,21 @@ data_dir = "/var/blackship"
                 }
             };
 
+            // A stopped jail can't run tail: read the file from the host
+            if jail::jail_getid(&full_name).is_err() {
+                if follow {
+                    eprintln!("Warning: jail '{}' is not running; not following", full_name);
+                }
+                if !log_path.exists() {
+                    return Err(error::Error::JailOperation(format!(
+                        "No log file found in {}",
+                        log_dir.display()
+                    )));
+                }
+                print!("{}", console::tail_host_file(&jail_path, &log_path, lines)?);
+                return Ok(());
+            }
+
             // Build tail command
             let mut tail_args = vec![];
             if follow {
@@ -511,13 +951,22 @@ data_dir = "/var/blackship"
             std::process::exit(status.code().unwrap_or(1));
         }
 
-        Commands::Supervise => {
+        Commands::Supervise { status_addr, metrics_addr } => {
             // Load config and save it for later use (before moving into async)
-            let config = manifest::load(&cli.config)?;
-            let project_name = config.config.project_name();
-            let project_prefix = format!("{}-", project_name);
-            let jails_for_health = config.jails.clone();
+            let config = load_config(&config_path, cli.verbose)?;
+            let jails_for_health: Vec<(String, manifest::JailDef)> = config
+                .jails
+                .iter()
+                .map(|j| (config.jail_name(&j.name), j.clone()))
+                .collect();
             let rate_limit = config.config.rate_limit.clone();
+            let data_dir = config.config.data_dir.clone();
+            let supervise_config = config.config.supervise.clone();
+            let notifications = config.notifications.clone();
+            let snapshots = config.snapshots.clone();
+            let snapshot_zfs = zfs_manager(&config.config);
+            let log_max_size = config.logging.max_size_bytes();
+            let log_keep = config.logging.keep;
 
             let bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
             let bridge = Arc::new(Mutex::new(bridge));
@@ -527,7 +976,17 @@ data_dir = "/var/blackship"
             })?;
 
             rt.block_on(async {
-                let warden = warden::Warden::new(Arc::clone(&bridge));
+                let status_file = data_dir.join("status.json");
+                let mut warden = warden::Warden::new(Arc::clone(&bridge), supervise_config)
+                    .with_status_file(status_file.clone());
+                // Metrics are only collected when served
+                let metrics = metrics_addr.as_ref().map(|_| Arc::new(metrics::Metrics::new()));
+                if let Some(metrics) = &metrics {
+                    warden = warden.with_metrics(Arc::clone(metrics));
+                }
+                if let Some(notifications) = notifications {
+                    warden = warden.with_notifications(notifications);
+                }
                 let sender = warden.sender();
 
                 // Create a WardenHandle before moving warden into spawn
@@ -540,91 +999,146 @@ data_dir = "/var/blackship"
                     br.set_warden_handle(warden_handle_for_orch);
                 }
 
-                // Start all jails
+                // Adopt jails that are already running, then start the rest
+                bridge.lock().await.adopt_running();
+                if let Err(e) = bridge::Bridge::up_missing_async(&bridge).await {
+                    eprintln!("Warning: Failed to start some jails: {}", e);
+                }
+
+                if let Some(addr) = &status_addr
+                    && let Err(e) = warden::spawn_status_server(addr, status_file)
                 {
-                    let mut br = bridge.lock().await;
-                    if let Err(e) = br.up(None) {
-                        eprintln!("Warning: Failed to start some jails: {}", e);
+                    eprintln!("Warning: {}", e);
+                }
+                if let (Some(addr), Some(metrics)) = (&metrics_addr, &metrics) {
+                    let bridge = Arc::clone(&bridge);
+                    let metrics = Arc::clone(metrics);
+                    if let Err(e) = metrics::spawn_metrics_server(addr, metrics, bridge) {
+                        eprintln!("Warning: {}", e);
                     }
                 }
 
                 // Spawn the Warden event loop
-                let mut warden = warden;
                 let warden_task = tokio::spawn(async move {
                     warden.run().await;
                 });
 
                 // Spawn health monitors for jails with health checks enabled
-                // Collect stop signals to cleanly shutdown health checkers
-                let mut health_stop_signals = Vec::new();
+                // Keep stop signals to cleanly shutdown (or reload) health checkers
+                let mut health_monitors: HashMap<String, Arc<AtomicBool>> = HashMap::new();
+
+                for (full_name, jail_def) in &jails_for_health {
+                    let handle = warden_handle_for_health.clone();
+                    if let Some(stop_signal) = spawn_health_monitor(
+                        jail_def, full_name, &data_dir, &rate_limit, handle, metrics.clone(),
+                    ) {
+                        health_monitors.insert(full_name.clone(), stop_signal);
+                    }
+                }
 
-                for jail_def in &jails_for_health {
-                    if jail_def.healthcheck.enabled && !jail_def.healthcheck.checks.is_empty() {
-                        let full_name = if jail_def.name.starts_with(&project_prefix) {
-                            jail_def.name.clone()
-                        } else {
-                            format!("{}-{}", project_name, jail_def.name)
-                        };
-                        let healthcheck_config = jail_def.healthcheck.clone();
-                        let handle = warden_handle_for_health.clone();
-                        let health_capacity = rate_limit.health_capacity;
-                        let health_refill_rate = rate_limit.health_refill_rate;
-
-                        // Create health checker with warden handle
-                        let mut checker = sickbay::HealthChecker::with_rate_limit(
-                            &full_name,
-                            healthcheck_config,
-                            health_capacity,
-                            health_refill_rate,
-                        ).with_warden_handle(handle);
-
-                        // Try to get JID for the jail
-                        if let Ok(jid) = jail::jail_getid(&full_name) {
-                            checker = checker.with_jid(jid);
-                        }
+                let mut snapshot_scheduler =
+                    spawn_snapshot_scheduler(&snapshots, snapshot_zfs, &jails_for_health);
+
+                let log_rotator = spawn_log_rotator(&data_dir, log_max_size, log_keep);
 
-                        // Get stop signal before moving checker into spawned task
-                        let stop_signal = checker.stop_signal();
-                        health_stop_signals.push(stop_signal);
+                let mut hangup = signal(SignalKind::hangup()).expect("Failed to listen for SIGHUP");
 
-                        tokio::spawn(async move {
-                            // Run health checks in a loop until stopped
-                            while !checker.is_stopped() {
-                                if let Err(e) = checker.run_checks() {
-                                    eprintln!("Health check error for {}: {}", checker.jail_name(), e);
+                println!("Warden supervisor started. Press Ctrl+C to stop, send SIGHUP to reload.");
+
+                // Wait for Ctrl+C, reloading the configuration on SIGHUP
+                loop {
+                    tokio::select! {
+                        result = tokio::signal::ctrl_c() => {
+                            result.expect("Failed to listen for Ctrl+C");
+                            break;
+                        }
+                        _ = hangup.recv() => {
+                            println!("\nReloading configuration...");
+                            let config = match load_config(&config_path, cli.verbose) {
+                                Ok(config) => config,
+                                Err(e) => {
+                                    eprintln!("Warning: Failed to reload configuration: {}", e);
+                                    continue;
+                                }
+                            };
+                            let jails: Vec<(String, manifest::JailDef)> = config
+                                .jails
+                                .iter()
+                                .map(|j| (config.jail_name(&j.name), j.clone()))
+                                .collect();
+                            let rate_limit = config.config.rate_limit.clone();
+                            let data_dir = config.config.data_dir.clone();
+                            let snapshots = config.snapshots.clone();
+                            let snapshot_zfs = zfs_manager(&config.config);
+
+                            // Holding the bridge lock serializes the reload with the Warden
+                            let diff = {
+                                let mut br = bridge.lock().await;
+                                tokio::task::block_in_place(|| br.reload(config))
+                            };
+                            let diff = match diff {
+                                Ok(diff) => diff,
+                                Err(e) => {
+                                    eprintln!("Warning: Failed to reload configuration: {}", e);
+                                    continue;
                                 }
+                            };
 
-                                // Check status and log transitions
-                                let status = checker.status();
-                                if status == sickbay::HealthStatus::Failing {
-                                    eprintln!("Health check failing for jail '{}'", checker.jail_name());
+                            // Changed jails get a new monitor with their new checks
+                            for full_name in diff.removed.iter().chain(&diff.changed) {
+                                if let Some(stop_signal) = health_monitors.remove(full_name) {
+                                    stop_signal.store(true, Ordering::SeqCst);
                                 }
+                            }
+                            for (full_name, jail_def) in &jails {
+                                if !diff.added.contains(full_name)
+                                    && !diff.changed.contains(full_name)
+                                {
+                                    continue;
+                                }
+                                let handle = warden_handle_for_health.clone();
+                                if let Some(stop_signal) = spawn_health_monitor(
+                                    jail_def,
+                                    full_name,
+                                    &data_dir,
+                                    &rate_limit,
+                                    handle,
+                                    metrics.clone(),
+                                ) {
+                                    health_monitors.insert(full_name.clone(), stop_signal);
+                                }
+                            }
 
-                                tokio::time::sleep(tokio::time::Duration::from_secs(
-                                    checker.interval().as_secs()
-                                )).await;
+                            // Restart the snapshot scheduler with the new schedule and jails
+                            if let Some(stop_signal) = snapshot_scheduler.take() {
+                                stop_signal.store(true, Ordering::SeqCst);
                             }
-                            println!("Health monitor stopped for jail '{}'", checker.jail_name());
-                        });
+                            snapshot_scheduler =
+                                spawn_snapshot_scheduler(&snapshots, snapshot_zfs, &jails);
 
-                        println!("Spawned health monitor for jail '{}'", full_name);
+                            println!(
+                                "Configuration reloaded: {} jail(s) added, {} removed, {} changed",
+                                diff.added.len(),
+                                diff.removed.len(),
+                                diff.changed.len()
+                            );
+                        }
                     }
                 }
 
-                println!("Warden supervisor started. Press Ctrl+C to stop.");
-
-                // Wait for Ctrl+C
-                tokio::signal::ctrl_c().await.expect("Failed to listen for Ctrl+C");
-
                 println!("\nShutting down...");
 
                 // Stop all health checkers
-                for stop_signal in &health_stop_signals {
-                    stop_signal.store(true, std::sync::atomic::Ordering::SeqCst);
+                for stop_signal in health_monitors.values() {
+                    stop_signal.store(true, Ordering::SeqCst);
+                }
+                if !health_monitors.is_empty() {
+                    println!("Stopped {} health monitor(s)", health_monitors.len());
                 }
-                if !health_stop_signals.is_empty() {
-                    println!("Stopped {} health monitor(s)", health_stop_signals.len());
+                if let Some(stop_signal) = snapshot_scheduler {
+                    stop_signal.store(true, Ordering::SeqCst);
                 }
+                log_rotator.store(true, Ordering::SeqCst);
 
                 // Request Warden shutdown
                 warden::Warden::request_shutdown(&sender).await;
@@ -641,8 +1155,11 @@ data_dir = "/var/blackship"
             release,
             force,
             archives,
+            arch,
+            jobs,
+            rate_limit,
         } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let mut bs = provision::Provisioner::from_config(&config.config)?;
 
             // Override archives if specified on command line
@@ -653,15 +1170,24 @@ data_dir = "/var/blackship"
                     config.config.cache_dir.clone(),
                     archives,
                     config.config.retry.clone(),
-                )?;
+                )?
+                .with_mirrors(config.config.mirror_urls.clone())
+                .with_rate_limit(config.config.download_rate_limit());
+            }
+            if let Some(arch) = arch {
+                bs = bs.with_arch(arch);
+            }
+            bs = bs.with_jobs(jobs).with_offline(offline);
+            if let Some(rate) = rate_limit {
+                bs = bs.with_rate_limit(rate);
             }
 
             bs.bootstrap(&release, force)?;
         }
 
         Commands::Releases { action, json } => {
-            let config = manifest::load(&cli.config)?;
-            let bs = provision::Provisioner::from_config(&config.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
+            let bs = provision::Provisioner::from_config(&config.config)?.with_offline(offline);
 
             match action.unwrap_or(ReleasesAction::List) {
                 ReleasesAction::List => {
@@ -699,6 +1225,166 @@ data_dir = "/var/blackship"
                         std::process::exit(1);
                     }
                 }
+                ReleasesAction::VerifyJail { jail } => {
+                    let jail_def = config
+                        .get_jail(&jail)
+                        .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+                    let release = jail_def.release.as_deref().ok_or_else(|| {
+                        error::Error::ConfigValidation(format!(
+                            "Jail '{}' has no release to compare against",
+                            jail
+                        ))
+                    })?;
+                    let root = jail_def.effective_path(&config.config, &config.jail_name(&jail));
+                    let drift = bs.verify_jail(release, &root)?;
+
+                    if json {
+                        let json_data = serde_json::json!({
+                            "jail": jail,
+                            "release": release,
+                            "modified": drift.modified,
+                            "added": drift.added,
+                            "removed": drift.removed,
+                        });
+                        println!("{}", serde_json::to_string_pretty(&json_data).unwrap());
+                    } else if drift.is_clean() {
+                        println!("Jail '{}' matches release '{}'.", jail, release);
+                    } else {
+                        println!("Jail '{}' differs from release '{}':", jail, release);
+                        for (label, paths) in [
+                            ("M", &drift.modified),
+                            ("A", &drift.added),
+                            ("D", &drift.removed),
+                        ] {
+                            for path in paths {
+                                println!("  {} /{}", label, path.display());
+                            }
+                        }
+                        println!(
+                            "{} modified, {} added, {} removed",
+                            drift.modified.len(),
+                            drift.added.len(),
+                            drift.removed.len()
+                        );
+                    }
+                }
+                ReleasesAction::FetchLatest { arch, force } => {
+                    let bs = match arch {
+                        Some(arch) => bs.with_arch(arch),
+                        None => bs,
+                    };
+                    let release = bs.fetch_latest(force)?;
+                    println!("Latest release: {}", release);
+                }
+            }
+        }
+
+        Commands::AddJail {
+            name,
+            release,
+            network,
+            ip,
+            depends_on,
+            start,
+        } => {
+            let jail = manifest::edit::add_jail_file(
+                &config_path,
+                manifest::edit::NewJail {
+                    name: name.clone(),
+                    release,
+                    depends_on,
+                    network,
+                    ip,
+                },
+            )?;
+            println!("Added jail '{}' to {}", name, config_path.display());
+            if let Some(ip) = jail.ip {
+                println!("  IP: {}", ip);
+            }
+
+            if start {
+                let config = load_config(&config_path, cli.verbose)?;
+                let mut bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
+                bridge.up(Some(&name))?;
+            }
+        }
+
+        Commands::Config { action } => match action {
+            ConfigAction::Get { path } => {
+                let content =
+                    std::fs::read_to_string(&config_path).map_err(|e| error::Error::ConfigRead {
+                        path: config_path.clone(),
+                        source: e,
+                    })?;
+                println!("{}", manifest::edit::get(&content, &path)?);
+            }
+            ConfigAction::Set { path, value } => {
+                manifest::edit::set_file(&config_path, &path, &value)?;
+                println!("Set {} in {}", path, config_path.display());
+            }
+        },
+
+        Commands::Cache { action, json } => {
+            let config = load_config(&config_path, cli.verbose)?;
+            let bs = provision::Provisioner::from_config(&config.config)?;
+            let cache_dir = config.config.cache_dir.display();
+
+            match action.unwrap_or(CacheAction::List) {
+                CacheAction::List => {
+                    let files = bs.cached_files()?;
+                    if json {
+                        let json_data: Vec<_> = files
+                            .iter()
+                            .map(|f| {
+                                serde_json::json!({
+                                    "name": f.name,
+                                    "size": f.size,
+                                    "age_secs": f.age.as_secs(),
+                                    "partial": f.partial
+                                })
+                            })
+                            .collect();
+                        println!("{}", serde_json::to_string_pretty(&json_data).unwrap());
+                    } else if files.is_empty() {
+                        println!("Cache {} is empty.", cache_dir);
+                    } else {
+                        println!("{:<40} {:>8} {:>8}", "NAME", "SIZE", "AGE");
+                        for file in &files {
+                            let name = if file.partial {
+                                format!("{} (partial)", file.name)
+                            } else {
+                                file.name.clone()
+                            };
+                            println!(
+                                "{:<40} {:>8} {:>8}",
+                                name,
+                                bridge::format_size(file.size),
+                                bridge::format_uptime(file.age)
+                            );
+                        }
+                        let total: u64 = files.iter().map(|f| f.size).sum();
+                        println!("\n{} files, {} total", files.len(), bridge::format_size(total));
+                    }
+                }
+                CacheAction::Size => {
+                    let files = bs.cached_files()?;
+                    let total: u64 = files.iter().map(|f| f.size).sum();
+                    println!(
+                        "{} ({} files in {})",
+                        bridge::format_size(total),
+                        files.len(),
+                        cache_dir
+                    );
+                }
+                CacheAction::Clear { older_than } => {
+                    let removed = bs.clear_cache(older_than)?;
+                    let freed: u64 = removed.iter().map(|f| f.size).sum();
+                    println!(
+                        "Removed {} files, freed {}",
+                        removed.len(),
+                        bridge::format_size(freed)
+                    );
+                }
             }
         }
 
@@ -712,6 +1398,7 @@ data_dir = "/var/blackship"
                     subnet,
                     gateway,
                     bridge,
+                    members,
                 } => {
                     let subnet: IpNet = subnet.parse().map_err(|e| {
                         error::Error::Network(format!("Invalid subnet: {}", e))
@@ -725,6 +1412,16 @@ data_dir = "/var/blackship"
                         None
                     };
 
+                    // Check uplinks before touching the bridge
+                    for member in &members {
+                        if !network::ioctl::interface_exists(member)? {
+                            return Err(error::Error::Network(format!(
+                                "Interface '{}' does not exist",
+                                member
+                            )));
+                        }
+                    }
+
                     // Create bridge
                     let br = Bridge::create_or_open(&bridge)?;
 
@@ -739,6 +1436,18 @@ data_dir = "/var/blackship"
                     if let Some(gw) = gateway_ip {
                         println!("  Gateway: {}", gw);
                     }
+
+                    // Bridge the segment to the physical network
+                    let existing = br.members()?;
+                    for member in &members {
+                        if existing.contains(member) {
+                            println!("  Member: {} (already added)", member);
+                            continue;
+                        }
+                        network::ioctl::set_interface_up(member, true)?;
+                        br.add_member(member)?;
+                        println!("  Member: {}", member);
+                    }
                 }
                 NetworkAction::Destroy { name, force } => {
                     destroy_bridge(&name, force)?;
@@ -772,18 +1481,114 @@ data_dir = "/var/blackship"
                     println!("Detaching jail '{}' from network '{}'", jail, network);
                     println!("Note: Detach is done automatically during 'down'.");
                 }
+                NetworkAction::Gc { dry_run, force } => {
+                    let config = load_config(&config_path, cli.verbose)?;
+                    let bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
+                    bridge.gc_epairs(dry_run, force)?;
+                }
+                NetworkAction::Ping {
+                    jail,
+                    count,
+                    resolve,
+                } => {
+                    use network::ping::{ping_from_host, ping_from_jail, resolve_in_jail};
+
+                    let config = load_config(&config_path, cli.verbose)?;
+                    let (service_name, full_name) = config
+                        .resolve_jail_names(&jail)
+                        .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+                    let jail_def = config
+                        .get_jail(&service_name)
+                        .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+                    let jid = jail::jail_getid(&full_name)
+                        .map_err(|_| error::Error::JailNotRunning(full_name.clone()))?;
+                    let net = jail_def.network.as_ref().ok_or_else(|| {
+                        error::Error::Network(format!("Jail '{}' has no network config", jail))
+                    })?;
+
+                    // VNET jails ping their gateway from inside; shared-IP jails are
+                    // pinged from the host
+                    let stats = if net.vnet {
+                        let gateway = config.jail_gateway(jail_def).ok_or_else(|| {
+                            error::Error::Network(format!(
+                                "No gateway configured for VNET jail '{}'",
+                                jail
+                            ))
+                        })?;
+                        println!("Pinging gateway {} from inside '{}'...", gateway, full_name);
+                        ping_from_jail(jid, gateway, count)?
+                    } else {
+                        let ip = net.address().ok_or_else(|| {
+                            error::Error::Network(format!(
+                                "No IP address configured for jail '{}'",
+                                jail
+                            ))
+                        })?;
+                        println!("Pinging '{}' at {} from the host...", full_name, ip);
+                        ping_from_host(ip, count)?
+                    };
+
+                    let latency = stats
+                        .avg_ms
+                        .map(|ms| format!(", avg {:.3} ms", ms))
+                        .unwrap_or_default();
+                    println!(
+                        "  Ping: {}/{} received, {:.1}% loss{}",
+                        stats.received, stats.transmitted, stats.loss, latency
+                    );
+
+                    // DNS is resolved inside the jail so its own resolv.conf is used
+                    let dns_ok = match resolve_in_jail(jid, &resolve) {
+                        Ok(_) => {
+                            println!("  DNS: resolved '{}'", resolve);
+                            true
+                        }
+                        Err(e) => {
+                            println!("  DNS: {}", e);
+                            false
+                        }
+                    };
+
+                    if !stats.reachable() || !dns_ok {
+                        std::process::exit(1);
+                    }
+                }
+            }
+        }
+
+        Commands::Health {
+            action: Some(HealthAction::Reset { jail }),
+            ..
+        } => {
+            let config = load_config(&config_path, cli.verbose)?;
+            let (_service_name, full_name) = config
+                .resolve_jail_names(&jail)
+                .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+
+            // The supervise health monitor picks this up on its next cycle
+            let marker = sickbay::breaker_reset_marker(&config.config.data_dir, &full_name);
+            if let Some(parent) = marker.parent() {
+                std::fs::create_dir_all(parent)?;
             }
+            std::fs::write(&marker, b"")?;
+            println!(
+                "Requested circuit breaker reset for jail '{}' (applied by `blackship supervise`)",
+                full_name
+            );
         }
 
         Commands::Health {
+            action: None,
             jail,
             watch,
+            once,
+            wait,
             interval,
             json,
         } => {
             use sickbay::{HealthChecker, HealthStatus};
 
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
 
             // Filter jails based on input
             let jails: Vec<_> = if let Some(jail_name) = &jail {
@@ -846,11 +1651,35 @@ data_dir = "/var/blackship"
                 println!("{}", "-".repeat(54));
             }
 
+            let wait_deadline =
+                wait.map(|secs| std::time::Instant::now() + std::time::Duration::from_secs(secs));
+
             loop {
                 let mut json_results: Vec<serde_json::Value> = Vec::new();
 
-                for checker in &mut checkers {
-                    let status = checker.run_checks()?;
+                let statuses = checkers
+                    .iter_mut()
+                    .map(|checker| checker.run_all_checks())
+                    .collect::<Result<Vec<_>>>()?;
+                let all_healthy = checkers
+                    .iter()
+                    .zip(&statuses)
+                    .all(|(checker, status)| {
+                        *status == HealthStatus::Healthy || !checker.is_enabled()
+                    });
+
+                // With --wait, keep polling (without output) until healthy or timed out
+                if let Some(deadline) = wait_deadline
+                    && !all_healthy
+                {
+                    let remaining = deadline.saturating_duration_since(std::time::Instant::now());
+                    if !remaining.is_zero() {
+                        std::thread::sleep(remaining.min(std::time::Duration::from_secs(interval)));
+                        continue;
+                    }
+                }
+
+                for (checker, status) in checkers.iter().zip(statuses) {
                     let check_results = checker.get_check_results();
 
                     if json {
@@ -865,14 +1694,15 @@ data_dir = "/var/blackship"
 
                         let checks: Vec<_> = check_results
                             .iter()
-                            .map(|(check, result, failures)| {
+                            .map(|(check, result, failures, breaker)| {
                                 serde_json::json!({
                                     "name": check.name,
                                     "passed": result.as_ref().map(|r| r.passed),
                                     "output": result.as_ref().map(|r| r.output.clone()),
                                     "duration_ms": result.as_ref().map(|r| r.duration.as_millis()),
                                     "age_ms": result.as_ref().map(|r| r.age().as_millis()),
-                                    "failures": failures
+                                    "failures": failures,
+                                    "breaker": breaker
                                 })
                             })
                             .collect();
@@ -885,8 +1715,8 @@ data_dir = "/var/blackship"
                     } else {
                         let checks_summary: String = check_results
                             .iter()
-                            .map(|(_check, result, failures)| {
-                                match result {
+                            .map(|(_check, result, failures, breaker)| {
+                                let summary = match result {
                                     Some(r) => {
                                         let summary = r.summary();
                                         if *failures > 0 {
@@ -896,6 +1726,11 @@ data_dir = "/var/blackship"
                                         }
                                     }
                                     None => "?".to_string(),
+                                };
+                                if *breaker == "closed" {
+                                    summary
+                                } else {
+                                    format!("{} [breaker {}]", summary, breaker)
                                 }
                             })
                             .collect::<Vec<_>>()
@@ -924,6 +1759,9 @@ data_dir = "/var/blackship"
                 }
 
                 if !watch {
+                    if (once || wait.is_some()) && !all_healthy {
+                        std::process::exit(1);
+                    }
                     break;
                 }
 
@@ -935,12 +1773,24 @@ data_dir = "/var/blackship"
             }
         }
 
+        Commands::Wait {
+            jail,
+            condition,
+            timeout,
+            interval,
+        } => {
+            let config = load_config(&config_path, cli.verbose)?;
+            bridge::wait_for(&config, &jail, condition, timeout, interval)?;
+        }
+
         Commands::Build {
             file,
             name,
             build_args,
             context,
             dry_run,
+            target,
+            snapshot_layers,
         } => {
             use blueprint::{parse_jailfile, BuildContext, TemplateExecutor};
 
@@ -959,7 +1809,10 @@ data_dir = "/var/blackship"
                     e
                 ))
             })?;
-            let jailfile = parse_jailfile(&content)?;
+            let mut jailfile = parse_jailfile(&content)?;
+            if let Some(target) = &target {
+                jailfile.truncate_to_stage(target)?;
+            }
 
             // Determine jail name
             let service_name = name
@@ -967,13 +1820,43 @@ data_dir = "/var/blackship"
                 .unwrap_or_else(|| "unnamed".to_string());
 
             // Determine target path
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let full_name = config.jail_name(&service_name);
-            let target_path = config.config.data_dir.join("jails").join(&full_name);
+
+            // Layer snapshots need the jail root to be its own ZFS dataset
+            let layer_zfs = if snapshot_layers && !dry_run {
+                if let Some(pool) = config.config.zpool.as_ref()
+                    && config.config.zfs_enabled
+                {
+                    let zfs = zfs::ZfsManager::new(pool, &config.config.dataset);
+                    zfs.init()?;
+                    Some(zfs)
+                } else {
+                    eprintln!(
+                        "Warning: --snapshot-layers requires zfs_enabled; \
+                         building without layer snapshots"
+                    );
+                    None
+                }
+            } else {
+                None
+            };
+            let target_path = match &layer_zfs {
+                Some(zfs) => zfs.jail_path(&full_name),
+                None => config.config.data_dir.join("jails").join(&full_name),
+            };
+            let mut fresh_root = !target_path.exists();
+            if let Some(zfs) = &layer_zfs
+                && !zfs.dataset_exists(&zfs.get_jail_dataset(&full_name))?
+            {
+                let quota = config.get_jail(&service_name).and_then(|j| j.quota.as_deref());
+                zfs.create_jail_dataset(&full_name, quota)?;
+                fresh_root = true;
+            }
 
             // Check if base release exists and copy it
             if let Some(release) = &jailfile.from {
-                let bs = provision::Provisioner::from_config(&config.config)?;
+                let bs = provision::Provisioner::from_config(&config.config)?.with_offline(offline);
                 let release_path = config.config.releases_dir.join(release);
 
                 if !release_path.exists() {
@@ -982,7 +1865,7 @@ data_dir = "/var/blackship"
                 }
 
                 // Copy release to target (if not dry run)
-                if !dry_run && !target_path.exists() {
+                if !dry_run && fresh_root {
                     println!("Creating jail root from {}...", release);
                     std::fs::create_dir_all(&target_path)?;
                     // Use cp -a for full copy preserving permissions
@@ -1004,6 +1887,18 @@ data_dir = "/var/blackship"
                 }
             }
 
+            // Intermediate stages are built from their own releases
+            let stages = jailfile.stages();
+            let (_, intermediate) = stages.split_last().expect("a Jailfile has at least one stage");
+            for release in intermediate.iter().filter_map(|stage| stage.release) {
+                if !dry_run && !config.config.releases_dir.join(release).exists() {
+                    println!("Stage release '{}' not found. Bootstrapping...", release);
+                    provision::Provisioner::from_config(&config.config)?
+                        .with_offline(offline)
+                        .bootstrap(release, false)?;
+                }
+            }
+
             // Create build context
             let mut ctx =
                 BuildContext::new(&context_dir, &target_path, &full_name).verbose(cli.verbose);
@@ -1014,7 +1909,15 @@ data_dir = "/var/blackship"
             }
 
             // Create and run executor
-            let mut executor = TemplateExecutor::new(ctx).dry_run(dry_run);
+            let mut executor = TemplateExecutor::new(ctx)
+                .dry_run(dry_run)
+                .devfs_ruleset(config.config.build_devfs_ruleset)
+                .run_timeout(config.config.build_run_timeout)
+                .nameservers(&config.config.build_nameservers)
+                .releases_dir(&config.config.releases_dir);
+            if let Some(zfs) = layer_zfs {
+                executor = executor.snapshot_layers(zfs);
+            }
 
             if dry_run {
                 println!("=== DRY RUN - No changes will be made ===\n");
@@ -1110,7 +2013,7 @@ data_dir = "/var/blackship"
                     scan_directory(&templates_subdir, &mut templates);
 
                     // 3. Try to load config and check data_dir/templates
-                    if let Ok(config) = manifest::load(&cli.config) {
+                    if let Ok(config) = manifest::load(&config_path) {
                         let data_templates = config.config.data_dir.join("templates");
                         scan_directory(&data_templates, &mut templates);
                     }
@@ -1198,7 +2101,7 @@ data_dir = "/var/blackship"
                         println!("\nInstructions ({}):", jailfile.instructions.len());
                         for instr in &jailfile.instructions {
                             match instr {
-                                Instruction::Run(cmd) => println!("  RUN {}", cmd),
+                                Instruction::Run(cmd, _) => println!("  RUN {}", cmd),
                                 Instruction::Copy(spec) => {
                                     println!("  COPY {} -> {}", spec.src, spec.dest)
                                 }
@@ -1242,6 +2145,67 @@ data_dir = "/var/blackship"
                         }
                     }
                 }
+
+                TemplateAction::Generate { jail, output, force } => {
+                    use blueprint::generate::{self, GeneratedJailfile};
+
+                    let config = load_config(&config_path, cli.verbose)?;
+                    let (service_name, full_name) = config
+                        .resolve_jail_names(&jail)
+                        .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+                    let jail_def = config
+                        .get_jail(&service_name)
+                        .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+                    let root = jail_def.effective_path(&config.config, &full_name);
+                    let release = jail_def
+                        .release
+                        .clone()
+                        .or_else(|| generate::userland_release(&root))
+                        .ok_or_else(|| {
+                            error::Error::ConfigValidation(format!(
+                                "Cannot tell which release jail '{}' uses; set `release`",
+                                jail
+                            ))
+                        })?;
+
+                    let out_dir = output.unwrap_or_else(|| std::path::PathBuf::from(&service_name));
+                    if out_dir.join("Jailfile").exists() && !force {
+                        eprintln!(
+                            "Error: {} already exists. Use -y/--force to overwrite.",
+                            out_dir.join("Jailfile").display()
+                        );
+                        std::process::exit(1);
+                    }
+
+                    let mut generated = GeneratedJailfile::new(&release);
+                    let bs = provision::Provisioner::from_config(&config.config)?
+                        .with_offline(offline);
+                    if bs.is_bootstrapped(&release) {
+                        let drift = provision::detect_drift(&bs.release_path(&release), &root)?;
+                        generated = generated.with_drift(&drift);
+                    } else {
+                        generated.notes.push(format!(
+                            "release {} is not bootstrapped; configuration files were not captured",
+                            release
+                        ));
+                    }
+                    match generate::installed_packages(&root) {
+                        Ok(packages) => generated.packages = packages,
+                        Err(e) => generated.notes.push(format!("packages not detected: {}", e)),
+                    }
+
+                    let jailfile = generated.write(&root, &out_dir)?;
+                    println!(
+                        "Generated {} ({} package(s), {} config file(s))",
+                        jailfile.display(),
+                        generated.packages.len(),
+                        generated.config_files.len()
+                    );
+                    for note in &generated.notes {
+                        eprintln!("Warning: {}", note);
+                    }
+                    println!("Review it before building: this is a best-effort reconstruction.");
+                }
             }
         }
 
@@ -1254,7 +2218,7 @@ data_dir = "/var/blackship"
         } => {
             use std::net::IpAddr;
 
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let mut bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
 
             // Parse bind IP if provided
@@ -1287,7 +2251,7 @@ data_dir = "/var/blackship"
         }
 
         Commands::Ports { jail } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let bridge = bridge::Bridge::new(config)?;
 
             println!("Port forwarding status:");
@@ -1327,7 +2291,7 @@ data_dir = "/var/blackship"
         }
 
         Commands::Unexpose { jail } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let (_service_name, full_name) = config
                 .resolve_jail_names(&jail)
                 .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
@@ -1336,18 +2300,30 @@ data_dir = "/var/blackship"
             println!("Removed all port forwards for jail '{}'", full_name);
         }
 
-        Commands::Cleanup { jail, force } => {
-            let config = manifest::load(&cli.config)?;
+        Commands::Cleanup { jail, all: _, force } => {
+            let config = load_config(&config_path, cli.verbose)?;
             let mut bridge = bridge::Bridge::new(config)?;
-            bridge.cleanup(&jail, force)?;
+            match jail {
+                Some(jail) => bridge.cleanup(&jail, force)?,
+                None => bridge.cleanup_failed(force)?,
+            }
+        }
+
+        Commands::Prune { dry_run, force } => {
+            let config = load_config(&config_path, cli.verbose)?;
+            let bridge = bridge::Bridge::new(config)?;
+            bridge.prune(dry_run, force)?;
         }
 
         Commands::Export {
             jail,
             output,
             zfs_send,
+            reproducible,
+            compress_level,
+            threads,
         } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
 
             // Find jail config
             let (service_name, full_name) = config
@@ -1383,12 +2359,52 @@ data_dir = "/var/blackship"
                 let dataset = format!("{}/{}/jails/{}", pool, config.config.dataset, full_name);
                 export::export_jail_zfs(&full_name, &dataset, &output_path, hostname, ip.as_deref())?;
             } else {
-                export::export_jail(&full_name, &jail_path, &output_path, hostname, ip.as_deref())?;
+                export::export_jail(
+                    &full_name,
+                    &jail_path,
+                    &output_path,
+                    hostname,
+                    ip.as_deref(),
+                    &export::ExportOptions {
+                        reproducible,
+                        level: compress_level,
+                        threads,
+                    },
+                )?;
             }
         }
 
-        Commands::Import { file, name, force } => {
-            let config = manifest::load(&cli.config)?;
+        Commands::Backup { jail, to, port } => {
+            let config = load_config(&config_path, cli.verbose)?;
+            let target = backup::BackupTarget::parse(&to)?.with_port(port);
+
+            let (_, full_name) = config
+                .resolve_jail_names(&jail)
+                .ok_or_else(|| error::Error::JailNotFound(jail.clone()))?;
+            if !config.config.zfs_enabled {
+                return Err(error::Error::ZfsNotEnabled);
+            }
+            let pool = config
+                .config
+                .zpool
+                .as_ref()
+                .ok_or(error::Error::ZfsNotEnabled)?;
+            let dataset = format!("{}/{}/jails/{}", pool, config.config.dataset, full_name);
+            backup::backup_jail_zfs(&dataset, &target)?;
+        }
+
+        Commands::Import {
+            file,
+            name,
+            force,
+            verify,
+        } => {
+            let config = load_config(&config_path, cli.verbose)?;
+
+            if verify {
+                export::verify_archive(&file)?;
+                println!("Checksum verified: {}", file.display());
+            }
 
             // Determine target path
             let metadata = export::read_metadata(&file)?;
@@ -1413,7 +2429,7 @@ data_dir = "/var/blackship"
         }
 
         Commands::Snapshot { action } => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
 
             // Require ZFS for snapshots
             if !config.config.zfs_enabled {
@@ -1524,29 +2540,33 @@ data_dir = "/var/blackship"
         }
 
         Commands::Clone { source, name } => {
-            let config = manifest::load(&cli.config)?;
-
-            // Require ZFS for cloning
-            if !config.config.zfs_enabled {
-                return Err(error::Error::ZfsNotEnabled);
-            }
+            let config = load_config(&config_path, cli.verbose)?;
 
-            // Parse source format: jail@snapshot
-            let parts: Vec<&str> = source.split('@').collect();
-            if parts.len() != 2 {
+            // Parse source format: jail@snapshot (ZFS) or jail (filesystem copy)
+            let (source_jail, snapshot) = match source.split_once('@') {
+                Some((jail, snapshot)) => (jail, Some(snapshot)),
+                None => (source.as_str(), None),
+            };
+            if config.config.zfs_enabled && snapshot.is_none() {
                 return Err(error::Error::JailOperation(
                     "Source must be in format 'jail@snapshot'".into(),
                 ));
             }
-            let (source_jail, snapshot) = (parts[0], parts[1]);
+            if !config.config.zfs_enabled && snapshot.is_some() {
+                return Err(error::Error::JailOperation(
+                    "Cloning from a snapshot requires ZFS; \
+                     pass just the jail name to copy it instead"
+                        .into(),
+                ));
+            }
 
             // Verify source jail exists
             let (source_service, source_full) = config
                 .resolve_jail_names(source_jail)
                 .ok_or_else(|| error::Error::JailNotFound(source_jail.to_string()))?;
-            if config.get_jail(&source_service).is_none() {
-                return Err(error::Error::JailNotFound(source_jail.to_string()));
-            }
+            let source_def = config
+                .get_jail(&source_service)
+                .ok_or_else(|| error::Error::JailNotFound(source_jail.to_string()))?;
 
             // Check new name doesn't already exist
             if config.resolve_jail_names(&name).is_some() {
@@ -1556,30 +2576,52 @@ data_dir = "/var/blackship"
                 )));
             }
 
-            let pool = config
-                .config
-                .zpool
-                .as_ref()
-                .ok_or(error::Error::ZfsNotEnabled)?;
-            let zfs = zfs::ZfsManager::new(pool, &config.config.dataset);
-
             let new_full_name = config.jail_name(&name);
-            let new_path = zfs.clone_from_snapshot(&source_full, snapshot, &new_full_name)?;
+            let new_path = if let Some(snapshot) = snapshot {
+                let pool = config
+                    .config
+                    .zpool
+                    .as_ref()
+                    .ok_or(error::Error::ZfsNotEnabled)?;
+                let zfs = zfs::ZfsManager::new(pool, &config.config.dataset);
+                let new_path = zfs.clone_from_snapshot(&source_full, snapshot, &new_full_name)?;
+                println!(
+                    "Cloned '{}@{}' to new jail '{}'",
+                    source_full, snapshot, new_full_name
+                );
+                new_path
+            } else {
+                eprintln!(
+                    "Warning: ZFS is not enabled; copying the jail root. \
+                     The copy is not copy-on-write and uses as much space as the source."
+                );
+                if jail::jail_getid(&source_full).is_ok() {
+                    eprintln!(
+                        "Warning: Jail '{}' is running; files changing during the copy \
+                         may be inconsistent",
+                        source_full
+                    );
+                }
+                let source_path = source_def.effective_path(&config.config, &source_full);
+                let new_path = config.config.data_dir.join("jails").join(&new_full_name);
+                export::copy_jail(&source_path, &new_path, &name)?;
+                println!("Copied '{}' to new jail '{}'", source_full, new_full_name);
+                new_path
+            };
 
-            println!(
-                "Cloned '{}@{}' to new jail '{}'",
-                source_full, snapshot, new_full_name
-            );
             println!("Path: {}", new_path.display());
             println!("\nTo use this jail, add it to blackship.toml:");
             println!("  [[jails]]");
             println!("  name = \"{}\"", name);
             println!("  path = \"{}\"", new_path.display());
+            if snapshot.is_none() {
+                println!("  hostname = \"{}\"", name);
+            }
         }
 
         // Commands that require config and bridge
         _ => {
-            let config = manifest::load(&cli.config)?;
+            let config = load_config(&config_path, cli.verbose)?;
             let mut bridge = bridge::Bridge::new(config)?.verbose(cli.verbose);
 
             match cli.command {
@@ -1595,33 +2637,78 @@ data_dir = "/var/blackship"
                         bridge.up(jail.as_deref())?;
                     }
                 }
-                Commands::Down { jail, all, dry_run } => {
+                Commands::Down {
+                    jail,
+                    all,
+                    remove,
+                    dry_run,
+                } => {
                     // Require either jail name or --all
                     if jail.is_none() && !all {
                         eprintln!("Error: specify a jail name or use --all to stop all jails");
                         std::process::exit(1);
                     }
                     if dry_run {
-                        bridge.down_dry_run(jail.as_deref())?;
+                        bridge.down_dry_run(jail.as_deref(), remove)?;
+                    } else if remove {
+                        bridge.down_and_remove(jail.as_deref())?;
                     } else {
                         bridge.down(jail.as_deref())?;
                     }
                 }
-                Commands::Restart { jail, all, dry_run } => {
+                Commands::Restart {
+                    jail,
+                    all,
+                    dry_run,
+                    rolling,
+                    batch_size,
+                    timeout,
+                } => {
                     // Require either jail name or --all
                     if jail.is_none() && !all {
                         eprintln!("Error: specify a jail name or use --all to restart all jails");
                         std::process::exit(1);
                     }
                     if dry_run {
-                        bridge.down_dry_run(jail.as_deref())?;
+                        bridge.down_dry_run(jail.as_deref(), false)?;
                         bridge.up_dry_run(jail.as_deref())?;
+                    } else if rolling {
+                        bridge.rolling_restart(jail.as_deref(), batch_size, timeout)?;
                     } else {
                         bridge.restart(jail.as_deref())?;
                     }
                 }
-                Commands::Ps { json } => {
-                    bridge.ps(json)?;
+                Commands::Scale { targets } => {
+                    for (name, replicas) in targets {
+                        let instances = bridge.scale(&name, replicas)?;
+                        println!("'{}' scaled to {} instance(s):", name, instances.len());
+                        for instance in instances {
+                            println!("  {}", instance);
+                        }
+                    }
+                }
+                Commands::Pause { jail } => {
+                    bridge.pause(&jail)?;
+                }
+                Commands::Resume { jail } => {
+                    bridge.resume(&jail)?;
+                }
+                Commands::Ps {
+                    json,
+                    filters,
+                    columns,
+                    sort,
+                } => {
+                    bridge.ps(json, &filters, &columns, sort)?;
+                }
+                Commands::Du { json, sort } => {
+                    bridge.du(json, sort)?;
+                }
+                Commands::Graph { format } => {
+                    print!("{}", bridge.graph(format)?);
+                }
+                Commands::Rename { jail, new_name } => {
+                    bridge.rename(&jail, &new_name)?;
                 }
                 Commands::Check => {
                     bridge.check()?;
@@ -1639,18 +2726,24 @@ data_dir = "/var/blackship"
                 | Commands::Init { .. }
                 | Commands::Armada { .. }
                 | Commands::Logs { .. }
-                | Commands::Supervise
+                | Commands::Supervise { .. }
                 | Commands::Bootstrap { .. }
                 | Commands::Releases { .. }
+                | Commands::Cache { .. }
+                | Commands::Config { .. }
+                | Commands::AddJail { .. }
                 | Commands::Network { .. }
                 | Commands::Health { .. }
+                | Commands::Wait { .. }
                 | Commands::Build { .. }
                 | Commands::Template { .. }
                 | Commands::Expose { .. }
                 | Commands::Ports { .. }
                 | Commands::Unexpose { .. }
                 | Commands::Cleanup { .. }
+                | Commands::Prune { .. }
                 | Commands::Export { .. }
+                | Commands::Backup { .. }
                 | Commands::Import { .. }
                 | Commands::Snapshot { .. }
                 | Commands::Clone { .. } => unreachable!(),
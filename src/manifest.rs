@@ -5,20 +5,91 @@
 use crate::error::{Error, Result};
 use crate::sickbay::checker::HealthCheckConfig;
 use crate::hooks::Hook;
-use serde::Deserialize;
+use crate::jail::mount::MountSpec;
+use crate::network::epair::EpairNaming;
+use ipnet::IpNet;
+use serde::{Deserialize, Serialize};
 use std::collections::HashMap;
 use std::fs;
 use std::net::IpAddr;
 use std::path::{Path, PathBuf};
+use std::time::Duration;
+
+pub mod compose;
+pub mod edit;
+
+/// Default configuration file name
+pub const DEFAULT_CONFIG_FILE: &str = "blackship.toml";
+
+/// Configuration file names searched by discovery, in order of preference
+const CONFIG_FILE_NAMES: &[&str] = &[
+    DEFAULT_CONFIG_FILE,
+    "blackship.yaml",
+    "blackship.yml",
+    "blackship.json",
+];
+
+/// Find the nearest configuration file, starting at `start` and walking up its ancestors
+///
+/// Stops at the first directory containing a `blackship.{toml,yaml,yml,json}` file
+/// (like git does for `.git`). TOML wins when several formats sit side by side.
+pub fn discover(start: &Path) -> Option<PathBuf> {
+    start.ancestors().find_map(|dir| {
+        CONFIG_FILE_NAMES
+            .iter()
+            .map(|name| dir.join(name))
+            .find(|candidate| candidate.is_file())
+    })
+}
+
+/// Configuration file format
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ConfigFormat {
+    /// TOML (default)
+    Toml,
+    /// YAML (.yaml/.yml)
+    Yaml,
+    /// JSON (.json)
+    Json,
+}
+
+impl ConfigFormat {
+    /// Detect the format from a file extension, defaulting to TOML
+    pub fn from_path(path: &Path) -> Self {
+        match path
+            .extension()
+            .and_then(|e| e.to_str())
+            .map(|e| e.to_ascii_lowercase())
+            .as_deref()
+        {
+            Some("yaml") | Some("yml") => ConfigFormat::Yaml,
+            Some("json") => ConfigFormat::Json,
+            _ => ConfigFormat::Toml,
+        }
+    }
+
+    /// Deserialize a configuration in this format
+    pub fn parse(self, content: &str) -> Result<BlackshipConfig> {
+        Ok(match self {
+            ConfigFormat::Toml => toml::from_str(content)?,
+            ConfigFormat::Yaml => serde_yaml::from_str(content)?,
+            ConfigFormat::Json => serde_json::from_str(content)?,
+        })
+    }
+}
 
 /// Load configuration from a file
+///
+/// The format (TOML, YAML or JSON) is detected from the file extension.
 pub fn load(path: &Path) -> Result<BlackshipConfig> {
     let content = fs::read_to_string(path).map_err(|e| Error::ConfigRead {
         path: path.to_path_buf(),
         source: e,
     })?;
 
-    let mut config: BlackshipConfig = toml::from_str(&content)?;
+    let mut config = ConfigFormat::from_path(path).parse(&content)?;
+    config.resolve_presets()?;
+    config.expand_instances()?;
 
     // Set default project name from directory if not specified
     if config.config.project.is_none() {
@@ -37,6 +108,7 @@ pub fn load(path: &Path) -> Result<BlackshipConfig> {
         config.config.project = Some(project_name);
     }
 
+    config.link_parents();
     config.validate()?;
 
     Ok(config)
@@ -46,12 +118,21 @@ pub fn load(path: &Path) -> Result<BlackshipConfig> {
 ///
 /// Files are merged in order, with later files overriding earlier ones.
 /// Uses Docker Compose-style deep merge: later files only override fields they specify.
+/// Files may mix formats; each is parsed according to its extension.
 pub fn load_merged(paths: &[PathBuf]) -> Result<BlackshipConfig> {
+    load_merged_explained(paths).map(|(config, _)| config)
+}
+
+/// Load and merge multiple configuration files, tracking which file provided each field
+///
+/// Same semantics as [`load_merged`], additionally returning the [`Provenance`] of the result.
+pub fn load_merged_explained(paths: &[PathBuf]) -> Result<(BlackshipConfig, Provenance)> {
     if paths.is_empty() {
         return Err(Error::ConfigValidation("No configuration files provided".into()));
     }
 
     let mut base: Option<BlackshipConfig> = None;
+    let mut provenance = Provenance::default();
     let first_path = &paths[0];
 
     for path in paths {
@@ -60,7 +141,8 @@ pub fn load_merged(paths: &[PathBuf]) -> Result<BlackshipConfig> {
             source: e,
         })?;
 
-        let config: BlackshipConfig = toml::from_str(&content)?;
+        let config = ConfigFormat::from_path(path).parse(&content)?;
+        provenance.record(&config, path);
 
         base = Some(match base {
             None => config,
@@ -69,6 +151,8 @@ pub fn load_merged(paths: &[PathBuf]) -> Result<BlackshipConfig> {
     }
 
     let mut config = base.unwrap();
+    config.resolve_presets()?;
+    config.expand_instances()?;
 
     // Set default project name from first config's directory if not specified
     if config.config.project.is_none() {
@@ -87,12 +171,149 @@ pub fn load_merged(paths: &[PathBuf]) -> Result<BlackshipConfig> {
         config.config.project = Some(project_name);
     }
 
+    config.link_parents();
     config.validate()?;
-    Ok(config)
+    Ok((config, provenance))
+}
+
+/// Source file of each field in a merged configuration
+///
+/// Keys are dotted field paths such as `config.data_dir`, `networks.backend`,
+/// `jails.web.path` or `jails.web.params.allow.raw_sockets`.
+#[derive(Debug, Default)]
+pub struct Provenance {
+    sources: std::collections::BTreeMap<String, PathBuf>,
+}
+
+impl Provenance {
+    /// Get the file that provided the final value of a field
+    pub fn source(&self, field: &str) -> Option<&Path> {
+        self.sources.get(field).map(PathBuf::as_path)
+    }
+
+    /// Iterate over all fields and their source files, sorted by field
+    pub fn iter(&self) -> impl Iterator<Item = (&str, &Path)> {
+        self.sources.iter().map(|(k, v)| (k.as_str(), v.as_path()))
+    }
+
+    /// Record the fields a file contributes, mirroring the rules of `BlackshipConfig::merge`
+    ///
+    /// Must be called once per file, in merge order.
+    fn record(&mut self, config: &BlackshipConfig, path: &Path) {
+        let mut set = |field: String, provided: bool| {
+            if provided {
+                self.sources.insert(field, path.to_path_buf());
+            }
+        };
+
+        for (name, provided) in global_fields(&config.config) {
+            set(format!("config.{}", name), provided);
+        }
+        for net in &config.networks {
+            set(format!("networks.{}", net.name), true);
+        }
+        set("notifications".into(), config.notifications.is_some());
+        for (name, provided) in logging_fields(&config.logging) {
+            set(format!("logging.{}", name), provided);
+        }
+        for (name, provided) in snapshots_fields(&config.snapshots) {
+            set(format!("snapshots.{}", name), provided);
+        }
+        for name in config.presets.keys() {
+            set(format!("presets.{}", name), true);
+        }
+
+        for jail in &config.jails {
+            for (name, provided) in jail_fields(jail) {
+                set(format!("jails.{}.{}", jail.name, name), provided);
+            }
+            for key in jail.params.keys() {
+                set(format!("jails.{}.params.{}", jail.name, key), true);
+            }
+        }
+    }
+}
+
+/// `[config]` fields and whether a file provides them, mirroring `GlobalConfig::merge`
+fn global_fields(global: &GlobalConfig) -> Vec<(&'static str, bool)> {
+    vec![
+        // Always taken from the later file
+        ("data_dir", true),
+        ("zfs_enabled", true),
+        ("rate_limit", true),
+        ("health", true),
+        ("retry", true),
+        ("supervise", true),
+        // Taken from the later file only when set (or non-default)
+        ("project", global.project.is_some()),
+        ("zpool", global.zpool.is_some()),
+        ("dataset", global.dataset != default_dataset()),
+        ("releases_dir", global.releases_dir != default_releases_dir()),
+        ("cache_dir", global.cache_dir != default_cache_dir()),
+        ("mirror_url", global.mirror_url != default_mirror_url()),
+        ("mirror_urls", !global.mirror_urls.is_empty()),
+        ("download_rate_limit", global.download_rate_limit.is_some()),
+        ("bootstrap_archives", global.bootstrap_archives != default_bootstrap_archives()),
+        ("build_devfs_ruleset", global.build_devfs_ruleset != default_build_devfs_ruleset()),
+        ("build_run_timeout", global.build_run_timeout != 0),
+        ("build_nameservers", !global.build_nameservers.is_empty()),
+        ("bridge", global.bridge.is_some()),
+        ("epair_naming", global.epair_naming != EpairNaming::default()),
+        ("sibling_hosts", global.sibling_hosts.is_some()),
+        ("hooks", !global.hooks.is_empty()),
+    ]
+}
+
+/// `[logging]` fields and whether a file provides them
+fn logging_fields(logging: &LoggingConfig) -> Vec<(&'static str, bool)> {
+    vec![
+        ("syslog", logging.syslog.is_some()),
+        ("max_size", logging.max_size.is_some()),
+        ("keep", logging.keep != default_log_keep()),
+    ]
+}
+
+/// `[snapshots]` fields and whether a file provides them
+fn snapshots_fields(snapshots: &SnapshotsConfig) -> Vec<(&'static str, bool)> {
+    vec![
+        ("schedule", snapshots.schedule.is_some()),
+        ("keep", snapshots.keep != default_snapshot_keep()),
+    ]
+}
+
+/// Jail fields (except `params`, recorded per key) and whether a file
+/// provides them, mirroring `JailDef::merge`
+fn jail_fields(jail: &JailDef) -> Vec<(&'static str, bool)> {
+    vec![
+        ("name", true),
+        ("path", jail.path.is_some()),
+        ("release", jail.release.is_some()),
+        ("build", jail.build.is_some()),
+        ("jailfile", jail.jailfile.is_some()),
+        ("hostname", jail.hostname.is_some()),
+        ("quota", jail.quota.is_some()),
+        ("depends_on", !jail.depends_on.is_empty()),
+        ("conditions", !jail.conditions.is_empty()),
+        ("autostart", jail.autostart.is_some()),
+        ("oneshot", jail.oneshot.is_some()),
+        ("restart", jail.restart.is_some()),
+        ("readiness", jail.readiness.is_some()),
+        ("start_timeout", jail.start_timeout.is_some()),
+        ("preset", jail.preset.is_some()),
+        ("securelevel", jail.securelevel.is_some()),
+        ("devfs_ruleset", jail.devfs_ruleset.is_some()),
+        ("count", jail.count.is_some()),
+        ("parent", jail.parent.is_some()),
+        ("network", jail.network.is_some()),
+        ("mount", jail.mount.is_some()),
+        ("hooks", !jail.hooks.is_empty()),
+        ("skip_hooks", !jail.skip_hooks.is_empty()),
+        ("healthcheck", jail.healthcheck.enabled || !jail.healthcheck.checks.is_empty()),
+    ]
 }
 
 /// Root configuration structure
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct BlackshipConfig {
     /// Global configuration settings
     pub config: GlobalConfig,
@@ -105,6 +326,28 @@ pub struct BlackshipConfig {
     /// Jail definitions
     #[serde(default)]
     pub jails: Vec<JailDef>,
+
+    /// Failure/recovery notifications sent by the Warden
+    #[serde(default)]
+    pub notifications: Option<NotificationConfig>,
+
+    /// Event logging settings (`[logging]`)
+    #[serde(default)]
+    pub logging: LoggingConfig,
+
+    /// Automatic snapshots taken while supervising (`[snapshots]`)
+    #[serde(default)]
+    pub snapshots: SnapshotsConfig,
+
+    /// Named jail parameter sets (`[presets.<name>]`), applied with `preset = "<name>"`
+    ///
+    /// A preset named like a built-in one (see [`builtin_preset`]) replaces it.
+    #[serde(default)]
+    pub presets: HashMap<String, HashMap<String, toml::Value>>,
+
+    /// Definitions of jails with `count`, by base name (filled when instances are expanded)
+    #[serde(skip)]
+    pub templates: HashMap<String, JailDef>,
 }
 
 impl BlackshipConfig {
@@ -130,6 +373,168 @@ impl BlackshipConfig {
             }
         }
 
+        // Mounts must name valid filesystems and mount points
+        for jail in &self.jails {
+            if let Some(mount) = &jail.mount {
+                mount.specs().map_err(|e| {
+                    Error::ConfigValidation(format!("Jail '{}' mount: {}", jail.name, e))
+                })?;
+            }
+        }
+
+        // securelevel(7) ranges from -1 (permanently insecure) to 3
+        for jail in &self.jails {
+            if let Some(level) = jail.securelevel
+                && !(-1..=3).contains(&level)
+            {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' has securelevel = {}; expected -1 to 3",
+                    jail.name, level
+                )));
+            }
+        }
+
+        // Nested jails need a parent that allows enough children
+        for jail in &self.jails {
+            let Some(parent) = &jail.parent else {
+                continue;
+            };
+            let parent_def = self.get_jail(parent).ok_or_else(|| {
+                Error::ConfigValidation(format!(
+                    "Jail '{}' has unknown parent '{}'",
+                    jail.name, parent
+                ))
+            })?;
+            let max = parent_def
+                .params
+                .get("children.max")
+                .and_then(|v| v.as_integer())
+                .unwrap_or(0);
+            let children = self
+                .jails
+                .iter()
+                .filter(|j| j.parent.as_ref() == Some(parent))
+                .count();
+            if max <= 0 {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' is the parent of '{}' but does not set children.max",
+                    parent, jail.name
+                )));
+            }
+            if children as i64 > max {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' has {} nested jails but children.max = {}",
+                    parent, children, max
+                )));
+            }
+            if jail.network.as_ref().is_some_and(|n| n.vnet) {
+                return Err(Error::ConfigValidation(format!(
+                    "Nested jail '{}' cannot use VNET",
+                    jail.name
+                )));
+            }
+
+            let mut ancestor = parent_def;
+            for _ in 0..self.jails.len() {
+                if ancestor.name == jail.name {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' is nested in itself",
+                        jail.name
+                    )));
+                }
+                match ancestor.parent.as_deref().and_then(|p| self.get_jail(p)) {
+                    Some(next) => ancestor = next,
+                    None => break,
+                }
+            }
+
+            // The kernel only creates a nested jail below its parent's root
+            let parent_root = self.jail_root(parent_def);
+            if !self.jail_root(jail).starts_with(&parent_root) {
+                return Err(Error::ConfigValidation(format!(
+                    "Nested jail '{}' must have its path inside its parent's root {}",
+                    jail.name,
+                    parent_root.display()
+                )));
+            }
+        }
+
+        // One-shot jails are defined by the command they run
+        for jail in &self.jails {
+            if jail.oneshot_enabled() && !jail.params.contains_key("exec.start") {
+                return Err(Error::ConfigValidation(format!(
+                    "One-shot jail '{}' needs an exec.start command",
+                    jail.name
+                )));
+            }
+        }
+
+        // jail(8) exec.* params are run by blackship, so only the ones it knows work
+        for jail in &self.jails {
+            for (key, value) in &jail.params {
+                let checked = match key.as_str() {
+                    "exec.start" => crate::hooks::exec_start_command(value).map(|_| ()),
+                    key if key.starts_with("exec.") => {
+                        crate::hooks::native_exec_hooks(key, value).map(|_| ())
+                    }
+                    _ => Ok(()),
+                };
+                checked
+                    .map_err(|e| Error::ConfigValidation(format!("Jail '{}': {}", jail.name, e)))?;
+            }
+        }
+
+        // A start timeout bounds the readiness command
+        for jail in &self.jails {
+            match (jail.start_timeout, &jail.readiness) {
+                (Some(0), _) => {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' has start_timeout = 0",
+                        jail.name
+                    )));
+                }
+                (Some(_), None) => {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' has a start_timeout but no readiness command",
+                        jail.name
+                    )));
+                }
+                _ => {}
+            }
+        }
+
+        // Check dependency conditions
+        for jail in &self.jails {
+            for (dep, spec) in &jail.conditions {
+                if !jail.depends_on.contains(dep) {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' has a condition on '{}', which is not in its depends_on",
+                        jail.name, dep
+                    )));
+                }
+                let Some(dep_def) = self.get_jail(dep) else { continue };
+                match spec.condition() {
+                    DependencyCondition::ServiceHealthy
+                        if !dep_def.healthcheck.enabled || dep_def.healthcheck.checks.is_empty() =>
+                    {
+                        return Err(Error::ConfigValidation(format!(
+                            "Jail '{}' waits for '{}' to be healthy, but '{}' has no health checks",
+                            jail.name, dep, dep
+                        )));
+                    }
+                    DependencyCondition::ServiceCompletedSuccessfully
+                        if !dep_def.params.contains_key("exec.start") =>
+                    {
+                        return Err(Error::ConfigValidation(format!(
+                            "Jail '{}' waits for '{}' to complete, but '{}' has no exec.start",
+                            jail.name, dep, dep
+                        )));
+                    }
+                    _ => {}
+                }
+            }
+        }
+
         // Check for self-dependencies
         for jail in &self.jails {
             if jail.depends_on.contains(&jail.name) {
@@ -140,6 +545,133 @@ impl BlackshipConfig {
             }
         }
 
+        // Check physical interface pass-through
+        let mut passed_through = std::collections::HashMap::new();
+        for jail in &self.jails {
+            let Some(network) = &jail.network else { continue };
+            let Some(interface) = &network.vnet_interface else { continue };
+            if !network.vnet {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' sets vnet_interface, which requires vnet = true",
+                    jail.name
+                )));
+            }
+            if network.bridge.is_some() || network.vlan_id.is_some() {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' sets vnet_interface, so bridge and vlan_id (epair settings) \
+                     do not apply",
+                    jail.name
+                )));
+            }
+            if let Some(other) = passed_through.insert(interface, &jail.name) {
+                return Err(Error::ConfigValidation(format!(
+                    "Jails '{}' and '{}' both pass through interface '{}'",
+                    other, jail.name, interface
+                )));
+            }
+        }
+
+        // Check DNS settings so a typo can't produce a broken resolv.conf
+        for jail in &self.jails {
+            if let Some(network) = &jail.network {
+                network.dns.validate().map_err(|e| {
+                    Error::ConfigValidation(format!("Jail '{}': {}", jail.name, e))
+                })?;
+            }
+        }
+
+        // Check /etc/hosts entries
+        for jail in &self.jails {
+            let hosts = jail.network.iter().flat_map(|n| &n.hosts);
+            for entry in hosts {
+                if entry.names.is_empty() || entry.names.iter().any(|n| !is_hostname(n)) {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' has an invalid hosts entry for {}: {:?}",
+                        jail.name, entry.ip, entry.names
+                    )));
+                }
+            }
+        }
+
+        // Check that skipped hooks name an existing global hook
+        for jail in &self.jails {
+            for skip in &jail.skip_hooks {
+                if !self.config.hooks.iter().any(|h| h.name.as_ref() == Some(skip)) {
+                    return Err(Error::ConfigValidation(format!(
+                        "Jail '{}' skips unknown global hook '{}'",
+                        jail.name, skip
+                    )));
+                }
+            }
+        }
+
+        // Check that quotas are well-formed and have a dataset to apply to
+        for jail in &self.jails {
+            let Some(quota) = &jail.quota else { continue };
+            if quota != "none" && parse_size(quota).is_none() {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' has an invalid quota '{}' (expected e.g. 512M, 10G, 1.5T or none)",
+                    jail.name, quota
+                )));
+            }
+            if !self.config.zfs_enabled || jail.path.is_some() {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' sets a quota, which requires a ZFS dataset \
+                     (zfs_enabled and no custom path)",
+                    jail.name
+                )));
+            }
+        }
+
+        // Check that notifications have somewhere to go
+        if let Some(notifications) = &self.notifications
+            && notifications.webhook_url.is_none()
+            && notifications.command.is_none()
+        {
+            return Err(Error::ConfigValidation(
+                "[notifications] requires a webhook_url or a command".into(),
+            ));
+        }
+
+        if let Some(rate) = &self.config.download_rate_limit
+            && parse_size(rate).is_none_or(|bytes| bytes == 0)
+        {
+            return Err(Error::ConfigValidation(format!(
+                "Invalid download_rate_limit '{}' (expected bytes per second such as 500K or 10M)",
+                rate
+            )));
+        }
+
+        if let Some(size) = &self.logging.max_size
+            && parse_size(size).is_none_or(|bytes| bytes == 0)
+        {
+            return Err(Error::ConfigValidation(format!(
+                "Invalid [logging] max_size '{}' (expected a size such as 512K or 10M)",
+                size
+            )));
+        }
+
+        // Check the snapshot schedule
+        if let Some(schedule) = &self.snapshots.schedule {
+            if parse_schedule(schedule).is_none() {
+                return Err(Error::ConfigValidation(format!(
+                    "Invalid snapshot schedule '{}' (expected hourly, daily, weekly \
+                     or a duration such as 30m, 6h, 2d)",
+                    schedule
+                )));
+            }
+            if !self.config.zfs_enabled {
+                return Err(Error::ConfigValidation(
+                    "[snapshots] schedule requires zfs_enabled".into(),
+                ));
+            }
+            if self.snapshots.keep == 0 {
+                return Err(Error::ConfigValidation(
+                    "[snapshots] keep must be at least 1".into(),
+                ));
+            }
+        }
+
         // Check ZFS configuration
         if self.config.zfs_enabled && self.config.zpool.is_none() {
             return Err(Error::ConfigValidation(
@@ -182,17 +714,71 @@ impl BlackshipConfig {
     }
 
     /// Get the full jail name with project prefix
-    /// Format: {project}-{service_name}
+    /// Format: {project}-{service_name}, prefixed with `{parent}.` for nested jails
     pub fn jail_name(&self, service_name: &str) -> String {
+        // Bounded so a parent cycle (rejected by validate) cannot loop forever
+        let mut name = self.prefixed_name(service_name);
+        let mut jail = self.get_jail(service_name);
+        for _ in 0..self.jails.len() {
+            let Some(parent) = jail.and_then(|j| j.parent.as_deref()) else {
+                break;
+            };
+            name = format!("{}.{}", self.prefixed_name(parent), name);
+            jail = self.get_jail(parent);
+        }
+        name
+    }
+
+    /// A service name with the project prefix, without any parent
+    fn prefixed_name(&self, service_name: &str) -> String {
         let project = self.config.project_name();
-        let prefix = format!("{}-", project);
-        if service_name.starts_with(&prefix) {
+        if service_name.starts_with(&format!("{}-", project)) {
             service_name.to_string()
         } else {
             format!("{}-{}", project, service_name)
         }
     }
 
+    /// Root directory of a jail
+    ///
+    /// A nested jail without its own `path` lives at `jails/<name>` inside its
+    /// parent's root, since the kernel only creates child jails below it.
+    pub fn jail_root(&self, jail: &JailDef) -> PathBuf {
+        // Nested jails without a path, from the innermost outwards
+        let mut nested = Vec::new();
+        let mut current = jail;
+        let base = loop {
+            if let Some(path) = &current.path {
+                break path.clone();
+            }
+            match current.parent.as_deref().and_then(|p| self.get_jail(p)) {
+                // Bounded so a parent cycle (rejected by validate) cannot loop forever
+                Some(parent) if nested.len() < self.jails.len() => {
+                    nested.push(current);
+                    current = parent;
+                }
+                _ => break current.effective_path(&self.config, &self.jail_name(&current.name)),
+            }
+        };
+        nested.iter().rev().fold(base, |root, j| {
+            root.join("jails").join(self.prefixed_name(&j.name))
+        })
+    }
+
+    /// Get the primary (first attached) network definition for a jail
+    pub fn primary_network(&self, jail: &JailDef) -> Option<&NetworkConfig> {
+        let name = jail.network.as_ref()?.networks.first()?;
+        self.networks.iter().find(|n| &n.name == name)
+    }
+
+    /// Get the gateway for a jail (explicit gateway, else its primary network's gateway)
+    pub fn jail_gateway(&self, jail: &JailDef) -> Option<IpAddr> {
+        jail.network
+            .as_ref()
+            .and_then(|n| n.gateway)
+            .or_else(|| self.primary_network(jail).and_then(|n| n.effective_gateway()))
+    }
+
     /// Get the project name (explicit or random Black Ship name)
     #[allow(dead_code)]
     pub fn project_name(&self) -> String {
@@ -216,6 +802,23 @@ impl BlackshipConfig {
             }
         }
 
+        // Presets are replaced by name
+        self.presets.extend(other.presets);
+
+        // Notifications are replaced as a whole
+        if other.notifications.is_some() {
+            self.notifications = other.notifications;
+        }
+        self.logging.syslog = other.logging.syslog.or(self.logging.syslog);
+        self.logging.max_size = other.logging.max_size.or(self.logging.max_size);
+        if other.logging.keep != default_log_keep() {
+            self.logging.keep = other.logging.keep;
+        }
+        self.snapshots.schedule = other.snapshots.schedule.or(self.snapshots.schedule);
+        if other.snapshots.keep != default_snapshot_keep() {
+            self.snapshots.keep = other.snapshots.keep;
+        }
+
         // Merge jails by name (deep merge)
         for jail in other.jails {
             if let Some(existing) = self.jails.iter_mut().find(|j| j.name == jail.name) {
@@ -227,6 +830,288 @@ impl BlackshipConfig {
 
         self
     }
+
+    /// Apply each jail's `preset`: its params go under the jail's own, which win
+    pub fn resolve_presets(&mut self) -> Result<()> {
+        for jail in &mut self.jails {
+            let Some(name) = &jail.preset else { continue };
+            let preset = match self.presets.get(name) {
+                Some(preset) => preset.clone(),
+                None => builtin_preset(name).ok_or_else(|| {
+                    Error::ConfigValidation(format!(
+                        "Jail '{}' uses unknown preset '{}'",
+                        jail.name, name
+                    ))
+                })?,
+            };
+            let own = std::mem::replace(&mut jail.params, preset);
+            jail.params.extend(own);
+        }
+        Ok(())
+    }
+
+    /// Make each nested jail depend on its parent, so the parent starts first
+    /// and the nested jail stops first, and place it inside the parent's root
+    ///
+    /// Call once the project name is set, since default roots depend on it.
+    pub fn link_parents(&mut self) {
+        let roots: Vec<Option<PathBuf>> = self
+            .jails
+            .iter()
+            .map(|jail| {
+                (jail.parent.is_some() && jail.path.is_none()).then(|| self.jail_root(jail))
+            })
+            .collect();
+        for (jail, root) in self.jails.iter_mut().zip(roots) {
+            if let Some(parent) = &jail.parent
+                && !jail.depends_on.contains(parent)
+            {
+                jail.depends_on.push(parent.clone());
+            }
+            if root.is_some() {
+                jail.path = root;
+            }
+        }
+    }
+
+    /// Expand jails with `count = N` into instances `<name>-1` .. `<name>-N`
+    ///
+    /// `${instance_index}` in an instance's hostname, path, string params and hooks
+    /// is replaced with its number, and `depends_on` entries naming the base jail
+    /// expand to every instance.
+    pub fn expand_instances(&mut self) -> Result<()> {
+        let mut instances: HashMap<String, Vec<String>> = HashMap::new();
+        let mut jails = Vec::with_capacity(self.jails.len());
+
+        for jail in std::mem::take(&mut self.jails) {
+            let Some(count) = jail.count else {
+                jails.push(jail);
+                continue;
+            };
+            if count == 0 {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' has count = 0",
+                    jail.name
+                )));
+            }
+            if count > 1
+                && jail
+                    .network
+                    .as_ref()
+                    .is_some_and(|n| n.ip.is_some() || n.ip_cidr.is_some())
+            {
+                return Err(Error::ConfigValidation(format!(
+                    "Jail '{}' has count = {} but a static IP; attach it to a network \
+                     so each instance gets its own address",
+                    jail.name, count
+                )));
+            }
+
+            let names: Vec<String> = (1..=count).map(|i| instance_name(&jail.name, i)).collect();
+            for (index, name) in (1..=count).zip(&names) {
+                jails.push(jail.instance(name, index));
+            }
+            instances.insert(jail.name.clone(), names);
+            self.templates.insert(jail.name.clone(), jail);
+        }
+
+        // Dependencies on a templated jail mean every instance
+        for jail in &mut jails {
+            jail.depends_on = std::mem::take(&mut jail.depends_on)
+                .into_iter()
+                .flat_map(|dep| instances.get(&dep).cloned().unwrap_or_else(|| vec![dep]))
+                .collect();
+            jail.conditions = std::mem::take(&mut jail.conditions)
+                .into_iter()
+                .flat_map(|(dep, spec)| {
+                    let deps = instances.get(&dep).cloned().unwrap_or_else(|| vec![dep]);
+                    deps.into_iter().map(move |dep| (dep, spec.clone()))
+                })
+                .collect();
+        }
+
+        self.jails = jails;
+        Ok(())
+    }
+}
+
+/// When a jail is started again after its work ends
+///
+/// Consulted by the Warden before restarting a failed jail. For one-shot jails
+/// a failed `exec.start` is retried in place (any policy but `no`); a
+/// successful run is never repeated.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum RestartPolicy {
+    /// Never
+    No,
+    /// Only after a failure
+    OnFailure,
+    /// Always
+    #[default]
+    Always,
+    /// Like `always`, but a jail stopped with `down` stays down, also when
+    /// `supervise` starts again
+    UnlessStopped,
+}
+
+impl std::fmt::Display for RestartPolicy {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let name = match self {
+            RestartPolicy::No => "no",
+            RestartPolicy::OnFailure => "on-failure",
+            RestartPolicy::Always => "always",
+            RestartPolicy::UnlessStopped => "unless-stopped",
+        };
+        f.write_str(name)
+    }
+}
+
+/// Compose-style condition a dependency must meet before its dependents start
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Deserialize, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum DependencyCondition {
+    /// The dependency is running
+    #[default]
+    ServiceStarted,
+    /// All of the dependency's health checks pass
+    ServiceHealthy,
+    /// The dependency's `exec.start` exited with status 0 (one-shot jails)
+    ServiceCompletedSuccessfully,
+}
+
+impl DependencyCondition {
+    /// How long dependents wait for the condition unless configured
+    pub fn default_timeout(self) -> Duration {
+        Duration::from_secs(match self {
+            DependencyCondition::ServiceStarted => 30,
+            DependencyCondition::ServiceHealthy => 120,
+            DependencyCondition::ServiceCompletedSuccessfully => 300,
+        })
+    }
+}
+
+impl std::fmt::Display for DependencyCondition {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            DependencyCondition::ServiceStarted => write!(f, "service_started"),
+            DependencyCondition::ServiceHealthy => write!(f, "service_healthy"),
+            DependencyCondition::ServiceCompletedSuccessfully => {
+                write!(f, "service_completed_successfully")
+            }
+        }
+    }
+}
+
+/// A dependency condition: `"service_healthy"` or
+/// `{ condition = "service_healthy", timeout = 60 }` (seconds)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+#[serde(untagged)]
+pub enum DependencySpec {
+    /// Condition with its default timeout
+    Condition(DependencyCondition),
+    /// Condition with an explicit timeout
+    Detailed {
+        condition: DependencyCondition,
+        timeout: Option<u64>,
+    },
+}
+
+impl DependencySpec {
+    /// The condition to wait for
+    pub fn condition(&self) -> DependencyCondition {
+        match self {
+            DependencySpec::Condition(condition) | DependencySpec::Detailed { condition, .. } => {
+                *condition
+            }
+        }
+    }
+
+    /// How long to wait for the condition
+    pub fn timeout(&self) -> Duration {
+        match self {
+            DependencySpec::Detailed {
+                timeout: Some(secs),
+                ..
+            } => Duration::from_secs(*secs),
+            _ => self.condition().default_timeout(),
+        }
+    }
+}
+
+/// Parameters of a built-in preset
+///
+/// - `hardened`: raised securelevel, no raw sockets, mounts, chflags or child jails
+/// - `sysvipc`: private System V IPC namespaces (PostgreSQL and friends)
+pub fn builtin_preset(name: &str) -> Option<HashMap<String, toml::Value>> {
+    use toml::Value::{Boolean, Integer, String as Str};
+    let params = match name {
+        "hardened" => vec![
+            ("securelevel", Integer(3)),
+            ("enforce_statfs", Integer(2)),
+            ("children.max", Integer(0)),
+            ("allow.raw_sockets", Boolean(false)),
+            ("allow.mount", Boolean(false)),
+            ("allow.chflags", Boolean(false)),
+            ("allow.set_hostname", Boolean(false)),
+        ],
+        "sysvipc" => vec![
+            ("sysvmsg", Str("new".into())),
+            ("sysvsem", Str("new".into())),
+            ("sysvshm", Str("new".into())),
+        ],
+        _ => return None,
+    };
+    Some(params.into_iter().map(|(k, v)| (k.to_string(), v)).collect())
+}
+
+/// Parse a ZFS-style size ("1024", "512K", "10G", "1.5T", "2GB") into bytes
+pub fn parse_size(size: &str) -> Option<u64> {
+    let size = size.trim();
+    let split = size
+        .find(|c: char| !c.is_ascii_digit() && c != '.')
+        .unwrap_or(size.len());
+    let (number, unit) = size.split_at(split);
+    let number: f64 = number.parse().ok()?;
+    let unit = unit.to_ascii_uppercase();
+    let shift = match unit.strip_suffix('B').unwrap_or(&unit) {
+        "" => 0,
+        "K" => 10,
+        "M" => 20,
+        "G" => 30,
+        "T" => 40,
+        "P" => 50,
+        _ => return None,
+    };
+    Some((number * (1u64 << shift) as f64) as u64)
+}
+
+/// Parse a snapshot schedule ("hourly", "daily", "weekly", or "30m", "6h", "2d")
+pub fn parse_schedule(schedule: &str) -> Option<Duration> {
+    let schedule = schedule.trim();
+    let secs = match schedule {
+        "hourly" => 3600,
+        "daily" => 86400,
+        "weekly" => 7 * 86400,
+        _ => {
+            let split = schedule.find(|c: char| !c.is_ascii_digit())?;
+            let (number, unit) = schedule.split_at(split);
+            let number: u64 = number.parse().ok()?;
+            let unit = match unit {
+                "m" => 60,
+                "h" => 3600,
+                "d" => 86400,
+                _ => return None,
+            };
+            number.checked_mul(unit)?
+        }
+    };
+    (secs > 0).then(|| Duration::from_secs(secs))
+}
+
+/// Name of instance `index` of a jail with `count`
+pub fn instance_name(base: &str, index: u32) -> String {
+    format!("{}-{}", base, index)
 }
 
 /// Known Black Ship names from Warhammer 40K lore
@@ -257,7 +1142,7 @@ pub fn black_ship_name_from_path(path: &Path) -> String {
 }
 
 /// Global configuration settings
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 pub struct GlobalConfig {
     /// Project name (used as prefix for jail names)
     /// If not set, a random Black Ship name will be used
@@ -289,10 +1174,34 @@ pub struct GlobalConfig {
     #[serde(default = "default_mirror_url")]
     pub mirror_url: String,
 
+    /// Mirrors tried in order during bootstrap, each with the full retry
+    /// policy (replaces `mirror_url` when set)
+    #[serde(default)]
+    pub mirror_urls: Vec<String>,
+
+    /// Default bandwidth limit per bootstrap download, in bytes per second
+    /// with optional K/M/G suffixes (e.g. "500K", "10M"; default: unlimited)
+    pub download_rate_limit: Option<String>,
+
     /// Archives to bootstrap (base, lib32, ports, src)
     #[serde(default = "default_bootstrap_archives")]
     pub bootstrap_archives: Vec<String>,
 
+    /// devfs ruleset applied to `/dev` during Jailfile RUN steps
+    /// (default 4, `devfsrules_jail`; 0 exposes every device)
+    #[serde(default = "default_build_devfs_ruleset")]
+    pub build_devfs_ruleset: u16,
+
+    /// Timeout in seconds for Jailfile RUN steps without their own
+    /// `--timeout` (0 = no timeout)
+    #[serde(default)]
+    pub build_run_timeout: u64,
+
+    /// Nameservers written to resolv.conf for Jailfile RUN steps
+    /// (default: copy the host's /etc/resolv.conf)
+    #[serde(default)]
+    pub build_nameservers: Vec<IpAddr>,
+
     /// Rate limiting configuration
     #[serde(default)]
     pub rate_limit: RateLimitConfig,
@@ -306,8 +1215,23 @@ pub struct GlobalConfig {
     #[serde(default)]
     pub retry: RetryConfig,
 
+    /// Warden restart backoff and crash-loop detection
+    #[serde(default)]
+    pub supervise: SuperviseConfig,
+
     /// Bridge VLAN configuration (FreeBSD 15.0+)
     pub bridge: Option<BridgeVlanConfig>,
+
+    /// Naming of VNET epair interfaces: "numbered" (default) or "jail"
+    #[serde(default)]
+    pub epair_naming: EpairNaming,
+
+    /// Add every other jail's name and IP to each jail's /etc/hosts (default: false)
+    pub sibling_hosts: Option<bool>,
+
+    /// Lifecycle hooks applied to every jail (run before per-jail hooks)
+    #[serde(default)]
+    pub hooks: Vec<Hook>,
 }
 
 impl GlobalConfig {
@@ -317,6 +1241,16 @@ impl GlobalConfig {
         self.project.clone().unwrap_or_else(|| "blackship".to_string())
     }
 
+    /// Bootstrap download limit in bytes per second (0 = unlimited)
+    pub fn download_rate_limit(&self) -> u64 {
+        self.download_rate_limit.as_deref().and_then(parse_size).unwrap_or(0)
+    }
+
+    /// Whether jails get their siblings' names in /etc/hosts
+    pub fn sibling_hosts_enabled(&self) -> bool {
+        self.sibling_hosts.unwrap_or(false)
+    }
+
     /// Merge another GlobalConfig into this one
     /// Other's values override self's where specified
     fn merge(self, other: GlobalConfig) -> GlobalConfig {
@@ -329,11 +1263,20 @@ impl GlobalConfig {
             releases_dir: if other.releases_dir != default_releases_dir() { other.releases_dir } else { self.releases_dir },
             cache_dir: if other.cache_dir != default_cache_dir() { other.cache_dir } else { self.cache_dir },
             mirror_url: if other.mirror_url != default_mirror_url() { other.mirror_url } else { self.mirror_url },
+            mirror_urls: if other.mirror_urls.is_empty() { self.mirror_urls } else { other.mirror_urls },
+            download_rate_limit: other.download_rate_limit.or(self.download_rate_limit),
             bootstrap_archives: if other.bootstrap_archives != default_bootstrap_archives() { other.bootstrap_archives } else { self.bootstrap_archives },
+            build_devfs_ruleset: if other.build_devfs_ruleset != default_build_devfs_ruleset() { other.build_devfs_ruleset } else { self.build_devfs_ruleset },
+            build_run_timeout: if other.build_run_timeout != 0 { other.build_run_timeout } else { self.build_run_timeout },
+            build_nameservers: if other.build_nameservers.is_empty() { self.build_nameservers } else { other.build_nameservers },
             rate_limit: other.rate_limit, // Take other's rate limit config
             health: other.health, // Take other's health defaults
             retry: other.retry, // Take other's retry config
+            supervise: other.supervise, // Take other's supervise config
             bridge: other.bridge.or(self.bridge), // Merge bridge VLAN config
+            epair_naming: if other.epair_naming != EpairNaming::default() { other.epair_naming } else { self.epair_naming },
+            sibling_hosts: other.sibling_hosts.or(self.sibling_hosts),
+            hooks: if other.hooks.is_empty() { self.hooks } else { other.hooks },
         }
     }
 }
@@ -358,6 +1301,10 @@ fn default_bootstrap_archives() -> Vec<String> {
     vec!["base".into()]
 }
 
+fn default_build_devfs_ruleset() -> u16 {
+    crate::blueprint::executor::DEFAULT_DEVFS_RULESET
+}
+
 fn default_jail_start_capacity() -> f64 {
     3.0
 }
@@ -371,7 +1318,7 @@ fn default_health_refill_rate() -> f64 {
 }
 
 /// Rate limiting configuration
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RateLimitConfig {
     /// Max jails to start concurrently
     #[serde(default = "default_jail_start_capacity")]
@@ -416,7 +1363,7 @@ fn default_health_retries() -> u32 {
 ///
 /// These values are used as defaults for health checks when not specified
 /// at the individual check level.
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(dead_code)] // Config struct - fields are parsed from TOML
 pub struct HealthDefaults {
     /// Default interval between health checks in seconds
@@ -469,7 +1416,7 @@ fn default_jitter_factor() -> f64 {
 }
 
 /// Retry/backoff configuration for HTTP operations
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct RetryConfig {
     /// Base delay in milliseconds before first retry
     #[serde(default = "default_base_delay_ms")]
@@ -504,10 +1451,170 @@ impl Default for RetryConfig {
     }
 }
 
+// Supervise configuration defaults
+fn default_restart_base_delay_ms() -> u64 {
+    1000
+}
+
+fn default_restart_max_delay_ms() -> u64 {
+    60000
+}
+
+fn default_restart_jitter_factor() -> f64 {
+    0.5
+}
+
+fn default_max_restarts() -> u32 {
+    5
+}
+
+fn default_restart_window_secs() -> u64 {
+    300
+}
+
+/// Warden restart configuration (`[config.supervise]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SuperviseConfig {
+    /// Delay in milliseconds before the first restart
+    #[serde(default = "default_restart_base_delay_ms")]
+    pub base_delay_ms: u64,
+
+    /// Maximum delay in milliseconds between restarts
+    #[serde(default = "default_restart_max_delay_ms")]
+    pub max_delay_ms: u64,
+
+    /// Multiplier for exponential backoff
+    #[serde(default = "default_multiplier")]
+    pub multiplier: f64,
+
+    /// Jitter factor (0.0-1.0) to randomize delays
+    #[serde(default = "default_restart_jitter_factor")]
+    pub jitter_factor: f64,
+
+    /// Restarts allowed within the window before the jail is marked Failed
+    #[serde(default = "default_max_restarts")]
+    pub max_restarts: u32,
+
+    /// Crash-loop detection window in seconds
+    #[serde(default = "default_restart_window_secs")]
+    pub restart_window_secs: u64,
+}
+
+impl Default for SuperviseConfig {
+    fn default() -> Self {
+        Self {
+            base_delay_ms: default_restart_base_delay_ms(),
+            max_delay_ms: default_restart_max_delay_ms(),
+            multiplier: default_multiplier(),
+            jitter_factor: default_restart_jitter_factor(),
+            max_restarts: default_max_restarts(),
+            restart_window_secs: default_restart_window_secs(),
+        }
+    }
+}
+
+fn default_notification_rate() -> u32 {
+    10
+}
+
+/// Notification configuration (`[notifications]`)
+///
+/// The webhook receives a JSON POST with `jail`, `event`, `timestamp` and `reason`;
+/// the command runs via `sh -c` with the same values in `BLACKSHIP_*` env vars.
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct NotificationConfig {
+    /// URL to POST the JSON payload to
+    pub webhook_url: Option<String>,
+
+    /// Shell command to run for each notification
+    pub command: Option<String>,
+
+    /// Maximum notifications per minute (excess notifications are dropped)
+    #[serde(default = "default_notification_rate")]
+    pub max_per_minute: u32,
+}
+
+/// Event logging configuration (`[logging]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct LoggingConfig {
+    /// Also send status and health events to syslog (default: false)
+    pub syslog: Option<bool>,
+
+    /// Size at which `data_dir/logs/<jail>.log` is rotated (default: 10M)
+    pub max_size: Option<String>,
+
+    /// Rotated console logs kept per jail (`<jail>.log.1` ..)
+    #[serde(default = "default_log_keep")]
+    pub keep: usize,
+}
+
+impl Default for LoggingConfig {
+    fn default() -> Self {
+        Self {
+            syslog: None,
+            max_size: None,
+            keep: default_log_keep(),
+        }
+    }
+}
+
+fn default_log_keep() -> usize {
+    5
+}
+
+impl LoggingConfig {
+    /// Whether syslog output is enabled
+    pub fn syslog_enabled(&self) -> bool {
+        self.syslog.unwrap_or(false)
+    }
+
+    /// Console log rotation size in bytes
+    pub fn max_size_bytes(&self) -> u64 {
+        self.max_size
+            .as_deref()
+            .and_then(parse_size)
+            .unwrap_or(DEFAULT_LOG_MAX_SIZE)
+    }
+}
+
+/// Default console log rotation size (10 MiB)
+const DEFAULT_LOG_MAX_SIZE: u64 = 10 << 20;
+
+/// Automatic snapshot configuration (`[snapshots]`)
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct SnapshotsConfig {
+    /// How often `blackship supervise` snapshots ZFS-backed jails (unset = never)
+    pub schedule: Option<String>,
+
+    /// Automatic snapshots kept per jail; older ones are destroyed
+    #[serde(default = "default_snapshot_keep")]
+    pub keep: usize,
+}
+
+impl SnapshotsConfig {
+    /// Interval between automatic snapshots, if scheduled
+    pub fn interval(&self) -> Option<Duration> {
+        self.schedule.as_deref().and_then(parse_schedule)
+    }
+}
+
+impl Default for SnapshotsConfig {
+    fn default() -> Self {
+        Self {
+            schedule: None,
+            keep: default_snapshot_keep(),
+        }
+    }
+}
+
+fn default_snapshot_keep() -> usize {
+    24
+}
+
 /// Network configuration
 ///
 /// Used for defining virtual networks that jails can be attached to.
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 #[allow(dead_code)] // Config struct - fields are parsed from TOML
 pub struct NetworkConfig {
     /// Network name
@@ -520,8 +1627,20 @@ pub struct NetworkConfig {
     pub gateway: Option<IpAddr>,
 }
 
+impl NetworkConfig {
+    /// Get the gateway address (explicit, or first usable address in the subnet)
+    pub fn effective_gateway(&self) -> Option<IpAddr> {
+        self.gateway.or_else(|| {
+            self.subnet
+                .parse::<IpNet>()
+                .ok()
+                .and_then(|net| net.hosts().next())
+        })
+    }
+}
+
 /// Bridge with VLAN filtering configuration
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 #[allow(dead_code)] // Config struct - fields are parsed from TOML
 pub struct BridgeVlanConfig {
     /// Bridge interface name (e.g., "vswitch1")
@@ -536,7 +1655,7 @@ pub struct BridgeVlanConfig {
 }
 
 /// Physical trunk interface configuration for VLAN filtering
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct TrunkConfig {
     /// Physical interface (e.g., "igb1", "em0")
     pub interface: String,
@@ -550,8 +1669,11 @@ pub struct TrunkConfig {
     pub disable_hwfilter: bool,
 }
 
+/// Default time a jail's readiness command has to succeed
+const DEFAULT_START_TIMEOUT_SECS: u64 = 60;
+
 /// Jail definition from config file
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct JailDef {
     /// Unique jail name
     pub name: String,
@@ -574,10 +1696,47 @@ pub struct JailDef {
     /// Hostname for the jail
     pub hostname: Option<String>,
 
+    /// Maximum size of the jail's ZFS dataset (e.g., "10G"; "none" removes it)
+    pub quota: Option<String>,
+
     /// Jails that must be started before this one
     #[serde(default)]
     pub depends_on: Vec<String>,
 
+    /// Condition each dependency must meet before this jail starts, keyed by
+    /// dependency name (default: `service_started`)
+    #[serde(default)]
+    pub conditions: HashMap<String, DependencySpec>,
+
+    /// Run `exec.start` to completion, record its exit code and remove the jail
+    pub oneshot: Option<bool>,
+
+    /// What happens when the jail's work ends (default: `always`, or `no` for one-shot jails)
+    pub restart: Option<RestartPolicy>,
+
+    /// Command run inside the jail after it starts, retried until it exits 0;
+    /// the jail only counts as started (and the Warden is told) once it does
+    pub readiness: Option<String>,
+
+    /// Seconds `readiness` has to succeed before the start fails (default: 60)
+    pub start_timeout: Option<u64>,
+
+    /// Start this jail on `up` without an explicit name (default: true)
+    ///
+    /// Jails with `autostart = false` still start when named explicitly
+    /// or when an autostarted jail depends on them.
+    pub autostart: Option<bool>,
+
+    /// Named parameter preset applied under `params` (built-in or `[presets.<name>]`)
+    pub preset: Option<String>,
+
+    /// Kernel securelevel inside the jail (-1 to 3); overrides `params`
+    pub securelevel: Option<i32>,
+
+    /// devfs ruleset for devfs mounted in the jail (must be loaded on the host);
+    /// overrides `params`
+    pub devfs_ruleset: Option<u16>,
+
     /// Jail parameters (exec.start, allow.raw_sockets, etc.)
     #[serde(default)]
     pub params: HashMap<String, toml::Value>,
@@ -585,17 +1744,31 @@ pub struct JailDef {
     /// Network configuration
     pub network: Option<JailNetworkConfig>,
 
-    /// Mount configuration (_unused: future feature)
-    #[allow(dead_code)]
+    /// Filesystems mounted into the jail root
     pub mount: Option<JailMountConfig>,
 
     /// Lifecycle hooks
     #[serde(default)]
     pub hooks: Vec<Hook>,
 
+    /// Names of global hooks this jail opts out of
+    #[serde(default)]
+    pub skip_hooks: Vec<String>,
+
     /// Health check configuration
     #[serde(default)]
     pub healthcheck: HealthCheckConfig,
+
+    /// Number of instances; expands into `<name>-1` .. `<name>-<count>` on load
+    pub count: Option<u32>,
+
+    /// Jail this one is nested in; it is created as `<parent>.<name>` and
+    /// started after the parent, which must set `children.max`
+    pub parent: Option<String>,
+
+    /// Base jail name of an instance expanded from `count` (set on load)
+    #[serde(skip)]
+    pub template: Option<String>,
 }
 
 impl JailDef {
@@ -620,6 +1793,28 @@ impl JailDef {
         }
     }
 
+    /// Check whether this jail starts on `up` without an explicit name
+    pub fn autostarts(&self) -> bool {
+        self.autostart.unwrap_or(true)
+    }
+
+    /// Get the hooks to run for this jail
+    ///
+    /// Global hooks come first (minus any listed in `skip_hooks`), followed by the jail's own hooks.
+    pub fn effective_hooks(&self, global: &GlobalConfig) -> Vec<Hook> {
+        global
+            .hooks
+            .iter()
+            .filter(|h| {
+                h.name
+                    .as_ref()
+                    .is_none_or(|name| !self.skip_hooks.contains(name))
+            })
+            .chain(self.hooks.iter())
+            .cloned()
+            .collect()
+    }
+
     /// Merge another JailDef into this one (Docker Compose-style deep merge)
     ///
     /// Other's values override self's where specified (Option::Some or non-empty Vec)
@@ -631,7 +1826,17 @@ impl JailDef {
             build: other.build.or(self.build),
             jailfile: other.jailfile.or(self.jailfile),
             hostname: other.hostname.or(self.hostname),
+            quota: other.quota.or(self.quota),
             depends_on: if other.depends_on.is_empty() { self.depends_on } else { other.depends_on },
+            conditions: if other.conditions.is_empty() { self.conditions } else { other.conditions },
+            autostart: other.autostart.or(self.autostart),
+            oneshot: other.oneshot.or(self.oneshot),
+            restart: other.restart.or(self.restart),
+            readiness: other.readiness.or(self.readiness),
+            start_timeout: other.start_timeout.or(self.start_timeout),
+            preset: other.preset.or(self.preset),
+            securelevel: other.securelevel.or(self.securelevel),
+            devfs_ruleset: other.devfs_ruleset.or(self.devfs_ruleset),
             params: {
                 let mut merged = self.params;
                 merged.extend(other.params);
@@ -640,17 +1845,83 @@ impl JailDef {
             network: other.network.or(self.network),
             mount: other.mount.or(self.mount),
             hooks: if other.hooks.is_empty() { self.hooks } else { other.hooks },
+            skip_hooks: if other.skip_hooks.is_empty() { self.skip_hooks } else { other.skip_hooks },
             healthcheck: if other.healthcheck.enabled || !other.healthcheck.checks.is_empty() {
                 other.healthcheck
             } else {
                 self.healthcheck
             },
+            count: other.count.or(self.count),
+            parent: other.parent.or(self.parent),
+            template: other.template.or(self.template),
+        }
+    }
+
+    /// Whether this is a one-shot jail
+    pub fn oneshot_enabled(&self) -> bool {
+        self.oneshot.unwrap_or(false)
+    }
+
+    /// Time `readiness` has to succeed
+    pub fn start_timeout(&self) -> Duration {
+        Duration::from_secs(self.start_timeout.unwrap_or(DEFAULT_START_TIMEOUT_SECS))
+    }
+
+    /// Effective restart policy
+    pub fn restart_policy(&self) -> RestartPolicy {
+        self.restart.unwrap_or(if self.oneshot_enabled() {
+            RestartPolicy::No
+        } else {
+            RestartPolicy::Always
+        })
+    }
+
+    /// Condition and timeout for one of this jail's dependencies
+    pub fn dependency_condition(&self, dep: &str) -> (DependencyCondition, Duration) {
+        self.conditions
+            .get(dep)
+            .map(|spec| (spec.condition(), spec.timeout()))
+            .unwrap_or_else(|| {
+                let condition = DependencyCondition::default();
+                (condition, condition.default_timeout())
+            })
+    }
+
+    /// Build instance `index` of a jail with `count`
+    pub fn instance(&self, name: &str, index: u32) -> JailDef {
+        let index_str = index.to_string();
+        let subst = |s: &str| s.replace("${instance_index}", &index_str);
+
+        let mut jail = self.clone();
+        jail.name = name.to_string();
+        jail.count = None;
+        jail.template = Some(self.name.clone());
+        jail.hostname = jail.hostname.as_deref().map(subst);
+        jail.path = jail
+            .path
+            .as_ref()
+            .map(|p| PathBuf::from(subst(&p.to_string_lossy())));
+        for value in jail.params.values_mut() {
+            if let toml::Value::String(s) = value {
+                *s = subst(s);
+            }
+        }
+        for hook in &mut jail.hooks {
+            hook.command = subst(&hook.command);
+            for arg in &mut hook.args {
+                *arg = subst(arg);
+            }
+            for value in hook.env.values_mut() {
+                *value = subst(value);
+            }
+            hook.workdir = hook.workdir.as_deref().map(subst);
         }
+        jail
     }
 }
 
 /// Jail network configuration
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, Deserialize, Serialize)]
 pub struct JailNetworkConfig {
     /// Enable VNET (virtual network stack) for this jail
     /// When true, the jail gets its own network stack with epair interface
@@ -661,6 +1932,10 @@ pub struct JailNetworkConfig {
     /// e.g., "blackship0"
     pub bridge: Option<String>,
 
+    /// Physical interface to pass into the VNET jail instead of an epair, e.g. "ix1"
+    /// It is returned to the host when the jail stops
+    pub vnet_interface: Option<String>,
+
     /// Networks to attach to
     #[serde(default)]
     pub networks: Vec<String>,
@@ -674,6 +1949,11 @@ pub struct JailNetworkConfig {
     /// Gateway address for VNET jails
     pub gateway: Option<IpAddr>,
 
+    /// Host interface for a shared-IP (non-VNET) jail's address, e.g. "lo1" or "em0"
+    /// The address is added as an alias on start (giving the host a route to it)
+    /// and removed on stop
+    pub interface: Option<String>,
+
     /// Static MAC address
     /// If not specified, the system assigns a random MAC
     pub mac_address: Option<String>,
@@ -685,10 +1965,44 @@ pub struct JailNetworkConfig {
     /// DNS configuration for this jail
     #[serde(default)]
     pub dns: DnsConfig,
+
+    /// Extra `/etc/hosts` entries for this jail
+    #[serde(default)]
+    pub hosts: Vec<HostEntry>,
+}
+
+/// An `/etc/hosts` entry
+#[derive(Debug, Clone, PartialEq, Eq, Deserialize, Serialize)]
+pub struct HostEntry {
+    /// Address the names resolve to
+    pub ip: IpAddr,
+    /// Hostnames (the first is the canonical name)
+    pub names: Vec<String>,
+}
+
+impl JailNetworkConfig {
+    /// Statically configured address (`ip`, else the address part of `ip_cidr`)
+    pub fn address(&self) -> Option<IpAddr> {
+        self.ip.or_else(|| {
+            self.ip_cidr
+                .as_deref()
+                .and_then(|cidr| cidr.split('/').next())
+                .and_then(|addr| addr.parse().ok())
+        })
+    }
+
+    /// Prefix length for the IPv4 alias on `interface` (from `ip_cidr`, else /32)
+    pub fn alias_prefix_len(&self) -> u8 {
+        self.ip_cidr
+            .as_deref()
+            .and_then(|cidr| cidr.parse::<IpNet>().ok())
+            .map(|net| net.prefix_len())
+            .unwrap_or(32)
+    }
 }
 
 /// DNS configuration for a jail
-#[derive(Debug, Clone, Default, Deserialize)]
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct DnsConfig {
     /// DNS servers (e.g., ["8.8.8.8", "8.8.4.4"])
     /// If empty, uses "inherit" mode (copies from host)
@@ -699,6 +2013,10 @@ pub struct DnsConfig {
     #[serde(default)]
     pub search: Vec<String>,
 
+    /// Resolver options (e.g., ["timeout:2", "attempts:3"])
+    #[serde(default)]
+    pub options: Vec<String>,
+
     /// Domain name
     pub domain: Option<String>,
 
@@ -708,6 +2026,24 @@ pub struct DnsConfig {
     pub mode: String,
 }
 
+/// Whether a name can appear in /etc/hosts (letters, digits, `-`, `_` and `.`)
+fn is_hostname(name: &str) -> bool {
+    !name.is_empty()
+        && name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | '_' | '.'))
+}
+
+/// Parse a nameserver address, accepting IPv6 in brackets (`[2001:db8::1]`)
+fn parse_nameserver(ns: &str) -> Option<IpAddr> {
+    let ns = ns.trim();
+    let ns = ns
+        .strip_prefix('[')
+        .and_then(|n| n.strip_suffix(']'))
+        .unwrap_or(ns);
+    ns.parse().ok()
+}
+
 fn default_dns_mode() -> String {
     "inherit".to_string()
 }
@@ -718,6 +2054,29 @@ impl DnsConfig {
         self.mode == "inherit" || (self.mode != "custom" && self.nameservers.is_empty())
     }
 
+    /// Check the mode and that every nameserver is an IPv4 or IPv6 address
+    pub fn validate(&self) -> std::result::Result<(), String> {
+        if self.mode != "inherit" && self.mode != "custom" {
+            return Err(format!(
+                "invalid DNS mode '{}' (expected inherit or custom)",
+                self.mode
+            ));
+        }
+        if self.mode == "custom" && self.nameservers.is_empty() {
+            return Err("DNS mode 'custom' requires at least one nameserver".to_string());
+        }
+        for ns in &self.nameservers {
+            if parse_nameserver(ns).is_none() {
+                return Err(format!("invalid nameserver '{}' (expected an IP address)", ns));
+            }
+        }
+        let invalid_option = |o: &&String| o.trim().is_empty() || o.contains('\n');
+        if let Some(option) = self.options.iter().find(invalid_option) {
+            return Err(format!("invalid resolver option {:?}", option));
+        }
+        Ok(())
+    }
+
     /// Generate resolv.conf content
     pub fn to_resolv_conf(&self) -> Option<String> {
         if self.is_inherit() {
@@ -735,26 +2094,227 @@ impl DnsConfig {
         }
 
         for ns in &self.nameservers {
-            content.push_str(&format!("nameserver {}\n", ns));
+            // Written in canonical form; IPv6 addresses must not be bracketed
+            match parse_nameserver(ns) {
+                Some(ip) => content.push_str(&format!("nameserver {}\n", ip)),
+                None => content.push_str(&format!("nameserver {}\n", ns)),
+            }
+        }
+
+        if !self.options.is_empty() {
+            content.push_str(&format!("options {}\n", self.options.join(" ")));
         }
 
         Some(content)
     }
 }
 
-/// Jail mount configuration (_unused: future feature)
-#[derive(Debug, Clone, Deserialize)]
+/// Filesystems mounted into the jail root before it starts (`[jails.mount]`)
+#[derive(Debug, Clone, Default, Deserialize, Serialize)]
 pub struct JailMountConfig {
-    /// Volume mounts in "host:jail" format (_unused: future feature)
+    /// nullfs volumes as "host:jail", or "host:jail:ro" for read-only
     #[serde(default)]
-    #[allow(dead_code)]
     pub volumes: Vec<String>,
+
+    /// Mount fdescfs on /dev/fd
+    #[serde(default)]
+    pub fdescfs: bool,
+
+    /// Mount procfs on /proc
+    #[serde(default)]
+    pub procfs: bool,
+
+    /// tmpfs mounts
+    #[serde(default)]
+    pub tmpfs: Vec<TmpfsMount>,
+}
+
+/// A tmpfs mounted into a jail
+#[derive(Debug, Clone, Deserialize, Serialize)]
+pub struct TmpfsMount {
+    /// Mount point inside the jail
+    pub path: PathBuf,
+
+    /// Size limit (e.g., "512M"; default: unlimited)
+    pub size: Option<String>,
+}
+
+impl JailMountConfig {
+    /// Filesystems to mount, in mount order: volumes, fdescfs, procfs, tmpfs
+    pub fn specs(&self) -> std::result::Result<Vec<MountSpec>, String> {
+        let mut specs = Vec::new();
+        for volume in &self.volumes {
+            let (source, rest) = volume
+                .split_once(':')
+                .ok_or_else(|| format!("invalid volume `{}`: expected host:jail[:ro]", volume))?;
+            let (jail_path, read_only) = match rest.strip_suffix(":ro") {
+                Some(path) => (path, true),
+                None => (rest, false),
+            };
+            if !source.starts_with('/') {
+                return Err(format!("volume source `{}` must be an absolute path", source));
+            }
+            specs.push(MountSpec {
+                fstype: "nullfs",
+                source: Some(PathBuf::from(source)),
+                jail_path: PathBuf::from(jail_path),
+                read_only,
+                size: None,
+            });
+        }
+
+        let pseudo = [(self.fdescfs, "fdescfs", "/dev/fd"), (self.procfs, "procfs", "/proc")];
+        for (_, fstype, path) in pseudo.into_iter().filter(|(enabled, _, _)| *enabled) {
+            specs.push(MountSpec {
+                fstype,
+                source: None,
+                jail_path: PathBuf::from(path),
+                read_only: false,
+                size: None,
+            });
+        }
+
+        for tmpfs in &self.tmpfs {
+            let size = match &tmpfs.size {
+                Some(size) => Some(
+                    parse_size(size).ok_or_else(|| format!("invalid tmpfs size `{}`", size))?,
+                ),
+                None => None,
+            };
+            specs.push(MountSpec {
+                fstype: "tmpfs",
+                source: None,
+                jail_path: tmpfs.path.clone(),
+                read_only: false,
+                size,
+            });
+        }
+
+        // Mount points must be distinct absolute paths below the jail root
+        let mut seen = std::collections::HashSet::new();
+        for spec in &specs {
+            let path = &spec.jail_path;
+            let escapes = path.components().any(|c| c == std::path::Component::ParentDir);
+            if !path.is_absolute() || path == Path::new("/") || escapes {
+                return Err(format!(
+                    "mount point `{}` must be an absolute path inside the jail",
+                    path.display()
+                ));
+            }
+            if !seen.insert(path) {
+                return Err(format!("`{}` is mounted more than once", path.display()));
+            }
+        }
+
+        Ok(specs)
+    }
 }
 
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_discover_config_in_ancestor() {
+        let root = std::env::temp_dir().join(format!("blackship-discover-{}", std::process::id()));
+        let nested = root.join("services").join("web");
+        fs::create_dir_all(&nested).unwrap();
+        fs::write(
+            root.join(DEFAULT_CONFIG_FILE),
+            "[config]\ndata_dir = \"/var/blackship\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(discover(&nested), Some(root.join(DEFAULT_CONFIG_FILE)));
+        assert_eq!(discover(&root), Some(root.join(DEFAULT_CONFIG_FILE)));
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_config_format_detection() {
+        assert_eq!(ConfigFormat::from_path(Path::new("blackship.toml")), ConfigFormat::Toml);
+        assert_eq!(ConfigFormat::from_path(Path::new("blackship.yaml")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("blackship.YML")), ConfigFormat::Yaml);
+        assert_eq!(ConfigFormat::from_path(Path::new("blackship.json")), ConfigFormat::Json);
+        assert_eq!(ConfigFormat::from_path(Path::new("blackship")), ConfigFormat::Toml);
+    }
+
+    #[test]
+    fn test_parse_all_formats() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[networks]]
+name = "backend"
+subnet = "10.0.1.0/24"
+
+[[jails]]
+name = "db"
+path = "/jails/db"
+
+[jails.network]
+networks = ["backend"]
+ip = "10.0.1.10"
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+depends_on = ["db"]
+
+[[jails.hooks]]
+phase = "post_start"
+command = "/usr/local/bin/warmup"
+"#;
+
+        let yaml = r#"
+config:
+  data_dir: /var/blackship
+networks:
+  - name: backend
+    subnet: 10.0.1.0/24
+jails:
+  - name: db
+    path: /jails/db
+    network:
+      networks: [backend]
+      ip: 10.0.1.10
+  - name: web
+    path: /jails/web
+    depends_on: [db]
+    hooks:
+      - phase: post_start
+        command: /usr/local/bin/warmup
+"#;
+
+        let json = r#"{
+  "config": { "data_dir": "/var/blackship" },
+  "networks": [{ "name": "backend", "subnet": "10.0.1.0/24" }],
+  "jails": [
+    {
+      "name": "db",
+      "path": "/jails/db",
+      "network": { "networks": ["backend"], "ip": "10.0.1.10" }
+    },
+    {
+      "name": "web",
+      "path": "/jails/web",
+      "depends_on": ["db"],
+      "hooks": [{ "phase": "post_start", "command": "/usr/local/bin/warmup" }]
+    }
+  ]
+}"#;
+
+        let from_toml = ConfigFormat::Toml.parse(toml).unwrap();
+        let from_yaml = ConfigFormat::Yaml.parse(yaml).unwrap();
+        let from_json = ConfigFormat::Json.parse(json).unwrap();
+
+        assert!(from_toml.validate().is_ok());
+        assert_eq!(format!("{:?}", from_toml), format!("{:?}", from_yaml));
+        assert_eq!(format!("{:?}", from_toml), format!("{:?}", from_json));
+    }
+
     #[test]
     fn test_parse_minimal_config() {
         let toml = r#"
@@ -838,6 +2398,681 @@ data_dir = "/var/blackship"
 name = "webapp"
 path = "/jails/webapp"
 depends_on = ["nonexistent"]
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_autostart_default() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+
+[[jails]]
+name = "debug"
+path = "/jails/debug"
+autostart = false
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.get_jail("web").unwrap().autostarts());
+        assert!(!config.get_jail("debug").unwrap().autostarts());
+    }
+
+    #[test]
+    fn test_oneshot() {
+        let toml = r#"
+[[jails]]
+name = "migrate"
+oneshot = true
+
+[jails.params]
+"exec.start" = "/usr/local/bin/migrate"
+"#;
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        let migrate = config.get_jail("migrate").unwrap();
+        assert!(migrate.oneshot_enabled());
+        assert_eq!(migrate.restart_policy(), RestartPolicy::No);
+
+        let toml = "[[jails]]\nname = \"migrate\"\noneshot = true\nrestart = \"on-failure\"\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert_eq!(config.jails[0].restart_policy(), RestartPolicy::OnFailure);
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_readiness() {
+        let toml = "[[jails]]\nname = \"db\"\nreadiness = \"pg_isready\"\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.jails[0].start_timeout(), Duration::from_secs(60));
+
+        let toml = "[[jails]]\nname = \"db\"\nreadiness = \"pg_isready\"\nstart_timeout = 5\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.jails[0].start_timeout(), Duration::from_secs(5));
+
+        let toml = "[[jails]]\nname = \"db\"\nstart_timeout = 5\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_err());
+
+        let toml = "[[jails]]\nname = \"db\"\nreadiness = \"true\"\nstart_timeout = 0\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_dependency_conditions() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "db"
+
+[jails.healthcheck]
+enabled = true
+
+[[jails.healthcheck.checks]]
+name = "pg"
+command = "pg_isready"
+
+[[jails]]
+name = "migrate"
+
+[jails.params]
+"exec.start" = "/usr/local/bin/migrate"
+
+[[jails]]
+name = "app"
+depends_on = ["db", "migrate"]
+
+[jails.conditions]
+db = "service_healthy"
+migrate = { condition = "service_completed_successfully", timeout = 60 }
+"#;
+        let mut config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.get_jail("db").unwrap().restart_policy(), RestartPolicy::Always);
+        let app = config.get_jail("app").unwrap();
+        assert_eq!(
+            app.dependency_condition("db"),
+            (DependencyCondition::ServiceHealthy, Duration::from_secs(120))
+        );
+        assert_eq!(
+            app.dependency_condition("migrate"),
+            (DependencyCondition::ServiceCompletedSuccessfully, Duration::from_secs(60))
+        );
+
+        // db has health checks but no exec.start to complete
+        config.jails[2].conditions = toml::from_str(r#"db = "service_completed_successfully""#)
+            .unwrap();
+        assert!(config.validate().is_err());
+        config.jails[2].conditions = toml::from_str(r#"web = "service_started""#).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_resolve_presets() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[presets.web]
+"allow.raw_sockets" = true
+securelevel = 1
+
+[presets.hardened]
+securelevel = 2
+
+[[jails]]
+name = "web"
+preset = "web"
+
+[jails.params]
+securelevel = 2
+
+[[jails]]
+name = "db"
+preset = "sysvipc"
+
+[[jails]]
+name = "api"
+preset = "hardened"
+"#;
+        let mut config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.resolve_presets().unwrap();
+        let params = |name: &str| config.get_jail(name).unwrap().params.clone();
+
+        let web = params("web");
+        assert_eq!(web["allow.raw_sockets"], toml::Value::Boolean(true));
+        assert_eq!(web["securelevel"], toml::Value::Integer(2));
+        assert_eq!(params("db")["sysvshm"].as_str(), Some("new"));
+        // A user preset replaces the built-in of the same name
+        assert_eq!(params("api").len(), 1);
+
+        config.jails[0].preset = Some("missing".into());
+        assert!(config.resolve_presets().is_err());
+    }
+
+    #[test]
+    fn test_expand_instances() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+count = 3
+hostname = "web${instance_index}.local"
+depends_on = ["db"]
+
+[[jails]]
+name = "db"
+
+[[jails]]
+name = "lb"
+depends_on = ["web"]
+"#;
+
+        let mut config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.expand_instances().unwrap();
+        assert!(config.validate().is_ok());
+
+        let names: Vec<&str> = config.jails.iter().map(|j| j.name.as_str()).collect();
+        assert_eq!(names, vec!["web-1", "web-2", "web-3", "db", "lb"]);
+
+        let web2 = config.get_jail("web-2").unwrap();
+        assert_eq!(web2.hostname.as_deref(), Some("web2.local"));
+        assert_eq!(web2.template.as_deref(), Some("web"));
+        assert_eq!(web2.depends_on, vec!["db"]);
+
+        assert_eq!(
+            config.get_jail("lb").unwrap().depends_on,
+            vec!["web-1", "web-2", "web-3"]
+        );
+    }
+
+    #[test]
+    fn test_mount_specs() {
+        let toml = r#"
+[[jails]]
+name = "web"
+
+[jails.mount]
+volumes = ["/data/www:/var/www:ro"]
+fdescfs = true
+procfs = true
+
+[[jails.mount.tmpfs]]
+path = "/tmp"
+size = "64M"
+"#;
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        let specs = config.jails[0].mount.as_ref().unwrap().specs().unwrap();
+        let order: Vec<&str> = specs.iter().map(|s| s.fstype).collect();
+        assert_eq!(order, vec!["nullfs", "fdescfs", "procfs", "tmpfs"]);
+        assert_eq!(specs[0].source, Some(PathBuf::from("/data/www")));
+        assert!(specs[0].read_only);
+        assert_eq!(specs[1].jail_path, PathBuf::from("/dev/fd"));
+        assert_eq!(specs[3].size, Some(64 << 20));
+
+        let invalid = [
+            "volumes = [\"/data\"]",
+            "volumes = [\"data:/srv\"]",
+            "volumes = [\"/data:srv\"]",
+            "volumes = [\"/data:/../etc\"]",
+            "volumes = [\"/a:/proc\"]\nprocfs = true",
+            "tmpfs = [{ path = \"/tmp\", size = \"lots\" }]",
+        ];
+        for mount in invalid {
+            let toml = format!("[[jails]]\nname = \"web\"\n[jails.mount]\n{}\n", mount);
+            let config: BlackshipConfig = toml::from_str(&toml).unwrap();
+            assert!(config.validate().is_err(), "{}", mount);
+        }
+    }
+
+    #[test]
+    fn test_securelevel_and_devfs_ruleset() {
+        let toml = "[[jails]]\nname = \"web\"\nsecurelevel = 3\ndevfs_ruleset = 4\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.validate().unwrap();
+        assert_eq!(config.jails[0].securelevel, Some(3));
+        assert_eq!(config.jails[0].devfs_ruleset, Some(4));
+
+        for level in [-2, 4] {
+            let toml = format!("[[jails]]\nname = \"web\"\nsecurelevel = {}\n", level);
+            let config: BlackshipConfig = toml::from_str(&toml).unwrap();
+            assert!(config.validate().is_err());
+        }
+
+        let toml = "[[jails]]\nname = \"web\"\ndevfs_ruleset = -1\n";
+        assert!(toml::from_str::<BlackshipConfig>(toml).is_err());
+    }
+
+    #[test]
+    fn test_nested_jails() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+project = "app"
+
+[[jails]]
+name = "host"
+
+[jails.params]
+"children.max" = 2
+
+[[jails]]
+name = "web"
+parent = "host"
+
+[[jails]]
+name = "worker"
+parent = "web"
+"#;
+
+        // web is a parent too but does not allow children
+        let mut config: BlackshipConfig = toml::from_str(toml).unwrap();
+        config.link_parents();
+        assert!(config.validate().is_err());
+
+        let toml = toml.replace("parent = \"web\"", "parent = \"host\"");
+        let mut config: BlackshipConfig = toml::from_str(&toml).unwrap();
+        config.link_parents();
+        config.validate().unwrap();
+        assert_eq!(config.get_jail("web").unwrap().depends_on, vec!["host"]);
+        assert_eq!(config.jail_name("host"), "app-host");
+        assert_eq!(config.jail_name("web"), "app-host.app-web");
+        assert_eq!(
+            config.resolve_jail_names("app-host.app-worker"),
+            Some(("worker".to_string(), "app-host.app-worker".to_string()))
+        );
+        // Nested roots live inside the parent's root
+        assert_eq!(
+            config.get_jail("web").unwrap().path,
+            Some(PathBuf::from("/var/blackship/jails/app-host/jails/app-web"))
+        );
+
+        // An explicit path outside the parent's root is rejected
+        let outside = toml.replacen(
+            "parent = \"host\"",
+            "parent = \"host\"\npath = \"/jails/web\"",
+            1,
+        );
+        let mut config: BlackshipConfig = toml::from_str(&outside).unwrap();
+        config.link_parents();
+        assert!(config.validate().is_err());
+
+        // A third child exceeds children.max
+        let toml = format!("{}\n[[jails]]\nname = \"cron\"\nparent = \"host\"\n", toml);
+        let config: BlackshipConfig = toml::from_str(&toml).unwrap();
+        assert!(config.validate().is_err());
+
+        let toml = "[[jails]]\nname = \"a\"\nparent = \"missing\"\n";
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_expand_instances_rejects_static_ip() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+count = 2
+[jails.network]
+ip = "10.0.1.10"
+"#;
+
+        let mut config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.expand_instances().is_err());
+    }
+
+    #[test]
+    fn test_global_hooks() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[config.hooks]]
+name = "notify"
+phase = "post_start"
+command = "/usr/local/bin/notify"
+
+[[config.hooks]]
+phase = "pre_stop"
+command = "/usr/local/bin/drain"
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+
+[[jails.hooks]]
+phase = "post_start"
+command = "/usr/local/bin/warmup"
+
+[[jails]]
+name = "batch"
+path = "/jails/batch"
+skip_hooks = ["notify"]
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let web = config.jails[0].effective_hooks(&config.config);
+        let commands: Vec<&str> = web.iter().map(|h| h.command.as_str()).collect();
+        assert_eq!(
+            commands,
+            vec!["/usr/local/bin/notify", "/usr/local/bin/drain", "/usr/local/bin/warmup"]
+        );
+
+        let batch = config.jails[1].effective_hooks(&config.config);
+        assert_eq!(batch.len(), 1);
+        assert_eq!(batch[0].command, "/usr/local/bin/drain");
+    }
+
+    #[test]
+    fn test_jail_network_resolution() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[networks]]
+name = "backend"
+subnet = "10.0.1.0/24"
+
+[[jails]]
+name = "db"
+path = "/jails/db"
+
+[jails.network]
+networks = ["backend"]
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+
+[jails.network]
+networks = ["backend"]
+gateway = "10.0.1.254"
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        let db = &config.jails[0];
+        assert_eq!(config.primary_network(db).unwrap().subnet, "10.0.1.0/24");
+        assert_eq!(config.jail_gateway(db), Some("10.0.1.1".parse().unwrap()));
+        assert_eq!(
+            config.jail_gateway(&config.jails[1]),
+            Some("10.0.1.254".parse().unwrap())
+        );
+    }
+
+    #[test]
+    fn test_vnet_interface_validation() {
+        let config = |network: &str| {
+            let toml = format!(
+                "[config]\ndata_dir = \"/var/blackship\"\n\n\
+                 [[jails]]\nname = \"fast\"\n[jails.network]\n{}\n\n\
+                 [[jails]]\nname = \"edge\"\n[jails.network]\nvnet = true\nbridge = \"bs0\"\n",
+                network
+            );
+            toml::from_str::<BlackshipConfig>(&toml).unwrap().validate()
+        };
+
+        assert!(config("vnet = true\nvnet_interface = \"ix1\"").is_ok());
+
+        let err = config("vnet_interface = \"ix1\"").unwrap_err().to_string();
+        assert!(err.contains("requires vnet = true"), "{}", err);
+
+        let err = config("vnet = true\nvnet_interface = \"ix1\"\nbridge = \"bs0\"")
+            .unwrap_err()
+            .to_string();
+        assert!(err.contains("do not apply"), "{}", err);
+    }
+
+    #[test]
+    fn test_network_address_and_alias_prefix() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+[jails.network]
+ip_cidr = "10.0.2.10/24"
+interface = "lo1"
+
+[[jails]]
+name = "db"
+[jails.network]
+ip = "10.0.2.11"
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        let web = config.jails[0].network.as_ref().unwrap();
+        assert_eq!(web.address(), Some("10.0.2.10".parse().unwrap()));
+        assert_eq!(web.alias_prefix_len(), 24);
+        assert_eq!(web.interface.as_deref(), Some("lo1"));
+
+        let db = config.jails[1].network.as_ref().unwrap();
+        assert_eq!(db.address(), Some("10.0.2.11".parse().unwrap()));
+        assert_eq!(db.alias_prefix_len(), 32);
+    }
+
+    #[test]
+    fn test_parse_size() {
+        assert_eq!(parse_size("1024"), Some(1024));
+        assert_eq!(parse_size("512K"), Some(512 * 1024));
+        assert_eq!(parse_size("10G"), Some(10 << 30));
+        assert_eq!(parse_size("10gb"), Some(10 << 30));
+        assert_eq!(parse_size("1.5T"), Some(3 << 39));
+        assert_eq!(parse_size("10X"), None);
+        assert_eq!(parse_size("G"), None);
+    }
+
+    #[test]
+    fn test_dns_validation() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+
+[jails.network]
+ip = "10.0.1.10"
+
+[jails.network.dns]
+mode = "custom"
+nameservers = ["1.1.1.1", "[2606:4700:4700::1111]"]
+options = ["timeout:2", "attempts:3"]
+"#;
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+        let dns = &config.jails[0].network.as_ref().unwrap().dns;
+        assert_eq!(
+            dns.to_resolv_conf().unwrap(),
+            "nameserver 1.1.1.1\nnameserver 2606:4700:4700::1111\noptions timeout:2 attempts:3\n"
+        );
+
+        let config: BlackshipConfig = toml::from_str(&toml.replace("1.1.1.1", "1.1.1.l")).unwrap();
+        let err = config.validate().unwrap_err();
+        assert!(err.to_string().contains("1.1.1.l"), "{}", err);
+    }
+
+    #[test]
+    fn test_parse_schedule() {
+        assert_eq!(parse_schedule("hourly"), Some(Duration::from_secs(3600)));
+        assert_eq!(parse_schedule("weekly"), Some(Duration::from_secs(604800)));
+        assert_eq!(parse_schedule("30m"), Some(Duration::from_secs(1800)));
+        assert_eq!(parse_schedule("2d"), Some(Duration::from_secs(172800)));
+        assert_eq!(parse_schedule("0h"), None);
+        assert_eq!(parse_schedule("6"), None);
+        assert_eq!(parse_schedule("often"), None);
+        assert_eq!(parse_schedule("99999999999999999d"), None);
+    }
+
+    #[test]
+    fn test_quota_validation() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+zfs_enabled = true
+zpool = "zroot"
+
+[[jails]]
+name = "web"
+quota = "10G"
+"#;
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+
+        let config: BlackshipConfig = toml::from_str(&toml.replace("10G", "ten")).unwrap();
+        let err = config.validate().unwrap_err().to_string();
+        assert!(err.contains("invalid quota 'ten'"), "{}", err);
+
+        // No dataset to put the quota on
+        let toml = toml.replace("zfs_enabled = true", "zfs_enabled = false");
+        let config: BlackshipConfig = toml::from_str(&toml).unwrap();
+        assert!(config.validate().is_err());
+    }
+
+    #[test]
+    fn test_notifications_require_target() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[notifications]
+max_per_minute = 5
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_err());
+
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[notifications]
+webhook_url = "https://hooks.example.com/blackship"
+"#;
+
+        let config: BlackshipConfig = toml::from_str(toml).unwrap();
+        assert!(config.validate().is_ok());
+        assert_eq!(config.notifications.unwrap().max_per_minute, 10);
+    }
+
+    #[test]
+    fn test_merge_provenance() {
+        let root =
+            std::env::temp_dir().join(format!("blackship-provenance-{}", std::process::id()));
+        fs::create_dir_all(&root).unwrap();
+        let base = root.join("blackship.toml");
+        let over = root.join("blackship.override.toml");
+        fs::write(
+            &base,
+            r#"
+[config]
+data_dir = "/var/blackship"
+zpool = "zroot"
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+hostname = "web.local"
+"#,
+        )
+        .unwrap();
+        fs::write(
+            &over,
+            r#"
+[config]
+data_dir = "/srv/blackship"
+
+[[jails]]
+name = "web"
+hostname = "web.example.com"
+
+[jails.params]
+"allow.raw_sockets" = true
+"#,
+        )
+        .unwrap();
+
+        let (config, provenance) = load_merged_explained(&[base.clone(), over.clone()]).unwrap();
+        assert_eq!(config.jails[0].hostname.as_deref(), Some("web.example.com"));
+        assert_eq!(provenance.source("config.data_dir"), Some(over.as_path()));
+        assert_eq!(provenance.source("config.zpool"), Some(base.as_path()));
+        assert_eq!(provenance.source("jails.web.path"), Some(base.as_path()));
+        assert_eq!(provenance.source("jails.web.hostname"), Some(over.as_path()));
+        assert_eq!(
+            provenance.source("jails.web.params.allow.raw_sockets"),
+            Some(over.as_path())
+        );
+        assert_eq!(provenance.source("jails.web.release"), None);
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_provenance_covers_every_field() {
+        // The merges build their results field by field, so the compiler keeps
+        // them complete; the provenance lists must keep up with them.
+        let config: BlackshipConfig = toml::from_str(
+            "[config]\ndata_dir = \"/var/blackship\"\n\n[[jails]]\nname = \"web\"\n",
+        )
+        .unwrap();
+        let fields = |value: serde_json::Value| -> Vec<String> {
+            let mut keys: Vec<String> = value.as_object().unwrap().keys().cloned().collect();
+            keys.sort();
+            keys
+        };
+        let listed = |list: Vec<(&str, bool)>| -> Vec<String> {
+            let mut names: Vec<String> = list.into_iter().map(|(n, _)| n.to_string()).collect();
+            names.sort();
+            names
+        };
+
+        assert_eq!(
+            listed(global_fields(&config.config)),
+            fields(serde_json::to_value(&config.config).unwrap())
+        );
+        assert_eq!(
+            listed(logging_fields(&config.logging)),
+            fields(serde_json::to_value(&config.logging).unwrap())
+        );
+        assert_eq!(
+            listed(snapshots_fields(&config.snapshots)),
+            fields(serde_json::to_value(&config.snapshots).unwrap())
+        );
+        let mut jail_keys = fields(serde_json::to_value(&config.jails[0]).unwrap());
+        jail_keys.retain(|key| key != "params");
+        assert_eq!(listed(jail_fields(&config.jails[0])), jail_keys);
+    }
+
+    #[test]
+    fn test_skip_unknown_global_hook_error() {
+        let toml = r#"
+[config]
+data_dir = "/var/blackship"
+
+[[jails]]
+name = "web"
+path = "/jails/web"
+skip_hooks = ["missing"]
 "#;
 
         let config: BlackshipConfig = toml::from_str(toml).unwrap();
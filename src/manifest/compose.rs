@@ -0,0 +1,311 @@
+//! Best-effort import of docker-compose files
+//!
+//! Generates a blackship configuration from `services`, mapping what has an
+//! equivalent and leaving the rest as comments for manual review:
+//! - Each service becomes a `[[jails]]` entry (`.` in names becomes `-`)
+//! - `depends_on` (list or map form) and `hostname` carry over; `condition`s
+//!   are commented out, since health checks are not imported
+//! - `build` (a path or `context`) becomes `build`; it must hold a Jailfile
+//! - `command` and `environment` become `exec.start`, run through env(1);
+//!   since compose commands stay in the foreground, `command` is detached
+//!   with daemon(8) so the jail start does not wait for it
+//! - `ports` become `blackship expose` commands, since port forwards are not
+//!   part of the configuration file
+
+use crate::error::{Error, Result};
+use crate::hooks::shell_quote;
+use serde_yaml::{Mapping, Value};
+use std::fmt::Write;
+
+/// Command run by `exec.start` when the service has environment but no command
+const DEFAULT_START: &str = "/bin/sh /etc/rc";
+
+/// Generate a blackship configuration from docker-compose YAML
+pub fn import(content: &str) -> Result<String> {
+    let compose: Mapping = serde_yaml::from_str(content)?;
+    let services = compose
+        .get("services")
+        .and_then(Value::as_mapping)
+        .ok_or_else(|| {
+            Error::ConfigValidation("docker-compose file has no 'services' section".into())
+        })?;
+
+    let mut out = String::from(
+        "# Imported from docker-compose; review the commented lines before use\n\n\
+         [config]\n\
+         data_dir = \"/var/blackship\"\n",
+    );
+    for (key, _) in compose.iter().filter(|(key, _)| !matches!(key.as_str(), Some("services"))) {
+        if let Some(key) = key.as_str()
+            && key != "version"
+        {
+            let _ = writeln!(out, "# top-level '{}' not imported", key);
+        }
+    }
+
+    for (name, service) in services {
+        let Some(name) = name.as_str() else { continue };
+        let empty = Mapping::new();
+        let service = service.as_mapping().unwrap_or(&empty);
+        import_service(&mut out, name, service);
+    }
+    Ok(out)
+}
+
+/// Append the `[[jails]]` entry for one service
+fn import_service(out: &mut String, name: &str, service: &Mapping) {
+    let jail = jail_name(name);
+    let _ = write!(out, "\n[[jails]]\nname = {}\n", quote(&jail));
+
+    if let Some(hostname) = service.get("hostname").and_then(Value::as_str) {
+        let _ = writeln!(out, "hostname = {}", quote(hostname));
+    }
+    if let Some(build) = service.get("build") {
+        let context = build
+            .as_str()
+            .or_else(|| build.get("context").and_then(Value::as_str));
+        if let Some(context) = context {
+            let _ = writeln!(
+                out,
+                "build = {}  # expects a Jailfile, not a Dockerfile",
+                quote(context)
+            );
+        }
+    }
+    let depends_on: Vec<String> = match service.get("depends_on") {
+        Some(Value::Sequence(deps)) => deps.iter().filter_map(Value::as_str).collect(),
+        Some(Value::Mapping(deps)) => deps.keys().filter_map(Value::as_str).collect(),
+        _ => Vec::new(),
+    }
+    .into_iter()
+    .map(jail_name)
+    .collect();
+    if !depends_on.is_empty() {
+        let deps: Vec<String> = depends_on.iter().map(|d| quote(d)).collect();
+        let _ = writeln!(out, "depends_on = [{}]", deps.join(", "));
+    }
+    let conditions: Vec<String> = service
+        .get("depends_on")
+        .and_then(Value::as_mapping)
+        .into_iter()
+        .flatten()
+        .filter_map(|(dep, spec)| {
+            let condition = spec.get("condition").and_then(Value::as_str)?;
+            let dep = jail_name(dep.as_str()?);
+            (condition != "service_started")
+                .then(|| format!("{} = {}", quote(&dep), quote(condition)))
+        })
+        .collect();
+    if !conditions.is_empty() {
+        let _ = writeln!(
+            out,
+            "# conditions = {{ {} }}  # needs health checks / exec.start on the dependencies",
+            conditions.join(", ")
+        );
+    }
+    if let Some(image) = service.get("image").and_then(Value::as_str) {
+        let _ = writeln!(
+            out,
+            "# image: {} (no equivalent: set `release` or `build`)",
+            image
+        );
+    }
+    for port in service.get("ports").and_then(Value::as_sequence).into_iter().flatten() {
+        match expose_command(&jail, port) {
+            Some(command) => {
+                let _ = writeln!(out, "# ports: run `{}`", command);
+            }
+            None => comment_value(out, "ports", port),
+        }
+    }
+    for (key, value) in service {
+        let Some(key) = key.as_str() else { continue };
+        let handled = [
+            "hostname", "build", "depends_on", "image", "ports", "command", "environment",
+        ];
+        if !handled.contains(&key) {
+            comment_value(out, key, value);
+        }
+    }
+
+    if let Some(start) = exec_start(&jail, service) {
+        let _ = write!(out, "\n[jails.params]\n\"exec.start\" = {}\n", quote(&start));
+    }
+}
+
+/// Jail name for a compose service (jail names cannot contain `.`)
+fn jail_name(service: &str) -> String {
+    service.replace('.', "-")
+}
+
+/// `exec.start` built from the service's `command` and `environment`
+fn exec_start(jail: &str, service: &Mapping) -> Option<String> {
+    let command = match service.get("command") {
+        Some(Value::String(command)) => Some(command.clone()),
+        Some(Value::Sequence(words)) => Some(
+            words
+                .iter()
+                .filter_map(scalar)
+                .map(|word| shell_quote(&word))
+                .collect::<Vec<_>>()
+                .join(" "),
+        ),
+        _ => None,
+    };
+    let env: Vec<String> = match service.get("environment") {
+        Some(Value::Sequence(vars)) => vars.iter().filter_map(scalar).collect(),
+        Some(Value::Mapping(vars)) => vars
+            .iter()
+            .filter_map(|(k, v)| Some(format!("{}={}", k.as_str()?, scalar(v).unwrap_or_default())))
+            .collect(),
+        _ => Vec::new(),
+    };
+    let command = match (command, env.is_empty()) {
+        (None, true) => return None,
+        (None, false) => DEFAULT_START.to_string(),
+        (Some(command), _) => format!("daemon -f -p /var/run/{}.pid {}", jail, command),
+    };
+    if env.is_empty() {
+        return Some(command);
+    }
+    let vars: Vec<String> = env.iter().map(|var| shell_quote(var)).collect();
+    Some(format!("env {} {}", vars.join(" "), command))
+}
+
+/// `blackship expose` for a compose port mapping, if it has a host port
+///
+/// Accepts `"8080:80"`, `"127.0.0.1:8080:80/udp"` and the long form with
+/// `published`/`target`/`protocol`/`host_ip`.
+fn expose_command(jail: &str, port: &Value) -> Option<String> {
+    let (bind_ip, published, target, protocol) = match port {
+        Value::Mapping(_) => (
+            port.get("host_ip").and_then(Value::as_str).map(str::to_string),
+            port.get("published").and_then(scalar)?,
+            port.get("target").and_then(scalar)?,
+            port.get("protocol").and_then(Value::as_str).unwrap_or("tcp").to_string(),
+        ),
+        _ => {
+            let spec = scalar(port)?;
+            let (spec, protocol) = spec.split_once('/').unwrap_or((&spec, "tcp"));
+            let parts: Vec<&str> = spec.split(':').collect();
+            let (bind_ip, published, target) = match parts[..] {
+                [published, target] => (None, published, target),
+                [bind_ip, published, target] => (Some(bind_ip.to_string()), published, target),
+                _ => return None,
+            };
+            (bind_ip, published.to_string(), target.to_string(), protocol.to_string())
+        }
+    };
+    let published: u16 = published.parse().ok()?;
+    let target: u16 = target.parse().ok()?;
+
+    let mut command = format!("blackship expose {} -p {}", jail, published);
+    if target != published {
+        let _ = write!(command, " -i {}", target);
+    }
+    if protocol != "tcp" {
+        let _ = write!(command, " --proto {}", protocol);
+    }
+    if let Some(bind_ip) = bind_ip.filter(|ip| !ip.is_empty()) {
+        let _ = write!(command, " -I {}", bind_ip);
+    }
+    Some(command)
+}
+
+/// Comment out an unsupported key, keeping its YAML for reference
+fn comment_value(out: &mut String, key: &str, value: &Value) {
+    let mut entry = Mapping::new();
+    entry.insert(Value::String(key.to_string()), value.clone());
+    let yaml = serde_yaml::to_string(&entry).unwrap_or_default();
+    let _ = writeln!(out, "# unsupported:");
+    for line in yaml.lines() {
+        let _ = writeln!(out, "#   {}", line);
+    }
+}
+
+/// String form of a scalar (strings, numbers, booleans)
+fn scalar(value: &Value) -> Option<String> {
+    match value {
+        Value::String(s) => Some(s.clone()),
+        Value::Number(n) => Some(n.to_string()),
+        Value::Bool(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+/// TOML string literal
+fn quote(s: &str) -> String {
+    toml::Value::String(s.to_string()).to_string()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::manifest::ConfigFormat;
+
+    #[test]
+    fn test_import() {
+        let compose = r#"
+version: "3.8"
+services:
+  db:
+    image: postgres:16
+    environment:
+      POSTGRES_PASSWORD: "s3cret pass"
+    volumes:
+      - data:/var/lib/postgresql/data
+  web.app:
+    build: ./web
+    command: ["nginx", "-g", "daemon off;"]
+    depends_on:
+      db:
+        condition: service_healthy
+    ports:
+      - "8080:80"
+      - "127.0.0.1:5353:53/udp"
+volumes:
+  data:
+"#;
+        let toml = import(compose).unwrap();
+        assert!(toml.contains("# top-level 'volumes' not imported"), "{}", toml);
+        assert!(toml.contains("# image: postgres:16"), "{}", toml);
+        assert!(toml.contains("#   volumes:"), "{}", toml);
+        assert!(toml.contains("# ports: run `blackship expose web-app -p 8080 -i 80`"), "{}", toml);
+        assert!(
+            toml.contains("blackship expose web-app -p 5353 -i 53 --proto udp -I 127.0.0.1"),
+            "{}",
+            toml
+        );
+
+        let config = ConfigFormat::Toml.parse(&toml).unwrap();
+        config.validate().unwrap();
+        let db = config.get_jail("db").unwrap();
+        let start = db.params["exec.start"].as_str().unwrap();
+        assert_eq!(start, "env 'POSTGRES_PASSWORD=s3cret pass' /bin/sh /etc/rc");
+
+        let web = config.get_jail("web-app").unwrap();
+        assert_eq!(web.depends_on, vec!["db"]);
+        assert!(toml.contains("# conditions = { \"db\" = \"service_healthy\" }"), "{}", toml);
+        assert_eq!(web.build.as_deref(), Some(std::path::Path::new("./web")));
+        assert_eq!(
+            web.params["exec.start"].as_str(),
+            Some("daemon -f -p /var/run/web-app.pid nginx -g 'daemon off;'")
+        );
+    }
+
+    #[test]
+    fn test_exec_start_detaches_command() {
+        let service: Mapping =
+            serde_yaml::from_str("command: node server.js\nenvironment: [PORT=3000]\n").unwrap();
+        assert_eq!(
+            exec_start("api", &service).as_deref(),
+            Some("env PORT=3000 daemon -f -p /var/run/api.pid node server.js")
+        );
+        assert_eq!(exec_start("api", &Mapping::new()), None);
+    }
+
+    #[test]
+    fn test_import_requires_services() {
+        assert!(import("version: '3'\n").is_err());
+    }
+}
@@ -0,0 +1,428 @@
+//! Format-preserving edits of `blackship.toml`
+//!
+//! Values are addressed by dotted paths: `config.mirror_url`, or
+//! `jails.web.network.ip` where `web` selects the `[[jails]]` entry with that
+//! `name` (a number selects by position instead). Comments, ordering and
+//! whitespace outside the edited value are left untouched.
+//!
+//! New `[[jails]]` entries are appended the same way (see [`add_jail`]).
+
+use super::{BlackshipConfig, ConfigFormat};
+use crate::error::{Error, Result};
+use crate::network::ip::IpPool;
+use ipnet::IpNet;
+use std::fs;
+use std::net::IpAddr;
+use std::path::Path;
+use toml_edit::{Array, ArrayOfTables, DocumentMut, Item, Table, TableLike, Value, value};
+
+/// A `[[jails]]` entry to append with [`add_jail`]
+#[derive(Debug, Clone, Default)]
+pub struct NewJail {
+    /// Jail name
+    pub name: String,
+    /// Release to provision the jail from
+    pub release: Option<String>,
+    /// Jails that must start first
+    pub depends_on: Vec<String>,
+    /// Network to attach to (`[[networks]]` name)
+    pub network: Option<String>,
+    /// Address; picked from `network` when unset
+    pub ip: Option<IpAddr>,
+}
+
+/// Print the value at `key` in a TOML document
+///
+/// Strings are printed without quotes; tables as `key = value` lines.
+pub fn get(content: &str, key: &str) -> Result<String> {
+    let mut doc = parse(content)?;
+    let segments = split(key)?;
+    let (table, last) = resolve(doc.as_table_mut(), &segments, false)?;
+
+    let Some(last) = last else {
+        return Ok(render_table(table));
+    };
+    match table.get(last) {
+        Some(Item::Value(Value::String(s))) => Ok(s.value().clone()),
+        Some(Item::Value(value)) => Ok(value.to_string().trim().to_string()),
+        Some(item) => match item.as_table_like() {
+            Some(table) => Ok(render_table(table)),
+            None => Ok(item.to_string().trim().to_string()),
+        },
+        None => Err(not_found(key)),
+    }
+}
+
+/// Set the value at `key` in a TOML document, returning the new document
+///
+/// `value` is parsed as a TOML value (`42`, `true`, `["a", "b"]`,
+/// `"quoted"`), falling back to a plain string. Missing tables on the way
+/// are created; `[[jails]]` entries must already exist. Keys that are not
+/// configuration fields are refused, since they would never be read.
+pub fn set(content: &str, key: &str, value: &str) -> Result<String> {
+    let mut doc = parse(content)?;
+    let segments = split(key)?;
+    let (table, last) = resolve(doc.as_table_mut(), &segments, true)?;
+    let last = last.ok_or_else(|| {
+        Error::ConfigValidation(format!("'{}' is a table; set one of its keys instead", key))
+    })?;
+
+    let mut new = value
+        .parse::<Value>()
+        .unwrap_or_else(|_| Value::from(value));
+    // Keep the comment and spacing around a replaced value
+    if let Some(old) = table.get(last).and_then(Item::as_value) {
+        *new.decor_mut() = old.decor().clone();
+    }
+    table.insert(last, Item::Value(new));
+
+    let updated = doc.to_string();
+    check_known_field(&updated, key)?;
+    Ok(updated)
+}
+
+/// Refuse a `key` that the parsed configuration drops
+///
+/// Unknown keys are ignored when parsing, so the configuration is
+/// re-serialized and the key must still be there.
+fn check_known_field(content: &str, key: &str) -> Result<()> {
+    let config: BlackshipConfig = ConfigFormat::Toml.parse(content)?;
+    let serialized = toml::to_string(&config).map_err(|e| {
+        Error::ConfigValidation(format!("Failed to serialize configuration: {}", e))
+    })?;
+    get(&serialized, key)
+        .map(|_| ())
+        .map_err(|_| Error::ConfigValidation(format!("'{}' is not a configuration field", key)))
+}
+
+/// Append a `[[jails]]` entry to a TOML document, returning the new document
+///
+/// Refuses a name that is already taken.
+pub fn add_jail(content: &str, jail: &NewJail) -> Result<String> {
+    let mut doc = parse(content)?;
+    let jails = doc
+        .entry("jails")
+        .or_insert_with(|| Item::ArrayOfTables(ArrayOfTables::new()))
+        .as_array_of_tables_mut()
+        .ok_or_else(|| {
+            Error::ConfigValidation("`jails` must be written as [[jails]] tables".into())
+        })?;
+    if jails
+        .iter()
+        .any(|t| t.get("name").and_then(Item::as_str) == Some(jail.name.as_str()))
+    {
+        return Err(Error::ConfigValidation(format!(
+            "Jail '{}' already exists",
+            jail.name
+        )));
+    }
+
+    let mut table = Table::new();
+    table.insert("name", value(&jail.name));
+    if let Some(release) = &jail.release {
+        table.insert("release", value(release));
+    }
+    if !jail.depends_on.is_empty() {
+        table.insert("depends_on", value(Array::from_iter(&jail.depends_on)));
+    }
+    if jail.network.is_some() || jail.ip.is_some() {
+        let mut network = Table::new();
+        if let Some(name) = &jail.network {
+            network.insert("networks", value(Array::from_iter([name])));
+        }
+        if let Some(ip) = jail.ip {
+            network.insert("ip", value(ip.to_string()));
+        }
+        table.insert("network", Item::Table(network));
+    }
+    jails.push(table);
+
+    Ok(doc.to_string())
+}
+
+/// Set a value in a configuration file, validating the result first
+///
+/// The file is replaced atomically (write to a temporary file, then rename),
+/// and only when the edited configuration passes validation.
+pub fn set_file(path: &Path, key: &str, value: &str) -> Result<()> {
+    let content = read_toml(path)?;
+    write_validated(path, set(&content, key, value)?)
+}
+
+/// Append a jail to a configuration file, validating the result first
+///
+/// Without an explicit IP, the next free address of `jail.network` is
+/// assigned, skipping addresses other jails already use. Returns the entry
+/// as written.
+pub fn add_jail_file(path: &Path, mut jail: NewJail) -> Result<NewJail> {
+    let content = read_toml(path)?;
+    if jail.ip.is_none()
+        && let Some(network) = &jail.network
+    {
+        let config: BlackshipConfig = ConfigFormat::Toml.parse(&content)?;
+        jail.ip = Some(next_free_ip(&config, network)?);
+    }
+    write_validated(path, add_jail(&content, &jail)?)?;
+    Ok(jail)
+}
+
+/// Next address in a configured network that no jail uses
+fn next_free_ip(config: &BlackshipConfig, network: &str) -> Result<IpAddr> {
+    let def = config
+        .networks
+        .iter()
+        .find(|n| n.name == network)
+        .ok_or_else(|| Error::ConfigValidation(format!("Unknown network '{}'", network)))?;
+    let subnet: IpNet = def.subnet.parse().map_err(|e| {
+        Error::ConfigValidation(format!(
+            "Invalid subnet '{}' for network '{}': {}",
+            def.subnet, network, e
+        ))
+    })?;
+    let mut pool = match def.effective_gateway() {
+        Some(gateway) => IpPool::with_gateway(subnet, gateway)?,
+        None => IpPool::new(subnet)?,
+    };
+
+    for jail in &config.jails {
+        let Some(net) = &jail.network else { continue };
+        let cidr = net
+            .ip_cidr
+            .as_deref()
+            .and_then(|cidr| cidr.split('/').next())
+            .and_then(|ip| ip.parse().ok());
+        for ip in net.ip.into_iter().chain(cidr) {
+            // Addresses outside the subnet or already counted are fine to skip
+            let _ = pool.allocate_specific(ip);
+        }
+    }
+    pool.allocate()
+}
+
+/// Read a configuration file that `config set`/`add-jail` can edit
+fn read_toml(path: &Path) -> Result<String> {
+    if ConfigFormat::from_path(path) != ConfigFormat::Toml {
+        return Err(Error::ConfigValidation(format!(
+            "{} is not TOML; only TOML configuration files can be edited",
+            path.display()
+        )));
+    }
+    fs::read_to_string(path).map_err(|e| Error::ConfigRead {
+        path: path.to_path_buf(),
+        source: e,
+    })
+}
+
+/// Validate an edited configuration and atomically replace `path` with it
+fn write_validated(path: &Path, updated: String) -> Result<()> {
+    let mut config: BlackshipConfig = ConfigFormat::Toml.parse(&updated)?;
+    config.resolve_presets()?;
+    config.expand_instances()?;
+    config.link_parents();
+    config.validate()?;
+
+    let file_name = path.file_name().and_then(|n| n.to_str()).unwrap_or("blackship.toml");
+    let tmp = path.with_file_name(format!(".{}.{}.tmp", file_name, std::process::id()));
+    fs::write(&tmp, updated)?;
+    fs::rename(&tmp, path).inspect_err(|_| {
+        let _ = fs::remove_file(&tmp);
+    })?;
+    Ok(())
+}
+
+fn parse(content: &str) -> Result<DocumentMut> {
+    content
+        .parse()
+        .map_err(|e| Error::ConfigValidation(format!("Failed to parse TOML: {}", e)))
+}
+
+fn split(key: &str) -> Result<Vec<&str>> {
+    let segments: Vec<&str> = key.split('.').collect();
+    if segments.iter().any(|s| s.is_empty()) {
+        return Err(Error::ConfigValidation(format!("Invalid config path '{}'", key)));
+    }
+    Ok(segments)
+}
+
+fn not_found(key: &str) -> Error {
+    Error::ConfigValidation(format!("'{}' is not set", key))
+}
+
+/// Walk `segments` down from `root`
+///
+/// Returns the table holding the final key and that key, or just the table
+/// when the path ends on a `[[jails]]`-style entry. With `create`, missing
+/// tables are added along the way.
+fn resolve<'a, 'k>(
+    root: &'a mut dyn TableLike,
+    segments: &[&'k str],
+    create: bool,
+) -> Result<(&'a mut dyn TableLike, Option<&'k str>)> {
+    let path = segments.join(".");
+    let mut current = root;
+    let mut i = 0;
+
+    while i < segments.len() {
+        let key = segments[i];
+        if i == segments.len() - 1 && !matches!(current.get(key), Some(Item::ArrayOfTables(_))) {
+            return Ok((current, Some(key)));
+        }
+
+        if current.get(key).is_none() {
+            if !create {
+                return Err(not_found(&path));
+            }
+            current.insert(key, Item::Table(Table::new()));
+        }
+        current = match current.get_mut(key) {
+            Some(Item::ArrayOfTables(tables)) => {
+                let selector = segments.get(i + 1).ok_or_else(|| {
+                    Error::ConfigValidation(format!(
+                        "'{}' is a list; select an entry by name, e.g. {}.<name>",
+                        path, key
+                    ))
+                })?;
+                i += 1;
+                select(tables, selector).ok_or_else(|| {
+                    Error::ConfigValidation(format!("No [[{}]] entry named '{}'", key, selector))
+                })?
+            }
+            Some(item) => item.as_table_like_mut().ok_or_else(|| {
+                Error::ConfigValidation(format!(
+                    "'{}' in '{}' is a value, not a table",
+                    key, path
+                ))
+            })?,
+            None => return Err(not_found(&path)),
+        };
+        i += 1;
+    }
+
+    Ok((current, None))
+}
+
+/// Pick an array-of-tables entry by its `name`, or by position
+fn select<'a>(tables: &'a mut ArrayOfTables, selector: &str) -> Option<&'a mut dyn TableLike> {
+    let by_name = tables
+        .iter()
+        .position(|t| t.get("name").and_then(Item::as_str) == Some(selector));
+    let index = by_name.or_else(|| selector.parse().ok())?;
+    tables.get_mut(index).map(|t| t as &mut dyn TableLike)
+}
+
+fn render_table(table: &dyn TableLike) -> String {
+    table
+        .iter()
+        .map(|(key, item)| format!("{} = {}", key, item.to_string().trim()))
+        .collect::<Vec<_>>()
+        .join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CONFIG: &str = r#"# Fleet
+[config]
+data_dir = "/var/blackship"  # keep
+
+[[jails]]
+name = "web"
+
+[jails.network]
+ip = "10.0.1.10"   # public
+
+[[jails]]
+name = "db"
+"#;
+
+    #[test]
+    fn test_get() {
+        assert_eq!(get(CONFIG, "config.data_dir").unwrap(), "/var/blackship");
+        assert_eq!(get(CONFIG, "jails.web.network.ip").unwrap(), "10.0.1.10");
+        assert_eq!(get(CONFIG, "jails.1.name").unwrap(), "db");
+        assert!(get(CONFIG, "jails.web.network").unwrap().contains("ip = \"10.0.1.10\""));
+        assert!(get(CONFIG, "jails.db.network.ip").is_err());
+        assert!(get(CONFIG, "jails.cache.network.ip").is_err());
+    }
+
+    #[test]
+    fn test_set_preserves_formatting() {
+        let updated = set(CONFIG, "jails.web.network.ip", "10.0.1.20").unwrap();
+        assert!(updated.contains("ip = \"10.0.1.20\"   # public"));
+        assert!(updated.starts_with("# Fleet\n"));
+        assert!(updated.contains("data_dir = \"/var/blackship\"  # keep"));
+
+        let updated = set(&updated, "config.zfs_enabled", "true").unwrap();
+        assert_eq!(get(&updated, "config.zfs_enabled").unwrap(), "true");
+
+        let updated = set(&updated, "jails.db.depends_on", r#"["web"]"#).unwrap();
+        assert_eq!(get(&updated, "jails.db.depends_on").unwrap(), r#"["web"]"#);
+
+        assert!(set(CONFIG, "jails.web", "x").is_err());
+        assert!(set(CONFIG, "config.data_dir.x", "1").is_err());
+
+        // Keys the configuration does not read are refused
+        assert!(set(CONFIG, "jails.web.ip", "10.0.1.20").is_err());
+        assert!(set(CONFIG, "config.mirror", "https://example.org").is_err());
+        let updated = set(CONFIG, "jails.db.network.ip", "10.0.1.11").unwrap();
+        assert_eq!(get(&updated, "jails.db.network.ip").unwrap(), "10.0.1.11");
+    }
+
+    #[test]
+    fn test_set_file_validates() {
+        let dir = std::env::temp_dir().join(format!("blackship-config-set-{}", std::process::id()));
+        fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("blackship.toml");
+        fs::write(&path, CONFIG).unwrap();
+
+        set_file(&path, "jails.web.depends_on", r#"["db"]"#).unwrap();
+        assert!(fs::read_to_string(&path).unwrap().contains("depends_on = [\"db\"]"));
+
+        // An unknown dependency fails validation and leaves the file alone
+        let before = fs::read_to_string(&path).unwrap();
+        assert!(set_file(&path, "jails.db.depends_on", r#"["ghost"]"#).is_err());
+        assert_eq!(fs::read_to_string(&path).unwrap(), before);
+
+        fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn test_add_jail() {
+        let jail = NewJail {
+            name: "cache".into(),
+            release: Some("15.0-RELEASE".into()),
+            depends_on: vec!["db".into()],
+            network: Some("backend".into()),
+            ip: Some("10.0.1.12".parse().unwrap()),
+        };
+        let updated = add_jail(CONFIG, &jail).unwrap();
+        assert!(updated.starts_with(CONFIG));
+        assert!(updated.ends_with(
+            "[[jails]]\nname = \"cache\"\nrelease = \"15.0-RELEASE\"\ndepends_on = [\"db\"]\n\n\
+             [jails.network]\nnetworks = [\"backend\"]\nip = \"10.0.1.12\"\n"
+        ));
+
+        let duplicate = NewJail {
+            name: "web".into(),
+            ..Default::default()
+        };
+        assert!(add_jail(CONFIG, &duplicate).is_err());
+    }
+
+    #[test]
+    fn test_next_free_ip() {
+        let content = format!(
+            "{}\n[[networks]]\nname = \"backend\"\nsubnet = \"10.0.1.0/24\"\n\n{}",
+            "[config]\ndata_dir = \"/var/blackship\"\n",
+            "[[jails]]\nname = \"db\"\n\n[jails.network]\nip = \"10.0.1.2\"\n"
+        );
+        let config: BlackshipConfig = ConfigFormat::Toml.parse(&content).unwrap();
+        // .1 is the gateway and .2 is taken by db
+        assert_eq!(
+            next_free_ip(&config, "backend").unwrap(),
+            "10.0.1.3".parse::<IpAddr>().unwrap()
+        );
+        assert!(next_free_ip(&config, "frontend").is_err());
+    }
+}
@@ -0,0 +1,300 @@
+//! Prometheus metrics for the Warden
+//!
+//! Served by `blackship supervise --metrics-addr` in the Prometheus text
+//! format. Jail and IP pool state is read from the bridge on every scrape;
+//! restarts, health and check durations are recorded as the Warden and the
+//! health monitors report them.
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use std::sync::{Arc, Mutex, MutexGuard};
+use std::time::Duration;
+
+use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+use crate::bridge::Bridge;
+use crate::error::{Error, Result};
+use crate::jail::jail_getid;
+use crate::sickbay::HealthStatus;
+use crate::warden::http_response;
+
+/// Every health status, in exposition order
+const HEALTH_STATUSES: [HealthStatus; 6] = [
+    HealthStatus::Starting,
+    HealthStatus::Healthy,
+    HealthStatus::Unhealthy,
+    HealthStatus::Failing,
+    HealthStatus::Suspended,
+    HealthStatus::Unknown,
+];
+
+/// Samples reported by the Warden and the health monitors
+#[derive(Default)]
+struct Samples {
+    /// Restart attempts per jail
+    restarts: BTreeMap<String, u64>,
+    /// Last reported health status per jail
+    health: BTreeMap<String, HealthStatus>,
+    /// Duration of the last run per (jail, check), in seconds
+    check_durations: BTreeMap<(String, String), f64>,
+}
+
+/// Metrics collected while supervising
+#[derive(Default)]
+pub struct Metrics {
+    samples: Mutex<Samples>,
+}
+
+impl Metrics {
+    /// Create an empty metrics registry
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn samples(&self) -> MutexGuard<'_, Samples> {
+        self.samples.lock().unwrap_or_else(|e| e.into_inner())
+    }
+
+    /// Count a restart attempt by the Warden
+    pub fn record_restart(&self, jail: &str) {
+        *self.samples().restarts.entry(jail.to_string()).or_default() += 1;
+    }
+
+    /// Record a jail's current health status
+    pub fn set_health(&self, jail: &str, status: HealthStatus) {
+        self.samples().health.insert(jail.to_string(), status);
+    }
+
+    /// Record how long a health check took
+    pub fn record_check(&self, jail: &str, check: &str, duration: Duration) {
+        self.samples()
+            .check_durations
+            .insert((jail.to_string(), check.to_string()), duration.as_secs_f64());
+    }
+
+    /// Render all metrics in the Prometheus text format
+    ///
+    /// `jails` is whether each jail is running; `pools` holds each network's
+    /// allocated and usable address counts.
+    pub fn render(&self, jails: &[(String, bool)], pools: &[(String, usize, u128)]) -> String {
+        let samples = self.samples();
+        let mut out = String::new();
+
+        header(&mut out, "blackship_jail_up", "gauge", "Whether the jail is running");
+        for (jail, up) in jails {
+            let _ = writeln!(
+                out,
+                "blackship_jail_up{{jail=\"{}\"}} {}",
+                escape(jail),
+                u8::from(*up)
+            );
+        }
+
+        header(
+            &mut out,
+            "blackship_jail_health",
+            "gauge",
+            "Health status of jails with health checks (1 for the current status)",
+        );
+        for (jail, current) in &samples.health {
+            for status in HEALTH_STATUSES {
+                let _ = writeln!(
+                    out,
+                    "blackship_jail_health{{jail=\"{}\",status=\"{}\"}} {}",
+                    escape(jail),
+                    status,
+                    u8::from(status == *current)
+                );
+            }
+        }
+
+        header(
+            &mut out,
+            "blackship_jail_restarts_total",
+            "counter",
+            "Restarts attempted by the Warden",
+        );
+        for (jail, _) in jails {
+            let restarts = samples.restarts.get(jail).copied().unwrap_or(0);
+            let _ = writeln!(
+                out,
+                "blackship_jail_restarts_total{{jail=\"{}\"}} {}",
+                escape(jail),
+                restarts
+            );
+        }
+
+        header(
+            &mut out,
+            "blackship_health_check_duration_seconds",
+            "gauge",
+            "Duration of the last run of each health check",
+        );
+        for ((jail, check), secs) in &samples.check_durations {
+            let _ = writeln!(
+                out,
+                "blackship_health_check_duration_seconds{{jail=\"{}\",check=\"{}\"}} {}",
+                escape(jail),
+                escape(check),
+                secs
+            );
+        }
+
+        header(
+            &mut out,
+            "blackship_ip_pool_allocated",
+            "gauge",
+            "Addresses in use in the network's IP pool, including the gateway",
+        );
+        for (network, allocated, _) in pools {
+            let _ = writeln!(
+                out,
+                "blackship_ip_pool_allocated{{network=\"{}\"}} {}",
+                escape(network),
+                allocated
+            );
+        }
+
+        header(
+            &mut out,
+            "blackship_ip_pool_size",
+            "gauge",
+            "Usable addresses in the network's IP pool",
+        );
+        for (network, _, capacity) in pools {
+            let _ = writeln!(
+                out,
+                "blackship_ip_pool_size{{network=\"{}\"}} {}",
+                escape(network),
+                capacity
+            );
+        }
+
+        out
+    }
+}
+
+/// Write the HELP and TYPE lines of a metric
+fn header(out: &mut String, name: &str, kind: &str, help: &str) {
+    let _ = writeln!(out, "# HELP {} {}", name, help);
+    let _ = writeln!(out, "# TYPE {} {}", name, kind);
+}
+
+/// Escape a label value
+fn escape(value: &str) -> String {
+    value
+        .replace('\\', "\\\\")
+        .replace('"', "\\\"")
+        .replace('\n', "\\n")
+}
+
+/// Path of an HTTP request, without the query string
+fn request_path(request: &[u8]) -> Option<&str> {
+    let line = request.split(|&b| b == b'\n').next()?;
+    let path = std::str::from_utf8(line).ok()?.split_whitespace().nth(1)?;
+    path.split('?').next()
+}
+
+/// Serve `/metrics` over HTTP on `addr` (`host:port`)
+pub fn spawn_metrics_server(
+    addr: &str,
+    metrics: Arc<Metrics>,
+    bridge: Arc<tokio::sync::Mutex<Bridge>>,
+) -> Result<()> {
+    let bind_error = |e: std::io::Error| {
+        Error::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to listen on {}: {}", addr, e),
+        ))
+    };
+    let listener = std::net::TcpListener::bind(addr).map_err(bind_error)?;
+    listener.set_nonblocking(true).map_err(bind_error)?;
+    let listener = tokio::net::TcpListener::from_std(listener).map_err(bind_error)?;
+
+    tokio::spawn(async move {
+        loop {
+            let mut stream = match listener.accept().await {
+                Ok((stream, _)) => stream,
+                Err(e) => {
+                    // Transient (e.g. out of file descriptors); keep serving
+                    eprintln!("Warning: Metrics server failed to accept a connection: {}", e);
+                    tokio::time::sleep(Duration::from_millis(100)).await;
+                    continue;
+                }
+            };
+            let metrics = Arc::clone(&metrics);
+            let bridge = Arc::clone(&bridge);
+            tokio::spawn(async move {
+                let mut request = [0u8; 1024];
+                let n = stream.read(&mut request).await.unwrap_or(0);
+                let response = if request_path(&request[..n]) == Some("/metrics") {
+                    // Snapshot under the lock, probe the jails after releasing it
+                    let (names, pools) = {
+                        let br = bridge.lock().await;
+                        (br.configured_jail_names(), br.ip_pool_usage())
+                    };
+                    let jails: Vec<(String, bool)> = names
+                        .into_iter()
+                        .map(|name| {
+                            let up = jail_getid(&name).is_ok();
+                            (name, up)
+                        })
+                        .collect();
+                    http_response(
+                        "200 OK",
+                        "text/plain; version=0.0.4",
+                        &metrics.render(&jails, &pools),
+                    )
+                } else {
+                    http_response("404 Not Found", "text/plain", "Not Found\n")
+                };
+                let _ = stream.write_all(response.as_bytes()).await;
+                let _ = stream.shutdown().await;
+            });
+        }
+    });
+
+    println!("Warden: Serving metrics on http://{}/metrics", addr);
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_render() {
+        let metrics = Metrics::new();
+        metrics.record_restart("proj_web");
+        metrics.record_restart("proj_web");
+        metrics.set_health("proj_web", HealthStatus::Healthy);
+        metrics.record_check("proj_web", "http", Duration::from_millis(250));
+
+        let jails = vec![("proj_web".to_string(), true), ("proj_db".to_string(), false)];
+        let pools = vec![("internal".to_string(), 3, 254)];
+        let out = metrics.render(&jails, &pools);
+
+        assert!(out.contains("# TYPE blackship_jail_up gauge\n"));
+        assert!(out.contains("blackship_jail_up{jail=\"proj_web\"} 1\n"));
+        assert!(out.contains("blackship_jail_up{jail=\"proj_db\"} 0\n"));
+        assert!(out.contains("blackship_jail_health{jail=\"proj_web\",status=\"healthy\"} 1\n"));
+        assert!(out.contains("blackship_jail_health{jail=\"proj_web\",status=\"failing\"} 0\n"));
+        assert!(!out.contains("blackship_jail_health{jail=\"proj_db\""));
+        assert!(out.contains("blackship_jail_restarts_total{jail=\"proj_web\"} 2\n"));
+        assert!(out.contains("blackship_jail_restarts_total{jail=\"proj_db\"} 0\n"));
+        assert!(out.contains(
+            "blackship_health_check_duration_seconds{jail=\"proj_web\",check=\"http\"} 0.25\n"
+        ));
+        assert!(out.contains("blackship_ip_pool_allocated{network=\"internal\"} 3\n"));
+        assert!(out.contains("blackship_ip_pool_size{network=\"internal\"} 254\n"));
+    }
+
+    #[test]
+    fn test_request_path() {
+        assert_eq!(request_path(b"GET /metrics HTTP/1.1\r\nHost: x\r\n\r\n"), Some("/metrics"));
+        assert_eq!(request_path(b"GET /metrics?x=1 HTTP/1.1\r\n"), Some("/metrics"));
+        assert_eq!(request_path(b"GET / HTTP/1.1\r\n"), Some("/"));
+        assert_eq!(request_path(b""), None);
+        assert_eq!(escape("a\"b\\c"), "a\\\"b\\\\c");
+    }
+}
@@ -5,11 +5,13 @@
 //! - Epair interface creation for VNET jails
 //! - IP address allocation and management
 //! - VNET jail network configuration
+//! - Connectivity diagnostics (ping, DNS)
 
 pub mod bridge;
 pub mod epair;
 pub mod ioctl;
 pub mod ip;
+pub mod ping;
 pub mod vnet;
 
 pub use bridge::Bridge;
@@ -7,13 +7,29 @@
 use crate::error::{Error, Result};
 use crate::jail::jexec_with_output;
 use crate::network::ioctl;
+use serde::{Deserialize, Serialize};
+use sha2::{Digest, Sha256};
 use std::sync::atomic::{AtomicU32, Ordering};
 
 /// Counter for generating unique epair names
 static EPAIR_COUNTER: AtomicU32 = AtomicU32::new(0);
 
+/// Longest interface name FreeBSD accepts (IF_NAMESIZE minus the NUL)
+const MAX_IFNAME_LEN: usize = 15;
+
+/// How epair interfaces of VNET jails are named (`[config] epair_naming`)
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize, Default)]
+#[serde(rename_all = "snake_case")]
+pub enum EpairNaming {
+    /// `e<N>a_<jail>` / `e<N>b_<jail>`, numbered per invocation
+    #[default]
+    Numbered,
+    /// `<jail>h` (host side) / `<jail>j` (jail side), the same on every start
+    Jail,
+}
+
 /// An epair interface pair for connecting VNET jails to bridges
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct EpairInterface {
     /// Host-side interface name (e.g., "epair0a")
     host_side: String,
@@ -80,6 +96,66 @@ impl EpairInterface {
         })
     }
 
+    /// Create an epair with stable names derived from the jail name
+    ///
+    /// Creates `<jail>h` (host side) and `<jail>j` (jail side); see
+    /// [`EpairInterface::stable_base`] for how long names are shortened. A
+    /// pair left behind by an earlier start of the jail is destroyed and
+    /// recreated, so its names are reused.
+    pub fn create_stable(jail_name: &str) -> Result<Self> {
+        let base = Self::stable_base(jail_name);
+        let host_side = format!("{}h", base);
+        let jail_side = format!("{}j", base);
+
+        if ioctl::interface_exists(&host_side)? {
+            // While the jail runs, its side of the pair is inside the jail
+            if !ioctl::interface_exists(&jail_side)? {
+                return Err(Error::Network(format!(
+                    "Interface {} is in use by a running jail",
+                    host_side
+                )));
+            }
+            ioctl::destroy_interface(&host_side)?;
+        }
+
+        let epair = Self::create()?;
+        if let Err(e) = ioctl::rename_interface(&epair.host_side, &host_side) {
+            let _ = ioctl::destroy_interface(&epair.host_side);
+            return Err(e);
+        }
+        if let Err(e) = ioctl::rename_interface(&epair.jail_side, &jail_side) {
+            let _ = ioctl::destroy_interface(&host_side);
+            return Err(e);
+        }
+
+        Ok(Self {
+            host_side,
+            jail_side,
+        })
+    }
+
+    /// Base of stable interface names for a jail
+    ///
+    /// The jail name with everything but ASCII letters, digits and `_`
+    /// dropped. Names longer than 14 characters (leaving room for the `h`/`j`
+    /// suffix within the 15-character limit) are cut to their first 9
+    /// characters followed by 5 hex digits of a SHA-256 of the full jail name,
+    /// so jails sharing a long prefix still get distinct interfaces.
+    pub fn stable_base(jail_name: &str) -> String {
+        let sanitized: String = jail_name
+            .chars()
+            .filter(|c| c.is_ascii_alphanumeric() || *c == '_')
+            .collect();
+        if sanitized.is_empty() {
+            return "jail".to_string();
+        }
+        if sanitized.len() < MAX_IFNAME_LEN {
+            return sanitized;
+        }
+        let hash = format!("{:x}", Sha256::digest(jail_name.as_bytes()));
+        format!("{}{}", &sanitized[..9], &hash[..5])
+    }
+
     /// Get the host-side interface name
     pub fn host_side(&self) -> &str {
         &self.host_side
@@ -112,9 +188,13 @@ impl EpairInterface {
         addr: &str,
         gateway: Option<&str>,
     ) -> Result<()> {
+        // IPv6 addresses need the inet6 family (and -inet6 for the default route)
+        let family = if addr.contains(':') { "inet6" } else { "inet" };
+
         // Configure IP address inside jail using native jexec syscall
-        let (exit_code, _stdout, stderr) = jexec_with_output(jid, &["ifconfig", interface, addr])
-            .map_err(|e| Error::Network(format!("Failed to configure interface: {}", e)))?;
+        let (exit_code, _stdout, stderr) =
+            jexec_with_output(jid, &["ifconfig", interface, family, addr])
+                .map_err(|e| Error::Network(format!("Failed to configure interface: {}", e)))?;
 
         if exit_code != 0 {
             let stderr_str = String::from_utf8_lossy(&stderr);
@@ -138,8 +218,10 @@ impl EpairInterface {
 
         // Set default route if gateway provided using native jexec syscall
         if let Some(gw) = gateway {
-            let (exit_code, _stdout, stderr) = jexec_with_output(jid, &["route", "add", "default", gw])
-                .map_err(|e| Error::Network(format!("Failed to add route: {}", e)))?;
+            let route_family = if gw.contains(':') { "-inet6" } else { "-inet" };
+            let (exit_code, _stdout, stderr) =
+                jexec_with_output(jid, &["route", "add", route_family, "default", gw])
+                    .map_err(|e| Error::Network(format!("Failed to add route: {}", e)))?;
 
             if exit_code != 0 {
                 let stderr_str = String::from_utf8_lossy(&stderr);
@@ -156,6 +238,24 @@ impl EpairInterface {
         Ok(())
     }
 
+    /// Add an additional address (alias) to an interface inside the jail
+    pub fn add_address_in_jail(jid: i32, interface: &str, addr: &str) -> Result<()> {
+        let family = if addr.contains(':') { "inet6" } else { "inet" };
+        let (exit_code, _stdout, stderr) =
+            jexec_with_output(jid, &["ifconfig", interface, family, addr, "alias"])
+                .map_err(|e| Error::Network(format!("Failed to add address: {}", e)))?;
+
+        if exit_code != 0 {
+            let stderr_str = String::from_utf8_lossy(&stderr);
+            return Err(Error::Network(format!(
+                "Failed to add {} to {} in jail {}: {}",
+                addr, interface, jid, stderr_str
+            )));
+        }
+
+        Ok(())
+    }
+
     /// Destroy the epair using ioctl (destroys both ends)
     pub fn destroy(&self) -> Result<()> {
         // Destroying either end destroys both
@@ -188,10 +288,80 @@ impl EpairInterface {
     }
 }
 
+/// Host-side names of blackship epairs that no running jail uses
+///
+/// A jail's side of the pair is moved into its VNET while the jail runs, so
+/// when both ends of an `e<N>a_<jail>` / `e<N>b_<jail>` (or `<jail>h` /
+/// `<jail>j`) pair are on the host, the pair was left behind by a failed
+/// start or a missed cleanup (or a start is in progress right now).
+///
+/// Only names derived from `jails` (full jail names) are considered, so
+/// epairs of other tools or hand-made VNET jails are left alone.
+pub fn orphaned_epairs(jails: &[String]) -> Result<Vec<String>> {
+    Ok(orphaned_in(&ioctl::list_interfaces()?, jails))
+}
+
+/// Host sides from `interfaces` whose jail side is also listed and whose
+/// name belongs to one of `jails`
+fn orphaned_in(interfaces: &[String], jails: &[String]) -> Vec<String> {
+    let stable: Vec<String> = jails.iter().map(|j| EpairInterface::stable_base(j)).collect();
+    let numbered: Vec<String> = jails.iter().map(|j| EpairInterface::sanitize_name(j)).collect();
+    let mut orphans: Vec<String> = interfaces
+        .iter()
+        .filter(|name| {
+            if let Some(base) = name.strip_suffix('h')
+                && stable.iter().any(|s| s == base)
+            {
+                return interfaces.contains(&format!("{}j", base));
+            }
+            let Some((counter, jail)) = name.strip_prefix('e').and_then(|n| n.split_once("a_"))
+            else {
+                return false;
+            };
+            !counter.is_empty()
+                && counter.chars().all(|c| c.is_ascii_digit())
+                && numbered.iter().any(|n| n == jail)
+                && interfaces.contains(&format!("e{}b_{}", counter, jail))
+        })
+        .cloned()
+        .collect();
+    orphans.sort();
+    orphans
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_orphaned_epairs() {
+        let interfaces: Vec<String> = [
+            "em0", "lo0", "e0a_web", "e1a_db", "e1b_db", "epair0a", "epair0b", "apph", "cacheh",
+            "cachej", "e2a_other", "e2b_other", "vmh", "vmj",
+        ]
+        .iter()
+        .map(|s| s.to_string())
+        .collect();
+        let jails: Vec<String> =
+            ["web", "db", "app", "cache"].iter().map(|s| s.to_string()).collect();
+        // The jail sides of e0a_web and apph are inside running jails; epair0,
+        // e2a_other and vmh don't belong to configured jails
+        assert_eq!(orphaned_in(&interfaces, &jails), vec!["cacheh", "e1a_db"]);
+    }
+
+    #[test]
+    fn test_stable_base() {
+        assert_eq!(EpairInterface::stable_base("bs-web"), "bsweb");
+        assert_eq!(EpairInterface::stable_base("myproject-db_1"), "myprojectdb_1");
+        assert_eq!(EpairInterface::stable_base("---"), "jail");
+
+        let long = EpairInterface::stable_base("myproject-frontend");
+        let other = EpairInterface::stable_base("myproject-frontend2");
+        assert_eq!(long.len(), 14);
+        assert!(long.starts_with("myproject"));
+        assert_ne!(long, other);
+    }
+
     #[test]
     fn test_sanitize_name() {
         assert_eq!(EpairInterface::sanitize_name("myjail"), "myjail");
@@ -285,6 +285,20 @@ pub fn set_mac_address(name: &str, mac: &str) -> Result<()> {
 
 /// Move interface to VNET jail
 pub fn move_to_vnet(name: &str, jid: i32) -> Result<()> {
+    // SIOCSIFVNET ioctl (FreeBSD-specific)
+    const SIOCSIFVNET: libc::c_ulong = 0xc020695a;
+    vnet_ioctl(name, jid, SIOCSIFVNET, "move interface to VNET")
+}
+
+/// Return an interface from a VNET jail to the host (`ifconfig <name> -vnet <jid>`)
+pub fn reclaim_from_vnet(name: &str, jid: i32) -> Result<()> {
+    // SIOCSIFRVNET ioctl (FreeBSD-specific)
+    const SIOCSIFRVNET: libc::c_ulong = 0xc020695b;
+    vnet_ioctl(name, jid, SIOCSIFRVNET, "reclaim interface from VNET")
+}
+
+/// Issue an interface/jail ioctl (SIOCSIFVNET or SIOCSIFRVNET)
+fn vnet_ioctl(name: &str, jid: i32, request: libc::c_ulong, action: &str) -> Result<()> {
     use std::net::UdpSocket;
 
     let sock = UdpSocket::bind("0.0.0.0:0")
@@ -308,14 +322,12 @@ pub fn move_to_vnet(name: &str, jid: i32) -> Result<()> {
 
     req.ifr_jid = jid;
 
-    // SIOCSIFVNET ioctl (FreeBSD-specific)
-    const SIOCSIFVNET: libc::c_ulong = 0xc020695a;
-
-    let result = unsafe { libc::ioctl(sock.as_raw_fd(), SIOCSIFVNET, &req) };
+    let result = unsafe { libc::ioctl(sock.as_raw_fd(), request, &req) };
 
     if result < 0 {
         return Err(Error::Network(format!(
-            "Failed to move interface to VNET: {}",
+            "Failed to {}: {}",
+            action,
             std::io::Error::last_os_error()
         )));
     }
@@ -549,11 +561,162 @@ pub fn set_ipv4_address(name: &str, addr: &str) -> Result<()> {
     Ok(())
 }
 
+/// Build a sockaddr_in for an IPv4 address
+fn sockaddr_in(ip: std::net::Ipv4Addr) -> libc::sockaddr_in {
+    let mut addr: libc::sockaddr_in = unsafe { std::mem::zeroed() };
+    addr.sin_len = std::mem::size_of::<libc::sockaddr_in>() as u8;
+    addr.sin_family = libc::AF_INET as u8;
+    addr.sin_addr.s_addr = u32::from_be_bytes(ip.octets()).to_be();
+    addr
+}
+
+/// Add an IPv4 alias to an interface (like `ifconfig <name> inet <ip>/<prefix> alias`)
+pub fn add_ipv4_alias(name: &str, ip: std::net::Ipv4Addr, prefix: u8) -> Result<()> {
+    use std::net::{Ipv4Addr, UdpSocket};
+
+    let sock = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| Error::Network(format!("Failed to create socket: {}", e)))?;
+
+    // struct in_aliasreq from netinet/in_var.h
+    #[repr(C)]
+    struct InAliasReq {
+        ifra_name: [libc::c_char; libc::IF_NAMESIZE],
+        ifra_addr: libc::sockaddr_in,
+        ifra_broadaddr: libc::sockaddr_in,
+        ifra_mask: libc::sockaddr_in,
+        ifra_vhid: libc::c_int,
+    }
+
+    let mut req: InAliasReq = unsafe { std::mem::zeroed() };
+    copy_ifname(&mut req.ifra_name, name)?;
+
+    let netmask = if prefix == 0 { 0u32 } else { !0u32 << (32 - prefix.min(32)) };
+    req.ifra_addr = sockaddr_in(ip);
+    req.ifra_mask = sockaddr_in(Ipv4Addr::from(netmask));
+
+    // SIOCAIFADDR ioctl (_IOW('i', 43, struct in_aliasreq))
+    const SIOCAIFADDR: libc::c_ulong = 0x8044692b;
+
+    let result = unsafe { libc::ioctl(sock.as_raw_fd(), SIOCAIFADDR, &req) };
+
+    if result < 0 {
+        return Err(Error::Network(format!(
+            "Failed to add alias {}/{} to {}: {}",
+            ip,
+            prefix,
+            name,
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    Ok(())
+}
+
+/// Remove an IPv4 alias from an interface (like `ifconfig <name> inet <ip> -alias`)
+pub fn delete_ipv4_alias(name: &str, ip: std::net::Ipv4Addr) -> Result<()> {
+    use std::net::UdpSocket;
+
+    let sock = UdpSocket::bind("0.0.0.0:0")
+        .map_err(|e| Error::Network(format!("Failed to create socket: {}", e)))?;
+
+    #[repr(C)]
+    struct IfReqAddr {
+        ifr_name: [libc::c_char; libc::IF_NAMESIZE],
+        ifr_addr: libc::sockaddr_in,
+    }
+
+    let mut req: IfReqAddr = unsafe { std::mem::zeroed() };
+    copy_ifname(&mut req.ifr_name, name)?;
+    req.ifr_addr = sockaddr_in(ip);
+
+    // SIOCDIFADDR ioctl
+    const SIOCDIFADDR: libc::c_ulong = 0x80206919;
+
+    let result = unsafe { libc::ioctl(sock.as_raw_fd(), SIOCDIFADDR, &req) };
+
+    if result < 0 {
+        return Err(Error::Network(format!(
+            "Failed to remove alias {} from {}: {}",
+            ip,
+            name,
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    Ok(())
+}
+
+/// List the subnets configured on host interfaces
+///
+/// Uses getifaddrs(3); returns (interface name, address/prefix) pairs for
+/// every IPv4 and IPv6 address.
+pub fn host_networks() -> Result<Vec<(String, ipnet::IpNet)>> {
+    use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+    let mut ifap: *mut libc::ifaddrs = std::ptr::null_mut();
+    if unsafe { libc::getifaddrs(&mut ifap) } != 0 {
+        return Err(Error::Network(format!(
+            "Failed to get interface addresses: {}",
+            std::io::Error::last_os_error()
+        )));
+    }
+
+    let mut networks = Vec::new();
+    let mut cur = ifap;
+    while !cur.is_null() {
+        let ifa = unsafe { &*cur };
+        cur = ifa.ifa_next;
+        if ifa.ifa_addr.is_null() || ifa.ifa_netmask.is_null() {
+            continue;
+        }
+
+        let name = unsafe { std::ffi::CStr::from_ptr(ifa.ifa_name) }
+            .to_string_lossy()
+            .into_owned();
+
+        let (addr, prefix) = match i32::from(unsafe { (*ifa.ifa_addr).sa_family }) {
+            libc::AF_INET => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in) };
+                let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in) };
+                let ip = Ipv4Addr::from(u32::from_be(addr.sin_addr.s_addr));
+                let prefix = u32::from_be(mask.sin_addr.s_addr).count_ones() as u8;
+                (IpAddr::V4(ip), prefix)
+            }
+            libc::AF_INET6 => {
+                let addr = unsafe { &*(ifa.ifa_addr as *const libc::sockaddr_in6) };
+                let mask = unsafe { &*(ifa.ifa_netmask as *const libc::sockaddr_in6) };
+                let ip = Ipv6Addr::from(addr.sin6_addr.s6_addr);
+                let prefix = u128::from_be_bytes(mask.sin6_addr.s6_addr).count_ones() as u8;
+                (IpAddr::V6(ip), prefix)
+            }
+            _ => continue,
+        };
+
+        if let Ok(net) = ipnet::IpNet::new(addr, prefix) {
+            networks.push((name, net));
+        }
+    }
+
+    unsafe { libc::freeifaddrs(ifap) };
+
+    Ok(networks)
+}
+
 /// List all bridge interfaces on the system
 ///
-/// Uses if_nameindex(3) and filters for interfaces matching "bridge*" pattern
+/// Filters [`list_interfaces`] for interfaces matching "bridge*" pattern
 pub fn list_bridges() -> Result<Vec<String>> {
-    let mut bridges = Vec::new();
+    Ok(list_interfaces()?
+        .into_iter()
+        .filter(|name| name.starts_with("bridge"))
+        .collect())
+}
+
+/// List all network interfaces on the system
+///
+/// Uses if_nameindex(3)
+pub fn list_interfaces() -> Result<Vec<String>> {
+    let mut interfaces = Vec::new();
 
     // Get list of all network interfaces
     let if_list = unsafe { libc::if_nameindex() };
@@ -576,10 +739,7 @@ pub fn list_bridges() -> Result<Vec<String>> {
             .to_string_lossy()
             .into_owned();
 
-        // Filter for bridge interfaces
-        if name.starts_with("bridge") {
-            bridges.push(name);
-        }
+        interfaces.push(name);
 
         i += 1;
     }
@@ -587,7 +747,7 @@ pub fn list_bridges() -> Result<Vec<String>> {
     // Free the interface list
     unsafe { libc::if_freenameindex(if_list) };
 
-    Ok(bridges)
+    Ok(interfaces)
 }
 
 /// Disable hardware VLAN filtering on an interface
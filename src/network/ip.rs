@@ -3,12 +3,12 @@
 //! Provides:
 //! - IP pool allocation from subnets
 //! - Tracking of allocated addresses
-//! - Support for IPv4 and IPv6
+//! - Support for IPv4 and IPv6 (IPv6 pools allocate sequentially from a cursor)
 
 use crate::error::{Error, Result};
 use ipnet::{IpNet, Ipv4Net, Ipv6Net};
 use std::collections::HashSet;
-use std::net::IpAddr;
+use std::net::{IpAddr, Ipv6Addr};
 
 /// IP address pool for a network
 #[derive(Debug, Clone)]
@@ -19,6 +19,8 @@ pub struct IpPool {
     gateway: IpAddr,
     /// Set of allocated addresses
     allocated: HashSet<IpAddr>,
+    /// Next IPv6 host offset to try (IPv6 subnets are too large to enumerate)
+    v6_cursor: u128,
 }
 
 impl IpPool {
@@ -35,6 +37,7 @@ impl IpPool {
             subnet,
             gateway,
             allocated,
+            v6_cursor: 0,
         })
     }
 
@@ -54,6 +57,7 @@ impl IpPool {
             subnet,
             gateway,
             allocated,
+            v6_cursor: 0,
         })
     }
 
@@ -111,12 +115,17 @@ impl IpPool {
     }
 
     fn allocate_v6(&mut self, net: Ipv6Net) -> Result<IpAddr> {
-        // For IPv6, we iterate through hosts
-        // Note: For large subnets, this could be slow
-        let hosts = net.hosts();
-        for addr in hosts.take(65536) {
-            // Limit iteration
-            let ip = IpAddr::V6(addr);
+        // Walk forward from the cursor, wrapping within the host range. At most
+        // `allocated.len() + 1` probes are needed to find a free address, so a
+        // /64 is never enumerated.
+        let (first, last) = Self::v6_host_range(net);
+        let host_count = (last - first).saturating_add(1);
+        let probes = host_count.min(self.allocated.len() as u128 + 1);
+
+        for _ in 0..probes {
+            let offset = self.v6_cursor % host_count;
+            self.v6_cursor = offset.wrapping_add(1);
+            let ip = IpAddr::V6(Ipv6Addr::from(first + offset));
             if !self.allocated.contains(&ip) {
                 self.allocated.insert(ip);
                 return Ok(ip);
@@ -129,6 +138,20 @@ impl IpPool {
         )))
     }
 
+    /// Usable IPv6 host addresses as integers (first, last)
+    ///
+    /// Skips the Subnet-Router anycast address (all-zero host part) except
+    /// on /127 and /128 networks.
+    fn v6_host_range(net: Ipv6Net) -> (u128, u128) {
+        let network = u128::from(net.network());
+        let broadcast = u128::from(net.broadcast());
+        if net.prefix_len() < 127 {
+            (network + 1, broadcast)
+        } else {
+            (network, broadcast)
+        }
+    }
+
     fn first_usable(subnet: &IpNet) -> Result<IpAddr> {
         match subnet {
             IpNet::V4(net) => net
@@ -136,34 +159,47 @@ impl IpPool {
                 .next()
                 .map(IpAddr::V4)
                 .ok_or_else(|| Error::Network("Network too small for gateway".to_string())),
-            IpNet::V6(net) => net
-                .hosts()
-                .next()
-                .map(IpAddr::V6)
-                .ok_or_else(|| Error::Network("Network too small for gateway".to_string())),
+            IpNet::V6(net) => {
+                let (first, _) = Self::v6_host_range(*net);
+                Ok(IpAddr::V6(Ipv6Addr::from(first)))
+            }
         }
     }
 
-    // Test-only accessors for verifying internal state
-    #[cfg(test)]
+    /// Get the pool's subnet
     pub fn subnet(&self) -> IpNet {
         self.subnet
     }
 
-    #[cfg(test)]
+    /// Number of addresses in use, including the gateway
+    pub fn allocated_count(&self) -> usize {
+        self.allocated.len()
+    }
+
+    /// Number of usable host addresses in the subnet
+    pub fn capacity(&self) -> u128 {
+        match self.subnet {
+            // Network and broadcast addresses are unusable below /31
+            IpNet::V4(net) if net.prefix_len() < 31 => (1u128 << (32 - net.prefix_len())) - 2,
+            IpNet::V4(net) => 1u128 << (32 - net.prefix_len()),
+            IpNet::V6(net) => {
+                let (first, last) = Self::v6_host_range(net);
+                (last - first).saturating_add(1)
+            }
+        }
+    }
+
+    /// Get the pool's gateway address
     pub fn gateway(&self) -> IpAddr {
         self.gateway
     }
 
+    // Test-only accessors for verifying internal state
+
     #[cfg(test)]
     pub fn is_available(&self, addr: &IpAddr) -> bool {
         self.subnet.contains(addr) && !self.allocated.contains(addr)
     }
-
-    #[cfg(test)]
-    pub fn allocated_count(&self) -> usize {
-        self.allocated.len()
-    }
 }
 
 /// IP allocator that manages multiple networks
@@ -184,6 +220,16 @@ impl IpAllocator {
         self.pools.insert(name, pool);
     }
 
+    /// Get a pool by name
+    pub fn get_pool(&self, name: &str) -> Option<&IpPool> {
+        self.pools.get(name)
+    }
+
+    /// Iterate over the pools by network name
+    pub fn pools(&self) -> impl Iterator<Item = (&str, &IpPool)> {
+        self.pools.iter().map(|(name, pool)| (name.as_str(), pool))
+    }
+
     /// Get a mutable pool by name
     pub fn get_pool_mut(&mut self, name: &str) -> Option<&mut IpPool> {
         self.pools.get_mut(name)
@@ -221,6 +267,16 @@ mod tests {
         assert_eq!(pool.allocated_count(), 1); // Gateway is allocated
     }
 
+    #[test]
+    fn test_ip_pool_capacity() {
+        let capacity = |subnet: &str| IpPool::new(subnet.parse().unwrap()).unwrap().capacity();
+        assert_eq!(capacity("10.0.1.0/24"), 254);
+        assert_eq!(capacity("10.0.1.0/31"), 2);
+        assert_eq!(capacity("10.0.1.1/32"), 1);
+        assert_eq!(capacity("fd00::/120"), 255);
+        assert_eq!(capacity("fd00::/64"), (1u128 << 64) - 1);
+    }
+
     #[test]
     fn test_ip_allocation() {
         let subnet: IpNet = "10.0.1.0/24".parse().unwrap();
@@ -260,4 +316,53 @@ mod tests {
 
         assert!(!pool.is_available(&specific));
     }
+
+    #[test]
+    fn test_ipv6_pool_gateway_reservation() {
+        let subnet: IpNet = "fd00:1::/120".parse().unwrap();
+        let pool = IpPool::new(subnet).unwrap();
+
+        // Gateway skips the Subnet-Router anycast address
+        assert_eq!(pool.gateway(), "fd00:1::1".parse::<IpAddr>().unwrap());
+        assert!(!pool.is_available(&pool.gateway()));
+        assert_eq!(pool.allocated_count(), 1);
+    }
+
+    #[test]
+    fn test_ipv6_allocation_and_release() {
+        let subnet: IpNet = "fd00:1::/120".parse().unwrap();
+        let mut pool = IpPool::new(subnet).unwrap();
+
+        let ip = pool.allocate().unwrap();
+        assert_eq!(ip, "fd00:1::2".parse::<IpAddr>().unwrap());
+        let ip2 = pool.allocate().unwrap();
+        assert_eq!(ip2, "fd00:1::3".parse::<IpAddr>().unwrap());
+
+        // Released addresses are reused only after the cursor wraps
+        pool.release(&ip);
+        assert!(pool.is_available(&ip));
+        let ip3 = pool.allocate().unwrap();
+        assert_eq!(ip3, "fd00:1::4".parse::<IpAddr>().unwrap());
+
+        // Exhaust the /120 (255 hosts, gateway reserved); the freed address comes back
+        let mut count = 0;
+        while pool.allocate().is_ok() {
+            count += 1;
+        }
+        assert_eq!(count, 255 - 4 + 1);
+        assert!(!pool.is_available(&ip));
+    }
+
+    #[test]
+    fn test_ipv6_large_subnet_allocates_quickly() {
+        let subnet: IpNet = "fd00:2::/64".parse().unwrap();
+        let mut pool = IpPool::new(subnet).unwrap();
+
+        let specific: IpAddr = "fd00:2::2".parse().unwrap();
+        pool.allocate_specific(specific).unwrap();
+
+        // Skips the gateway and the reserved address
+        let ip = pool.allocate().unwrap();
+        assert_eq!(ip, "fd00:2::3".parse::<IpAddr>().unwrap());
+    }
 }
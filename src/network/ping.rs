@@ -0,0 +1,171 @@
+//! Connectivity diagnostics for jails
+//!
+//! Provides:
+//! - ping(8) from the host or from inside a jail, with loss/latency summary
+//! - DNS resolution check inside a jail (uses the jail's own resolv.conf)
+
+use crate::error::{Error, Result};
+use crate::jail::jexec_with_output;
+use std::net::IpAddr;
+use std::process::Command;
+
+/// Summary of a ping run
+#[derive(Debug, Clone, PartialEq)]
+pub struct PingStats {
+    /// Packets sent
+    pub transmitted: u32,
+    /// Packets received
+    pub received: u32,
+    /// Packet loss percentage
+    pub loss: f64,
+    /// Average round-trip time in milliseconds (None if nothing came back)
+    pub avg_ms: Option<f64>,
+}
+
+impl PingStats {
+    /// Whether at least one reply was received
+    pub fn reachable(&self) -> bool {
+        self.received > 0
+    }
+}
+
+/// ping(8) invocation for an address (ping6 for IPv6)
+fn ping_args(target: IpAddr, count: u32) -> Vec<String> {
+    let program = match target {
+        IpAddr::V4(_) => "ping",
+        IpAddr::V6(_) => "ping6",
+    };
+    vec![
+        program.to_string(),
+        "-c".to_string(),
+        count.to_string(),
+        "-t".to_string(),
+        // Overall timeout: one second per packet plus slack
+        (count + 2).to_string(),
+        target.to_string(),
+    ]
+}
+
+/// Ping an address from the host
+pub fn ping_from_host(target: IpAddr, count: u32) -> Result<PingStats> {
+    let args = ping_args(target, count);
+    let output = Command::new(&args[0])
+        .args(&args[1..])
+        .output()
+        .map_err(|e| Error::CommandFailed {
+            command: args[0].clone(),
+            message: e.to_string(),
+        })?;
+    parse_ping_output(&String::from_utf8_lossy(&output.stdout)).ok_or_else(|| {
+        Error::CommandFailed {
+            command: args.join(" "),
+            message: String::from_utf8_lossy(&output.stderr).trim().to_string(),
+        }
+    })
+}
+
+/// Ping an address from inside a jail
+pub fn ping_from_jail(jid: i32, target: IpAddr, count: u32) -> Result<PingStats> {
+    let args = ping_args(target, count);
+    let argv: Vec<&str> = args.iter().map(String::as_str).collect();
+    let (_, stdout, stderr) = jexec_with_output(jid, &argv)?;
+    parse_ping_output(&String::from_utf8_lossy(&stdout)).ok_or_else(|| Error::CommandFailed {
+        command: args.join(" "),
+        message: String::from_utf8_lossy(&stderr).trim().to_string(),
+    })
+}
+
+/// Resolve a hostname inside a jail, returning the resolver output on success
+pub fn resolve_in_jail(jid: i32, hostname: &str) -> Result<String> {
+    let (code, stdout, stderr) = jexec_with_output(jid, &["host", "-W", "3", hostname])?;
+    if code == 0 {
+        Ok(String::from_utf8_lossy(&stdout).trim().to_string())
+    } else {
+        let message = format!(
+            "{}{}",
+            String::from_utf8_lossy(&stdout),
+            String::from_utf8_lossy(&stderr)
+        );
+        Err(Error::Network(format!(
+            "Failed to resolve '{}': {}",
+            hostname,
+            message.trim()
+        )))
+    }
+}
+
+/// Parse the summary lines of ping(8) output
+///
+/// ```text
+/// 3 packets transmitted, 3 packets received, 0.0% packet loss
+/// round-trip min/avg/max/stddev = 0.041/0.055/0.070/0.012 ms
+/// ```
+pub fn parse_ping_output(output: &str) -> Option<PingStats> {
+    let summary = output.lines().find(|l| l.contains("packets transmitted"))?;
+    let mut transmitted = None;
+    let mut received = None;
+    let mut loss = None;
+    for part in summary.split(',') {
+        let part = part.trim();
+        let value = part.split_whitespace().next()?;
+        if part.ends_with("packets transmitted") {
+            transmitted = value.parse().ok();
+        } else if part.contains("received") {
+            received = value.parse().ok();
+        } else if part.ends_with("packet loss") {
+            loss = value.trim_end_matches('%').parse().ok();
+        }
+    }
+
+    let avg_ms = output
+        .lines()
+        .find(|l| l.starts_with("round-trip"))
+        .and_then(|l| l.split('=').nth(1))
+        .and_then(|values| values.trim().split('/').nth(1))
+        .and_then(|avg| avg.parse().ok());
+
+    Some(PingStats {
+        transmitted: transmitted?,
+        received: received?,
+        loss: loss?,
+        avg_ms,
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_ping_output() {
+        let output = "\
+PING 10.0.0.1 (10.0.0.1): 56 data bytes
+64 bytes from 10.0.0.1: icmp_seq=0 ttl=64 time=0.041 ms
+
+--- 10.0.0.1 ping statistics ---
+3 packets transmitted, 3 packets received, 0.0% packet loss
+round-trip min/avg/max/stddev = 0.041/0.055/0.070/0.012 ms
+";
+        let stats = parse_ping_output(output).unwrap();
+        assert_eq!(stats.transmitted, 3);
+        assert_eq!(stats.received, 3);
+        assert_eq!(stats.loss, 0.0);
+        assert_eq!(stats.avg_ms, Some(0.055));
+        assert!(stats.reachable());
+    }
+
+    #[test]
+    fn test_parse_ping_output_total_loss() {
+        let output = "\
+--- 10.0.0.9 ping statistics ---
+3 packets transmitted, 0 packets received, 100.0% packet loss
+";
+        let stats = parse_ping_output(output).unwrap();
+        assert_eq!(stats.received, 0);
+        assert_eq!(stats.loss, 100.0);
+        assert_eq!(stats.avg_ms, None);
+        assert!(!stats.reachable());
+
+        assert!(parse_ping_output("ping: cannot resolve").is_none());
+    }
+}
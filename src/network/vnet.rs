@@ -4,15 +4,24 @@
 //! - VNET jail parameter configuration
 //! - Full network setup for VNET jails
 //! - Integration with bridges and epairs
+//! - Passing a physical interface through instead of an epair
+//! - Persisting setups under `data_dir/vnet` so a later invocation can
+//!   tear them down
 
-use crate::error::Result;
+use crate::error::{Error, Result};
+use crate::network::ioctl::{self, interface_exists};
+use crate::network::epair::EpairNaming;
 use crate::network::{Bridge, EpairInterface};
+use serde::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fs;
 use std::net::IpAddr;
+use std::path::{Path, PathBuf};
 
 /// VNET network configuration for a jail
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VnetConfig {
-    /// Bridge to connect to
+    /// Bridge to connect to (empty when a physical interface is passed through)
     pub bridge: String,
     /// IP address with prefix (e.g., "10.0.1.10/24")
     pub ip: String,
@@ -22,6 +31,14 @@ pub struct VnetConfig {
     pub mac_address: Option<String>,
     /// VLAN ID for this jail's interface (untagged/PVID)
     pub vlan_id: Option<u16>,
+    /// Additional addresses with prefix (e.g. the IPv6 address of a dual-stack jail)
+    pub extra_addresses: Vec<String>,
+    /// How the epair interfaces are named
+    #[serde(default)]
+    pub epair_naming: EpairNaming,
+    /// Physical interface moved into the jail instead of an epair
+    #[serde(default)]
+    pub interface: Option<String>,
 }
 
 impl VnetConfig {
@@ -33,9 +50,18 @@ impl VnetConfig {
             gateway,
             mac_address: None,
             vlan_id: None,
+            extra_addresses: Vec::new(),
+            epair_naming: EpairNaming::default(),
+            interface: None,
         }
     }
 
+    /// Pass a physical interface into the jail instead of creating an epair
+    pub fn with_interface(mut self, interface: String) -> Self {
+        self.interface = Some(interface);
+        self
+    }
+
     /// Set static MAC address for the jail-side interface
     pub fn with_mac_address(mut self, mac: String) -> Self {
         self.mac_address = Some(mac);
@@ -47,13 +73,25 @@ impl VnetConfig {
         self.vlan_id = Some(vlan_id);
         self
     }
+
+    /// Set how the epair interfaces are named
+    pub fn with_epair_naming(mut self, naming: EpairNaming) -> Self {
+        self.epair_naming = naming;
+        self
+    }
+
+    /// Add an extra address (with prefix) to configure on the jail's interface
+    pub fn with_extra_address(mut self, addr: String) -> Self {
+        self.extra_addresses.push(addr);
+        self
+    }
 }
 
 /// Network setup for a VNET jail
-#[derive(Debug, Clone)]
+#[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct VnetSetup {
-    /// Epair interface pair
-    pub epair: EpairInterface,
+    /// Epair interface pair (none when a physical interface is passed through)
+    pub epair: Option<EpairInterface>,
     /// Bridge the epair is connected to
     pub bridge_name: String,
     /// IP configuration
@@ -65,12 +103,34 @@ impl VnetSetup {
     ///
     /// This creates the epair, adds it to the bridge, but does NOT
     /// move the interface into the jail (that happens during jail creation).
+    /// A physical interface is only checked to be on the host.
     pub fn create(jail_name: &str, config: VnetConfig) -> Result<Self> {
+        if let Some(interface) = &config.interface {
+            // An interface in a jail's VNET is not visible on the host
+            if !interface_exists(interface)? {
+                return Err(Error::Network(format!(
+                    "Interface '{}' is not on the host (missing, or already in a jail)",
+                    interface
+                )));
+            }
+            if let Some(ref mac) = config.mac_address {
+                ioctl::set_mac_address(interface, mac)?;
+            }
+            return Ok(Self {
+                epair: None,
+                bridge_name: config.bridge.clone(),
+                config,
+            });
+        }
+
         // Open or create the bridge
         let bridge = Bridge::create_or_open(&config.bridge)?;
 
         // Create epair for this jail
-        let epair = EpairInterface::create_for_jail(jail_name)?;
+        let epair = match config.epair_naming {
+            EpairNaming::Numbered => EpairInterface::create_for_jail(jail_name)?,
+            EpairNaming::Jail => EpairInterface::create_stable(jail_name)?,
+        };
 
         // Set static MAC address if configured (before adding to bridge)
         if let Some(ref mac) = config.mac_address {
@@ -86,7 +146,7 @@ impl VnetSetup {
         }
 
         Ok(Self {
-            epair,
+            epair: Some(epair),
             bridge_name: config.bridge.clone(),
             config,
         })
@@ -94,13 +154,19 @@ impl VnetSetup {
 
     /// Get the interface name that will be used inside the jail
     pub fn jail_interface(&self) -> &str {
-        self.epair.jail_side()
+        match &self.epair {
+            Some(epair) => epair.jail_side(),
+            None => self.config.interface.as_deref().unwrap_or_default(),
+        }
     }
 
     /// Move the jail-side interface into the jail and configure it
     pub fn attach_to_jail(&self, jid: i32) -> Result<()> {
         // Move interface into jail
-        self.epair.move_to_jail(jid)?;
+        match &self.epair {
+            Some(epair) => epair.move_to_jail(jid)?,
+            None => ioctl::move_to_vnet(self.jail_interface(), jid)?,
+        }
 
         // Configure interface inside jail
         EpairInterface::configure_in_jail(
@@ -110,18 +176,112 @@ impl VnetSetup {
             Some(&self.config.gateway.to_string()),
         )?;
 
+        for addr in &self.config.extra_addresses {
+            EpairInterface::add_address_in_jail(jid, self.jail_interface(), addr)?;
+        }
+
         Ok(())
     }
 
+    /// Return a passed-through physical interface to the host
+    ///
+    /// Called before the jail is removed; epairs are destroyed by [`Self::cleanup`].
+    pub fn detach_from_jail(&self, jid: i32) -> Result<()> {
+        match (&self.epair, &self.config.interface) {
+            (None, Some(interface)) => ioctl::reclaim_from_vnet(interface, jid),
+            _ => Ok(()),
+        }
+    }
+
+    /// Directory holding persisted setups
+    fn state_dir(data_dir: &Path) -> PathBuf {
+        data_dir.join("vnet")
+    }
+
+    /// File holding a jail's persisted setup
+    fn state_path(data_dir: &Path, jail_name: &str) -> PathBuf {
+        Self::state_dir(data_dir).join(format!("{}.json", jail_name))
+    }
+
+    /// Persist the setup so a later invocation can clean it up
+    pub fn save(&self, data_dir: &Path, jail_name: &str) -> Result<()> {
+        fs::create_dir_all(Self::state_dir(data_dir))?;
+        let json = serde_json::to_string_pretty(self)?;
+        fs::write(Self::state_path(data_dir, jail_name), json)?;
+        Ok(())
+    }
+
+    /// Move a jail's persisted setup to a new jail name, returning whether
+    /// there was one
+    pub fn rename(data_dir: &Path, jail_name: &str, new_name: &str) -> Result<bool> {
+        let path = Self::state_path(data_dir, jail_name);
+        if !path.exists() {
+            return Ok(false);
+        }
+        fs::rename(path, Self::state_path(data_dir, new_name))?;
+        Ok(true)
+    }
+
+    /// Forget a jail's persisted setup
+    pub fn forget(data_dir: &Path, jail_name: &str) {
+        let _ = fs::remove_file(Self::state_path(data_dir, jail_name));
+    }
+
+    /// Load persisted setups (jail name -> setup)
+    ///
+    /// Records whose epair no longer exists (e.g. after a reboot), or whose
+    /// physical interface is back on the host, are removed.
+    pub fn load_all(data_dir: &Path) -> HashMap<String, VnetSetup> {
+        let mut setups = HashMap::new();
+        let Ok(entries) = fs::read_dir(Self::state_dir(data_dir)) else {
+            return setups;
+        };
+        for path in entries.flatten().map(|e| e.path()) {
+            let Some(jail_name) = path
+                .file_name()
+                .and_then(|n| n.to_str())
+                .and_then(|n| n.strip_suffix(".json"))
+            else {
+                continue;
+            };
+            let setup: VnetSetup = match fs::read_to_string(&path)
+                .map_err(|e| e.to_string())
+                .and_then(|json| serde_json::from_str(&json).map_err(|e| e.to_string()))
+            {
+                Ok(setup) => setup,
+                Err(e) => {
+                    eprintln!("Warning: Ignoring VNET state {}: {}", path.display(), e);
+                    continue;
+                }
+            };
+            let live = match (&setup.epair, &setup.config.interface) {
+                (Some(epair), _) => interface_exists(epair.host_side()).unwrap_or(false),
+                (None, Some(interface)) => !interface_exists(interface).unwrap_or(true),
+                (None, None) => false,
+            };
+            if live {
+                setups.insert(jail_name.to_string(), setup);
+            } else {
+                let _ = fs::remove_file(&path);
+            }
+        }
+        setups
+    }
+
     /// Clean up the network setup
     pub fn cleanup(&self) -> Result<()> {
+        // A physical interface has nothing to tear down
+        let Some(epair) = &self.epair else {
+            return Ok(());
+        };
+
         // Remove from bridge (if still connected)
         if let Ok(bridge) = Bridge::open(&self.bridge_name) {
-            let _ = bridge.remove_member(self.epair.host_side());
+            let _ = bridge.remove_member(epair.host_side());
         }
 
         // Destroy the epair
-        self.epair.destroy()
+        epair.destroy()
     }
 }
 
@@ -139,5 +299,69 @@ mod tests {
 
         assert_eq!(config.bridge, "blackship0");
         assert_eq!(config.ip, "10.0.1.10/24");
+        assert!(config.extra_addresses.is_empty());
+    }
+
+    #[test]
+    fn test_vnet_config_dual_stack() {
+        let config = VnetConfig::new(
+            "blackship0".to_string(),
+            "10.0.1.10/24".to_string(),
+            "10.0.1.1".parse().unwrap(),
+        )
+        .with_extra_address("fd00:1::10/64".to_string());
+
+        assert_eq!(config.extra_addresses, vec!["fd00:1::10/64".to_string()]);
+    }
+
+    #[test]
+    fn test_persisted_setup_without_epair_is_dropped() {
+        let data_dir = std::env::temp_dir().join(format!("blackship-vnet-{}", std::process::id()));
+        let setup = VnetSetup {
+            epair: serde_json::from_str(
+                r#"{"host_side": "e9a_gone", "jail_side": "e9b_gone"}"#,
+            )
+            .unwrap(),
+            bridge_name: "blackship0".to_string(),
+            config: VnetConfig::new(
+                "blackship0".to_string(),
+                "10.0.1.10/24".to_string(),
+                "10.0.1.1".parse().unwrap(),
+            ),
+        };
+        setup.save(&data_dir, "test-web").unwrap();
+        let path = VnetSetup::state_path(&data_dir, "test-web");
+        let saved: VnetSetup = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(saved.epair.unwrap().host_side(), "e9a_gone");
+
+        // The epair doesn't exist, so the record is stale
+        assert!(VnetSetup::load_all(&data_dir).is_empty());
+        assert!(!path.exists());
+
+        fs::remove_dir_all(&data_dir).unwrap();
+    }
+
+    #[test]
+    fn test_persisted_passthrough_on_host_is_dropped() {
+        let data_dir =
+            std::env::temp_dir().join(format!("blackship-vnet-nic-{}", std::process::id()));
+        let setup = VnetSetup {
+            epair: None,
+            bridge_name: String::new(),
+            config: VnetConfig::new(
+                String::new(),
+                "10.0.1.10/24".to_string(),
+                "10.0.1.1".parse().unwrap(),
+            )
+            .with_interface("lo0".to_string()),
+        };
+        assert_eq!(setup.jail_interface(), "lo0");
+        setup.save(&data_dir, "test-fast").unwrap();
+
+        // lo0 is on the host, so no jail holds it any more
+        assert!(VnetSetup::load_all(&data_dir).is_empty());
+        assert!(!VnetSetup::state_path(&data_dir, "test-fast").exists());
+
+        fs::remove_dir_all(&data_dir).unwrap();
     }
 }
@@ -1,22 +1,27 @@
 //! Bootstrap functionality for fetching FreeBSD releases
 //!
 //! Provides:
-//! - Downloading and extracting FreeBSD base system archives
+//! - Downloading and extracting FreeBSD base system archives, several at once
 //! - Release management (list, verify)
+//! - Drift detection between a jail root and its base release
 //! - Support for different architectures
 //! - Retry with exponential backoff for network operations
+//! - Failover across a list of mirrors
+//! - Offline mode, bootstrapping only from the download cache
+//! - Download cache listing and cleanup
 
 use crate::manifest::RetryConfig;
 use crate::error::{Error, Result};
-use crate::supply::{download_file, fetch_text, url_exists};
+use crate::supply::{PARTIAL_SUFFIX, download_file, fetch_text, url_exists};
 use chrono_machines::{BackoffStrategy, ExponentialBackoff};
 use rand::rng;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::fs::{self, File};
 use std::io::BufReader;
 use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, SystemTime};
 use tar::Archive;
 use xz2::read::XzDecoder;
 
@@ -30,6 +35,40 @@ fn backoff_from_config(config: &RetryConfig) -> ExponentialBackoff {
         .jitter_factor(config.jitter_factor)
 }
 
+/// Archives downloaded and extracted at once during bootstrap by default
+pub const DEFAULT_BOOTSTRAP_JOBS: usize = 4;
+
+/// Partial downloads modified more recently than this may still be in
+/// progress and are never removed from the cache
+const PARTIAL_GRACE: Duration = Duration::from_secs(600);
+
+/// Alias for the newest bootstrapped RELEASE (see [`Provisioner::fetch_latest`])
+pub const LATEST_ALIAS: &str = "latest";
+
+/// Paths (relative to a jail root) that are expected to change at runtime
+/// and are ignored by drift detection
+const DRIFT_IGNORED: &[&str] = &[
+    "var", "tmp", "dev", "proc", "root", "home", "usr/home", "usr/local", "media", "mnt",
+];
+
+/// Differences between a jail root and its base release
+#[derive(Debug, Default)]
+pub struct Drift {
+    /// System files whose content differs from the release
+    pub modified: Vec<PathBuf>,
+    /// Files present in the jail but not in the release
+    pub added: Vec<PathBuf>,
+    /// Release files missing from the jail
+    pub removed: Vec<PathBuf>,
+}
+
+impl Drift {
+    /// Whether the jail root matches its release
+    pub fn is_clean(&self) -> bool {
+        self.modified.is_empty() && self.added.is_empty() && self.removed.is_empty()
+    }
+}
+
 /// Supported FreeBSD architectures
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum Arch {
@@ -39,6 +78,9 @@ pub enum Arch {
 }
 
 impl Arch {
+    /// All supported architectures
+    pub const ALL: [Arch; 3] = [Arch::Amd64, Arch::Arm64, Arch::I386];
+
     /// Get architecture from current system
     pub fn current() -> Result<Self> {
         let arch = std::env::consts::ARCH;
@@ -58,12 +100,37 @@ impl Arch {
             Arch::I386 => "i386",
         }
     }
+
+    /// Directory of this architecture's releases on the mirror
+    ///
+    /// arm64 is published under its TARGET/TARGET_ARCH pair.
+    pub fn mirror_path(&self) -> &'static str {
+        match self {
+            Arch::Amd64 => "amd64",
+            Arch::Arm64 => "arm64/aarch64",
+            Arch::I386 => "i386",
+        }
+    }
+}
+
+impl std::str::FromStr for Arch {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        match s {
+            "amd64" | "x86_64" => Ok(Arch::Amd64),
+            "arm64" | "aarch64" => Ok(Arch::Arm64),
+            "i386" | "x86" => Ok(Arch::I386),
+            _ => Err(Error::UnsupportedArch(s.to_string())),
+        }
+    }
 }
 
 /// A bootstrapped release
 #[derive(Debug)]
 pub struct Release {
-    /// Release name (e.g., "14.2-RELEASE")
+    /// Release name as referenced by jails (e.g., "14.2-RELEASE", or
+    /// "arm64/14.2-RELEASE" for a non-host architecture)
     pub name: String,
     /// Path to the extracted release
     pub path: PathBuf,
@@ -71,20 +138,39 @@ pub struct Release {
     pub arch: Arch,
 }
 
+/// A file in the download cache
+#[derive(Debug)]
+pub struct CachedFile {
+    /// File name within `cache_dir`
+    pub name: String,
+    /// Size in bytes
+    pub size: u64,
+    /// Time since the file was last modified
+    pub age: Duration,
+    /// Unfinished download (`.part`)
+    pub partial: bool,
+}
+
 /// Provisioner for fetching and managing releases
 pub struct Provisioner {
-    /// Base URL for FreeBSD mirror
-    mirror_url: String,
+    /// Base URLs of FreeBSD mirrors, tried in order
+    mirrors: Vec<String>,
     /// Directory for storing releases
     releases_dir: PathBuf,
     /// Cache directory for downloads
     cache_dir: PathBuf,
     /// Archives to download
     archives: Vec<String>,
-    /// Current architecture
+    /// Architecture to fetch releases for (host architecture by default)
     arch: Arch,
     /// Retry configuration for network operations
     retry_config: RetryConfig,
+    /// Archives fetched concurrently
+    jobs: usize,
+    /// Bytes per second per download (0 = unlimited)
+    rate_limit: u64,
+    /// Refuse network access; bootstrap only from cached archives
+    offline: bool,
 }
 
 impl Provisioner {
@@ -97,43 +183,109 @@ impl Provisioner {
         retry_config: RetryConfig,
     ) -> Result<Self> {
         Ok(Self {
-            mirror_url,
+            mirrors: vec![mirror_url],
             releases_dir,
             cache_dir,
             archives,
             arch: Arch::current()?,
             retry_config,
+            jobs: DEFAULT_BOOTSTRAP_JOBS,
+            rate_limit: 0,
+            offline: false,
         })
     }
 
     /// Create provisioner from config
     pub fn from_config(config: &crate::manifest::GlobalConfig) -> Result<Self> {
-        Self::new(
+        Ok(Self::new(
             config.mirror_url.clone(),
             config.releases_dir.clone(),
             config.cache_dir.clone(),
             config.bootstrap_archives.clone(),
             config.retry.clone(),
-        )
+        )?
+        .with_mirrors(config.mirror_urls.clone())
+        .with_rate_limit(config.download_rate_limit()))
     }
 
-    /// Get URL for a release archive
-    fn archive_url(&self, release: &str, archive: &str) -> String {
+    /// Try these mirrors in order instead of the single mirror URL
+    ///
+    /// An empty list keeps the current mirror.
+    pub fn with_mirrors(mut self, mirrors: Vec<String>) -> Self {
+        if !mirrors.is_empty() {
+            self.mirrors = mirrors;
+        }
+        self
+    }
+
+    /// Fetch releases for another architecture instead of the host's
+    pub fn with_arch(mut self, arch: Arch) -> Self {
+        self.arch = arch;
+        self
+    }
+
+    /// Fetch at most `jobs` archives at once (at least one)
+    pub fn with_jobs(mut self, jobs: usize) -> Self {
+        self.jobs = jobs.max(1);
+        self
+    }
+
+    /// Cap each download at `bytes_per_sec` (0 = unlimited)
+    pub fn with_rate_limit(mut self, bytes_per_sec: u64) -> Self {
+        self.rate_limit = bytes_per_sec;
+        self
+    }
+
+    /// Never touch the network: bootstrap only from archives already in the
+    /// cache, and fail on anything that needs a mirror
+    pub fn with_offline(mut self, offline: bool) -> Self {
+        self.offline = offline;
+        self
+    }
+
+    /// Fail with `Error::Offline` in offline mode
+    fn require_online(&self, action: &str) -> Result<()> {
+        if self.offline {
+            return Err(Error::Offline(action.to_string()));
+        }
+        Ok(())
+    }
+
+    /// Whether releases are fetched for the host architecture
+    fn is_host_arch(&self) -> bool {
+        Arch::current().is_ok_and(|host| host == self.arch)
+    }
+
+    /// Name under which a release is stored in `releases_dir`
+    ///
+    /// Host-architecture releases live directly in `releases_dir`; other
+    /// architectures are kept in a subdirectory named after the arch so both
+    /// can coexist.
+    fn release_key(&self, release: &str) -> String {
+        if self.is_host_arch() {
+            release.to_string()
+        } else {
+            format!("{}/{}", self.arch.freebsd_name(), release)
+        }
+    }
+
+    /// Get URL for a release archive on `mirror`
+    fn archive_url(&self, mirror: &str, release: &str, archive: &str) -> String {
         format!(
             "{}/{}/{}/{}.txz",
-            self.mirror_url,
-            self.arch.freebsd_name(),
+            mirror,
+            self.arch.mirror_path(),
             release,
             archive
         )
     }
 
-    /// Get URL for release MANIFEST
-    fn manifest_url(&self, release: &str) -> String {
+    /// Get URL for release MANIFEST on `mirror`
+    fn manifest_url(&self, mirror: &str, release: &str) -> String {
         format!(
             "{}/{}/{}/MANIFEST",
-            self.mirror_url,
-            self.arch.freebsd_name(),
+            mirror,
+            self.arch.mirror_path(),
             release
         )
     }
@@ -157,7 +309,7 @@ impl Provisioner {
 
     /// Get the path where a release would be extracted
     pub fn release_path(&self, release: &str) -> PathBuf {
-        self.releases_dir.join(release)
+        self.releases_dir.join(self.release_key(release))
     }
 
     /// Check if a release is already bootstrapped
@@ -166,7 +318,7 @@ impl Provisioner {
         path.exists() && path.join("bin").exists() && path.join("usr").exists()
     }
 
-    /// List all bootstrapped releases
+    /// List all bootstrapped releases, for every architecture
     pub fn list_releases(&self) -> Result<Vec<Release>> {
         let mut releases = Vec::new();
 
@@ -174,33 +326,136 @@ impl Provisioner {
             return Ok(releases);
         }
 
-        for entry in fs::read_dir(&self.releases_dir).map_err(Error::Io)? {
+        let host = Arch::current()?;
+        Self::scan_releases(&self.releases_dir, None, host, &mut releases)?;
+        for arch in Arch::ALL.into_iter().filter(|a| *a != host) {
+            let dir = self.releases_dir.join(arch.freebsd_name());
+            if dir.is_dir() {
+                Self::scan_releases(&dir, Some(arch.freebsd_name()), arch, &mut releases)?;
+            }
+        }
+
+        // Sort by name
+        releases.sort_by(|a, b| a.name.cmp(&b.name));
+
+        Ok(releases)
+    }
+
+    /// Collect the valid releases in one directory
+    fn scan_releases(
+        dir: &Path,
+        prefix: Option<&str>,
+        arch: Arch,
+        releases: &mut Vec<Release>,
+    ) -> Result<()> {
+        for entry in fs::read_dir(dir).map_err(Error::Io)? {
             let entry = entry.map_err(Error::Io)?;
             let path = entry.path();
 
-            if path.is_dir() {
-                // Check if it looks like a valid release
-                if path.join("bin").exists() && path.join("usr").exists()
-                    && let Some(name) = path.file_name().and_then(|n| n.to_str()) {
-                        releases.push(Release {
-                            name: name.to_string(),
-                            path: path.clone(),
-                            arch: self.arch,
-                        });
+            // Skip aliases such as `latest`; their target is listed itself
+            if entry.file_type().map_err(Error::Io)?.is_symlink() {
+                continue;
+            }
+
+            // Check if it looks like a valid release
+            if path.is_dir()
+                && path.join("bin").exists()
+                && path.join("usr").exists()
+                && let Some(name) = path.file_name().and_then(|n| n.to_str())
+            {
+                let name = match prefix {
+                    Some(prefix) => format!("{}/{}", prefix, name),
+                    None => name.to_string(),
+                };
+                releases.push(Release { name, path: path.clone(), arch });
+            }
+        }
+        Ok(())
+    }
+
+    /// Determine the newest RELEASE published on the mirror for this architecture
+    ///
+    /// Asks the mirrors in order and answers from the first that responds.
+    pub fn latest_release(&self) -> Result<String> {
+        self.require_online("finding the latest release needs the mirror listing")?;
+        self.with_failover(|mirror| {
+            let index_url = format!("{}/{}/", mirror, self.arch.mirror_path());
+            let index = fetch_text(&index_url, &self.retry_config)?;
+            parse_latest_release(&index).ok_or_else(|| {
+                Error::ReleaseNotFound(format!(
+                    "no N.N-RELEASE entries found in {} (unexpected mirror layout)",
+                    index_url
+                ))
+            })
+        })
+    }
+
+    /// Run `op` against each mirror in order until one succeeds
+    ///
+    /// Each attempt applies the full retry policy before moving on to the
+    /// next mirror; the last mirror's error is returned if all fail.
+    fn with_failover<T>(&self, mut op: impl FnMut(&str) -> Result<T>) -> Result<T> {
+        let mut last_err = None;
+        for (i, mirror) in self.mirrors.iter().enumerate() {
+            match op(mirror) {
+                Ok(value) => {
+                    if self.mirrors.len() > 1 {
+                        eprintln!("Using mirror {}", mirror);
                     }
+                    return Ok(value);
+                }
+                Err(e) => {
+                    if let Some(next) = self.mirrors.get(i + 1) {
+                        eprintln!("Warning: mirror {} failed: {}; trying {}", mirror, e, next);
+                    }
+                    last_err = Some(e);
+                }
             }
         }
+        Err(last_err.unwrap_or_else(|| Error::DownloadFailed("no mirrors configured".into())))
+    }
 
-        // Sort by name
-        releases.sort_by(|a, b| a.name.cmp(&b.name));
+    /// Bootstrap the newest RELEASE and point the `latest` alias at it
+    ///
+    /// Returns the resolved release name. An already bootstrapped release is
+    /// reused unless `force` is set.
+    pub fn fetch_latest(&self, force: bool) -> Result<String> {
+        let release = self.latest_release()?;
+        eprintln!("Latest release for {}: {}", self.arch.freebsd_name(), release);
 
-        Ok(releases)
+        if self.is_bootstrapped(&release) && !force {
+            eprintln!("{} is already bootstrapped", release);
+        } else {
+            self.bootstrap(&release, force)?;
+        }
+
+        let alias = self.release_path(LATEST_ALIAS);
+        if fs::symlink_metadata(&alias).is_ok() {
+            fs::remove_file(&alias).map_err(Error::Io)?;
+        }
+        std::os::unix::fs::symlink(&release, &alias).map_err(Error::Io)?;
+        eprintln!("{} -> {}", alias.display(), release);
+
+        Ok(release)
     }
 
     /// Bootstrap a FreeBSD release
     ///
-    /// Downloads and extracts the specified release archives.
+    /// Downloads and extracts the specified release archives, failing over to
+    /// the next mirror when one is missing the release or keeps failing. The
+    /// `latest` alias resolves to the newest RELEASE on the mirror.
+    ///
+    /// Offline, the release is extracted from the download cache instead and
+    /// fails if any archive is missing there.
     pub fn bootstrap(&self, release: &str, force: bool) -> Result<PathBuf> {
+        if self.offline && release != LATEST_ALIAS {
+            return self.bootstrap_cached(release, force);
+        }
+        if release == LATEST_ALIAS {
+            let release = self.fetch_latest(force)?;
+            return Ok(self.release_path(&release));
+        }
+
         let release_path = self.release_path(release);
 
         // Check if already exists
@@ -208,13 +463,83 @@ impl Provisioner {
             return Err(Error::ReleaseAlreadyExists(release.to_string()));
         }
 
+        self.with_failover(|mirror| self.bootstrap_from(mirror, release, &release_path))?;
+
+        eprintln!("Bootstrap complete: {}", release_path.display());
+        Ok(release_path)
+    }
+
+    /// Extract a release from previously downloaded archives, without the network
+    fn bootstrap_cached(&self, release: &str, force: bool) -> Result<PathBuf> {
+        let release_path = self.release_path(release);
+        if self.is_bootstrapped(release) && !force {
+            return Err(Error::ReleaseAlreadyExists(release.to_string()));
+        }
+
+        let cached: Vec<(&String, PathBuf)> = self
+            .archives
+            .iter()
+            .map(|archive| (archive, self.cache_file(release, archive)))
+            .collect();
+        if let Some((archive, file)) = cached.iter().find(|(_, file)| !file.exists()) {
+            return Err(Error::Offline(format!(
+                "{} is not bootstrapped and {}.txz is not cached at {}",
+                release,
+                archive,
+                file.display()
+            )));
+        }
+
+        eprintln!(
+            "Bootstrapping {} for {} from the download cache (offline)",
+            release,
+            self.arch.freebsd_name()
+        );
+        fs::create_dir_all(&release_path).map_err(Error::Io)?;
+        for (archive, file) in &cached {
+            eprintln!("Extracting {}.txz...", archive);
+            self.extract_txz(file, &release_path)?;
+        }
+
+        eprintln!("Bootstrap complete: {}", release_path.display());
+        Ok(release_path)
+    }
+
+    /// Where an archive of a release is kept in the download cache
+    fn cache_file(&self, release: &str, archive: &str) -> PathBuf {
+        self.cache_dir.join(format!(
+            "{}-{}.txz",
+            self.release_key(release).replace('/', "-"),
+            archive
+        ))
+    }
+
+    /// Download and extract a release's archives from one mirror
+    fn bootstrap_from(&self, mirror: &str, release: &str, release_path: &Path) -> Result<()> {
         // Verify release exists on mirror
-        let manifest_url = self.manifest_url(release);
+        let manifest_url = self.manifest_url(mirror, release);
         if !url_exists(&manifest_url, &self.retry_config) {
-            return Err(Error::ReleaseNotFound(release.to_string()));
+            let arch_url = format!("{}/{}/", mirror, self.arch.mirror_path());
+            if !url_exists(&arch_url, &self.retry_config) {
+                return Err(Error::UnsupportedArch(format!(
+                    "{} (not available on {})",
+                    self.arch.freebsd_name(),
+                    mirror
+                )));
+            }
+            return Err(Error::ReleaseNotFound(format!(
+                "{} for {}",
+                release,
+                self.arch.freebsd_name()
+            )));
         }
 
-        eprintln!("Bootstrapping {} for {}", release, self.arch.freebsd_name());
+        eprintln!(
+            "Bootstrapping {} for {} from {}",
+            release,
+            self.arch.freebsd_name(),
+            mirror
+        );
 
         // Fetch MANIFEST for checksums
         eprintln!("Fetching MANIFEST...");
@@ -223,78 +548,142 @@ impl Provisioner {
 
         // Create directories
         fs::create_dir_all(&self.cache_dir).map_err(Error::Io)?;
-        fs::create_dir_all(&release_path).map_err(Error::Io)?;
+        fs::create_dir_all(release_path).map_err(Error::Io)?;
 
-        // Download and extract each archive with retry
-        for archive in &self.archives {
-            let url = self.archive_url(release, archive);
-            let cache_file = self.cache_dir.join(format!("{}-{}.txz", release, archive));
+        // Download, verify and extract up to `jobs` archives at a time
+        let total = self.archives.len();
+        let workers = self.jobs.clamp(1, total.max(1));
+        let next = AtomicUsize::new(0);
+        let done = AtomicUsize::new(0);
+        let failed = AtomicBool::new(false);
+        let (checksums, next, done, failed) = (&checksums, &next, &done, &failed);
 
-            // Get expected checksum
-            let expected_sha256 = checksums.get(archive.as_str());
+        let mut results: Vec<(usize, Result<()>)> = thread::scope(|scope| {
+            let handles: Vec<_> = (0..workers)
+                .map(|_| {
+                    scope.spawn(move || {
+                        let mut results = Vec::new();
+                        // Stop picking up archives once one has failed for good
+                        while !failed.load(Ordering::Relaxed) {
+                            let index = next.fetch_add(1, Ordering::Relaxed);
+                            let Some(archive) = self.archives.get(index) else {
+                                break;
+                            };
+                            let expected = checksums.get(archive.as_str()).map(String::as_str);
+                            let result = self.fetch_archive(
+                                mirror,
+                                release,
+                                release_path,
+                                archive,
+                                expected,
+                            );
+                            match &result {
+                                Ok(()) => eprintln!(
+                                    "Finished {}.txz ({}/{})",
+                                    archive,
+                                    done.fetch_add(1, Ordering::Relaxed) + 1,
+                                    total
+                                ),
+                                Err(_) => failed.store(true, Ordering::Relaxed),
+                            }
+                            results.push((index, result));
+                        }
+                        results
+                    })
+                })
+                .collect();
+            handles
+                .into_iter()
+                .flat_map(|handle| {
+                    handle.join().unwrap_or_else(|_| {
+                        vec![(
+                            usize::MAX,
+                            Err(Error::ExtractionFailed("bootstrap worker panicked".into())),
+                        )]
+                    })
+                })
+                .collect()
+        });
 
-            // Retry loop for download and extract
-            let backoff = backoff_from_config(&self.retry_config);
-            let mut rng = rng();
-            let mut attempt: u8 = 0;
+        // Report the first failure in archive order
+        results.sort_by_key(|(index, _)| *index);
+        results.into_iter().try_for_each(|(_, result)| result)
+    }
 
-            loop {
-                attempt += 1;
+    /// Download, verify and extract one archive, retrying with backoff
+    ///
+    /// Release archives hold disjoint sets of files, so several archives can
+    /// extract into the same release at once.
+    fn fetch_archive(
+        &self,
+        mirror: &str,
+        release: &str,
+        release_path: &Path,
+        archive: &str,
+        expected_sha256: Option<&str>,
+    ) -> Result<()> {
+        let url = self.archive_url(mirror, release, archive);
+        let cache_file = self.cache_file(release, archive);
 
-                // Download if not cached or checksum doesn't match
-                let needs_download = if cache_file.exists() {
-                    if let Some(expected) = expected_sha256 {
-                        match crate::supply::sha256_file(&cache_file) {
-                            Ok(actual) => actual != *expected,
-                            Err(_) => true,
-                        }
-                    } else {
-                        false
+        // Retry loop for download and extract
+        let backoff = backoff_from_config(&self.retry_config);
+        let mut rng = rng();
+        let mut attempt: u8 = 0;
+
+        loop {
+            attempt += 1;
+
+            // Download if not cached or checksum doesn't match
+            let needs_download = if cache_file.exists() {
+                if let Some(expected) = expected_sha256 {
+                    match crate::supply::sha256_file(&cache_file) {
+                        Ok(actual) => actual != expected,
+                        Err(_) => true,
                     }
                 } else {
-                    true
-                };
+                    false
+                }
+            } else {
+                true
+            };
 
-                let result: Result<()> = (|| {
-                    if needs_download {
-                        eprintln!("Downloading {}.txz...", archive);
-                        download_file(
-                            &url,
-                            &cache_file,
-                            expected_sha256.map(|s| s.as_str()),
-                            &self.retry_config,
-                        )?;
-                    } else {
-                        eprintln!("Using cached {}.txz", archive);
-                    }
+            let result: Result<()> = (|| {
+                if needs_download {
+                    eprintln!("Downloading {}.txz...", archive);
+                    download_file(
+                        &url,
+                        &cache_file,
+                        expected_sha256,
+                        &self.retry_config,
+                        self.rate_limit,
+                    )?;
+                } else {
+                    eprintln!("Using cached {}.txz", archive);
+                }
 
-                    // Extract archive
-                    eprintln!("Extracting {}.txz...", archive);
-                    self.extract_txz(&cache_file, &release_path)?;
-                    Ok(())
-                })();
-
-                match result {
-                    Ok(()) => break,
-                    Err(e) => {
-                        if let Some(delay_ms) = backoff.delay(attempt, &mut rng) {
-                            eprintln!(
-                                "Archive {} attempt {} failed, retrying in {}ms...",
-                                archive, attempt, delay_ms
-                            );
-                            // Remove potentially corrupt cached file before retry
-                            let _ = fs::remove_file(&cache_file);
-                            thread::sleep(Duration::from_millis(delay_ms));
-                        } else {
-                            return Err(e);
-                        }
+                // Extract archive
+                eprintln!("Extracting {}.txz...", archive);
+                self.extract_txz(&cache_file, release_path)?;
+                Ok(())
+            })();
+
+            match result {
+                Ok(()) => return Ok(()),
+                Err(e) => {
+                    if let Some(delay_ms) = backoff.delay(attempt, &mut rng) {
+                        eprintln!(
+                            "Archive {} attempt {} failed, retrying in {}ms...",
+                            archive, attempt, delay_ms
+                        );
+                        // Remove potentially corrupt cached file before retry
+                        let _ = fs::remove_file(&cache_file);
+                        thread::sleep(Duration::from_millis(delay_ms));
+                    } else {
+                        return Err(e);
                     }
                 }
             }
         }
-
-        eprintln!("Bootstrap complete: {}", release_path.display());
-        Ok(release_path)
     }
 
     /// Extract a .txz (tar.xz) archive
@@ -326,6 +715,57 @@ impl Provisioner {
         Ok(())
     }
 
+    /// List the download cache, sorted by name
+    pub fn cached_files(&self) -> Result<Vec<CachedFile>> {
+        let mut files = Vec::new();
+        if !self.cache_dir.exists() {
+            return Ok(files);
+        }
+
+        let now = SystemTime::now();
+        for entry in fs::read_dir(&self.cache_dir).map_err(Error::Io)? {
+            let entry = entry.map_err(Error::Io)?;
+            let metadata = entry.metadata().map_err(Error::Io)?;
+            if !metadata.is_file() {
+                continue;
+            }
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let age = metadata
+                .modified()
+                .ok()
+                .and_then(|modified| now.duration_since(modified).ok())
+                .unwrap_or_default();
+            files.push(CachedFile {
+                partial: name.ends_with(PARTIAL_SUFFIX),
+                name,
+                size: metadata.len(),
+                age,
+            });
+        }
+        files.sort_by(|a, b| a.name.cmp(&b.name));
+        Ok(files)
+    }
+
+    /// Delete cached downloads, optionally only those older than `older_than`
+    ///
+    /// Partial downloads modified in the last ten minutes are kept, since a
+    /// bootstrap may still be writing them. Returns the files removed.
+    pub fn clear_cache(&self, older_than: Option<Duration>) -> Result<Vec<CachedFile>> {
+        let mut removed = Vec::new();
+        for file in self.cached_files()? {
+            if file.partial && file.age < PARTIAL_GRACE {
+                eprintln!("Skipping {} (download may be in progress)", file.name);
+                continue;
+            }
+            if older_than.is_some_and(|min_age| file.age < min_age) {
+                continue;
+            }
+            fs::remove_file(self.cache_dir.join(&file.name)).map_err(Error::Io)?;
+            removed.push(file);
+        }
+        Ok(removed)
+    }
+
     /// Delete a bootstrapped release
     pub fn delete(&self, release: &str) -> Result<()> {
         let path = self.release_path(release);
@@ -341,6 +781,20 @@ impl Provisioner {
         Ok(())
     }
 
+    /// Compare a jail root against the release it was created from
+    ///
+    /// The release itself is verified first so a damaged base doesn't show
+    /// up as jail drift.
+    pub fn verify_jail(&self, release: &str, jail_root: &Path) -> Result<Drift> {
+        if !self.verify(release)? {
+            return Err(Error::ExtractionFailed(format!(
+                "Release '{}' is corrupted or incomplete; cannot compare against it",
+                release
+            )));
+        }
+        detect_drift(&self.release_path(release), jail_root)
+    }
+
     /// Verify a bootstrapped release against MANIFEST
     pub fn verify(&self, release: &str) -> Result<bool> {
         if !self.is_bootstrapped(release) {
@@ -362,6 +816,106 @@ impl Provisioner {
     }
 }
 
+/// Compare two trees, ignoring [`DRIFT_IGNORED`] paths
+///
+/// Files with the same size and modification time are assumed unchanged
+/// (`cp -a` provisioning preserves both); anything else is hashed.
+pub fn detect_drift(release_root: &Path, jail_root: &Path) -> Result<Drift> {
+    if !jail_root.is_dir() {
+        return Err(Error::Io(std::io::Error::new(
+            std::io::ErrorKind::NotFound,
+            format!("Jail root {} does not exist", jail_root.display()),
+        )));
+    }
+
+    let mut base = BTreeMap::new();
+    collect_files(release_root, Path::new(""), &mut base)?;
+    let mut jail = BTreeMap::new();
+    collect_files(jail_root, Path::new(""), &mut jail)?;
+
+    let mut drift = Drift::default();
+    for (rel, base_meta) in &base {
+        match jail.get(rel) {
+            None => drift.removed.push(rel.clone()),
+            Some(jail_meta) => {
+                let differs = entry_differs(
+                    &release_root.join(rel),
+                    base_meta,
+                    &jail_root.join(rel),
+                    jail_meta,
+                )?;
+                if differs {
+                    drift.modified.push(rel.clone());
+                }
+            }
+        }
+    }
+    drift.added = jail.into_keys().filter(|rel| !base.contains_key(rel)).collect();
+
+    Ok(drift)
+}
+
+/// Recursively collect non-directory entries under `root`, keyed by relative path
+fn collect_files(root: &Path, rel: &Path, out: &mut BTreeMap<PathBuf, fs::Metadata>) -> Result<()> {
+    for entry in fs::read_dir(root.join(rel)).map_err(Error::Io)? {
+        let entry = entry.map_err(Error::Io)?;
+        let rel = rel.join(entry.file_name());
+        if DRIFT_IGNORED.iter().any(|ignored| rel.starts_with(ignored)) {
+            continue;
+        }
+        let meta = fs::symlink_metadata(entry.path()).map_err(Error::Io)?;
+        if meta.is_dir() {
+            collect_files(root, &rel, out)?;
+        } else {
+            out.insert(rel, meta);
+        }
+    }
+    Ok(())
+}
+
+/// Whether a release entry and the corresponding jail entry differ
+fn entry_differs(
+    base: &Path,
+    base_meta: &fs::Metadata,
+    jail: &Path,
+    jail_meta: &fs::Metadata,
+) -> Result<bool> {
+    let (base_link, jail_link) = (base_meta.is_symlink(), jail_meta.is_symlink());
+    if base_link || jail_link {
+        if base_link != jail_link {
+            return Ok(true);
+        }
+        let base_target = fs::read_link(base).map_err(Error::Io)?;
+        return Ok(base_target != fs::read_link(jail).map_err(Error::Io)?);
+    }
+    if base_meta.len() != jail_meta.len() {
+        return Ok(true);
+    }
+    if base_meta.modified().ok() == jail_meta.modified().ok() {
+        return Ok(false);
+    }
+    Ok(crate::supply::sha256_file(base)? != crate::supply::sha256_file(jail)?)
+}
+
+/// Pick the newest `N.N-RELEASE` entry from a mirror directory index
+///
+/// Only links of that exact form are considered, so BETA/RC builds and
+/// unrelated entries are ignored.
+pub fn parse_latest_release(index: &str) -> Option<String> {
+    index
+        .split("href=\"")
+        .skip(1)
+        .filter_map(|rest| rest.split('"').next())
+        .map(|href| href.trim_end_matches('/'))
+        .filter_map(|name| {
+            let version = name.strip_suffix("-RELEASE")?;
+            let (major, minor) = version.split_once('.')?;
+            Some(((major.parse::<u32>().ok()?, minor.parse::<u32>().ok()?), name))
+        })
+        .max_by_key(|(version, _)| *version)
+        .map(|(_, name)| name.to_string())
+}
+
 /// Clone a release to create a new jail filesystem (_unused: future feature)
 #[allow(dead_code)]
 pub fn clone_release(release_path: &Path, jail_path: &Path) -> Result<()> {
@@ -402,12 +956,15 @@ mod tests {
     #[test]
     fn test_manifest_parsing() {
         let provisioner = Provisioner {
-            mirror_url: String::new(),
+            mirrors: vec![String::new()],
             releases_dir: PathBuf::new(),
             cache_dir: PathBuf::new(),
             archives: vec![],
             arch: Arch::Amd64,
             retry_config: RetryConfig::default(),
+            jobs: DEFAULT_BOOTSTRAP_JOBS,
+            rate_limit: 0,
+            offline: false,
         };
 
         let manifest = "base.txz\tabc123\t100\t1000\nkernel.txz\tdef456\t50\t500";
@@ -419,19 +976,211 @@ mod tests {
 
     #[test]
     fn test_archive_url() {
+        let mirror = "https://download.freebsd.org/releases";
         let provisioner = Provisioner {
-            mirror_url: "https://download.freebsd.org/releases".to_string(),
+            mirrors: vec![mirror.to_string()],
             releases_dir: PathBuf::new(),
             cache_dir: PathBuf::new(),
             archives: vec![],
             arch: Arch::Amd64,
             retry_config: RetryConfig::default(),
+            jobs: DEFAULT_BOOTSTRAP_JOBS,
+            rate_limit: 0,
+            offline: false,
         };
 
-        let url = provisioner.archive_url("14.2-RELEASE", "base");
+        let url = provisioner.archive_url(mirror, "14.2-RELEASE", "base");
         assert_eq!(
             url,
             "https://download.freebsd.org/releases/amd64/14.2-RELEASE/base.txz"
         );
+
+        let provisioner = provisioner.with_arch("aarch64".parse().unwrap());
+        assert_eq!(
+            provisioner.archive_url(mirror, "14.2-RELEASE", "base"),
+            "https://download.freebsd.org/releases/arm64/aarch64/14.2-RELEASE/base.txz"
+        );
+    }
+
+    #[test]
+    fn test_detect_drift() {
+        let root = std::env::temp_dir().join(format!("blackship-drift-{}", std::process::id()));
+        let base = root.join("base");
+        let jail = root.join("jail");
+        for dir in [&base, &jail] {
+            fs::create_dir_all(dir.join("bin")).unwrap();
+            fs::create_dir_all(dir.join("etc")).unwrap();
+            fs::create_dir_all(dir.join("var/log")).unwrap();
+            fs::write(dir.join("bin/sh"), "shell").unwrap();
+        }
+        fs::write(base.join("etc/rc.conf"), "a=1\n").unwrap();
+        fs::write(jail.join("etc/rc.conf"), "a=2\n").unwrap();
+        fs::write(base.join("bin/ls"), "ls").unwrap();
+        fs::write(jail.join("etc/custom.conf"), "x").unwrap();
+        fs::write(jail.join("var/log/messages"), "runtime").unwrap();
+
+        let drift = detect_drift(&base, &jail).unwrap();
+        assert_eq!(drift.modified, vec![PathBuf::from("etc/rc.conf")]);
+        assert_eq!(drift.added, vec![PathBuf::from("etc/custom.conf")]);
+        assert_eq!(drift.removed, vec![PathBuf::from("bin/ls")]);
+        assert!(!drift.is_clean());
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_parse_latest_release() {
+        let index = r#"<a href="../">../</a>
+<a href="13.5-RELEASE/">13.5-RELEASE/</a>
+<a href="14.3-RELEASE/">14.3-RELEASE/</a>
+<a href="15.0-RELEASE/">15.0-RELEASE/</a>
+<a href="15.1-BETA2/">15.1-BETA2/</a>
+<a href="ISO-IMAGES/">ISO-IMAGES/</a>"#;
+        assert_eq!(parse_latest_release(index), Some("15.0-RELEASE".to_string()));
+
+        // Numeric, not lexical, ordering
+        let index = concat!(
+            r#"<a href="9.3-RELEASE/"></a>"#,
+            r#"<a href="14.10-RELEASE/"></a>"#,
+            r#"<a href="14.9-RELEASE/"></a>"#,
+        );
+        assert_eq!(parse_latest_release(index), Some("14.10-RELEASE".to_string()));
+
+        assert_eq!(parse_latest_release("<html>Moved</html>"), None);
+    }
+
+    #[test]
+    fn test_release_path_keyed_by_arch() {
+        let host = Arch::current().unwrap();
+        let other = Arch::ALL.into_iter().find(|a| *a != host).unwrap();
+        let provisioner = Provisioner::new(
+            String::new(),
+            PathBuf::from("/var/blackship/releases"),
+            PathBuf::new(),
+            vec![],
+            RetryConfig::default(),
+        )
+        .unwrap();
+
+        assert_eq!(
+            provisioner.release_path("14.2-RELEASE"),
+            PathBuf::from("/var/blackship/releases/14.2-RELEASE")
+        );
+        let provisioner = provisioner.with_arch(other);
+        assert_eq!(
+            provisioner.release_path("14.2-RELEASE"),
+            PathBuf::from("/var/blackship/releases")
+                .join(other.freebsd_name())
+                .join("14.2-RELEASE")
+        );
+        assert!("sparc64".parse::<Arch>().is_err());
+    }
+
+    #[test]
+    fn test_mirror_failover() {
+        let provisioner = Provisioner::new(
+            "https://download.freebsd.org/releases".to_string(),
+            PathBuf::new(),
+            PathBuf::new(),
+            vec![],
+            RetryConfig::default(),
+        )
+        .unwrap()
+        .with_mirrors(vec!["https://a.example".into(), "https://b.example".into()]);
+
+        let mut tried = Vec::new();
+        let used = provisioner
+            .with_failover(|mirror| {
+                tried.push(mirror.to_string());
+                if mirror == "https://a.example" {
+                    Err(Error::DownloadFailed("timeout".into()))
+                } else {
+                    Ok(mirror.to_string())
+                }
+            })
+            .unwrap();
+        assert_eq!(used, "https://b.example");
+        assert_eq!(tried, ["https://a.example", "https://b.example"]);
+
+        let err = provisioner
+            .with_failover(|mirror| Err::<(), _>(Error::DownloadFailed(mirror.to_string())))
+            .unwrap_err();
+        assert!(matches!(err, Error::DownloadFailed(m) if m == "https://b.example"));
+
+        // An empty list keeps the configured mirror
+        let provisioner = provisioner.with_mirrors(vec![]);
+        assert_eq!(provisioner.mirrors, ["https://a.example", "https://b.example"]);
+    }
+
+    #[test]
+    fn test_offline_bootstrap() {
+        let root = std::env::temp_dir().join(format!("blackship-offline-{}", std::process::id()));
+        let provisioner = Provisioner::new(
+            "https://download.freebsd.org/releases".to_string(),
+            root.join("releases"),
+            root.join("cache"),
+            vec!["base".into()],
+            RetryConfig::default(),
+        )
+        .unwrap()
+        .with_offline(true);
+
+        // Nothing cached: refuse instead of downloading
+        let err = provisioner.bootstrap("14.2-RELEASE", false).unwrap_err();
+        assert!(matches!(err, Error::Offline(ref m) if m.contains("base.txz")));
+        assert!(matches!(provisioner.latest_release(), Err(Error::Offline(_))));
+
+        // A cached archive is extracted without the network
+        let cache_file = provisioner.cache_file("14.2-RELEASE", "base");
+        fs::create_dir_all(cache_file.parent().unwrap()).unwrap();
+        let mut builder = tar::Builder::new(xz2::write::XzEncoder::new(
+            File::create(&cache_file).unwrap(),
+            1,
+        ));
+        let mut header = tar::Header::new_gnu();
+        header.set_size(2);
+        header.set_mode(0o755);
+        header.set_cksum();
+        builder.append_data(&mut header, "bin/sh", &b"sh"[..]).unwrap();
+        builder.into_inner().unwrap().finish().unwrap();
+
+        let path = provisioner.bootstrap("14.2-RELEASE", false).unwrap();
+        assert_eq!(fs::read(path.join("bin/sh")).unwrap(), b"sh");
+
+        fs::remove_dir_all(&root).unwrap();
+    }
+
+    #[test]
+    fn test_clear_cache() {
+        let root = std::env::temp_dir().join(format!("blackship-cache-{}", std::process::id()));
+        let provisioner = Provisioner::new(
+            String::new(),
+            root.join("releases"),
+            root.join("cache"),
+            vec![],
+            RetryConfig::default(),
+        )
+        .unwrap();
+        assert!(provisioner.cached_files().unwrap().is_empty());
+
+        fs::create_dir_all(root.join("cache")).unwrap();
+        fs::write(root.join("cache/14.2-RELEASE-base.txz"), "base").unwrap();
+        fs::write(root.join("cache/14.2-RELEASE-src.txz.part"), "sr").unwrap();
+
+        let files = provisioner.cached_files().unwrap();
+        assert_eq!(files.len(), 2);
+        assert_eq!((files[0].size, files[0].partial), (4, false));
+        assert!(files[1].partial);
+
+        // Fresh files are younger than a day
+        assert!(provisioner.clear_cache(Some(Duration::from_secs(86400))).unwrap().is_empty());
+
+        // The in-progress download survives a full clear
+        let removed = provisioner.clear_cache(None).unwrap();
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].name, "14.2-RELEASE-base.txz");
+        assert_eq!(provisioner.cached_files().unwrap().len(), 1);
+
+        fs::remove_dir_all(&root).unwrap();
     }
 }
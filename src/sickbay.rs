@@ -9,4 +9,4 @@
 pub mod checker;
 pub mod recovery;
 
-pub use checker::{HealthChecker, HealthStatus};
+pub use checker::{breaker_reset_marker, HealthChecker, HealthStatus};
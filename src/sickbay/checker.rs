@@ -8,8 +8,10 @@ use crate::jail::jexec::jexec_with_timeout;
 use crate::sickbay::recovery::{RecoveryAction, RecoveryConfig};
 use crate::warden::WardenHandle;
 use breaker_machines::{CircuitBreaker, CircuitBuilder};
-use serde::Deserialize;
+use serde::{Deserialize, Serialize};
+use std::cell::Cell;
 use std::collections::HashMap;
+use std::path::{Path, PathBuf};
 use std::process::Command;
 use std::sync::atomic::{AtomicBool, Ordering};
 use std::sync::Arc;
@@ -47,7 +49,7 @@ impl std::fmt::Display for HealthStatus {
 }
 
 /// Where to execute the health check
-#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Default)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize, Serialize, Default)]
 #[serde(rename_all = "snake_case")]
 pub enum CheckTarget {
     /// Execute on the host system
@@ -58,7 +60,7 @@ pub enum CheckTarget {
 }
 
 /// A single health check definition
-#[derive(Debug, Clone, Deserialize)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize)]
 pub struct HealthCheck {
     /// Check name for identification
     pub name: String,
@@ -187,7 +189,7 @@ impl CheckResult {
 }
 
 /// Health check configuration for a jail
-#[derive(Debug, Clone, Deserialize, Default)]
+#[derive(Debug, Clone, PartialEq, Deserialize, Serialize, Default)]
 pub struct HealthCheckConfig {
     /// Enable health checking for this jail
     #[serde(default)]
@@ -228,6 +230,8 @@ struct CheckState {
     rate_limit_tokens: f64,
     /// Rate limiter last refill time (seconds since UNIX epoch)
     rate_limit_last_refill: f64,
+    /// When this check is next due (None = due now)
+    next_run: Option<Instant>,
 }
 
 impl CheckState {
@@ -242,8 +246,14 @@ impl CheckState {
             recovery_attempts: 0,
             rate_limit_tokens: capacity,
             rate_limit_last_refill: now_secs,
+            next_run: None,
         }
     }
+
+    /// Whether the check is due to run at `now`
+    fn is_due(&self, now: Instant) -> bool {
+        self.next_run.is_none_or(|next| next <= now)
+    }
 }
 
 impl Default for CheckState {
@@ -257,8 +267,8 @@ impl Default for CheckState {
 pub struct HealthChecker {
     /// Jail name
     jail_name: String,
-    /// Jail ID (for executing commands inside jail)
-    jid: Option<i32>,
+    /// Jail ID (for executing commands inside jail), re-resolved when stale
+    jid: Cell<Option<i32>>,
     /// Health check configuration
     config: HealthCheckConfig,
     /// Current health status
@@ -269,8 +279,6 @@ pub struct HealthChecker {
     check_states: Vec<CheckState>,
     /// Stop signal for background monitoring
     stop_signal: Arc<AtomicBool>,
-    /// Interval between check cycles (from first check's interval)
-    check_interval: Duration,
     /// Circuit breakers per health check (keyed by check name)
     circuit_breakers: HashMap<String, CircuitBreaker>,
     /// Rate limiter capacity (burst size)
@@ -290,11 +298,6 @@ impl HealthChecker {
         rate_limit_refill_rate: f64,
     ) -> Self {
         let check_count = config.checks.len();
-        let interval = config
-            .checks
-            .first()
-            .map(|c| c.interval)
-            .unwrap_or(30);
 
         // Initialize circuit breakers for each health check
         let circuit_breakers = config
@@ -312,7 +315,7 @@ impl HealthChecker {
 
         Self {
             jail_name: jail_name.to_string(),
-            jid: None,
+            jid: Cell::new(None),
             config,
             status: HealthStatus::Unknown,
             started_at: Instant::now(),
@@ -320,7 +323,6 @@ impl HealthChecker {
                 .map(|_| CheckState::new(rate_limit_capacity))
                 .collect(),
             stop_signal: Arc::new(AtomicBool::new(false)),
-            check_interval: Duration::from_secs(interval),
             circuit_breakers,
             rate_limit_capacity,
             rate_limit_refill_rate,
@@ -335,8 +337,8 @@ impl HealthChecker {
     }
 
     /// Set jail ID for executing checks inside jail
-    pub fn with_jid(mut self, jid: i32) -> Self {
-        self.jid = Some(jid);
+    pub fn with_jid(self, jid: i32) -> Self {
+        self.jid.set(Some(jid));
         self
     }
 
@@ -360,9 +362,23 @@ impl HealthChecker {
         self.stop_signal.load(Ordering::SeqCst)
     }
 
-    /// Get the check interval
-    pub fn interval(&self) -> Duration {
-        self.check_interval
+    /// Time until the next check is due
+    ///
+    /// Each check runs on its own interval; callers should sleep this long
+    /// between `run_checks` calls. Never less than one second, so checks held
+    /// back by the start period or rate limiter don't cause a busy loop.
+    pub fn next_due(&self) -> Duration {
+        let now = Instant::now();
+        self.check_states
+            .iter()
+            .map(|state| {
+                state
+                    .next_run
+                    .map_or(Duration::ZERO, |next| next.saturating_duration_since(now))
+            })
+            .min()
+            .unwrap_or(Duration::from_secs(default_interval()))
+            .max(Duration::from_secs(1))
     }
 
     /// Check if health checking is enabled
@@ -370,7 +386,17 @@ impl HealthChecker {
         self.config.enabled && !self.config.checks.is_empty()
     }
 
-    /// Run a single iteration of all health checks
+    /// Run every health check now, regardless of its schedule
+    pub fn run_all_checks(&mut self) -> Result<HealthStatus> {
+        for state in &mut self.check_states {
+            state.next_run = None;
+        }
+        self.run_checks()
+    }
+
+    /// Run the health checks that are due
+    ///
+    /// Checks that are not yet due keep their previous result.
     pub fn run_checks(&mut self) -> Result<HealthStatus> {
         if !self.is_enabled() {
             return Ok(HealthStatus::Unknown);
@@ -400,8 +426,22 @@ impl HealthChecker {
             .duration_since(UNIX_EPOCH)
             .unwrap_or_default()
             .as_secs_f64();
+        let now = Instant::now();
 
         for (idx, check) in self.config.checks.iter().enumerate() {
+            let state = &self.check_states[idx];
+            if !state.is_due(now) {
+                // Not due yet - carry the previous outcome into the overall status
+                if state.failures >= check.retries {
+                    any_failing = true;
+                }
+                if state.last_result.as_ref().is_some_and(|r| !r.passed) {
+                    all_healthy = false;
+                }
+                results.push(None);
+                continue;
+            }
+
             // Check if circuit breaker is open for this check
             let breaker_closed = self
                 .circuit_breakers
@@ -446,6 +486,7 @@ impl HealthChecker {
             // Update rate limiter state after consuming a token
             self.check_states[idx].rate_limit_tokens = rate_result.new_tokens;
             self.check_states[idx].rate_limit_last_refill = now_secs;
+            self.check_states[idx].next_run = Some(now + Duration::from_secs(check.interval));
 
             let result = self.execute_check(check)?;
             let duration_secs = result.duration.as_secs_f64();
@@ -489,7 +530,7 @@ impl HealthChecker {
             if result.is_some() {
                 self.check_states[idx].last_result = result;
             }
-            // If result is None (rate limited), keep the previous last_result
+            // If result is None (not due or rate limited), keep the previous last_result
         }
 
         // Trigger recoveries after the loop to avoid borrow issues
@@ -518,10 +559,9 @@ impl HealthChecker {
         let (passed, output) = match check.target {
             CheckTarget::Host => self.execute_on_host(&check.command, check.timeout)?,
             CheckTarget::Jail => {
-                if let Some(jid) = self.jid {
-                    self.execute_in_jail(jid, &check.command, check.timeout)?
-                } else {
-                    (false, "No jail ID available".to_string())
+                match self.resolve_jid() {
+                    Some(jid) => self.execute_in_jail(jid, &check.command, check.timeout)?,
+                    None => (false, "No jail ID available".to_string()),
                 }
             }
         };
@@ -601,12 +641,37 @@ impl HealthChecker {
         }
     }
 
+    /// Get the cached jail ID, looking it up by name if none is cached
+    fn resolve_jid(&self) -> Option<i32> {
+        if self.jid.get().is_none() {
+            self.jid.set(jail_getid(&self.jail_name).ok());
+        }
+        self.jid.get()
+    }
+
     /// Execute a check command inside the jail with timeout enforcement
     ///
-    /// Uses native jail_attach(2) syscall instead of spawning jexec process
+    /// Uses native jail_attach(2) syscall instead of spawning jexec process.
+    /// The cached JID is checked against the jail's name first, so a jail
+    /// restarted under a new JID is checked under that one.
     fn execute_in_jail(&self, jid: i32, command: &str, timeout: u64) -> Result<(bool, String)> {
+        let jid = match jail_getid(&self.jail_name) {
+            Ok(current) => {
+                if current != jid {
+                    self.jid.set(Some(current));
+                }
+                current
+            }
+            Err(_) => {
+                self.jid.set(None);
+                return Ok((false, format!("Jail '{}' is not running", self.jail_name)));
+            }
+        };
+
         // Use native jexec with timeout - runs sh -c "<command>" inside jail
-        match jexec_with_timeout(jid, &["sh", "-c", command], timeout) {
+        let result = jexec_with_timeout(jid, &["sh", "-c", command], timeout);
+
+        match result {
             Ok((exit_code, stdout, stderr)) => {
                 let combined = format!("{}{}", stdout, stderr);
                 Ok((exit_code == 0, combined))
@@ -668,7 +733,7 @@ impl HealthChecker {
                             });
                         }
                         // Clear the stored JID since the jail is now stopped
-                        self.jid = None;
+                        self.jid.set(None);
                         println!(
                             "Recovery: Jail '{}' stopped. Manual restart required via 'blackship up {}'",
                             self.jail_name, self.jail_name
@@ -701,7 +766,7 @@ impl HealthChecker {
                             });
                         }
                         // Clear the stored JID since the jail is now stopped
-                        self.jid = None;
+                        self.jid.set(None);
                         println!("Recovery: Jail '{}' stopped successfully", self.jail_name);
                     }
                     Err(e) => {
@@ -738,7 +803,12 @@ impl HealthChecker {
     }
 
     /// Get check results for display
-    pub fn get_check_results(&self) -> Vec<(&HealthCheck, Option<&CheckResult>, u32)> {
+    ///
+    /// Each entry also carries the check's circuit breaker state
+    /// ("closed", "open" or "half_open").
+    pub fn get_check_results(
+        &self,
+    ) -> Vec<(&HealthCheck, Option<&CheckResult>, u32, &'static str)> {
         self.config
             .checks
             .iter()
@@ -748,10 +818,37 @@ impl HealthChecker {
                     check,
                     self.check_states[idx].last_result.as_ref(),
                     self.check_states[idx].failures,
+                    self.breaker_state(&check.name),
                 )
             })
             .collect()
     }
+
+    /// Get the circuit breaker state of a check
+    fn breaker_state(&self, check_name: &str) -> &'static str {
+        match self.circuit_breakers.get(check_name).map(|b| b.state_name()) {
+            Some("Open") => "open",
+            Some("HalfOpen") => "half_open",
+            _ => "closed",
+        }
+    }
+
+    /// Force-close all circuit breakers so suspended checks resume immediately
+    pub fn reset_breakers(&mut self) {
+        for breaker in self.circuit_breakers.values_mut() {
+            breaker.reset();
+        }
+        for state in &mut self.check_states {
+            state.failures = 0;
+        }
+    }
+}
+
+/// Path of the marker file requesting a breaker reset for a supervised jail
+///
+/// Written by `blackship health reset` and consumed by the `supervise` health monitor.
+pub fn breaker_reset_marker(data_dir: &Path, jail_name: &str) -> PathBuf {
+    data_dir.join("health").join(format!("{}.reset", jail_name))
 }
 
 #[cfg(test)]
@@ -824,4 +921,42 @@ retries = 3
         assert_eq!(check.target, CheckTarget::Jail);
         assert_eq!(check.interval, 30);
     }
+
+    #[test]
+    fn test_reset_breakers() {
+        let config = HealthCheckConfig::enabled().with_check(HealthCheck::new("test", "true"));
+        let mut checker = HealthChecker::new("test_jail", config);
+        checker.check_states[0].failures = 3;
+
+        checker.reset_breakers();
+
+        let results = checker.get_check_results();
+        assert_eq!(results[0].2, 0);
+        assert_eq!(results[0].3, "closed");
+    }
+
+    #[test]
+    fn test_next_due_uses_nearest_check() {
+        let config = HealthCheckConfig::enabled()
+            .with_check(HealthCheck::new("slow", "true").with_interval(300))
+            .with_check(HealthCheck::new("fast", "true").with_interval(10));
+        let mut checker = HealthChecker::new("test_jail", config);
+        let now = Instant::now();
+
+        // Nothing has run yet, so checks are due (clamped to the minimum sleep)
+        assert!(checker.check_states[0].is_due(now));
+        assert_eq!(checker.next_due(), Duration::from_secs(1));
+
+        checker.check_states[0].next_run = Some(now + Duration::from_secs(300));
+        checker.check_states[1].next_run = Some(now + Duration::from_secs(10));
+        assert!(!checker.check_states[1].is_due(now));
+        let due = checker.next_due();
+        assert!(due <= Duration::from_secs(10) && due > Duration::from_secs(5));
+    }
+
+    #[test]
+    fn test_breaker_reset_marker() {
+        let marker = breaker_reset_marker(Path::new("/var/blackship"), "proj-web");
+        assert_eq!(marker, PathBuf::from("/var/blackship/health/proj-web.reset"));
+    }
 }
@@ -5,6 +5,7 @@
 //! - SHA256 checksum verification
 //! - Resume support for interrupted downloads
 //! - Retry with exponential backoff
+//! - Per-connection bandwidth limits
 
 use crate::manifest::RetryConfig;
 use crate::error::{Error, Result};
@@ -15,7 +16,14 @@ use std::fs::{self, File};
 use std::io::{BufReader, Read, Write};
 use std::path::Path;
 use std::thread;
-use std::time::Duration;
+use std::time::{Duration, Instant};
+use throttle_machines::token_bucket;
+
+/// Suffix of a download in progress; renamed to the destination once complete
+pub const PARTIAL_SUFFIX: &str = ".part";
+
+/// Bytes per token of the download throttle
+const THROTTLE_UNIT: usize = 1024;
 
 /// Create backoff strategy from RetryConfig
 fn backoff_from_config(config: &RetryConfig) -> ExponentialBackoff {
@@ -27,12 +35,77 @@ fn backoff_from_config(config: &RetryConfig) -> ExponentialBackoff {
         .jitter_factor(config.jitter_factor)
 }
 
+/// Reader that keeps a transfer at or below a rate in bytes per second
+///
+/// Reads are paid for with one token per KiB from a token bucket that starts
+/// empty and holds at most one second of tokens, so there is no initial burst.
+struct Throttled<R> {
+    inner: R,
+    /// Tokens (KiB) added per second
+    refill_rate: f64,
+    tokens: f64,
+    last_refill: f64,
+    epoch: Instant,
+    /// Largest single read (one second of transfer)
+    max_read: usize,
+    /// Bytes read but not yet paid for
+    owed: usize,
+}
+
+impl<R: Read> Throttled<R> {
+    fn new(inner: R, bytes_per_sec: u64) -> Self {
+        Self {
+            inner,
+            refill_rate: bytes_per_sec as f64 / THROTTLE_UNIT as f64,
+            tokens: 0.0,
+            last_refill: 0.0,
+            epoch: Instant::now(),
+            max_read: (bytes_per_sec as usize).max(THROTTLE_UNIT),
+            owed: 0,
+        }
+    }
+
+    /// Take one token, sleeping until the bucket has one
+    fn take_token(&mut self) {
+        loop {
+            let now = self.epoch.elapsed().as_secs_f64();
+            let capacity = self.refill_rate.max(1.0);
+            let result =
+                token_bucket::check(self.tokens, self.last_refill, now, capacity, self.refill_rate);
+            self.tokens = result.new_tokens;
+            self.last_refill = now;
+            if result.allowed {
+                return;
+            }
+            thread::sleep(Duration::from_secs_f64(result.retry_after));
+        }
+    }
+}
+
+impl<R: Read> Read for Throttled<R> {
+    fn read(&mut self, buf: &mut [u8]) -> std::io::Result<usize> {
+        let len = buf.len().min(self.max_read);
+        let read = self.inner.read(&mut buf[..len])?;
+        self.owed += read;
+        while self.owed >= THROTTLE_UNIT {
+            self.take_token();
+            self.owed -= THROTTLE_UNIT;
+        }
+        Ok(read)
+    }
+}
+
 /// Download a file from URL to destination with optional checksum verification
+///
+/// A nonzero `rate_limit` caps this connection at that many bytes per second.
+/// The data is written to `<dest>.part` and only renamed to `dest` once
+/// complete and verified.
 pub fn download_file(
     url: &str,
     dest: &Path,
     expected_sha256: Option<&str>,
     retry_config: &RetryConfig,
+    rate_limit: u64,
 ) -> Result<()> {
     // Create parent directory if needed
     if let Some(parent) = dest.parent() {
@@ -85,12 +158,20 @@ pub fn download_file(
     }
 
     // Create output file
-    let mut file = File::create(dest).map_err(|e| {
-        Error::DownloadFailed(format!("Failed to create file {}: {}", dest.display(), e))
+    let mut partial = dest.as_os_str().to_owned();
+    partial.push(PARTIAL_SUFFIX);
+    let partial = std::path::PathBuf::from(partial);
+    let mut file = File::create(&partial).map_err(|e| {
+        Error::DownloadFailed(format!("Failed to create file {}: {}", partial.display(), e))
     })?;
 
     // Download with progress
-    let mut reader = response.into_body().into_reader();
+    let reader = response.into_body().into_reader();
+    let mut reader: Box<dyn Read> = if rate_limit > 0 {
+        Box::new(Throttled::new(reader, rate_limit))
+    } else {
+        Box::new(reader)
+    };
     let mut hasher = Sha256::new();
     let mut buffer = [0u8; 65536]; // 64KB buffer
     let mut downloaded: u64 = 0;
@@ -131,7 +212,7 @@ pub fn download_file(
         let actual = hex::encode(hasher.finalize());
         if actual != expected {
             // Remove the corrupt file
-            let _ = fs::remove_file(dest);
+            let _ = fs::remove_file(&partial);
             return Err(Error::ChecksumMismatch {
                 file: dest.display().to_string(),
                 expected: expected.to_string(),
@@ -141,7 +222,9 @@ pub fn download_file(
         eprintln!("Checksum verified: OK");
     }
 
-    Ok(())
+    fs::rename(&partial, dest).map_err(|e| {
+        Error::DownloadFailed(format!("Failed to move download to {}: {}", dest.display(), e))
+    })
 }
 
 /// Compute SHA256 hash of a file
@@ -247,4 +330,17 @@ mod tests {
         // Clean up
         let _ = fs::remove_file(&test_file);
     }
+
+    #[test]
+    fn test_throttled_reader() {
+        let data = vec![7u8; 16 * 1024];
+        let started = Instant::now();
+        let mut reader = Throttled::new(data.as_slice(), 32 * 1024);
+        let mut out = Vec::new();
+        reader.read_to_end(&mut out).unwrap();
+
+        assert_eq!(out, data);
+        // 16 KiB at 32 KiB/s from an empty bucket takes about half a second
+        assert!(started.elapsed() >= Duration::from_millis(400));
+    }
 }
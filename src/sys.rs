@@ -1,8 +1,9 @@
-//! System detection and version information
+//! System detection, version information, the mount table and disk usage
 
 use crate::error::{Error, Result};
 use std::ffi::CStr;
 use std::fmt;
+use std::path::{Path, PathBuf};
 
 /// FreeBSD release type
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -187,6 +188,113 @@ impl fmt::Display for OsVersion {
     }
 }
 
+/// An entry in the kernel mount table
+#[derive(Debug, Clone)]
+pub struct Mount {
+    /// Filesystem type (e.g., "devfs", "nullfs")
+    pub fstype: String,
+    /// Where the filesystem is mounted
+    pub mount_point: PathBuf,
+}
+
+/// Read the current mount table (getmntinfo(3))
+pub fn mounts() -> Result<Vec<Mount>> {
+    let mut buf: *mut libc::statfs = std::ptr::null_mut();
+    // SAFETY: getmntinfo points buf at a static array of `count` statfs entries
+    let count = unsafe { libc::getmntinfo(&mut buf, libc::MNT_NOWAIT) };
+    if count <= 0 || buf.is_null() {
+        return Err(Error::Io(std::io::Error::last_os_error()));
+    }
+    // SAFETY: see above; the entries stay valid until the next getmntinfo call
+    let entries = unsafe { std::slice::from_raw_parts(buf, count as usize) };
+
+    Ok(entries
+        .iter()
+        .map(|fs| {
+            // SAFETY: both fields are NUL-terminated by the kernel
+            let fstype = unsafe { CStr::from_ptr(fs.f_fstypename.as_ptr()) };
+            let on = unsafe { CStr::from_ptr(fs.f_mntonname.as_ptr()) };
+            Mount {
+                fstype: fstype.to_string_lossy().into_owned(),
+                mount_point: PathBuf::from(on.to_string_lossy().into_owned()),
+            }
+        })
+        .collect())
+}
+
+/// Mounts at or below `root`, deepest first (the order to unmount them in)
+pub fn mounts_under(root: &Path) -> Result<Vec<Mount>> {
+    let mut found: Vec<Mount> =
+        mounts()?.into_iter().filter(|m| m.mount_point.starts_with(root)).collect();
+    found.sort_by_key(|m| std::cmp::Reverse(m.mount_point.components().count()));
+    Ok(found)
+}
+
+/// Whether a devfs ruleset has rules loaded on the host (`devfs rule -s N show`)
+///
+/// Rulesets only exist once rules are added to them, from devfs.rules(5) or
+/// by hand; ruleset 0 means "no ruleset" and always exists.
+pub fn devfs_ruleset_exists(ruleset: u16) -> Result<bool> {
+    if ruleset == 0 {
+        return Ok(true);
+    }
+    let output = std::process::Command::new("devfs")
+        .args(["rule", "-s", &ruleset.to_string(), "show"])
+        .output()
+        .map_err(|e| Error::CommandFailed {
+            command: "devfs".to_string(),
+            message: e.to_string(),
+        })?;
+    Ok(output.status.success() && !output.stdout.trim_ascii().is_empty())
+}
+
+/// Disk space allocated to a directory tree, like `du -s -x`
+///
+/// Counts allocated blocks rather than file sizes, counts hard-linked files
+/// once and does not descend into other filesystems. The walk stops after
+/// `max_entries` entries; the returned flag is true when it was cut short.
+pub fn disk_usage(root: &Path, max_entries: usize) -> (u64, bool) {
+    use std::collections::HashSet;
+    use std::os::unix::fs::MetadataExt;
+
+    let Ok(root_meta) = std::fs::symlink_metadata(root) else {
+        return (0, false);
+    };
+    let device = root_meta.dev();
+    let mut seen = HashSet::new();
+    let mut total = root_meta.blocks() * 512;
+    let mut visited = 0;
+    let mut pending = vec![root.to_path_buf()];
+
+    while let Some(dir) = pending.pop() {
+        let Ok(entries) = std::fs::read_dir(&dir) else {
+            continue;
+        };
+        for entry in entries.flatten() {
+            visited += 1;
+            if visited > max_entries {
+                return (total, true);
+            }
+            let Ok(meta) = entry.metadata() else {
+                continue;
+            };
+            // Other filesystems (devfs, nullfs, child datasets) are skipped
+            if meta.dev() != device {
+                continue;
+            }
+            if meta.nlink() > 1 && !seen.insert(meta.ino()) {
+                continue;
+            }
+            total += meta.blocks() * 512;
+            if meta.is_dir() {
+                pending.push(entry.path());
+            }
+        }
+    }
+
+    (total, false)
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
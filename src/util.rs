@@ -0,0 +1,49 @@
+//! Small helpers shared across modules
+
+/// Match text against a glob pattern supporting `*` and `?`
+pub(crate) fn glob_match(pattern: &str, text: &str) -> bool {
+    let pattern: Vec<char> = pattern.chars().collect();
+    let text: Vec<char> = text.chars().collect();
+    let (mut p, mut t) = (0, 0);
+    // Position of the last `*` and the text position it was tried at
+    let mut backtrack: Option<(usize, usize)> = None;
+
+    while t < text.len() {
+        match pattern.get(p) {
+            Some('*') => {
+                backtrack = Some((p, t));
+                p += 1;
+            }
+            Some(&c) if c == '?' || c == text[t] => {
+                p += 1;
+                t += 1;
+            }
+            _ => match backtrack {
+                Some((star, star_t)) => {
+                    p = star + 1;
+                    t = star_t + 1;
+                    backtrack = Some((star, star_t + 1));
+                }
+                None => return false,
+            },
+        }
+    }
+
+    pattern[p..].iter().all(|&c| c == '*')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob_match() {
+        assert!(glob_match("web*", "web"));
+        assert!(glob_match("web*", "webapp"));
+        assert!(glob_match("*-db", "shop-db"));
+        assert!(glob_match("w?b", "web"));
+        assert!(glob_match("*a*b*", "xxaxxbxx"));
+        assert!(!glob_match("web*", "api"));
+        assert!(!glob_match("w?b", "webb"));
+    }
+}
@@ -1,21 +1,33 @@
 //! The Warden - Jail Supervisor
 //!
 //! Monitors jails and implements one-for-one restart strategy:
-//! - Auto-restarts failed jails
+//! - Auto-restarts failed jails, as each jail's `restart` policy allows
 //! - Uses exponential backoff between restart attempts
+//! - Crash-loop detection: too many restarts within a window marks the jail Failed
 //! - Circuit breaker to stop restart attempts after too many failures
+//! - Webhook/command notifications on failure and recovery
+//! - Status file (and optional endpoint) updated on every lifecycle/health transition
 
-use std::collections::HashMap;
+use std::collections::{HashMap, VecDeque};
+use std::os::unix::fs::FileTypeExt;
+use std::path::{Path, PathBuf};
+use std::process::Command;
 use std::sync::Arc;
-use std::time::Duration;
+use std::time::{Duration, Instant, SystemTime, UNIX_EPOCH};
 
 use breaker_machines::{CircuitBreaker, CircuitBuilder};
 use chrono_machines::{BackoffStrategy, ExponentialBackoff};
 use rand::rng;
+use throttle_machines::token_bucket;
+use tokio::io::{AsyncRead, AsyncReadExt, AsyncWrite, AsyncWriteExt};
 use tokio::sync::{mpsc, Mutex};
 
-use crate::error::Result;
+use crate::error::{Error, Result};
 use crate::bridge::Bridge;
+use crate::logging::{self, Level};
+use crate::manifest::{NotificationConfig, RestartPolicy, SuperviseConfig};
+use crate::metrics::Metrics;
+use crate::sickbay::HealthStatus;
 
 /// Events the Warden receives
 #[derive(Debug)]
@@ -24,6 +36,8 @@ pub enum WardenEvent {
     JailFailed { name: String },
     /// A jail's health check failed
     JailHealthFailed { name: String },
+    /// A jail's health status changed
+    JailHealthChanged { name: String, status: HealthStatus },
     /// A jail started successfully
     JailStarted { name: String },
     /// A jail was stopped (intentionally)
@@ -34,8 +48,14 @@ pub enum WardenEvent {
 
 /// Restart state tracking for a single jail
 struct RestartState {
-    /// Number of restart attempts
-    attempts: u8,
+    /// Restarts within the crash-loop window (oldest first)
+    recent: VecDeque<Instant>,
+    /// Set once the jail is crash-looping; cleared only by manual intervention
+    crash_looped: bool,
+    /// Crash-loop window
+    window: Duration,
+    /// Restarts allowed within the window
+    max_restarts: u32,
     /// Backoff calculator
     backoff: ExponentialBackoff,
     /// Circuit breaker to stop restart attempts
@@ -43,15 +63,19 @@ struct RestartState {
 }
 
 impl RestartState {
-    fn new(name: &str) -> Self {
+    fn new(name: &str, config: &SuperviseConfig) -> Self {
         Self {
-            attempts: 0,
+            recent: VecDeque::new(),
+            crash_looped: false,
+            window: Duration::from_secs(config.restart_window_secs),
+            max_restarts: config.max_restarts,
             backoff: ExponentialBackoff::new()
-                .base_delay_ms(1000)       // 1 second base delay
-                .max_delay_ms(60000)       // 60 seconds max delay
-                .multiplier(2.0)           // Double each time
-                .max_attempts(10)          // Max 10 attempts
-                .jitter_factor(0.5),       // 50% jitter
+                .base_delay_ms(config.base_delay_ms)
+                .max_delay_ms(config.max_delay_ms)
+                .multiplier(config.multiplier)
+                // Crash-loop detection bounds the attempts, not the backoff
+                .max_attempts(u8::MAX)
+                .jitter_factor(config.jitter_factor),
             breaker: CircuitBuilder::new(format!("warden_{}", name))
                 .failure_threshold(5)
                 .success_threshold(2)
@@ -60,24 +84,154 @@ impl RestartState {
         }
     }
 
-    fn reset(&mut self) {
-        self.attempts = 0;
+    /// Number of restarts within the window
+    fn attempts(&self) -> u8 {
+        self.recent.len().min(u8::MAX as usize) as u8
+    }
+
+    /// Forget restarts that fell out of the window
+    fn prune(&mut self, now: Instant) {
+        while let Some(&oldest) = self.recent.front() {
+            if now.duration_since(oldest) > self.window {
+                self.recent.pop_front();
+            } else {
+                break;
+            }
+        }
+    }
+
+    /// Check (and latch) whether the jail exceeded its restart budget
+    fn check_crash_loop(&mut self, now: Instant) -> bool {
+        self.prune(now);
+        if self.recent.len() >= self.max_restarts as usize {
+            self.crash_looped = true;
+        }
+        self.crash_looped
+    }
+
+    fn record_restart(&mut self, now: Instant) {
+        self.recent.push_back(now);
+    }
+
+    fn record_success(&mut self) {
         self.breaker.record_success(0.0);
     }
 
     fn record_failure(&mut self) {
-        self.attempts = self.attempts.saturating_add(1);
         self.breaker.record_failure(0.0);
     }
 
     fn next_delay(&self) -> Option<Duration> {
         let mut rng = rng();
-        self.backoff.delay(self.attempts, &mut rng)
+        self.backoff.delay(self.attempts(), &mut rng)
             .map(Duration::from_millis)
     }
 
     fn should_retry(&self) -> bool {
-        self.breaker.is_closed() && self.backoff.should_retry(self.attempts)
+        self.breaker.is_closed()
+    }
+}
+
+/// Sends failure/recovery notifications via webhook and/or command
+struct Notifier {
+    config: NotificationConfig,
+    /// Rate limiter state (tokens, last refill in seconds since epoch)
+    tokens: f64,
+    last_refill: f64,
+    /// Rate limiter start time for consistent timing
+    epoch: Instant,
+}
+
+impl Notifier {
+    fn new(config: NotificationConfig) -> Self {
+        Self {
+            tokens: f64::from(config.max_per_minute),
+            last_refill: 0.0,
+            epoch: Instant::now(),
+            config,
+        }
+    }
+
+    /// Take a token from the rate limiter, if one is available
+    fn allow(&mut self, now: Instant) -> bool {
+        let capacity = f64::from(self.config.max_per_minute);
+        let now_secs = now.duration_since(self.epoch).as_secs_f64();
+        let result = token_bucket::check(
+            self.tokens,
+            self.last_refill,
+            now_secs,
+            capacity,
+            capacity / 60.0,
+        );
+
+        if result.allowed {
+            self.tokens = result.new_tokens;
+            self.last_refill = now_secs;
+        }
+        result.allowed
+    }
+
+    /// Send a notification in the background (never blocks the event loop)
+    fn notify(&mut self, jail: &str, event: &str, reason: &str) {
+        if !self.allow(Instant::now()) {
+            eprintln!(
+                "Warden: Dropping '{}' notification for jail '{}' (rate limit)",
+                event, jail
+            );
+            return;
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or_default();
+
+        if let Some(url) = self.config.webhook_url.clone() {
+            let payload = serde_json::json!({
+                "jail": jail,
+                "event": event,
+                "timestamp": timestamp,
+                "reason": reason,
+            })
+            .to_string();
+
+            tokio::task::spawn_blocking(move || {
+                if let Err(e) = ureq::post(&url)
+                    .header("Content-Type", "application/json")
+                    .send(&payload)
+                {
+                    eprintln!("Warden: Failed to send webhook notification: {}", e);
+                }
+            });
+        }
+
+        if let Some(command) = self.config.command.clone() {
+            let env = [
+                ("BLACKSHIP_JAIL", jail.to_string()),
+                ("BLACKSHIP_EVENT", event.to_string()),
+                ("BLACKSHIP_TIMESTAMP", timestamp.to_string()),
+                ("BLACKSHIP_REASON", reason.to_string()),
+            ];
+
+            tokio::task::spawn_blocking(move || {
+                match Command::new("sh").args(["-c", &command]).envs(env).status() {
+                    Ok(status) if !status.success() => {
+                        eprintln!("Warden: Notification command exited with {}", status);
+                    }
+                    Err(e) => eprintln!("Warden: Failed to run notification command: {}", e),
+                    Ok(_) => {}
+                }
+            });
+        }
+    }
+}
+
+/// Why a failed jail must not be restarted under its policy, if it must not
+fn restart_refusal(policy: RestartPolicy, stopped_by_user: bool) -> Option<&'static str> {
+    match policy {
+        RestartPolicy::No => Some("restart policy is 'no'"),
+        RestartPolicy::UnlessStopped if stopped_by_user => Some("it was stopped by the user"),
+        RestartPolicy::OnFailure | RestartPolicy::Always | RestartPolicy::UnlessStopped => None,
     }
 }
 
@@ -91,17 +245,83 @@ pub struct Warden {
     restart_states: HashMap<String, RestartState>,
     /// Reference to bridge for restart operations
     bridge: Arc<Mutex<Bridge>>,
+    /// Restart backoff and crash-loop settings
+    config: SuperviseConfig,
+    /// Optional failure/recovery notifications
+    notifier: Option<Notifier>,
+    /// Last reported health status per jail
+    health: HashMap<String, HealthStatus>,
+    /// Where to write the status file, if anywhere
+    status_file: Option<PathBuf>,
+    /// Prometheus metrics, when served
+    metrics: Option<Arc<Metrics>>,
 }
 
 impl Warden {
     /// Create a new Warden for the given bridge
-    pub fn new(bridge: Arc<Mutex<Bridge>>) -> Self {
+    pub fn new(bridge: Arc<Mutex<Bridge>>, config: SuperviseConfig) -> Self {
         let (tx, rx) = mpsc::channel(100);
         Self {
             rx,
             tx,
             restart_states: HashMap::new(),
             bridge,
+            config,
+            notifier: None,
+            health: HashMap::new(),
+            status_file: None,
+            metrics: None,
+        }
+    }
+
+    /// Record restarts and health transitions in `metrics`
+    pub fn with_metrics(mut self, metrics: Arc<Metrics>) -> Self {
+        self.metrics = Some(metrics);
+        self
+    }
+
+    /// Write per-jail state and health to `path` on every transition
+    pub fn with_status_file(mut self, path: PathBuf) -> Self {
+        self.status_file = Some(path);
+        self
+    }
+
+    /// Rewrite the status file, if one is configured
+    async fn write_status(&self) {
+        let Some(path) = &self.status_file else {
+            return;
+        };
+        // Probe the jails and write the file after releasing the bridge lock
+        let snapshot = self.bridge.lock().await.status_snapshot();
+        let health = self.health.clone();
+        let path = path.clone();
+        let written = tokio::task::spawn_blocking(move || {
+            let report = snapshot.report(&health);
+            let content = serde_json::to_string_pretty(&report).unwrap_or_default();
+
+            // Write to a temporary file first so readers never see a partial file
+            let tmp = path.with_extension("json.tmp");
+            let result = std::fs::write(&tmp, content).and_then(|_| std::fs::rename(&tmp, &path));
+            if let Err(e) = result {
+                eprintln!("Warden: Failed to write status file {}: {}", path.display(), e);
+            }
+        })
+        .await;
+        if let Err(e) = written {
+            eprintln!("Warden: Failed to write status file: {}", e);
+        }
+    }
+
+    /// Send notifications for failure and recovery events
+    pub fn with_notifications(mut self, config: NotificationConfig) -> Self {
+        self.notifier = Some(Notifier::new(config));
+        self
+    }
+
+    /// Send a notification if notifications are configured
+    fn notify(&mut self, jail: &str, event: &str, reason: &str) {
+        if let Some(notifier) = &mut self.notifier {
+            notifier.notify(jail, event, reason);
         }
     }
 
@@ -115,26 +335,49 @@ impl Warden {
     /// This should be spawned as a tokio task
     pub async fn run(&mut self) {
         println!("Warden: Starting jail supervisor");
+        self.write_status().await;
 
         while let Some(event) = self.rx.recv().await {
             match event {
                 WardenEvent::JailFailed { name } => {
-                    println!("Warden: Jail '{}' failed, initiating restart", name);
+                    logging::event(
+                        Level::Warning,
+                        &format!("Warden: Jail '{}' failed, initiating restart", name),
+                    );
+                    self.notify(&name, "failed", "Jail failed");
                     self.handle_failure(&name).await;
                 }
                 WardenEvent::JailHealthFailed { name } => {
-                    println!("Warden: Jail '{}' health check failed, initiating restart", name);
+                    logging::event(
+                        Level::Warning,
+                        &format!(
+                            "Warden: Jail '{}' health check failed, initiating restart",
+                            name
+                        ),
+                    );
+                    self.notify(&name, "health_failed", "Health check failed");
                     self.handle_failure(&name).await;
                 }
-                WardenEvent::JailStarted { name } => {
-                    println!("Warden: Jail '{}' started successfully", name);
-                    if let Some(state) = self.restart_states.get_mut(&name) {
-                        state.reset();
+                WardenEvent::JailHealthChanged { name, status } => {
+                    if let Some(metrics) = &self.metrics {
+                        metrics.set_health(&name, status);
                     }
+                    self.health.insert(name, status);
+                }
+                WardenEvent::JailStarted { name } => {
+                    // Restart history is kept so crash loops are still detected
+                    logging::event(
+                        Level::Info,
+                        &format!("Warden: Jail '{}' started successfully", name),
+                    );
                 }
                 WardenEvent::JailStopped { name } => {
-                    println!("Warden: Jail '{}' stopped intentionally", name);
-                    // Don't restart intentionally stopped jails
+                    logging::event(
+                        Level::Info,
+                        &format!("Warden: Jail '{}' stopped intentionally", name),
+                    );
+                    // Don't restart intentionally stopped jails; this also clears
+                    // any crash-loop latch
                     self.restart_states.remove(&name);
                 }
                 WardenEvent::Shutdown => {
@@ -142,6 +385,7 @@ impl Warden {
                     break;
                 }
             }
+            self.write_status().await;
         }
 
         println!("Warden: Supervisor stopped");
@@ -149,15 +393,58 @@ impl Warden {
 
     /// Handle a jail failure by attempting restart with backoff
     async fn handle_failure(&mut self, name: &str) {
+        let refusal = {
+            let br = self.bridge.lock().await;
+            let policy = br.restart_policy(name).unwrap_or_default();
+            restart_refusal(policy, br.stopped_by_user(name))
+        };
+        if let Some(reason) = refusal {
+            logging::event(
+                Level::Notice,
+                &format!("Warden: Not restarting jail '{}' ({})", name, reason),
+            );
+            return;
+        }
+
+        let config = &self.config;
         let state = self
             .restart_states
             .entry(name.to_string())
-            .or_insert_with(|| RestartState::new(name));
+            .or_insert_with(|| RestartState::new(name, config));
+
+        let now = Instant::now();
+        if state.crash_looped {
+            eprintln!(
+                "Warden: Not restarting crash-looping jail '{}' (stop and start it manually)",
+                name
+            );
+            return;
+        }
+        if state.check_crash_loop(now) {
+            logging::event(
+                Level::Warning,
+                &format!(
+                    "Warden: Jail '{}' restarted {} times within {}s, marking it Failed. \
+                     Restarts are suspended until it is stopped and started manually.",
+                    name,
+                    state.recent.len(),
+                    state.window.as_secs()
+                ),
+            );
+            let reason = format!(
+                "Restarted {} times within {}s, restarts suspended",
+                state.recent.len(),
+                state.window.as_secs()
+            );
+            self.bridge.lock().await.mark_failed(name);
+            self.notify(name, "crash_loop", &reason);
+            return;
+        }
 
         // Check if we should retry
         if !state.should_retry() {
             eprintln!(
-                "Warden: Not restarting jail '{}' (circuit breaker open or max attempts reached)",
+                "Warden: Not restarting jail '{}' (circuit breaker open)",
                 name
             );
             return;
@@ -167,16 +454,24 @@ impl Warden {
         let delay = match state.next_delay() {
             Some(d) => d,
             None => {
-                eprintln!("Warden: Max retries reached for jail '{}'", name);
+                logging::event(
+                    Level::Error,
+                    &format!("Warden: Max retries reached for jail '{}'", name),
+                );
                 return;
             }
         };
 
-        state.record_failure();
+        state.record_restart(now);
+        if let Some(metrics) = &self.metrics {
+            metrics.record_restart(name);
+        }
 
         println!(
             "Warden: Restarting jail '{}' in {:?} (attempt {})",
-            name, delay, state.attempts
+            name,
+            delay,
+            state.attempts()
         );
 
         // Wait for backoff period
@@ -188,16 +483,27 @@ impl Warden {
             br.restart_jail(name)
         };
 
+        let state = self.restart_states.get_mut(name);
         match result {
             Ok(_) => {
-                println!("Warden: Jail '{}' restarted successfully", name);
-                if let Some(state) = self.restart_states.get_mut(name) {
-                    state.reset();
+                logging::event(
+                    Level::Notice,
+                    &format!("Warden: Jail '{}' restarted successfully", name),
+                );
+                if let Some(state) = state {
+                    state.record_success();
                 }
+                self.notify(name, "recovered", "Jail restarted successfully");
             }
             Err(e) => {
-                eprintln!("Warden: Failed to restart jail '{}': {}", name, e);
-                // Failure already recorded before restart attempt
+                logging::event(
+                    Level::Warning,
+                    &format!("Warden: Failed to restart jail '{}': {}", name, e),
+                );
+                if let Some(state) = state {
+                    state.record_failure();
+                }
+                self.notify(name, "restart_failed", &e.to_string());
             }
         }
     }
@@ -249,6 +555,14 @@ impl WardenHandle {
             .map_err(|_| crate::error::Error::Io(std::io::Error::other("Warden channel closed")))
     }
 
+    /// Report a jail's new health status (never blocks; dropped if the queue is full)
+    pub fn report_health(&self, name: &str, status: HealthStatus) {
+        let _ = self.sender.try_send(WardenEvent::JailHealthChanged {
+            name: name.to_string(),
+            status,
+        });
+    }
+
     /// Notify that a jail stopped intentionally (blocking version)
     pub fn notify_stopped_blocking(&self, name: &str) -> Result<()> {
         self.sender
@@ -259,23 +573,194 @@ impl WardenHandle {
     }
 }
 
+/// Serve the status file over HTTP on `addr`
+///
+/// An address starting with `/` is a unix socket path; anything else is a
+/// TCP `host:port`. Every request gets the current status file as JSON.
+pub fn spawn_status_server(addr: &str, status_file: PathBuf) -> Result<()> {
+    let bind_error = |e: std::io::Error| {
+        Error::Io(std::io::Error::new(
+            e.kind(),
+            format!("Failed to listen on {}: {}", addr, e),
+        ))
+    };
+
+    if addr.starts_with('/') {
+        // Remove a stale socket left by a previous supervisor, but nothing else
+        match std::fs::symlink_metadata(addr) {
+            Ok(meta) if meta.file_type().is_socket() => {
+                std::fs::remove_file(addr).map_err(bind_error)?;
+            }
+            Ok(_) => {
+                return Err(Error::ConfigValidation(format!(
+                    "--status-addr {} exists and is not a socket; refusing to replace it",
+                    addr
+                )));
+            }
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => {}
+            Err(e) => return Err(bind_error(e)),
+        }
+        let listener = tokio::net::UnixListener::bind(addr).map_err(bind_error)?;
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_status(stream, status_file.clone()));
+                    }
+                    Err(e) => {
+                        // Transient (e.g. out of file descriptors); keep serving
+                        eprintln!("Warning: Status server failed to accept a connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        });
+    } else {
+        let listener = std::net::TcpListener::bind(addr).map_err(bind_error)?;
+        listener.set_nonblocking(true).map_err(bind_error)?;
+        let listener = tokio::net::TcpListener::from_std(listener).map_err(bind_error)?;
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve_status(stream, status_file.clone()));
+                    }
+                    Err(e) => {
+                        // Transient (e.g. out of file descriptors); keep serving
+                        eprintln!("Warning: Status server failed to accept a connection: {}", e);
+                        tokio::time::sleep(Duration::from_millis(100)).await;
+                    }
+                }
+            }
+        });
+    }
+
+    println!("Warden: Serving status on {}", addr);
+    Ok(())
+}
+
+/// Answer a single status request
+async fn serve_status<S: AsyncRead + AsyncWrite + Unpin>(mut stream: S, status_file: PathBuf) {
+    // The request itself is irrelevant: every path returns the status
+    let mut request = [0u8; 1024];
+    let _ = stream.read(&mut request).await;
+    let response = status_response(&status_file);
+    let _ = stream.write_all(response.as_bytes()).await;
+    let _ = stream.shutdown().await;
+}
+
+/// Build the HTTP response for the status endpoint
+fn status_response(status_file: &Path) -> String {
+    match std::fs::read_to_string(status_file) {
+        Ok(body) => http_response("200 OK", "application/json", &body),
+        Err(e) => http_response(
+            "503 Service Unavailable",
+            "application/json",
+            &serde_json::json!({ "error": e.to_string() }).to_string(),
+        ),
+    }
+}
+
+/// Format a complete HTTP/1.0 response
+pub(crate) fn http_response(status: &str, content_type: &str, body: &str) -> String {
+    format!(
+        "HTTP/1.0 {}\r\nContent-Type: {}\r\nContent-Length: {}\r\n\
+         Connection: close\r\n\r\n{}",
+        status,
+        content_type,
+        body.len(),
+        body
+    )
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
+    #[test]
+    fn test_status_response() {
+        let dir = std::env::temp_dir().join(format!("blackship-status-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("status.json");
+
+        let response = status_response(&path);
+        assert!(response.starts_with("HTTP/1.0 503"));
+
+        std::fs::write(&path, r#"{"jails":[]}"#).unwrap();
+        let response = status_response(&path);
+        assert!(response.starts_with("HTTP/1.0 200 OK\r\n"));
+        assert!(response.contains("Content-Length: 12\r\n"));
+        assert!(response.ends_with("\r\n\r\n{\"jails\":[]}"));
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
     #[test]
     fn test_restart_state_backoff() {
-        let state = RestartState::new("test_jail");
+        let state = RestartState::new("test_jail", &SuperviseConfig::default());
         assert!(state.should_retry());
         let delay = state.next_delay();
         assert!(delay.is_some());
     }
 
     #[test]
-    fn test_restart_state_reset() {
-        let mut state = RestartState::new("test_jail");
-        state.attempts = 5;
-        state.reset();
-        assert_eq!(state.attempts, 0);
+    fn test_restart_refusal() {
+        assert!(restart_refusal(RestartPolicy::No, false).is_some());
+        assert!(restart_refusal(RestartPolicy::OnFailure, true).is_none());
+        assert!(restart_refusal(RestartPolicy::Always, true).is_none());
+        assert!(restart_refusal(RestartPolicy::UnlessStopped, false).is_none());
+        assert!(restart_refusal(RestartPolicy::UnlessStopped, true).is_some());
+    }
+
+    #[test]
+    fn test_notifier_rate_limit() {
+        let mut notifier = Notifier::new(NotificationConfig {
+            webhook_url: None,
+            command: Some("true".to_string()),
+            max_per_minute: 2,
+        });
+        let now = Instant::now();
+
+        assert!(notifier.allow(now));
+        assert!(notifier.allow(now));
+        assert!(!notifier.allow(now));
+
+        // One token refills every 30 seconds
+        assert!(notifier.allow(now + Duration::from_secs(31)));
+    }
+
+    #[test]
+    fn test_crash_loop_detection() {
+        let config = SuperviseConfig {
+            max_restarts: 3,
+            restart_window_secs: 60,
+            ..SuperviseConfig::default()
+        };
+        let mut state = RestartState::new("test_jail", &config);
+        let start = Instant::now();
+
+        for _ in 0..3 {
+            assert!(!state.check_crash_loop(start));
+            state.record_restart(start);
+        }
+        assert!(state.check_crash_loop(start));
+
+        // The latch holds even once the restarts age out of the window
+        assert!(state.check_crash_loop(start + Duration::from_secs(120)));
+    }
+
+    #[test]
+    fn test_restarts_age_out_of_window() {
+        let config = SuperviseConfig {
+            max_restarts: 2,
+            restart_window_secs: 60,
+            ..SuperviseConfig::default()
+        };
+        let mut state = RestartState::new("test_jail", &config);
+        let start = Instant::now();
+
+        state.record_restart(start);
+        assert_eq!(state.attempts(), 1);
+        assert!(!state.check_crash_loop(start + Duration::from_secs(61)));
+        assert_eq!(state.attempts(), 0);
     }
 }
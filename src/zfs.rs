@@ -9,6 +9,11 @@ use crate::error::{Error, Result};
 use std::path::PathBuf;
 use std::process::Command;
 
+/// Prefix of snapshots taken on a schedule (`auto-<unix time>`)
+///
+/// Only these are pruned by the retention policy; manual snapshots are kept.
+pub const AUTO_SNAPSHOT_PREFIX: &str = "auto-";
+
 /// ZFS dataset manager
 pub struct ZfsManager {
     /// ZFS pool name (_unused: future feature)
@@ -72,6 +77,26 @@ impl ZfsManager {
         Ok(output.status.success())
     }
 
+    /// List the jail names that have a dataset under the jails dataset
+    pub fn list_jail_datasets(&self) -> Result<Vec<String>> {
+        let jails = self.jails_dataset();
+        let output = Command::new("zfs")
+            .args(["list", "-H", "-o", "name", "-t", "filesystem", "-d", "1", &jails])
+            .output()
+            .map_err(|e| Error::Zfs(format!("Failed to run zfs list: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::Zfs(format!("Failed to list datasets under '{}'", jails)));
+        }
+
+        let prefix = format!("{}/", jails);
+        Ok(String::from_utf8_lossy(&output.stdout)
+            .lines()
+            .filter_map(|line| line.strip_prefix(&prefix))
+            .map(String::from)
+            .collect())
+    }
+
     /// Create a dataset with default properties
     fn create_dataset(&self, dataset: &str) -> Result<()> {
         let status = Command::new("zfs")
@@ -89,10 +114,10 @@ impl ZfsManager {
         }
     }
 
-    /// Create a dataset for a jail
+    /// Create a dataset for a jail, optionally capped at `quota` (e.g. "10G")
     ///
     /// Creates: pool/blackship/jails/<name>
-    pub fn create_jail_dataset(&self, name: &str) -> Result<PathBuf> {
+    pub fn create_jail_dataset(&self, name: &str, quota: Option<&str>) -> Result<PathBuf> {
         let dataset = self.jail_dataset(name);
 
         if self.dataset_exists(&dataset)? {
@@ -103,9 +128,45 @@ impl ZfsManager {
         }
 
         self.create_dataset(&dataset)?;
+        if let Some(quota) = quota
+            && let Err(e) = self.set_quota(name, quota)
+        {
+            let _ = self.destroy_jail_dataset(name);
+            return Err(e);
+        }
         Ok(self.jail_path(name))
     }
 
+    /// Set (or with "none", remove) the quota of a jail's dataset
+    ///
+    /// Only changes the property when it differs from the current value.
+    pub fn set_quota(&self, name: &str, quota: &str) -> Result<()> {
+        let dataset = self.jail_dataset(name);
+        // Compare exact byte counts; "none" reads back as 0
+        let wanted = crate::manifest::parse_size(quota).unwrap_or(0).to_string();
+        if self.get_property_parsable(&dataset, "quota")? == wanted {
+            return Ok(());
+        }
+        self.set_property(&dataset, "quota", quota)
+    }
+
+    /// Get a dataset property as an exact (parsable) value
+    fn get_property_parsable(&self, dataset: &str, property: &str) -> Result<String> {
+        let output = Command::new("zfs")
+            .args(["get", "-Hp", "-o", "value", property, dataset])
+            .output()
+            .map_err(|e| Error::Zfs(format!("Failed to run zfs get: {}", e)))?;
+
+        if output.status.success() {
+            Ok(String::from_utf8_lossy(&output.stdout).trim().to_string())
+        } else {
+            Err(Error::Zfs(format!(
+                "Failed to get property '{}' for dataset '{}'",
+                property, dataset
+            )))
+        }
+    }
+
     /// Destroy a jail's dataset
     ///
     /// Warning: This recursively destroys all child datasets
@@ -132,6 +193,35 @@ impl ZfsManager {
         }
     }
 
+    /// Rename a jail's dataset (its mountpoint follows the new name)
+    ///
+    /// Returns the new mountpoint, or None when the jail has no dataset.
+    pub fn rename_jail_dataset(&self, name: &str, new_name: &str) -> Result<Option<PathBuf>> {
+        let dataset = self.jail_dataset(name);
+        let target = self.jail_dataset(new_name);
+
+        if !self.dataset_exists(&dataset)? {
+            return Ok(None);
+        }
+        if self.dataset_exists(&target)? {
+            return Err(Error::Zfs(format!("Dataset '{}' already exists", target)));
+        }
+
+        let status = Command::new("zfs")
+            .args(["rename", &dataset, &target])
+            .status()
+            .map_err(|e| Error::Zfs(format!("Failed to run zfs rename: {}", e)))?;
+
+        if status.success() {
+            Ok(Some(self.jail_path(new_name)))
+        } else {
+            Err(Error::Zfs(format!(
+                "Failed to rename dataset '{}' to '{}'",
+                dataset, target
+            )))
+        }
+    }
+
     /// Get dataset properties
     /// Get a ZFS property value (_unused: future feature)
     #[allow(dead_code)]
@@ -151,8 +241,31 @@ impl ZfsManager {
         }
     }
 
-    /// Set a dataset property (_unused: future feature)
-    #[allow(dead_code)]
+    /// Space used by a jail's dataset, including its snapshots and children
+    pub fn jail_usage(&self, jail: &str) -> Result<DatasetUsage> {
+        let dataset = self.jail_dataset(jail);
+        let output = Command::new("zfs")
+            .args(["get", "-Hp", "-o", "value", "used,referenced,quota", &dataset])
+            .output()
+            .map_err(|e| Error::Zfs(format!("Failed to run zfs get: {}", e)))?;
+
+        if !output.status.success() {
+            return Err(Error::Zfs(format!("Dataset '{}' does not exist", dataset)));
+        }
+
+        let stdout = String::from_utf8_lossy(&output.stdout);
+        let mut values = stdout.lines().map(|l| l.trim().parse::<u64>());
+        match (values.next(), values.next(), values.next()) {
+            (Some(Ok(used)), Some(Ok(referenced)), Some(Ok(quota))) => Ok(DatasetUsage {
+                used,
+                referenced,
+                quota: (quota > 0).then_some(quota),
+            }),
+            _ => Err(Error::Zfs(format!("Unexpected zfs get output for '{}'", dataset))),
+        }
+    }
+
+    /// Set a dataset property
     pub fn set_property(&self, dataset: &str, property: &str, value: &str) -> Result<()> {
         let status = Command::new("zfs")
             .args(["set", &format!("{}={}", property, value), dataset])
@@ -304,6 +417,28 @@ impl ZfsManager {
         }
     }
 
+    /// Take a scheduled snapshot of a jail and prune old ones
+    ///
+    /// Keeps the newest `keep` automatic snapshots. Returns the new snapshot.
+    pub fn auto_snapshot(&self, jail: &str, keep: usize) -> Result<String> {
+        use std::time::{SystemTime, UNIX_EPOCH};
+        let ts = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap_or_default()
+            .as_secs();
+        let name = self.create_snapshot(jail, Some(&format!("{}{}", AUTO_SNAPSHOT_PREFIX, ts)))?;
+
+        let names: Vec<String> = self
+            .list_snapshots(jail)?
+            .into_iter()
+            .map(|s| s.name)
+            .collect();
+        for old in expired_auto_snapshots(&names, keep) {
+            self.delete_snapshot(jail, &old)?;
+        }
+        Ok(name)
+    }
+
     /// Delete a snapshot
     pub fn delete_snapshot(&self, jail: &str, snapshot: &str) -> Result<()> {
         let dataset = self.jail_dataset(jail);
@@ -373,13 +508,38 @@ impl ZfsManager {
         }
     }
 
-    /// Get the dataset name for a jail (public accessor) (_unused: future feature)
-    #[allow(dead_code)]
+    /// Get the dataset name for a jail (public accessor)
     pub fn get_jail_dataset(&self, name: &str) -> String {
         self.jail_dataset(name)
     }
 }
 
+/// Space accounting of a dataset, in bytes
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct DatasetUsage {
+    /// Space used by the dataset, its snapshots and children
+    pub used: u64,
+    /// Data referenced by the dataset itself
+    pub referenced: u64,
+    /// Quota, if one is set
+    pub quota: Option<u64>,
+}
+
+/// Automatic snapshots beyond the newest `keep`, oldest first
+fn expired_auto_snapshots(names: &[String], keep: usize) -> Vec<String> {
+    let mut auto: Vec<(u64, &String)> = names
+        .iter()
+        .filter_map(|name| {
+            let ts = name.strip_prefix(AUTO_SNAPSHOT_PREFIX)?.parse().ok()?;
+            Some((ts, name))
+        })
+        .collect();
+    auto.sort();
+    auto.dedup();
+    let expired = auto.len().saturating_sub(keep);
+    auto.into_iter().take(expired).map(|(_, name)| name.clone()).collect()
+}
+
 /// Information about a ZFS snapshot
 #[derive(Debug, Clone)]
 pub struct SnapshotInfo {
@@ -407,4 +567,15 @@ mod tests {
             PathBuf::from("/zroot/blackship/jails/test")
         );
     }
+
+    #[test]
+    fn test_expired_auto_snapshots() {
+        let names: Vec<String> = ["auto-300", "manual", "auto-100", "auto-200", "snap-50"]
+            .iter()
+            .map(|s| s.to_string())
+            .collect();
+        assert_eq!(expired_auto_snapshots(&names, 2), vec!["auto-100"]);
+        assert_eq!(expired_auto_snapshots(&names, 1), vec!["auto-100", "auto-200"]);
+        assert!(expired_auto_snapshots(&names, 5).is_empty());
+    }
 }